@@ -0,0 +1,159 @@
+//! Matching of the arguments of a call to the parameters of a function.
+//!
+//! Arguments are passed either by position, `draw(1, 2)`, or by the name of the parameter they
+//! are passed to, `draw(y: 2, x: 1)`. Positional arguments come first and fill the parameters in
+//! order, and labelled arguments fill the remaining parameters by name. Parameters declared with
+//! a default value, `greeting = "hello"`, may be left out.
+//!
+//! A call that passes only the first few arguments by position, and leaves out a parameter
+//! without a default value, is a partial application. It evaluates to a closure taking the
+//! remaining arguments, so `draw(1)` is a function drawing at `x = 1` given `y`.
+
+use kali_span::Span;
+use lasso::Spur;
+
+use crate::{DestructorKind, Expr, ExprKind, Ident};
+
+/// An error raised when the arguments of a call cannot be matched to the parameters of a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgumentError {
+    /// A positional argument follows a labelled argument.
+    PositionalAfterLabelled(Span),
+    /// A label does not name a parameter of the function.
+    UnknownLabel(Ident),
+    /// A parameter is given more than one argument, where the label is that of the later one.
+    Duplicate(Ident),
+    /// More positional arguments are passed than the function has parameters.
+    TooMany {
+        /// The number of parameters of the function.
+        expected: usize,
+        /// The span of the first argument without a parameter.
+        span: Span,
+    },
+    /// A spread is passed alongside labelled arguments.
+    Spread(Span),
+}
+
+/// A parameter of a function, as seen by its callers.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    /// The label arguments are passed to the parameter by, if any.
+    pub label: Option<Spur>,
+    /// The value passed to the parameter when a call leaves it out, if any.
+    pub default: Option<Expr>,
+}
+
+/// Returns the parameters of a function, if `expr` is a lambda.
+///
+/// A parameter that binds its argument to a variable is labelled with the name of the variable.
+/// Parameters that destructure their argument have no label, and can only be passed by position.
+///
+/// # Arguments
+///
+/// * `expr` - The expression defining the function.
+///
+/// # Returns
+///
+/// Each parameter in order, or `None` if `expr` is not a lambda.
+pub fn parameters(expr: &Expr) -> Option<Vec<Parameter>> {
+    let ExprKind::Lambda { params, .. } = &expr.kind else {
+        return None;
+    };
+    Some(
+        params
+            .iter()
+            .map(|param| Parameter {
+                label: match &param.parameter.kind {
+                    DestructorKind::Var(ident) => Some(ident.key),
+                    _ => None,
+                },
+                default: param.default.clone(),
+            })
+            .collect(),
+    )
+}
+
+/// Returns whether an argument is passed by label.
+pub fn is_labelled(expr: &Expr) -> bool {
+    matches!(expr.kind, ExprKind::Labelled { .. })
+}
+
+/// Returns whether the arguments of a call must be matched to the parameters of the function
+/// rather than passed as they are, because some are labelled or left out.
+///
+/// Calls that spread a list into their arguments are only matched if they also pass labelled
+/// arguments, which [`match_arguments`] rejects. Partial applications are not matched, as the
+/// arguments they leave out are passed to the closure they evaluate to.
+pub fn needs_matching(parameters: &[Parameter], arguments: &[Expr]) -> bool {
+    arguments.iter().any(is_labelled)
+        || (arguments.len() < parameters.len()
+            && !arguments
+                .iter()
+                .any(|argument| matches!(argument.kind, ExprKind::Spread(_)))
+            && !is_partial(parameters, arguments))
+}
+
+/// Returns whether a call is a partial application of a function, such as `add(1)` given
+/// `let add = x, y -> x + y`.
+///
+/// A call is partial if it passes at least one argument, passes every argument by position, and
+/// leaves out a parameter without a default value. Calls that only leave out parameters with
+/// default values are complete, and pass those values instead.
+pub fn is_partial(parameters: &[Parameter], arguments: &[Expr]) -> bool {
+    !arguments.is_empty()
+        && arguments.len() < parameters.len()
+        && !arguments
+            .iter()
+            .any(|argument| is_labelled(argument) || matches!(argument.kind, ExprKind::Spread(_)))
+        && parameters[arguments.len()..]
+            .iter()
+            .any(|parameter| parameter.default.is_none())
+}
+
+/// Matches the arguments of a call to the parameters of a function.
+///
+/// # Arguments
+///
+/// * `parameters` - The parameters of the function, as returned by [`parameters`].
+/// * `arguments` - The arguments of the call.
+///
+/// # Returns
+///
+/// For each parameter, the index of the argument passed to it, or `None` if it was left out and
+/// takes its default value, should it have one.
+///
+/// # Errors
+///
+/// Returns an `ArgumentError` if the arguments are not ordered correctly, or do not correspond
+/// to the parameters.
+pub fn match_arguments(
+    parameters: &[Parameter],
+    arguments: &[Expr],
+) -> Result<Vec<Option<usize>>, ArgumentError> {
+    let mut matched = vec![None; parameters.len()];
+    let mut labelled = false;
+    for (index, argument) in arguments.iter().enumerate() {
+        match &argument.kind {
+            ExprKind::Labelled { label, .. } => {
+                labelled = true;
+                let parameter = parameters
+                    .iter()
+                    .position(|parameter| parameter.label == Some(label.key))
+                    .ok_or(ArgumentError::UnknownLabel(*label))?;
+                if matched[parameter].replace(index).is_some() {
+                    return Err(ArgumentError::Duplicate(*label));
+                }
+            }
+            ExprKind::Spread(_) => return Err(ArgumentError::Spread(argument.span)),
+            _ if labelled => return Err(ArgumentError::PositionalAfterLabelled(argument.span)),
+            _ if index >= parameters.len() => {
+                return Err(ArgumentError::TooMany {
+                    expected: parameters.len(),
+                    span: argument.span,
+                });
+            }
+            _ => matched[index] = Some(index),
+        }
+    }
+    Ok(matched)
+}
@@ -1,6 +1,9 @@
 use std::hash::Hash;
 
-mod visit;
+pub mod arguments;
+pub mod pipe;
+pub mod visit;
+pub mod visit_mut;
 
 /// Represents a module in the source code, containing a collection of items and a string interning cache.
 #[derive(Debug, Clone)]
@@ -17,7 +20,7 @@ pub struct Ident {
     /// The index of the identifier in the table.
     pub key: lasso::Spur,
     /// The span of the identifier in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
 }
 
 impl PartialEq for Ident {
@@ -38,22 +41,41 @@ impl Hash for Ident {
 #[derive(Debug, Clone)]
 pub struct Item {
     /// The span of the item in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
     /// The kind of the item (e.g., function, struct, etc.).
     pub kind: ItemKind,
     /// The visibility of the item (e.g., private, exported, or inherited).
     pub visibility: Visibility,
+    /// The attributes attached to the item, e.g. `#allow(shadowing)`.
+    pub attributes: Vec<Attribute>,
 }
 
-/// An enumeration of [`Item`] kinds.
+/// Represents an attribute attached to an item, e.g. `#allow(shadowing)`.
 #[derive(Debug, Clone)]
+pub struct Attribute {
+    /// The span of the attribute in the source code.
+    pub span: kali_span::Span,
+    /// The name of the attribute.
+    pub name: Ident,
+    /// The arguments of the attribute.
+    pub arguments: Vec<Ident>,
+}
+
+/// An enumeration of [`Item`] kinds.
+#[derive(Debug, Clone, strum::IntoStaticStr)]
 pub enum ItemKind {
     /// Represents an import statement in the source code.
     Import(ImportTree),
+    /// An export statement (e.g. `export { a, b as c }`).
+    Export(ExportKind),
     /// Represents a type alias.
     TypeAlias(TypeAlias),
     /// Represents a definition in the source code.
     Definition(Definition),
+    /// A function defined by one or more clauses (e.g. `fn fib 0 = 0; fn fib n = ...`).
+    Function(Function),
+    /// A placeholder for an item that failed to parse.
+    Error,
 }
 
 /// Represents the visibility of an item in the source code.
@@ -74,11 +96,11 @@ pub struct ImportTree {
     /// The kind of the import tree.
     pub kind: ImportTreeKind,
     /// The span of the import tree in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
 }
 
 /// A tree of imports.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, strum::IntoStaticStr)]
 pub enum ImportTreeKind {
     /// Represents a specific item in the import tree with an optional alias.
     Item {
@@ -100,6 +122,29 @@ pub enum ImportTreeKind {
     List(Vec<ImportTree>),
 }
 
+/// The kind of an export statement.
+///
+/// Items can also be exported where they are defined, which is recorded in their [`Visibility`].
+#[derive(Debug, Clone, strum::IntoStaticStr)]
+pub enum ExportKind {
+    /// Names defined in the module, each with an optional alias (e.g. `export { a, b as c }`).
+    List(Vec<ExportItem>),
+    /// Every item exported by another module (e.g. `export * from "path"`).
+    Glob {
+        /// The path of the module, as written in the string literal.
+        path: lasso::Spur,
+    },
+}
+
+/// A name in an export list.
+#[derive(Debug, Clone)]
+pub struct ExportItem {
+    /// The name being exported.
+    pub name: Ident,
+    /// An optional alias the name is exported as.
+    pub alias: Option<Ident>,
+}
+
 /// Represents a type alias.
 #[derive(Debug, Clone)]
 pub struct TypeAlias {
@@ -113,23 +158,33 @@ pub struct TypeAlias {
 #[derive(Debug, Clone)]
 pub struct Type {
     /// The span of the type in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
     /// The kind of type (primitive, function, tuple, etc.).
     pub kind: TypeKind,
 }
 
 /// Represents the kind of type in the source code.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, strum::IntoStaticStr)]
 pub enum TypeKind {
     /// A primitive type (e.g., int, float, bool, string, unit).
     Primitive(PrimitiveTypeKind),
     /// A named type, which refers to a type with a specific identifier.
     Named(Ident),
+    /// A named type applied to type arguments (e.g., `List<int>`).
+    Generic {
+        /// The name of the generic type.
+        name: Ident,
+        /// The type arguments.
+        arguments: Vec<Type>,
+    },
     /// A tuple type containing multiple types.
     Tuple(Vec<Type>),
     /// A list type containing elements of a single type.
     List(Box<Type>),
-    /// A record type with named fields.
+    /// A record type with named fields, in the order they are written.
+    ///
+    /// Records are identified by the names of their fields, so the order of the fields does not
+    /// change the type: `{ x: int, y: int }` and `{ y: int, x: int }` are the same type.
     Record(indexmap::IndexMap<Ident, Type>),
     /// A function type with parameters and a return type.
     Fn(Vec<Type>, Box<Type>),
@@ -152,9 +207,9 @@ pub enum TypeKind {
 /// Enumeration of primitive types.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PrimitiveTypeKind {
-    /// Integer type.
+    /// Signed 64-bit integer type (`int`).
     Integer,
-    /// Natural number type.
+    /// Unsigned 64-bit integer type (`nat`).
     Natural,
     /// Floating-point type.
     Float,
@@ -162,6 +217,8 @@ pub enum PrimitiveTypeKind {
     Bool,
     /// String type.
     String,
+    /// Byte string type (`bytes`), holding arbitrary binary data.
+    Bytes,
     /// Unit type (empty tuple).
     Unit,
 }
@@ -175,17 +232,40 @@ pub struct Definition {
     pub expr: Expr,
 }
 
+/// A function defined by clauses, each of which matches its arguments against a list of patterns.
+///
+/// Calls evaluate the body of the first clause whose patterns all match the arguments.
+#[derive(Debug, Clone)]
+pub struct Function {
+    /// The name of the function.
+    pub name: Ident,
+    /// The clauses of the function, in the order they are tried. Every clause has the same
+    /// number of patterns.
+    pub clauses: Vec<Clause>,
+}
+
+/// A single clause of a [`Function`] (e.g. `fn fib 0 = 0`).
+#[derive(Debug, Clone)]
+pub struct Clause {
+    /// The span of the clause in the source code.
+    pub span: kali_span::Span,
+    /// The patterns the arguments are matched against, one per argument.
+    pub patterns: Vec<Pattern>,
+    /// The expression to evaluate if every pattern matches.
+    pub body: Expr,
+}
+
 /// Represents an expression item in the source code.
 #[derive(Debug, Clone)]
 pub struct Expr {
     /// The span of the expression in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
     /// The kind of expression.
     pub kind: ExprKind,
 }
 
 /// Represents the kind of expression.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, strum::IntoStaticStr)]
 pub enum ExprKind {
     /// A variable expression.
     Var(Ident),
@@ -202,15 +282,41 @@ pub enum ExprKind {
     },
     /// A unary expression.
     UnaryExpr { op: UnaryOp, expr: Box<Expr> },
+    /// An operator section, a binary operator given only one of its operands (e.g. `(+ 1)` or
+    /// `(2 *)`), which is a function of the other.
+    Section {
+        /// The binary operator of the section.
+        op: BinaryOp,
+        /// The operand given to the operator.
+        operand: Box<Expr>,
+        /// The side of the operator the given operand is on.
+        side: Side,
+    },
     /// A tuple expression.
     Tuple(Vec<Expr>),
     /// A list expression.
     List(Vec<Expr>),
     /// A record expression.
     Record {
-        /// The fields of the record, mapping identifiers to expressions.
+        /// The fields of the record, mapping identifiers to expressions, in the order they are
+        /// written and evaluated.
         fields: indexmap::IndexMap<Ident, Expr>,
     },
+    /// A set expression (e.g. `#[1, 2]`), holding each of its elements once.
+    Set(Vec<Expr>),
+    /// A range expression (e.g. `1..10` or `1..=10`), of the numbers from its start up to its
+    /// end, which are counted as they are iterated over rather than stored.
+    Range {
+        /// The first number of the range.
+        start: Box<Expr>,
+        /// The number the range stops at.
+        end: Box<Expr>,
+        /// Whether the range includes its end, as in `1..=10`.
+        inclusive: bool,
+    },
+    /// A map expression (e.g. `#{ "a": 1 }`), mapping keys to values, in the order they are
+    /// written and evaluated.
+    Map(Vec<(Expr, Expr)>),
     /// A conditional expression (if-else).
     Conditional {
         /// The condition to evaluate.
@@ -227,6 +333,26 @@ pub enum ExprKind {
         /// The list of match arms.
         arms: Vec<MatchArm>,
     },
+    /// A while loop (e.g. `while x do y`), evaluating its body for as long as its condition holds.
+    While {
+        /// The condition checked before each iteration.
+        condition: Box<Expr>,
+        /// The expression evaluated on each iteration.
+        body: Box<Expr>,
+    },
+    /// A for loop (e.g. `for x in xs do y`), evaluating its body for each element of a list and
+    /// collecting the results into a list.
+    For {
+        /// The destructor each element is bound to.
+        binding: Destructor,
+        /// The list iterated over.
+        iterable: Box<Expr>,
+        /// The expression evaluated for each element.
+        body: Box<Expr>,
+    },
+    /// A block of expressions separated by `;` (e.g. `do { a; b }`), evaluated in order, whose
+    /// value is that of the last. The expressions before it are evaluated for their effects.
+    Sequence(Vec<Expr>),
     /// A lambda expression.
     Lambda {
         /// The params to the lambda function.
@@ -243,14 +369,45 @@ pub enum ExprKind {
         /// The arguments passed to the function.
         arguments: Vec<Expr>,
     },
+    /// A list spread into a list literal or an argument list (e.g. `..xs`).
+    Spread(Box<Expr>),
+    /// An argument passed to a parameter by name (e.g. `x: 1`).
+    Labelled {
+        /// The name of the parameter.
+        label: Ident,
+        /// The value passed to the parameter.
+        value: Box<Expr>,
+    },
+    /// An error propagation (e.g. `f(x)?`), which is the value of an `ok` result, and returns
+    /// an `err` result from the enclosing function.
+    Try(Box<Expr>),
+    /// A tuple projection (e.g. `t.0`), the element of a tuple at an index counted from zero.
+    Projection {
+        /// The tuple projected from.
+        tuple: Box<Expr>,
+        /// The index of the element.
+        index: usize,
+    },
+    /// An index into a list (e.g. `xs[0]`), the element at a position counted from zero, which
+    /// fails when the program runs if the list is too short.
+    Index {
+        /// The list indexed.
+        list: Box<Expr>,
+        /// The position of the element.
+        index: Box<Expr>,
+    },
+    /// A typed hole (e.g. `?todo`), standing in for an expression that is yet to be written.
+    Hole(Ident),
+    /// A placeholder for an expression that failed to parse.
+    Error,
 }
 
 #[derive(Debug, Clone)]
 /// Represents the kind of literal in the source code.
 pub enum LiteralKind {
-    /// A natural number literal (e.g., `42`).
+    /// A natural number literal (e.g., `42`), which fits in a `nat`.
     Natural(u64),
-    /// An integer literal (e.g., `-7`).
+    /// A negative integer literal (e.g., `-7`), which fits in an `int`.
     Integer(i64),
     /// A floating-point literal (e.g., `3.14`).
     Float(f64),
@@ -258,6 +415,8 @@ pub enum LiteralKind {
     Bool(bool),
     /// A string literal (e.g., `"hello"`).
     String(lasso::Spur),
+    /// A byte string literal (e.g., `b"\x89PNG"`).
+    Bytes(Vec<u8>),
     /// A unit literal (empty tuple, `()`).
     Unit,
 }
@@ -268,7 +427,7 @@ pub struct BinaryOp {
     /// The kind of binary operator (e.g., addition, subtraction, etc.).
     pub kind: BinaryOpKind,
     /// The span of the binary operator in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
 }
 
 /// Represents the kind of binary operator in the source code.
@@ -316,6 +475,17 @@ pub enum BinaryOpKind {
     Cons,
     #[strum(to_string = "@")]
     Concat,
+    #[strum(to_string = "|>")]
+    Pipe,
+}
+
+/// The side of a binary operator an operand is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The left-hand side, as in `(2 *)`.
+    Left,
+    /// The right-hand side, as in `(+ 1)`.
+    Right,
 }
 
 /// Represents a unary operator in the source code, including its kind and span.
@@ -324,7 +494,7 @@ pub struct UnaryOp {
     /// The kind of unary operator (e.g., negation, logical not, bitwise not).
     pub kind: UnaryOpKind,
     /// The span of the unary operator in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
 }
 
 /// An enumeration of unary operators.
@@ -348,7 +518,7 @@ pub enum UnaryOpKind {
 #[derive(Debug, Clone)]
 pub struct MatchArm {
     /// The span of the match arm in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
     /// The pattern to match.
     pub pattern: Pattern,
     /// The expression to execute if the pattern matches.
@@ -359,13 +529,13 @@ pub struct MatchArm {
 #[derive(Debug, Clone)]
 pub struct Pattern {
     /// The span of the pattern in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
     /// The kind of pattern (literal, identifier, tuple, etc.).
     pub kind: PatternKind,
 }
 
 /// The different kinds of patterns.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, strum::IntoStaticStr)]
 pub enum PatternKind {
     /// A literal pattern (e.g., `42`, `"foo"`, `true`).
     Literal(LiteralKind),
@@ -375,7 +545,7 @@ pub enum PatternKind {
     Tuple(Vec<Pattern>),
     /// A wildcard pattern (`_`).
     Wildcard,
-    /// A record pattern (e.g., `{ x, y }`).
+    /// A record pattern (e.g., `{ x, y }`), whose fields are matched by name.
     Record(indexmap::IndexMap<Ident, Pattern>),
     /// A cons pattern (e.g., `a :: b`).
     Cons {
@@ -384,6 +554,23 @@ pub enum PatternKind {
     },
     /// The empty list.
     EmptyList,
+    /// A range pattern (e.g., `1..10` or `1..=10`), matching the numbers in the range.
+    Range {
+        /// The first number matched.
+        start: LiteralKind,
+        /// The number the range stops at.
+        end: LiteralKind,
+        /// Whether the end is matched too, as in `1..=10`.
+        inclusive: bool,
+    },
+    /// A string prefix pattern (e.g., `"GET " ++ path`), matching strings that start with the
+    /// prefix and matching the rest of the string against `rest`.
+    Prefix {
+        /// The characters the string starts with.
+        prefix: lasso::Spur,
+        /// The pattern the rest of the string is matched against.
+        rest: Box<Pattern>,
+    },
     /// An or-pattern (e.g., `A | B`).
     Or {
         lhs: Box<Pattern>,
@@ -400,25 +587,27 @@ pub struct LambdaParam {
     pub parameter: Destructor,
     /// The optional type annotation for the argument.
     pub ty: Option<Type>,
+    /// The value passed when a call omits the argument (e.g. `greeting = "hello"`).
+    pub default: Option<Expr>,
 }
 
 /// Represents a destructor pattern, which is a pattern with only free variables.
 #[derive(Debug, Clone)]
 pub struct Destructor {
     /// The span of the destructor in the source code.
-    pub span: chumsky::span::SimpleSpan,
+    pub span: kali_span::Span,
     /// THe kind of destructor.
     pub kind: DestructorKind,
 }
 
 /// Represents the kind of a destructor pattern.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, strum::IntoStaticStr)]
 pub enum DestructorKind {
     /// A simple destructor with a single identifier.
     Var(Ident),
     /// A tuple destructor with multiple patterns.
     Tuple(Vec<Destructor>),
-    /// A record destructor with named fields.
+    /// A record destructor with named fields, which are bound by name.
     Record(indexmap::IndexMap<Ident, Destructor>),
     /// A cons destructor (e.g., `a :: b`).
     Cons {
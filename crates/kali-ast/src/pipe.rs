@@ -0,0 +1,52 @@
+//! Rewriting of pipelines into calls.
+//!
+//! The pipeline operator passes a value to a function, so `x |> f` is `f(x)`. When the function
+//! is itself a call, the value is added to its arguments, so that `xs |> map f` is `map(f, xs)`.
+//! Pipelines are left-associative, and `x |> f |> g y` is `g(y, f(x))`.
+
+use crate::{Expr, ExprKind};
+
+/// Where a piped value is placed among the arguments of a call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Placement {
+    /// The value is passed after the other arguments, so `x |> f y` is `f(y, x)`.
+    #[default]
+    Last,
+    /// The value is passed before the other arguments, so `x |> f y` is `f(x, y)`.
+    First,
+}
+
+/// Builds the call a pipeline stands for.
+///
+/// # Arguments
+///
+/// * `value` - The value being piped, on the left of `|>`.
+/// * `function` - The function the value is piped into, on the right of `|>`.
+/// * `placement` - Where the value is placed if `function` is a call.
+///
+/// # Returns
+///
+/// A call of `function` with `value` as an argument, spanning both.
+pub fn pipe(value: Expr, function: Expr, placement: Placement) -> Expr {
+    let span = value.span.union(function.span);
+    let kind = match function.kind {
+        ExprKind::Call {
+            function,
+            mut arguments,
+        } => {
+            match placement {
+                Placement::Last => arguments.push(value),
+                Placement::First => arguments.insert(0, value),
+            }
+            ExprKind::Call {
+                function,
+                arguments,
+            }
+        }
+        _ => ExprKind::Call {
+            function: Box::new(function),
+            arguments: vec![value],
+        },
+    };
+    Expr { span, kind }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    Definition, Destructor, Expr, ExprKind, Ident, ImportTree, ImportTreeKind, Item, ItemKind,
-    LambdaParam, MatchArm, Module, Pattern, Type, TypeAlias, TypeKind,
+    Clause, Definition, Destructor, DestructorKind, ExportKind, Expr, ExprKind, Function, Ident,
+    ImportTree, ImportTreeKind, Item, ItemKind, LambdaParam, MatchArm, Module, Pattern,
+    PatternKind, Type, TypeAlias, TypeKind,
 };
 
 /// A trait for visiting elements of the Kali Abstract Syntax Tree (AST).
@@ -19,6 +20,7 @@ use crate::{
 /// * `visit` - The method responsible for visiting a specific target of type `T`
 ///   within the Kali AST. It takes a reference to the target and returns a `Result`
 ///   indicating success or failure.
+#[allow(unused_variables)]
 pub trait Visitor {
     /// The associated error type that may be returned during the visit operation.
     type Error;
@@ -101,6 +103,32 @@ pub trait Visitor {
         Ok(())
     }
 
+    /// Visits a function within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `function` - A reference to the `Function` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_function(&mut self, function: &Function) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a clause of a function within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `clause` - A reference to the `Clause` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_clause(&mut self, clause: &Clause) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Visits an identifier within the Kali AST.
     ///
     /// # Arguments
@@ -183,7 +211,7 @@ pub trait Visitor {
 /// A tree walker for the Kali AST.
 pub struct Walker {
     /// Specifies the order in which the AST should be walked.
-    order: WalkOrder,
+    pub(crate) order: WalkOrder,
 }
 
 /// Represents the order in which the AST should be walked.
@@ -196,6 +224,11 @@ pub enum WalkOrder {
 }
 
 impl Walker {
+    /// Creates a walker that visits nodes in the given order.
+    pub fn new(order: WalkOrder) -> Walker {
+        Walker { order }
+    }
+
     /// Walks a module within the Kali AST.
     ///
     /// # Arguments
@@ -239,8 +272,19 @@ impl Walker {
         }
         match &item.kind {
             ItemKind::Import(import_tree) => self.walk_import_tree(visitor, import_tree)?,
+            ItemKind::Export(ExportKind::List(items)) => {
+                for item in items {
+                    self.walk_ident(visitor, &item.name)?;
+                    if let Some(alias) = &item.alias {
+                        self.walk_ident(visitor, alias)?;
+                    }
+                }
+            }
+            ItemKind::Export(ExportKind::Glob { .. }) => {}
             ItemKind::TypeAlias(type_alias) => self.walk_type_alias(visitor, type_alias)?,
             ItemKind::Definition(definition) => self.walk_definition(visitor, definition)?,
+            ItemKind::Function(function) => self.walk_function(visitor, function)?,
+            ItemKind::Error => {}
         }
         if let WalkOrder::PostOrder = self.order {
             visitor.visit_item(item)?;
@@ -339,6 +383,12 @@ impl Walker {
             TypeKind::Named(ident) => {
                 self.walk_ident(visitor, ident)?;
             }
+            TypeKind::Generic { name, arguments } => {
+                self.walk_ident(visitor, name)?;
+                for argument in arguments {
+                    self.walk_type(visitor, argument)?;
+                }
+            }
             TypeKind::Tuple(items) => {
                 for item in items {
                     self.walk_type(visitor, item)?;
@@ -401,6 +451,62 @@ impl Walker {
         Ok(())
     }
 
+    /// Walks a function within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `Visitor` implementation.
+    /// * `function` - A reference to the `Function` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_function<V: Visitor>(
+        &self,
+        visitor: &mut V,
+        function: &Function,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_function(function)?;
+        }
+        self.walk_ident(visitor, &function.name)?;
+        for clause in &function.clauses {
+            self.walk_clause(visitor, clause)?;
+        }
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_function(function)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a clause of a function within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `Visitor` implementation.
+    /// * `clause` - A reference to the `Clause` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_clause<V: Visitor>(
+        &self,
+        visitor: &mut V,
+        clause: &Clause,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_clause(clause)?;
+        }
+        for pattern in &clause.patterns {
+            self.walk_pattern(visitor, pattern)?;
+        }
+        self.walk_expr(visitor, &clause.body)?;
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_clause(clause)?;
+        }
+        Ok(())
+    }
+
     /// Walks an identifier within the Kali AST.
     ///
     /// # Arguments
@@ -435,23 +541,41 @@ impl Walker {
                 self.walk_ident(visitor, ident)?;
             }
             ExprKind::Literal(_) => {}
-            ExprKind::BinaryExpr { op: _, lhs, rhs } => {
+            ExprKind::BinaryExpr { op: _, lhs, rhs }
+            | ExprKind::Range {
+                start: lhs,
+                end: rhs,
+                ..
+            }
+            | ExprKind::Index {
+                list: lhs,
+                index: rhs,
+            } => {
                 self.walk_expr(visitor, lhs)?;
                 self.walk_expr(visitor, rhs)?;
             }
             ExprKind::UnaryExpr { op: _, expr } => {
                 self.walk_expr(visitor, expr)?;
             }
+            ExprKind::Section { operand, .. } => {
+                self.walk_expr(visitor, operand)?;
+            }
             ExprKind::Tuple(exprs) => {
                 for e in exprs {
                     self.walk_expr(visitor, e)?;
                 }
             }
-            ExprKind::List(exprs) => {
+            ExprKind::List(exprs) | ExprKind::Set(exprs) => {
                 for e in exprs {
                     self.walk_expr(visitor, e)?;
                 }
             }
+            ExprKind::Map(entries) => {
+                for (key, value) in entries {
+                    self.walk_expr(visitor, key)?;
+                    self.walk_expr(visitor, value)?;
+                }
+            }
             ExprKind::Record { fields } => {
                 for (_, value) in fields {
                     self.walk_expr(visitor, value)?;
@@ -474,6 +598,24 @@ impl Walker {
                     self.walk_match_arm(visitor, arm)?;
                 }
             }
+            ExprKind::While { condition, body } => {
+                self.walk_expr(visitor, condition)?;
+                self.walk_expr(visitor, body)?;
+            }
+            ExprKind::For {
+                binding,
+                iterable,
+                body,
+            } => {
+                self.walk_destructor(visitor, binding)?;
+                self.walk_expr(visitor, iterable)?;
+                self.walk_expr(visitor, body)?;
+            }
+            ExprKind::Sequence(exprs) => {
+                for e in exprs {
+                    self.walk_expr(visitor, e)?;
+                }
+            }
             ExprKind::Lambda {
                 params,
                 ret_ty,
@@ -496,6 +638,15 @@ impl Walker {
                     self.walk_expr(visitor, arg)?;
                 }
             }
+            ExprKind::Spread(expr)
+            | ExprKind::Try(expr)
+            | ExprKind::Projection { tuple: expr, .. } => {
+                self.walk_expr(visitor, expr)?;
+            }
+            ExprKind::Labelled { value, .. } => {
+                self.walk_expr(visitor, value)?;
+            }
+            ExprKind::Hole(_) | ExprKind::Error => {}
         }
 
         if let WalkOrder::PostOrder = self.order {
@@ -519,8 +670,14 @@ impl Walker {
         visitor: &mut V,
         match_arm: &MatchArm,
     ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_match_arm(match_arm)?;
+        }
         self.walk_pattern(visitor, &match_arm.pattern)?;
         self.walk_expr(visitor, &match_arm.expr)?;
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_match_arm(match_arm)?;
+        }
         Ok(())
     }
 
@@ -542,6 +699,32 @@ impl Walker {
         if let WalkOrder::PreOrder = self.order {
             visitor.visit_pattern(pattern)?;
         }
+        match &pattern.kind {
+            PatternKind::Var(ident) => {
+                self.walk_ident(visitor, ident)?;
+            }
+            PatternKind::Tuple(patterns) => {
+                for pattern in patterns {
+                    self.walk_pattern(visitor, pattern)?;
+                }
+            }
+            PatternKind::Record(fields) => {
+                for (key, value) in fields {
+                    self.walk_ident(visitor, key)?;
+                    self.walk_pattern(visitor, value)?;
+                }
+            }
+            PatternKind::Prefix { rest, .. } => self.walk_pattern(visitor, rest)?,
+            PatternKind::Cons { lhs, rhs } | PatternKind::Or { lhs, rhs } => {
+                self.walk_pattern(visitor, lhs)?;
+                self.walk_pattern(visitor, rhs)?;
+            }
+            PatternKind::Literal(_)
+            | PatternKind::Wildcard
+            | PatternKind::EmptyList
+            | PatternKind::Range { .. }
+            | PatternKind::Rest => {}
+        }
         if let WalkOrder::PostOrder = self.order {
             visitor.visit_pattern(pattern)?;
         }
@@ -566,6 +749,27 @@ impl Walker {
         if let WalkOrder::PreOrder = self.order {
             visitor.visit_destructor(destructor)?;
         }
+        match &destructor.kind {
+            DestructorKind::Var(ident) => {
+                self.walk_ident(visitor, ident)?;
+            }
+            DestructorKind::Tuple(destructors) => {
+                for destructor in destructors {
+                    self.walk_destructor(visitor, destructor)?;
+                }
+            }
+            DestructorKind::Record(fields) => {
+                for (key, value) in fields {
+                    self.walk_ident(visitor, key)?;
+                    self.walk_destructor(visitor, value)?;
+                }
+            }
+            DestructorKind::Cons { lhs, rhs } => {
+                self.walk_destructor(visitor, lhs)?;
+                self.walk_destructor(visitor, rhs)?;
+            }
+            DestructorKind::Rest => {}
+        }
         if let WalkOrder::PostOrder = self.order {
             visitor.visit_destructor(destructor)?;
         }
@@ -594,6 +798,9 @@ impl Walker {
         if let Some(param_type) = &lambda_param.ty {
             self.walk_type(visitor, param_type)?;
         }
+        if let Some(default) = &lambda_param.default {
+            self.walk_expr(visitor, default)?;
+        }
         if let WalkOrder::PostOrder = self.order {
             visitor.visit_lambda_param(lambda_param)?;
         }
@@ -0,0 +1,826 @@
+//! Mutable traversal of the Kali AST.
+//!
+//! [`VisitorMut`] mirrors [`Visitor`](crate::visit::Visitor), but is given mutable references to
+//! the nodes it visits, so that passes such as constant folding, desugaring and renaming can
+//! rewrite the tree in place. It is driven by the `walk_*_mut` methods of [`Walker`].
+
+use indexmap::IndexMap;
+
+use crate::{
+    Clause, Definition, Destructor, DestructorKind, ExportKind, Expr, ExprKind, Function, Ident,
+    ImportTree, ImportTreeKind, Item, ItemKind, LambdaParam, MatchArm, Module, Pattern,
+    PatternKind, Type, TypeAlias, TypeKind,
+    visit::{WalkOrder, Walker},
+};
+
+/// A trait for visiting and modifying elements of the Kali Abstract Syntax Tree (AST).
+///
+/// This trait provides a mechanism for modifying individual elements of the Kali AST in place.
+/// In a pre-order walk, a node is visited before its children are walked, so that changes made
+/// to the node determine which children are walked.
+///
+/// # Associated Types
+///
+/// * `Error` - The type of error that may be returned during the visit operation.
+#[allow(unused_variables)]
+pub trait VisitorMut {
+    /// The associated error type that may be returned during the visit operation.
+    type Error;
+
+    /// Visits a module within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - A mutable reference to the `Module` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_module_mut(&mut self, module: &mut Module) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits an item within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - A mutable reference to the `Item` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_item_mut(&mut self, item: &mut Item) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits an import tree within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `import_tree` - A mutable reference to the `ImportTree` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_import_tree_mut(&mut self, import_tree: &mut ImportTree) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a type alias within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_alias` - A mutable reference to the `TypeAlias` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_type_alias_mut(&mut self, type_alias: &mut TypeAlias) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a type within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - A mutable reference to the `Type` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_type_mut(&mut self, ty: &mut Type) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a definition within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `definition` - A mutable reference to the `Definition` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_definition_mut(&mut self, definition: &mut Definition) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a function within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `function` - A mutable reference to the `Function` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_function_mut(&mut self, function: &mut Function) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a clause of a function within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `clause` - A mutable reference to the `Clause` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_clause_mut(&mut self, clause: &mut Clause) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits an identifier within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `ident` - A mutable reference to the `Ident` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_ident_mut(&mut self, ident: &mut Ident) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits an expression within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - A mutable reference to the `Expr` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a match arm within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `match_arm` - A mutable reference to the `MatchArm` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_match_arm_mut(&mut self, match_arm: &mut MatchArm) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a pattern within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A mutable reference to the `Pattern` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_pattern_mut(&mut self, pattern: &mut Pattern) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a destructor within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `destructor` - A mutable reference to the `Destructor` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_destructor_mut(&mut self, destructor: &mut Destructor) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Visits a lambda parameter within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `lambda_param` - A mutable reference to the `LambdaParam` to be visited.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn visit_lambda_param_mut(
+        &mut self,
+        lambda_param: &mut LambdaParam,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl Walker {
+    /// Walks a module within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `module` - A mutable reference to the `Module` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_module_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        module: &mut Module,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_module_mut(module)?;
+        }
+        for item in &mut module.items {
+            self.walk_item_mut(visitor, item)?;
+        }
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_module_mut(module)?;
+        }
+        Ok(())
+    }
+
+    /// Walks an item within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `item` - A mutable reference to the `Item` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_item_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        item: &mut Item,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_item_mut(item)?;
+        }
+        match &mut item.kind {
+            ItemKind::Import(import_tree) => self.walk_import_tree_mut(visitor, import_tree)?,
+            ItemKind::Export(ExportKind::List(items)) => {
+                for item in items {
+                    self.walk_ident_mut(visitor, &mut item.name)?;
+                    if let Some(alias) = &mut item.alias {
+                        self.walk_ident_mut(visitor, alias)?;
+                    }
+                }
+            }
+            ItemKind::Export(ExportKind::Glob { .. }) => {}
+            ItemKind::TypeAlias(type_alias) => self.walk_type_alias_mut(visitor, type_alias)?,
+            ItemKind::Definition(definition) => self.walk_definition_mut(visitor, definition)?,
+            ItemKind::Function(function) => self.walk_function_mut(visitor, function)?,
+            ItemKind::Error => {}
+        }
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_item_mut(item)?;
+        }
+        Ok(())
+    }
+
+    /// Walks an import tree within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `import_tree` - A mutable reference to the `ImportTree` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_import_tree_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        import_tree: &mut ImportTree,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_import_tree_mut(import_tree)?;
+        }
+
+        match &mut import_tree.kind {
+            ImportTreeKind::Item { name, alias } => {
+                visitor.visit_ident_mut(name)?;
+                if let Some(alias) = alias {
+                    visitor.visit_ident_mut(alias)?;
+                }
+            }
+            ImportTreeKind::Segment { name, child } => {
+                visitor.visit_ident_mut(name)?;
+                self.walk_import_tree_mut(visitor, child)?;
+            }
+            ImportTreeKind::Glob => {
+                visitor.visit_import_tree_mut(import_tree)?;
+            }
+            ImportTreeKind::List(import_trees) => {
+                for tree in import_trees {
+                    self.walk_import_tree_mut(visitor, tree)?;
+                }
+            }
+        }
+
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_import_tree_mut(import_tree)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a type alias within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `type_alias` - A mutable reference to the `TypeAlias` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_type_alias_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        type_alias: &mut TypeAlias,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_type_alias_mut(type_alias)?;
+        }
+        self.walk_type_mut(visitor, &mut type_alias.ty)?;
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_type_alias_mut(type_alias)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a type within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `ty` - A mutable reference to the `Type` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_type_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        ty: &mut Type,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_type_mut(ty)?;
+        }
+
+        match &mut ty.kind {
+            TypeKind::Primitive(_) => {}
+            TypeKind::Named(ident) => {
+                self.walk_ident_mut(visitor, ident)?;
+            }
+            TypeKind::Generic { name, arguments } => {
+                self.walk_ident_mut(visitor, name)?;
+                for argument in arguments {
+                    self.walk_type_mut(visitor, argument)?;
+                }
+            }
+            TypeKind::Tuple(items) => {
+                for item in items {
+                    self.walk_type_mut(visitor, item)?;
+                }
+            }
+            TypeKind::List(inner_type) => {
+                self.walk_type_mut(visitor, inner_type)?;
+            }
+            TypeKind::Record(index_map) => {
+                walk_entries_mut(index_map, |key, value| {
+                    self.walk_ident_mut(visitor, key)?;
+                    self.walk_type_mut(visitor, value)
+                })?;
+            }
+            TypeKind::Fn(params, return_type) => {
+                for param in params {
+                    self.walk_type_mut(visitor, param)?;
+                }
+                self.walk_type_mut(visitor, return_type)?;
+            }
+            crate::TypeKind::Intersection { lhs, rhs } => {
+                self.walk_type_mut(visitor, lhs)?;
+                self.walk_type_mut(visitor, rhs)?;
+            }
+            crate::TypeKind::Union { lhs, rhs } => {
+                self.walk_type_mut(visitor, lhs)?;
+                self.walk_type_mut(visitor, rhs)?;
+            }
+        }
+
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_type_mut(ty)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a definition within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `definition` - A mutable reference to the `Definition` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_definition_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        definition: &mut Definition,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_definition_mut(definition)?;
+        }
+        self.walk_destructor_mut(visitor, &mut definition.name)?;
+        self.walk_expr_mut(visitor, &mut definition.expr)?;
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_definition_mut(definition)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a function within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `function` - A mutable reference to the `Function` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_function_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        function: &mut Function,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_function_mut(function)?;
+        }
+        self.walk_ident_mut(visitor, &mut function.name)?;
+        for clause in &mut function.clauses {
+            self.walk_clause_mut(visitor, clause)?;
+        }
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_function_mut(function)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a clause of a function within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `clause` - A mutable reference to the `Clause` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_clause_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        clause: &mut Clause,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_clause_mut(clause)?;
+        }
+        for pattern in &mut clause.patterns {
+            self.walk_pattern_mut(visitor, pattern)?;
+        }
+        self.walk_expr_mut(visitor, &mut clause.body)?;
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_clause_mut(clause)?;
+        }
+        Ok(())
+    }
+
+    /// Walks an identifier within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `ident` - A mutable reference to the `Ident` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_ident_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        ident: &mut Ident,
+    ) -> Result<(), V::Error> {
+        visitor.visit_ident_mut(ident)?;
+        Ok(())
+    }
+
+    /// Walks an expression within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `expr` - A mutable reference to the `Expr` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_expr_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        expr: &mut Expr,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_expr_mut(expr)?;
+        }
+        match &mut expr.kind {
+            ExprKind::Var(ident) => {
+                self.walk_ident_mut(visitor, ident)?;
+            }
+            ExprKind::Literal(_) => {}
+            ExprKind::BinaryExpr { op: _, lhs, rhs }
+            | ExprKind::Range {
+                start: lhs,
+                end: rhs,
+                ..
+            }
+            | ExprKind::Index {
+                list: lhs,
+                index: rhs,
+            } => {
+                self.walk_expr_mut(visitor, lhs)?;
+                self.walk_expr_mut(visitor, rhs)?;
+            }
+            ExprKind::UnaryExpr { op: _, expr } => {
+                self.walk_expr_mut(visitor, expr)?;
+            }
+            ExprKind::Section { operand, .. } => {
+                self.walk_expr_mut(visitor, operand)?;
+            }
+            ExprKind::Tuple(exprs) => {
+                for e in exprs {
+                    self.walk_expr_mut(visitor, e)?;
+                }
+            }
+            ExprKind::List(exprs) | ExprKind::Set(exprs) => {
+                for e in exprs {
+                    self.walk_expr_mut(visitor, e)?;
+                }
+            }
+            ExprKind::Map(entries) => {
+                for (key, value) in entries {
+                    self.walk_expr_mut(visitor, key)?;
+                    self.walk_expr_mut(visitor, value)?;
+                }
+            }
+            ExprKind::Record { fields } => {
+                for (_, value) in fields {
+                    self.walk_expr_mut(visitor, value)?;
+                }
+            }
+            ExprKind::Conditional {
+                condition,
+                body,
+                otherwise,
+            } => {
+                self.walk_expr_mut(visitor, condition)?;
+                self.walk_expr_mut(visitor, body)?;
+                if let Some(otherwise_expr) = otherwise {
+                    self.walk_expr_mut(visitor, otherwise_expr)?;
+                }
+            }
+            ExprKind::Match { value, arms } => {
+                self.walk_expr_mut(visitor, value)?;
+                for arm in arms {
+                    self.walk_match_arm_mut(visitor, arm)?;
+                }
+            }
+            ExprKind::While { condition, body } => {
+                self.walk_expr_mut(visitor, condition)?;
+                self.walk_expr_mut(visitor, body)?;
+            }
+            ExprKind::For {
+                binding,
+                iterable,
+                body,
+            } => {
+                self.walk_destructor_mut(visitor, binding)?;
+                self.walk_expr_mut(visitor, iterable)?;
+                self.walk_expr_mut(visitor, body)?;
+            }
+            ExprKind::Sequence(exprs) => {
+                for e in exprs {
+                    self.walk_expr_mut(visitor, e)?;
+                }
+            }
+            ExprKind::Lambda {
+                params,
+                ret_ty,
+                body,
+            } => {
+                for param in params {
+                    self.walk_lambda_param_mut(visitor, param)?;
+                }
+                if let Some(return_type) = ret_ty {
+                    self.walk_type_mut(visitor, return_type)?;
+                }
+                self.walk_expr_mut(visitor, body)?;
+            }
+            ExprKind::Call {
+                function,
+                arguments,
+            } => {
+                self.walk_expr_mut(visitor, function)?;
+                for arg in arguments {
+                    self.walk_expr_mut(visitor, arg)?;
+                }
+            }
+            ExprKind::Spread(expr)
+            | ExprKind::Try(expr)
+            | ExprKind::Projection { tuple: expr, .. } => {
+                self.walk_expr_mut(visitor, expr)?;
+            }
+            ExprKind::Labelled { value, .. } => {
+                self.walk_expr_mut(visitor, value)?;
+            }
+            ExprKind::Hole(_) | ExprKind::Error => {}
+        }
+
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_expr_mut(expr)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a match arm within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `match_arm` - A mutable reference to the `MatchArm` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_match_arm_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        match_arm: &mut MatchArm,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_match_arm_mut(match_arm)?;
+        }
+        self.walk_pattern_mut(visitor, &mut match_arm.pattern)?;
+        self.walk_expr_mut(visitor, &mut match_arm.expr)?;
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_match_arm_mut(match_arm)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a pattern within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `pattern` - A mutable reference to the `Pattern` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_pattern_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        pattern: &mut Pattern,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_pattern_mut(pattern)?;
+        }
+        match &mut pattern.kind {
+            PatternKind::Var(ident) => {
+                self.walk_ident_mut(visitor, ident)?;
+            }
+            PatternKind::Tuple(patterns) => {
+                for pattern in patterns {
+                    self.walk_pattern_mut(visitor, pattern)?;
+                }
+            }
+            PatternKind::Record(fields) => {
+                walk_entries_mut(fields, |key, value| {
+                    self.walk_ident_mut(visitor, key)?;
+                    self.walk_pattern_mut(visitor, value)
+                })?;
+            }
+            PatternKind::Prefix { rest, .. } => self.walk_pattern_mut(visitor, rest)?,
+            PatternKind::Cons { lhs, rhs } | PatternKind::Or { lhs, rhs } => {
+                self.walk_pattern_mut(visitor, lhs)?;
+                self.walk_pattern_mut(visitor, rhs)?;
+            }
+            PatternKind::Literal(_)
+            | PatternKind::Wildcard
+            | PatternKind::EmptyList
+            | PatternKind::Range { .. }
+            | PatternKind::Rest => {}
+        }
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_pattern_mut(pattern)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a destructor within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `destructor` - A mutable reference to the `Destructor` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_destructor_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        destructor: &mut Destructor,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_destructor_mut(destructor)?;
+        }
+        match &mut destructor.kind {
+            DestructorKind::Var(ident) => {
+                self.walk_ident_mut(visitor, ident)?;
+            }
+            DestructorKind::Tuple(destructors) => {
+                for destructor in destructors {
+                    self.walk_destructor_mut(visitor, destructor)?;
+                }
+            }
+            DestructorKind::Record(fields) => {
+                walk_entries_mut(fields, |key, value| {
+                    self.walk_ident_mut(visitor, key)?;
+                    self.walk_destructor_mut(visitor, value)
+                })?;
+            }
+            DestructorKind::Cons { lhs, rhs } => {
+                self.walk_destructor_mut(visitor, lhs)?;
+                self.walk_destructor_mut(visitor, rhs)?;
+            }
+            DestructorKind::Rest => {}
+        }
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_destructor_mut(destructor)?;
+        }
+        Ok(())
+    }
+
+    /// Walks a lambda parameter within the Kali AST.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - A mutable reference to the `VisitorMut` implementation.
+    /// * `lambda_param` - A mutable reference to the `LambdaParam` to be walked.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn walk_lambda_param_mut<V: VisitorMut>(
+        &self,
+        visitor: &mut V,
+        lambda_param: &mut LambdaParam,
+    ) -> Result<(), V::Error> {
+        if let WalkOrder::PreOrder = self.order {
+            visitor.visit_lambda_param_mut(lambda_param)?;
+        }
+        self.walk_destructor_mut(visitor, &mut lambda_param.parameter)?;
+        if let Some(param_type) = &mut lambda_param.ty {
+            self.walk_type_mut(visitor, param_type)?;
+        }
+        if let Some(default) = &mut lambda_param.default {
+            self.walk_expr_mut(visitor, default)?;
+        }
+        if let WalkOrder::PostOrder = self.order {
+            visitor.visit_lambda_param_mut(lambda_param)?;
+        }
+        Ok(())
+    }
+}
+
+/// Walks the entries of a map whose keys may be modified.
+///
+/// The keys of a map cannot be modified in place, so the map is rebuilt from its entries, even
+/// if walking one of them fails.
+fn walk_entries_mut<T, E>(
+    map: &mut IndexMap<Ident, T>,
+    mut walk: impl FnMut(&mut Ident, &mut T) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut entries: Vec<_> = std::mem::take(map).into_iter().collect();
+    let result = entries
+        .iter_mut()
+        .try_for_each(|(key, value)| walk(key, value));
+    *map = entries.into_iter().collect();
+    result
+}
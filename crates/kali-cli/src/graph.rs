@@ -0,0 +1,60 @@
+//! The import graph of the modules being checked, for checking modules that do not depend on one
+//! another at once.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use crate::interfaces::Loader;
+
+/// Splits files into layers, each importing only from files in the layers before it.
+///
+/// The files of a layer do not depend on one another, so can be checked in parallel once the
+/// layers before it have been, with the interfaces of their imports already written. Files in an
+/// import cycle are left for the last layer, and keep their order within each layer.
+///
+/// # Arguments
+///
+/// * `files` - The files to check.
+/// * `loader` - The loader resolving the imports of each file.
+pub fn layers(files: &[PathBuf], loader: &Loader) -> Vec<Vec<PathBuf>> {
+    let checked: HashSet<&PathBuf> = files.iter().collect();
+    // the imports of each file that are themselves being checked
+    let mut imports: HashMap<&PathBuf, Vec<PathBuf>> = files
+        .iter()
+        .map(|file| {
+            let module = std::fs::read_to_string(file)
+                .ok()
+                .and_then(|src| kali_parse::parse_str(&src).ok());
+            let dependencies = match module {
+                Some(module) => loader.dependencies(file, &module),
+                // errors are reported when the file is checked itself
+                None => Vec::new(),
+            };
+            let dependencies = dependencies
+                .into_iter()
+                .filter(|dependency| dependency != file && checked.contains(dependency))
+                .collect();
+            (file, dependencies)
+        })
+        .collect();
+
+    let mut layers = Vec::new();
+    let mut remaining: Vec<&PathBuf> = files.iter().collect();
+    while !remaining.is_empty() {
+        let (layer, rest): (Vec<&PathBuf>, Vec<&PathBuf>) = remaining
+            .into_iter()
+            .partition(|file| imports[file].is_empty());
+        if layer.is_empty() {
+            layers.push(rest.into_iter().cloned().collect());
+            break;
+        }
+        for dependencies in imports.values_mut() {
+            dependencies.retain(|dependency| !layer.contains(&dependency));
+        }
+        layers.push(layer.into_iter().cloned().collect());
+        remaining = rest;
+    }
+    layers
+}
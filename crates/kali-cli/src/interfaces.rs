@@ -0,0 +1,264 @@
+//! Loading the interfaces of imported modules, for separate compilation.
+//!
+//! A module `a::b` is found on the search path of the package the importing module belongs to,
+//! or outside of a package, relative to the importing module: imported from `src/main.kali`, it
+//! is the file `src/a/b.kali`. Its interface is written alongside it as `src/a/b.kali-iface`. An
+//! interface is reused while it is newer than its source and the interfaces of the modules it
+//! imports, and the module is type checked again otherwise.
+//!
+//! A module that cannot be found, or that has errors, has no interface, and is reported at the
+//! imports of it. So is a module importing one of those, as its interface would be incomplete.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use kali_ast::{ImportTree, ImportTreeKind, ItemKind, Module};
+use kali_error::Diagnostic;
+use kali_package::Package;
+use kali_span::Span;
+use kali_type::{ModuleInterface, TypeInferenceEngine};
+
+/// The extension of module interface files.
+const EXTENSION: &str = "kali-iface";
+
+/// The interfaces of the modules a module imports.
+pub struct Imports {
+    /// The interfaces of the imported modules that exist and type check, by module path.
+    pub interfaces: HashMap<String, ModuleInterface>,
+    /// When the newest of the interfaces was written.
+    pub newest: SystemTime,
+    /// The imported modules without an interface.
+    pub unresolved: Vec<Unresolved>,
+}
+
+/// An imported module without an interface.
+#[derive(Debug, Clone)]
+pub struct Unresolved {
+    /// The path of the module, e.g. `a::b`.
+    pub path: String,
+    /// The span of the first import from the module.
+    pub span: Span,
+    /// Why the module has no interface.
+    pub reason: Reason,
+}
+
+/// Why an imported module has no interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// The module has no file.
+    Missing,
+    /// The module has errors, or imports a module without an interface.
+    Failed,
+    /// The module imports the module importing it, directly or not.
+    Cycle,
+}
+
+impl Unresolved {
+    /// Returns the error reported at the import of the module. Modules in an import cycle are
+    /// checked without one another's interfaces, so are not reported.
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        let diagnostic = match self.reason {
+            Reason::Missing => {
+                Diagnostic::error(self.span, format!("cannot find module `{}`", self.path))
+            }
+            Reason::Failed => {
+                Diagnostic::error(self.span, format!("module `{}` has errors", self.path))
+                    .with_note("its errors are reported when it is checked")
+            }
+            Reason::Cycle => return None,
+        };
+        Some(diagnostic)
+    }
+}
+
+/// Loads the interfaces of modules, type checking those whose interfaces are out of date.
+#[derive(Default)]
+pub struct Loader {
+    /// The package being compiled, whose search path imports are resolved on.
+    package: Option<Package>,
+    /// The interfaces loaded so far, with when they were last written, by the path of their
+    /// module, or why the module has none.
+    loaded: HashMap<PathBuf, Result<(ModuleInterface, SystemTime), Reason>>,
+    /// The modules being loaded, to stop at import cycles.
+    loading: HashSet<PathBuf>,
+}
+
+impl Loader {
+    /// Creates a loader resolving imports within a package, or relative to the importing module
+    /// without one.
+    pub fn new(package: Option<Package>) -> Self {
+        Loader {
+            package,
+            ..Default::default()
+        }
+    }
+
+    /// Loads the interfaces of the modules a module imports.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the module.
+    /// * `module` - The parsed module.
+    ///
+    pub fn imports(&mut self, file: &Path, module: &Module) -> Imports {
+        let mut imports = Imports {
+            interfaces: HashMap::new(),
+            newest: SystemTime::UNIX_EPOCH,
+            unresolved: Vec::new(),
+        };
+        for (path, span, file) in self.resolve(file, module) {
+            match file.map_or(Err(Reason::Missing), |file| self.load(&file)) {
+                Ok((interface, modified)) => {
+                    imports.newest = imports.newest.max(modified);
+                    imports.interfaces.insert(path, interface);
+                }
+                Err(reason) => imports.unresolved.push(Unresolved { path, span, reason }),
+            }
+        }
+        imports
+    }
+
+    /// Returns the files of the modules a module imports that could be found.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the module.
+    /// * `module` - The parsed module.
+    pub fn dependencies(&self, file: &Path, module: &Module) -> Vec<PathBuf> {
+        self.resolve(file, module)
+            .into_iter()
+            .filter_map(|(_, _, file)| file)
+            .collect()
+    }
+
+    /// Resolves the paths of the modules a module imports to their files, `None` for those not on
+    /// the search path, alongside the span of the first import from each.
+    fn resolve(&self, file: &Path, module: &Module) -> Vec<(String, Span, Option<PathBuf>)> {
+        let dir = file.parent().unwrap_or(Path::new("."));
+        let search = self
+            .package
+            .as_ref()
+            .and_then(|package| package.owner(file))
+            .map(Package::search_path);
+        imported(module)
+            .into_iter()
+            .map(|(path, span)| {
+                let file = match &search {
+                    Some(search) => search.resolve(&path),
+                    None => Some(
+                        path.split("::")
+                            .fold(dir.to_path_buf(), |file, segment| file.join(segment))
+                            .with_extension("kali"),
+                    ),
+                };
+                (path, span, file)
+            })
+            .collect()
+    }
+
+    /// Loads the interface of a module, type checking it and writing its interface if it is
+    /// missing or out of date.
+    fn load(&mut self, file: &Path) -> Result<(ModuleInterface, SystemTime), Reason> {
+        if let Some(loaded) = self.loaded.get(file) {
+            return loaded.clone();
+        }
+        if !self.loading.insert(file.to_path_buf()) {
+            return Err(Reason::Cycle);
+        }
+        let loaded = self.check(file);
+        self.loading.remove(file);
+        self.loaded.insert(file.to_path_buf(), loaded.clone());
+        loaded
+    }
+
+    /// Reads the interface of a module if it is up to date, or type checks the module otherwise.
+    fn check(&mut self, file: &Path) -> Result<(ModuleInterface, SystemTime), Reason> {
+        let src = std::fs::read_to_string(file).map_err(|_| Reason::Missing)?;
+        let mut module = kali_parse::parse_str(&src).map_err(|_| Reason::Failed)?;
+        let imports = self.imports(file, &module);
+        if imports
+            .unresolved
+            .iter()
+            .any(|unresolved| unresolved.reason != Reason::Cycle)
+        {
+            return Err(Reason::Failed);
+        }
+
+        let path = file.with_extension(EXTENSION);
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|meta| meta.modified());
+        let fresh = match (modified(file), modified(&path)) {
+            (Ok(source), Ok(written)) if written >= source && written >= imports.newest => {
+                Some(written)
+            }
+            _ => None,
+        };
+        if let Some(written) = fresh {
+            let read = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|json| ModuleInterface::from_json(&json).ok());
+            if let Some(interface) = read {
+                return Ok((interface, written));
+            }
+        }
+
+        // errors in the module are reported when it is checked itself
+        kali_desugar::desugar(&mut module);
+        let (types, errors) =
+            TypeInferenceEngine::infer_recovering_with(&module, &imports.interfaces);
+        if !errors.is_empty() {
+            return Err(Reason::Failed);
+        }
+        let interface = ModuleInterface::new(&module, &types);
+        write(file, &interface);
+        Ok((interface, SystemTime::now()))
+    }
+}
+
+/// Writes the interface of a module alongside it, printing a warning if it could not be written.
+pub fn write(file: &Path, interface: &ModuleInterface) {
+    let path = file.with_extension(EXTENSION);
+    if let Err(err) = std::fs::write(&path, interface.to_json()) {
+        eprintln!("warning: could not write `{}`: {}", path.display(), err);
+    }
+}
+
+/// Returns the paths of the modules a module imports from, e.g. `a::b` for `import a::b::c`,
+/// alongside the span of the first import from each.
+fn imported(module: &Module) -> Vec<(String, Span)> {
+    let mut paths = Vec::new();
+    for item in &module.items {
+        if let ItemKind::Import(tree) = &item.kind {
+            let mut imported = Vec::new();
+            paths_of(module, tree, String::new(), &mut imported);
+            paths.extend(imported.into_iter().map(|path| (path, item.span)));
+        }
+    }
+    // sorting is stable, so the first import from each module is kept
+    paths.sort_by(|(a, _), (b, _)| a.cmp(b));
+    paths.dedup_by(|(a, _), (b, _)| a == b);
+    paths
+}
+
+/// Collects the paths of the modules an import tree imports from, given the path leading to it.
+fn paths_of(module: &Module, tree: &ImportTree, path: String, paths: &mut Vec<String>) {
+    match &tree.kind {
+        ImportTreeKind::Item { .. } | ImportTreeKind::Glob if !path.is_empty() => paths.push(path),
+        ImportTreeKind::Item { .. } | ImportTreeKind::Glob => {}
+        ImportTreeKind::Segment { name, child } => {
+            let name = module.cache.resolve(&name.key);
+            let path = match path.is_empty() {
+                true => name.to_string(),
+                false => format!("{}::{}", path, name),
+            };
+            paths_of(module, child, path, paths);
+        }
+        ImportTreeKind::List(trees) => {
+            for tree in trees {
+                paths_of(module, tree, path.clone(), paths);
+            }
+        }
+    }
+}
@@ -1,11 +1,25 @@
-use std::path::PathBuf;
+use std::{
+    cell::RefCell,
+    fmt::Write,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
 use clap::Parser;
-use kali_type::TypeInferenceEngine;
+use kali::Compiler;
+use kali_error::{Diagnostic, LintConfig, LintLevel, UnknownLint, ALL_LINTS};
+use kali_package::{Package, PackageError};
+use kali_parse::trivia::Trivia;
+use kali_print::{Context, FormatConfig, Indent, Print};
+use kali_span::SourceMap;
+use rayon::prelude::*;
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::EnvFilter;
+use tree::Node;
 
-// mod compiler;
+mod graph;
+mod interfaces;
+mod tree;
 
 /// Command line interface for the Kali programming language.
 #[derive(Parser)]
@@ -19,6 +33,32 @@ struct Args {
 
 #[derive(Parser)]
 enum Command {
+    /// Check a file for errors and lints.
+    Check {
+        /// The file to check. Without one, every module of the package in the current directory
+        /// is checked.
+        file: Option<PathBuf>,
+        /// The levels at which lints are reported.
+        #[clap(flatten)]
+        lints: LintArgs,
+        /// How diagnostics are printed.
+        #[clap(long, alias = "format", value_enum, default_value_t = MessageFormat::Human)]
+        message_format: MessageFormat,
+        /// Check that the file is a program, exporting a `main` function to start at.
+        #[clap(long, requires = "file")]
+        bin: bool,
+        /// The number of files checked at once, by default one per CPU.
+        #[clap(short, long, value_name = "N")]
+        jobs: Option<usize>,
+    },
+    /// Format a file, printing the formatted source code.
+    Fmt {
+        /// The file to format.
+        file: PathBuf,
+        /// Rewrite the file in place instead of printing it.
+        #[clap(long)]
+        write: bool,
+    },
     /// Debugging commands.
     Debug {
         /// The kind of debugging to perform.
@@ -27,16 +67,86 @@ enum Command {
     },
 }
 
+/// Flags controlling the level at which lints are reported.
+///
+/// Each flag takes the name of a lint, e.g. `shadowing`, or `warnings` for every lint. Flags naming
+/// a single lint take precedence over `warnings`, and otherwise `--deny` takes precedence over
+/// `--allow`, which takes precedence over `--warn`.
+#[derive(clap::Args)]
+struct LintArgs {
+    /// Report a lint as a warning.
+    #[clap(short = 'W', long = "warn", value_name = "LINT")]
+    warn: Vec<String>,
+    /// Do not report a lint.
+    #[clap(short = 'A', long = "allow", value_name = "LINT")]
+    allow: Vec<String>,
+    /// Report a lint as an error, failing the check.
+    #[clap(short = 'D', long = "deny", value_name = "LINT")]
+    deny: Vec<String>,
+}
+
+impl LintArgs {
+    /// Builds the lint configuration described by the flags.
+    fn config(&self) -> Result<LintConfig, UnknownLint> {
+        let mut config = LintConfig::default();
+        let flags = [
+            (&self.warn, LintLevel::Warn),
+            (&self.allow, LintLevel::Allow),
+            (&self.deny, LintLevel::Deny),
+        ];
+        // apply `warnings` first, so that flags naming a single lint override it
+        for all in [true, false] {
+            for (names, level) in &flags {
+                for name in names.iter().filter(|name| (*name == ALL_LINTS) == all) {
+                    config.set_named(name, *level)?;
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// How diagnostics are printed.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum MessageFormat {
+    /// A report showing the offending source code.
+    Human,
+    /// A single line per diagnostic, prefixed with its location.
+    Short,
+    /// A JSON object per line on standard output, for editors and CI.
+    Json,
+}
+
+/// How `kali debug parse` prints a syntax tree.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum AstFormat {
+    /// The `Debug` representation of the items.
+    Debug,
+    /// The source code the tree formats to.
+    Pretty,
+    /// A JSON object per node, with its kind, span and children.
+    Json,
+    /// A Graphviz graph, with nodes labelled by their kind and span.
+    Dot,
+}
+
 /// The kind of debugging to perform.
 #[derive(Parser)]
 enum DebugKind {
+    #[clap(alias = "tokens")]
     Lex {
         /// The file to lex.
         file: PathBuf,
+        /// Instead of printing the tokens, check that each span covers the text of its token.
+        #[clap(long)]
+        spans: bool,
     },
     Parse {
         /// The file to parse.
         file: PathBuf,
+        /// How the syntax tree is printed.
+        #[clap(long, value_enum, default_value_t = AstFormat::Debug)]
+        format: AstFormat,
     },
     Typecheck {
         /// The file to typecheck.
@@ -44,7 +154,7 @@ enum DebugKind {
     },
 }
 
-fn main() {
+fn main() -> ExitCode {
     let args = Args::parse();
 
     // initialise tracing
@@ -55,10 +165,399 @@ fn main() {
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
     match args.command {
+        Command::Check {
+            file,
+            lints,
+            message_format,
+            bin,
+            jobs,
+        } => check(file.as_deref(), &lints, message_format, bin, jobs),
+        Command::Fmt { file, write } => fmt(&file, write),
         Command::Debug { kind } => match kind {
-            DebugKind::Lex { file } => {}
-            DebugKind::Parse { file } => {}
-            DebugKind::Typecheck { file } => {}
+            DebugKind::Lex { file, spans } => debug_lex(&file, spans),
+            DebugKind::Parse { file, format } => debug_parse(&file, format),
+            DebugKind::Typecheck { file } => debug_typecheck(&file),
+        },
+    }
+}
+
+/// Checks a file, or without one, every module of the package in the current directory.
+///
+/// Imports are resolved on the search path of the package the file is in, if any. Modules that do
+/// not import from one another are checked in parallel on `jobs` threads, and their diagnostics
+/// printed once each layer of the [import graph](graph::layers) has been checked.
+///
+/// Fails if the package could not be loaded, or if any file fails to check.
+fn check(
+    file: Option<&Path>,
+    lints: &LintArgs,
+    format: MessageFormat,
+    bin: bool,
+    jobs: Option<usize>,
+) -> ExitCode {
+    let config = match lints.config() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let dir = match file {
+        Some(file) => file.parent().unwrap_or(Path::new("")).to_path_buf(),
+        None => std::env::current_dir().unwrap_or_default(),
+    };
+    let package = match Package::find(&dir) {
+        Ok(package) => Some(package),
+        // a single file can be checked on its own
+        Err(PackageError::NotFound(_)) if file.is_some() => None,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let files = match (file, &package) {
+        (Some(file), _) => vec![file.to_path_buf()],
+        (None, Some(package)) => match package.modules() {
+            Ok(modules) => modules,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                return ExitCode::FAILURE;
+            }
         },
+        (None, None) => unreachable!("a package is required without a file"),
+    };
+
+    let pool = match rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or_default())
+        .build()
+    {
+        Ok(pool) => pool,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let layers = graph::layers(&files, &interfaces::Loader::new(package.clone()));
+    let mut failed = false;
+    for layer in layers {
+        let checked: Vec<(bool, Output)> = pool.install(|| {
+            layer
+                .par_iter()
+                .map(|file| {
+                    let mut loader = interfaces::Loader::new(package.clone());
+                    let mut out = Output::default();
+                    let ok = check_file(file, &config, format, bin, &mut loader, &mut out);
+                    (ok, out)
+                })
+                .collect()
+        });
+        for (ok, out) in checked {
+            out.print();
+            failed |= !ok;
+        }
+    }
+    match failed {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+/// The output of checking a file, held until it can be printed in order with that of the files
+/// checked alongside it.
+#[derive(Default)]
+struct Output {
+    /// The text printed on standard output.
+    stdout: String,
+    /// The text printed on standard error.
+    stderr: String,
+}
+
+impl Output {
+    /// Prints the output.
+    fn print(&self) {
+        print!("{}", self.stdout);
+        eprint!("{}", self.stderr);
+    }
+}
+
+/// Parses, lints, desugars and typechecks a file, writing every diagnostic to `out`.
+///
+/// The modules the file imports are typechecked from their interfaces where these are up to
+/// date, and the interface of the file is written alongside it if it has no errors. Importing a
+/// module that cannot be found or has errors is an error. With `bin`, the file must also export
+/// an [entry point](kali_type::entry_point).
+///
+/// # Returns
+/// Whether the file could be read, and no diagnostic is an error.
+fn check_file(
+    file: &Path,
+    config: &LintConfig,
+    format: MessageFormat,
+    bin: bool,
+    loader: &mut interfaces::Loader,
+    out: &mut Output,
+) -> bool {
+    let src = match std::fs::read_to_string(file) {
+        Ok(src) => src,
+        Err(err) => {
+            let _ = writeln!(
+                out.stderr,
+                "error: could not read `{}`: {}",
+                file.display(),
+                err
+            );
+            return false;
+        }
+    };
+
+    let path = file.display().to_string();
+    let unresolved = RefCell::new(Vec::new());
+    let mut compiler = Compiler::new()
+        .with_lints(config.clone())
+        .with_imports(|module| {
+            let imports = loader.imports(file, module);
+            unresolved.borrow_mut().extend(imports.unresolved);
+            imports.interfaces
+        });
+    if bin {
+        compiler = compiler.with_entry_point();
+    }
+    let mut compilation = compiler.compile(path.clone(), src);
+    compilation.diagnostics.extend(
+        unresolved
+            .take()
+            .iter()
+            .filter_map(interfaces::Unresolved::diagnostic),
+    );
+    compilation
+        .diagnostics
+        .sort_by_key(|diagnostic| diagnostic.span.start);
+    let (sources, diagnostics) = (compiler.sources(), &compilation.diagnostics);
+
+    match format {
+        MessageFormat::Human => {
+            let mut cache = kali_error::cache(sources);
+            let mut report = Vec::new();
+            for diagnostic in diagnostics {
+                let _ = diagnostic.report(sources).write(&mut cache, &mut report);
+            }
+            out.stderr.push_str(&String::from_utf8_lossy(&report));
+        }
+        MessageFormat::Short => {
+            for diagnostic in diagnostics {
+                let _ = writeln!(
+                    out.stderr,
+                    "{}: {}",
+                    sources.lookup(diagnostic.span),
+                    diagnostic
+                );
+            }
+        }
+        MessageFormat::Json => {
+            for diagnostic in diagnostics {
+                let _ = writeln!(out.stdout, "{}", diagnostic.to_json(sources));
+            }
+        }
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors > 0 {
+        let _ = writeln!(out.stderr, "error: `{}` has {} error(s)", path, errors);
+        false
+    } else {
+        interfaces::write(file, &compilation.interface());
+        true
+    }
+}
+
+/// Formats a file, keeping its comments, and prints it or with `write`, rewrites it.
+///
+/// The file is laid out as configured by the `[format]` table of the manifest of the package it
+/// is in, if any.
+///
+/// Fails if the file could not be read or written, or has syntax errors.
+fn fmt(file: &Path, write: bool) -> ExitCode {
+    let Some(src) = read(file) else {
+        return ExitCode::FAILURE;
+    };
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let config = match Package::find(dir) {
+        Ok(package) => format_config(&package.manifest.format),
+        Err(PackageError::NotFound(_)) => FormatConfig::default(),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut sources = SourceMap::new();
+    let file_id = sources.add(file.display().to_string(), src);
+    let src = sources.source(file_id);
+    let (module, errors) = kali_parse::parse_file_recovering(file_id, src);
+    if !errors.is_empty() {
+        for diagnostic in errors.into_iter().map(Diagnostic::from) {
+            eprintln!("{}: {}", sources.lookup(diagnostic.span), diagnostic);
+        }
+        eprintln!("error: could not format `{}`", file.display());
+        return ExitCode::FAILURE;
+    }
+
+    let trivia = Trivia::collect(file_id, src);
+    let mut out = Vec::new();
+    let mut ctx = Context::new(&mut out, &module.cache)
+        .with_source(src)
+        .with_trivia(&trivia)
+        .with_config(config);
+    if let Err(err) = module.print(&mut ctx) {
+        eprintln!("error: could not print `{}`: {}", file.display(), err);
+        return ExitCode::FAILURE;
+    }
+    if !write {
+        print!("{}", String::from_utf8_lossy(&out));
+        return ExitCode::SUCCESS;
+    }
+    match std::fs::write(file, out) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: could not write `{}`: {}", file.display(), err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Builds the formatter configuration described by the `[format]` table of a manifest.
+fn format_config(format: &kali_package::Format) -> FormatConfig {
+    let default = FormatConfig::default();
+    let indent = match (format.tabs, format.indent_width) {
+        (Some(true), _) => Indent::Tabs,
+        (Some(false), width) | (None, width @ Some(_)) => Indent::Spaces(width.unwrap_or(4)),
+        (None, None) => default.indent,
+    };
+    FormatConfig {
+        max_width: format.max_width.unwrap_or(default.max_width),
+        indent,
+        trailing_commas: format.trailing_commas.unwrap_or(default.trailing_commas),
+        final_newline: format.final_newline.unwrap_or(default.final_newline),
+    }
+}
+
+/// Lexes a file and prints its tokens, or with `spans`, the tokens whose spans have drifted from
+/// their text.
+///
+/// Fails if the file could not be read, or with `spans`, if any token is misaligned.
+fn debug_lex(file: &Path, spans: bool) -> ExitCode {
+    let Some(src) = read(file) else {
+        return ExitCode::FAILURE;
+    };
+    let mut sources = SourceMap::new();
+    let file_id = sources.add(file.display().to_string(), src);
+    let src = sources.source(file_id);
+
+    if !spans {
+        for (token, span) in kali_parse::tokens(file_id, src) {
+            println!(
+                "{} {}..{} {:?}",
+                sources.lookup(span),
+                span.start,
+                span.end,
+                token
+            );
+        }
+        return ExitCode::SUCCESS;
+    }
+    let misaligned = kali_parse::misaligned_tokens(file_id, src);
+    for (token, span) in &misaligned {
+        eprintln!(
+            "{}: error: the span of `{}` covers {:?}",
+            sources.lookup(*span),
+            token,
+            src.get(span.into_range()).unwrap_or_default()
+        );
+    }
+    if misaligned.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Parses a file and prints its syntax tree, alongside any syntax errors.
+///
+/// Fails if the file could not be read or printed, or has syntax errors.
+fn debug_parse(file: &Path, format: AstFormat) -> ExitCode {
+    let Some(src) = read(file) else {
+        return ExitCode::FAILURE;
+    };
+    let mut sources = SourceMap::new();
+    let file_id = sources.add(file.display().to_string(), src);
+    let (module, errors) = kali_parse::parse_file_recovering(file_id, sources.source(file_id));
+    let failed = !errors.is_empty();
+    for diagnostic in errors.into_iter().map(Diagnostic::from) {
+        eprintln!("{}: {}", sources.lookup(diagnostic.span), diagnostic);
+    }
+
+    match format {
+        AstFormat::Debug => println!("{:#?}", module.items),
+        AstFormat::Pretty => {
+            let mut out = std::io::stdout();
+            let mut ctx =
+                Context::new(&mut out, &module.cache).with_source(sources.source(file_id));
+            if let Err(err) = module.print(&mut ctx) {
+                eprintln!("error: could not print `{}`: {}", file.display(), err);
+                return ExitCode::FAILURE;
+            }
+            println!();
+        }
+        AstFormat::Json => println!("{}", Node::of_module(&module).to_json()),
+        AstFormat::Dot => print!("{}", Node::of_module(&module).to_dot()),
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Typechecks a file and prints the type of each of its bindings and type aliases, alongside any
+/// diagnostics. Inference variables left unsolved are named as in a type scheme, unless they are
+/// shared by several bindings, so stand for a single type yet to be solved.
+///
+/// Fails if the file could not be read, or has errors.
+fn debug_typecheck(file: &Path) -> ExitCode {
+    let Some(src) = read(file) else {
+        return ExitCode::FAILURE;
+    };
+    let mut compiler = Compiler::new();
+    let compilation = compiler.compile(file.display().to_string(), src);
+    for diagnostic in &compilation.diagnostics {
+        eprintln!(
+            "{}: {}",
+            compiler.sources().lookup(diagnostic.span),
+            diagnostic
+        );
+    }
+    let shared = compilation.types.shared();
+    for (name, ty) in &compilation.types.aliases {
+        println!("type {} = {}", name, ty.scheme_except(&shared));
+    }
+    for (name, ty) in &compilation.types.bindings {
+        println!("{}: {}", name, ty.scheme_except(&shared));
+    }
+
+    if compilation.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Reads a file, printing an error if it could not be read.
+fn read(file: &Path) -> Option<String> {
+    match std::fs::read_to_string(file) {
+        Ok(src) => Some(src),
+        Err(err) => {
+            eprintln!("error: could not read `{}`: {}", file.display(), err);
+            None
+        }
     }
 }
@@ -0,0 +1,176 @@
+//! A generic tree view of the AST, for `kali debug parse`.
+//!
+//! The tree is recovered from a pre-order walk of the module: every node with a span becomes a
+//! child of the closest node before it whose span contains its own. Nodes without spans of their
+//! own, such as definitions and lambda parameters, are left out, and their children attach to the
+//! enclosing node instead.
+
+use std::{convert::Infallible, fmt::Write};
+
+use kali_ast::{
+    visit::{Visitor, WalkOrder, Walker},
+    Clause, Destructor, Expr, Ident, ImportTree, Item, MatchArm, Module, Pattern, Type,
+};
+use kali_error::json_string;
+use kali_span::Span;
+
+/// A node of the tree, labelled with its kind.
+pub struct Node {
+    /// The kind of the node, e.g. `Expr::BinaryExpr`.
+    pub label: String,
+    /// The span of the node, or `None` for the module.
+    pub span: Option<Span>,
+    /// The nodes within this one, in source order.
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    /// Builds the tree of a module.
+    pub fn of_module(module: &Module) -> Node {
+        let mut builder = Builder {
+            rodeo: &module.cache,
+            stack: vec![Node {
+                label: "Module".to_string(),
+                span: None,
+                children: Vec::new(),
+            }],
+        };
+        let Ok(()) = Walker::new(WalkOrder::PreOrder).walk_module(&mut builder, module);
+        while builder.stack.len() > 1 {
+            builder.close();
+        }
+        builder.stack.pop().unwrap()
+    }
+
+    /// Returns the label of the node followed by its span, if any.
+    fn title(&self) -> String {
+        match self.span {
+            Some(span) => format!("{} {}..{}", self.label, span.start, span.end),
+            None => self.label.clone(),
+        }
+    }
+
+    /// Serialises the tree as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        let span = match self.span {
+            Some(span) => format!("[{},{}]", span.start, span.end),
+            None => "null".to_string(),
+        };
+        let children: Vec<_> = self.children.iter().map(Node::to_json).collect();
+        format!(
+            r#"{{"kind":{},"span":{},"children":[{}]}}"#,
+            json_string(&self.label),
+            span,
+            children.join(",")
+        )
+    }
+
+    /// Renders the tree as a Graphviz graph, with nodes labelled by their kind and span.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph ast {\n    node [shape=box];\n");
+        self.write_dot(&mut out, &mut 0);
+        out.push_str("}\n");
+        out
+    }
+
+    /// Writes the node and the edges to its children, returning the ID of the node.
+    fn write_dot(&self, out: &mut String, next: &mut usize) -> usize {
+        let id = *next;
+        *next += 1;
+        let _ = writeln!(out, "    n{} [label={}];", id, json_string(&self.title()));
+        for child in &self.children {
+            let child = child.write_dot(out, next);
+            let _ = writeln!(out, "    n{} -> n{};", id, child);
+        }
+        id
+    }
+}
+
+/// Returns the label of a node, e.g. `Expr::Var`.
+fn label<'a>(node: &str, kind: impl Into<&'a str>) -> String {
+    format!("{}::{}", node, kind.into())
+}
+
+/// Builds a tree from a pre-order walk, keeping the path from the root to the last node visited.
+struct Builder<'a> {
+    rodeo: &'a lasso::Rodeo,
+    stack: Vec<Node>,
+}
+
+impl Builder<'_> {
+    /// Adds a node below the closest open node that contains it.
+    fn open(&mut self, label: impl Into<String>, span: Span) {
+        while self.stack.len() > 1
+            && !self
+                .stack
+                .last()
+                .and_then(|node| node.span)
+                .is_some_and(|parent| parent.contains(span))
+        {
+            self.close();
+        }
+        self.stack.push(Node {
+            label: label.into(),
+            span: Some(span),
+            children: Vec::new(),
+        });
+    }
+
+    /// Closes the last open node, adding it to the children of its parent.
+    fn close(&mut self) {
+        let node = self.stack.pop().unwrap();
+        self.stack.last_mut().unwrap().children.push(node);
+    }
+}
+
+impl Visitor for Builder<'_> {
+    type Error = Infallible;
+
+    fn visit_item(&mut self, item: &Item) -> Result<(), Infallible> {
+        self.open(label("Item", &item.kind), item.span);
+        Ok(())
+    }
+
+    fn visit_import_tree(&mut self, import_tree: &ImportTree) -> Result<(), Infallible> {
+        self.open(label("ImportTree", &import_tree.kind), import_tree.span);
+        Ok(())
+    }
+
+    fn visit_type(&mut self, ty: &Type) -> Result<(), Infallible> {
+        self.open(label("Type", &ty.kind), ty.span);
+        Ok(())
+    }
+
+    fn visit_clause(&mut self, clause: &Clause) -> Result<(), Infallible> {
+        self.open("Clause", clause.span);
+        Ok(())
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) -> Result<(), Infallible> {
+        self.open(
+            format!("Ident `{}`", self.rodeo.resolve(&ident.key)),
+            ident.span,
+        );
+        Ok(())
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> Result<(), Infallible> {
+        self.open(label("Expr", &expr.kind), expr.span);
+        Ok(())
+    }
+
+    fn visit_match_arm(&mut self, match_arm: &MatchArm) -> Result<(), Infallible> {
+        self.open("MatchArm", match_arm.span);
+        Ok(())
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) -> Result<(), Infallible> {
+        self.open(label("Pattern", &pattern.kind), pattern.span);
+        Ok(())
+    }
+
+    fn visit_destructor(&mut self, destructor: &Destructor) -> Result<(), Infallible> {
+        self.open(label("Destructor", &destructor.kind), destructor.span);
+        Ok(())
+    }
+}
@@ -0,0 +1,518 @@
+//! Compile-time evaluation of constant expressions.
+//!
+//! An expression is constant if it can be evaluated without running the program: it is built
+//! from literals, operators, tuples, lists, records, conditionals and matches, and only refers to
+//! the constant top-level definitions before it. Calls, lambdas, loops and anything else that
+//! needs the program to run are not constant.
+//!
+//! The evaluator follows the semantics the program would have when it runs, so evaluating a
+//! constant expression either gives the value the program would compute, or the error it would
+//! stop with. It works on the syntax tree before or after desugaring. Maps, sets and ranges are
+//! not evaluated yet.
+
+use std::collections::{BTreeMap, HashMap};
+
+use kali_ast::{
+    BinaryOpKind, Destructor, DestructorKind, Expr, ExprKind, ItemKind, LiteralKind, Module,
+    Pattern, PatternKind, UnaryOpKind,
+};
+use kali_span::Span;
+
+mod value;
+
+pub use value::Value;
+
+/// An error raised while evaluating an expression at compile time.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConstEvalError {
+    /// The expression cannot be evaluated without running the program.
+    #[error("expression cannot be evaluated at compile time")]
+    NotConstant(Span),
+    /// An arithmetic operation overflowed the type of its operands.
+    #[error("arithmetic overflow")]
+    Overflow(Span),
+    /// A division or remainder by zero.
+    #[error("division by zero")]
+    DivisionByZero(Span),
+    /// An index past the end of a list.
+    #[error("index {index} is out of bounds for a list of length {length}")]
+    IndexOutOfBounds {
+        /// The index taken.
+        index: u64,
+        /// The length of the list.
+        length: usize,
+        /// The span of the index.
+        span: Span,
+    },
+    /// A match expression without an arm matching its value.
+    #[error("no arm matches the value")]
+    NoMatch(Span),
+}
+
+impl ConstEvalError {
+    /// Returns the span of the expression the error was raised at.
+    pub fn span(&self) -> Span {
+        match self {
+            ConstEvalError::NotConstant(span)
+            | ConstEvalError::Overflow(span)
+            | ConstEvalError::DivisionByZero(span)
+            | ConstEvalError::IndexOutOfBounds { span, .. }
+            | ConstEvalError::NoMatch(span) => *span,
+        }
+    }
+
+    /// Returns whether the error means the program would stop when evaluating the expression,
+    /// rather than that the expression is not constant.
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, ConstEvalError::NotConstant(_))
+    }
+}
+
+/// The constant top-level definitions of a module.
+#[derive(Debug, Clone, Default)]
+pub struct Constants {
+    /// The values of the constant bindings of the module, by name.
+    pub values: BTreeMap<String, Value>,
+    /// The errors the program would stop with when evaluating a definition, in source order.
+    /// Definitions that are not constant are not errors.
+    pub errors: Vec<ConstEvalError>,
+}
+
+/// Evaluates the top-level definitions of a module that are constant.
+///
+/// Definitions are evaluated in order, so a constant may refer to the constants defined before
+/// it, but not to those after it.
+pub fn constants(module: &Module) -> Constants {
+    let mut evaluator = Evaluator::new(&module.cache);
+    let mut errors = Vec::new();
+    for item in &module.items {
+        match &item.kind {
+            ItemKind::Definition(definition) => match evaluator.evaluate(&definition.expr) {
+                Ok(value) => evaluator.define(&definition.name, value),
+                Err(error) => {
+                    evaluator.forget(&definition.name);
+                    if error.is_failure() {
+                        errors.push(error);
+                    }
+                }
+            },
+            // a function shadows a constant of the same name
+            ItemKind::Function(function) => {
+                evaluator.constants.remove(&function.name.key);
+            }
+            _ => {}
+        }
+    }
+    let values = evaluator
+        .constants
+        .iter()
+        .map(|(key, value)| (module.cache.resolve(key).to_string(), value.clone()))
+        .collect();
+    Constants { values, errors }
+}
+
+/// Evaluates constant expressions.
+pub struct Evaluator<'a> {
+    /// The interner that owns the keys of the module.
+    cache: &'a lasso::Rodeo,
+    /// The constants defined so far.
+    constants: HashMap<lasso::Spur, Value>,
+    /// The variables bound by patterns, innermost scope last.
+    scopes: Vec<HashMap<lasso::Spur, Value>>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Creates an evaluator with no constants defined.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The interner that owns the keys of the expressions evaluated.
+    pub fn new(cache: &'a lasso::Rodeo) -> Evaluator<'a> {
+        Evaluator {
+            cache,
+            constants: HashMap::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    /// Binds the names of a destructor to the parts of a value, so that later expressions may
+    /// refer to them. Names the value does not have a part for are forgotten.
+    pub fn define(&mut self, name: &Destructor, value: Value) {
+        let mut bindings = HashMap::new();
+        if self.destructure(name, value, &mut bindings) {
+            self.constants.extend(bindings);
+        } else {
+            self.forget(name);
+        }
+    }
+
+    /// Forgets the constants bound to the names of a destructor, e.g. when they are redefined
+    /// by a definition that is not constant.
+    pub fn forget(&mut self, name: &Destructor) {
+        match &name.kind {
+            DestructorKind::Var(ident) => {
+                self.constants.remove(&ident.key);
+            }
+            DestructorKind::Tuple(names) => names.iter().for_each(|name| self.forget(name)),
+            DestructorKind::Record(fields) => fields.values().for_each(|name| self.forget(name)),
+            DestructorKind::Cons { lhs, rhs } => {
+                self.forget(lhs);
+                self.forget(rhs);
+            }
+            DestructorKind::Rest => {}
+        }
+    }
+
+    /// Binds the names of a destructor to the parts of a value.
+    ///
+    /// # Returns
+    ///
+    /// Whether the value has the shape of the destructor.
+    fn destructure(
+        &self,
+        name: &Destructor,
+        value: Value,
+        bindings: &mut HashMap<lasso::Spur, Value>,
+    ) -> bool {
+        match (&name.kind, value) {
+            (DestructorKind::Var(ident), value) => {
+                bindings.insert(ident.key, value);
+                true
+            }
+            (DestructorKind::Rest, _) => true,
+            (DestructorKind::Tuple(names), Value::Tuple(values)) if names.len() == values.len() => {
+                names
+                    .iter()
+                    .zip(values)
+                    .all(|(name, value)| self.destructure(name, value, bindings))
+            }
+            (DestructorKind::Record(names), Value::Record(mut fields)) => {
+                names.iter().all(|(field, name)| {
+                    fields
+                        .remove(self.cache.resolve(&field.key))
+                        .is_some_and(|value| self.destructure(name, value, bindings))
+                })
+            }
+            (DestructorKind::Cons { lhs, rhs }, Value::List(mut values)) if !values.is_empty() => {
+                let head = values.remove(0);
+                self.destructure(lhs, head, bindings)
+                    && self.destructure(rhs, Value::List(values), bindings)
+            }
+            _ => false,
+        }
+    }
+
+    /// Evaluates an expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConstEvalError::NotConstant`] if the expression is not constant, or the error the
+    /// program would stop with when evaluating it.
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Value, ConstEvalError> {
+        let not_constant = || ConstEvalError::NotConstant(expr.span);
+        match &expr.kind {
+            ExprKind::Var(ident) => self
+                .scopes
+                .iter()
+                .rev()
+                .chain([&self.constants])
+                .find_map(|scope| scope.get(&ident.key))
+                .cloned()
+                .ok_or_else(not_constant),
+            ExprKind::Literal(literal) => Ok(self.literal(literal)),
+            ExprKind::BinaryExpr { op, lhs, rhs } => {
+                let lhs = self.evaluate(lhs)?;
+                // the right-hand side of `&&` and `||` is only evaluated if it is needed
+                match (op.kind, &lhs) {
+                    (BinaryOpKind::LogicalAnd, Value::Bool(false)) => return Ok(lhs),
+                    (BinaryOpKind::LogicalOr, Value::Bool(true)) => return Ok(lhs),
+                    _ => {}
+                }
+                let rhs = self.evaluate(rhs)?;
+                binary(op.kind, lhs, rhs, expr.span)
+            }
+            ExprKind::UnaryExpr { op, expr: operand } => {
+                let value = self.evaluate(operand)?;
+                unary(op.kind, value, expr.span)
+            }
+            ExprKind::Tuple(exprs) => Ok(Value::Tuple(self.evaluate_all(exprs)?)),
+            ExprKind::List(exprs) => {
+                let mut values = Vec::new();
+                for expr in exprs {
+                    match &expr.kind {
+                        ExprKind::Spread(list) => match self.evaluate(list)? {
+                            Value::List(list) => values.extend(list),
+                            _ => return Err(not_constant()),
+                        },
+                        _ => values.push(self.evaluate(expr)?),
+                    }
+                }
+                Ok(Value::List(values))
+            }
+            ExprKind::Record { fields } => {
+                let mut record = BTreeMap::new();
+                for (name, expr) in fields {
+                    let value = self.evaluate(expr)?;
+                    record.insert(self.cache.resolve(&name.key).to_string(), value);
+                }
+                Ok(Value::Record(record))
+            }
+            ExprKind::Conditional {
+                condition,
+                body,
+                otherwise,
+            } => match (self.evaluate(condition)?, otherwise) {
+                (Value::Bool(true), _) => self.evaluate(body),
+                (Value::Bool(false), Some(otherwise)) => self.evaluate(otherwise),
+                (Value::Bool(false), None) => Ok(Value::Unit),
+                _ => Err(not_constant()),
+            },
+            ExprKind::Match { value, arms } => {
+                let value = self.evaluate(value)?;
+                for arm in arms {
+                    let mut scope = HashMap::new();
+                    if self.matches(&arm.pattern, &value, &mut scope) {
+                        self.scopes.push(scope);
+                        let result = self.evaluate(&arm.expr);
+                        self.scopes.pop();
+                        return result;
+                    }
+                }
+                Err(ConstEvalError::NoMatch(expr.span))
+            }
+            ExprKind::Sequence(exprs) => Ok(self.evaluate_all(exprs)?.pop().unwrap_or(Value::Unit)),
+            ExprKind::Projection { tuple, index } => match self.evaluate(tuple)? {
+                Value::Tuple(mut values) if *index < values.len() => Ok(values.swap_remove(*index)),
+                _ => Err(not_constant()),
+            },
+            ExprKind::Index { list, index: at } => match (self.evaluate(list)?, self.evaluate(at)?)
+            {
+                (Value::List(mut values), Value::Natural(index)) => {
+                    let length = values.len();
+                    match usize::try_from(index) {
+                        Ok(index) if index < length => Ok(values.swap_remove(index)),
+                        _ => Err(ConstEvalError::IndexOutOfBounds {
+                            index,
+                            length,
+                            span: at.span,
+                        }),
+                    }
+                }
+                _ => Err(not_constant()),
+            },
+            ExprKind::Section { .. }
+            | ExprKind::Set(_)
+            | ExprKind::Range { .. }
+            | ExprKind::Map(_)
+            | ExprKind::While { .. }
+            | ExprKind::For { .. }
+            | ExprKind::Lambda { .. }
+            | ExprKind::Call { .. }
+            | ExprKind::Spread(_)
+            | ExprKind::Labelled { .. }
+            | ExprKind::Try(_)
+            | ExprKind::Hole(_)
+            | ExprKind::Error => Err(not_constant()),
+        }
+    }
+
+    /// Evaluates expressions in order.
+    fn evaluate_all(&mut self, exprs: &[Expr]) -> Result<Vec<Value>, ConstEvalError> {
+        exprs.iter().map(|expr| self.evaluate(expr)).collect()
+    }
+
+    fn literal(&self, literal: &LiteralKind) -> Value {
+        match literal {
+            LiteralKind::Natural(x) => Value::Natural(*x),
+            LiteralKind::Integer(x) => Value::Integer(*x),
+            LiteralKind::Float(x) => Value::Float(*x),
+            LiteralKind::Bool(x) => Value::Bool(*x),
+            LiteralKind::String(x) => Value::String(self.cache.resolve(x).to_string()),
+            LiteralKind::Bytes(bytes) => Value::Bytes(bytes.clone()),
+            LiteralKind::Unit => Value::Unit,
+        }
+    }
+
+    /// Matches a value against a pattern, binding the variables of the pattern in `scope`.
+    fn matches(
+        &self,
+        pattern: &Pattern,
+        value: &Value,
+        scope: &mut HashMap<lasso::Spur, Value>,
+    ) -> bool {
+        match (&pattern.kind, value) {
+            (PatternKind::Wildcard | PatternKind::Rest, _) => true,
+            (PatternKind::Var(ident), _) => {
+                scope.insert(ident.key, value.clone());
+                true
+            }
+            (PatternKind::Literal(literal), _) => self.literal(literal) == *value,
+            (PatternKind::Tuple(patterns), Value::Tuple(values)) => {
+                patterns.len() == values.len()
+                    && patterns
+                        .iter()
+                        .zip(values)
+                        .all(|(pattern, value)| self.matches(pattern, value, scope))
+            }
+            (PatternKind::Record(fields), Value::Record(record)) => {
+                fields.iter().all(|(name, pattern)| {
+                    record
+                        .get(self.cache.resolve(&name.key))
+                        .is_some_and(|value| self.matches(pattern, value, scope))
+                })
+            }
+            (PatternKind::Cons { lhs, rhs }, Value::List(values)) => match values.split_first() {
+                Some((head, tail)) => {
+                    self.matches(lhs, head, scope)
+                        && self.matches(rhs, &Value::List(tail.to_vec()), scope)
+                }
+                None => false,
+            },
+            (PatternKind::EmptyList, Value::List(values)) => values.is_empty(),
+            (
+                PatternKind::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                _,
+            ) => {
+                let (start, end) = (self.literal(start), self.literal(end));
+                start <= *value && (*value < end || *inclusive && *value == end)
+            }
+            (PatternKind::Prefix { prefix, rest }, Value::String(string)) => {
+                match string.strip_prefix(self.cache.resolve(prefix)) {
+                    Some(suffix) => self.matches(rest, &Value::String(suffix.to_string()), scope),
+                    None => false,
+                }
+            }
+            (PatternKind::Or { lhs, rhs }, _) => {
+                let mut lhs_scope = scope.clone();
+                if self.matches(lhs, value, &mut lhs_scope) {
+                    *scope = lhs_scope;
+                    true
+                } else {
+                    self.matches(rhs, value, scope)
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Evaluates a binary operation on two values.
+fn binary(op: BinaryOpKind, lhs: Value, rhs: Value, span: Span) -> Result<Value, ConstEvalError> {
+    use Value::{Bool, Bytes, Float, Integer, List, Natural, String};
+
+    let overflow = || ConstEvalError::Overflow(span);
+    let value = match (op, lhs, rhs) {
+        (BinaryOpKind::Equal, lhs, rhs) => Bool(lhs == rhs),
+        (BinaryOpKind::NotEqual, lhs, rhs) => Bool(lhs != rhs),
+        (BinaryOpKind::LessThan, lhs, rhs) => Bool(lhs < rhs),
+        (BinaryOpKind::LessThanOrEqual, lhs, rhs) => Bool(lhs <= rhs),
+        (BinaryOpKind::GreaterThan, lhs, rhs) => Bool(lhs > rhs),
+        (BinaryOpKind::GreaterThanOrEqual, lhs, rhs) => Bool(lhs >= rhs),
+        // the left-hand side did not decide the result
+        (BinaryOpKind::LogicalAnd | BinaryOpKind::LogicalOr, Bool(_), Bool(rhs)) => Bool(rhs),
+        (BinaryOpKind::Divide | BinaryOpKind::Modulo, Natural(_), Natural(0))
+        | (BinaryOpKind::Divide | BinaryOpKind::Modulo, Integer(_), Integer(0)) => {
+            return Err(ConstEvalError::DivisionByZero(span));
+        }
+        (op, Natural(lhs), Natural(rhs)) => Natural(match op {
+            BinaryOpKind::Add => lhs.checked_add(rhs).ok_or_else(overflow)?,
+            BinaryOpKind::Subtract => lhs.checked_sub(rhs).ok_or_else(overflow)?,
+            BinaryOpKind::Multiply => lhs.checked_mul(rhs).ok_or_else(overflow)?,
+            BinaryOpKind::Divide => lhs / rhs,
+            BinaryOpKind::Modulo => lhs % rhs,
+            BinaryOpKind::Exponentiate => u32::try_from(rhs)
+                .ok()
+                .and_then(|rhs| lhs.checked_pow(rhs))
+                .ok_or_else(overflow)?,
+            BinaryOpKind::BitwiseAnd => lhs & rhs,
+            BinaryOpKind::BitwiseOr => lhs | rhs,
+            BinaryOpKind::BitwiseXor => lhs ^ rhs,
+            BinaryOpKind::BitwiseShiftLeft => shift(rhs, |rhs| lhs.checked_shl(rhs), span)?,
+            BinaryOpKind::BitwiseShiftRight => shift(rhs, |rhs| lhs.checked_shr(rhs), span)?,
+            _ => return Err(ConstEvalError::NotConstant(span)),
+        }),
+        (op, Integer(lhs), Integer(rhs)) => Integer(match op {
+            BinaryOpKind::Add => lhs.checked_add(rhs).ok_or_else(overflow)?,
+            BinaryOpKind::Subtract => lhs.checked_sub(rhs).ok_or_else(overflow)?,
+            BinaryOpKind::Multiply => lhs.checked_mul(rhs).ok_or_else(overflow)?,
+            BinaryOpKind::Divide => lhs.checked_div(rhs).ok_or_else(overflow)?,
+            BinaryOpKind::Modulo => lhs.checked_rem(rhs).ok_or_else(overflow)?,
+            BinaryOpKind::Exponentiate => u32::try_from(rhs)
+                .ok()
+                .and_then(|rhs| lhs.checked_pow(rhs))
+                .ok_or_else(overflow)?,
+            BinaryOpKind::BitwiseAnd => lhs & rhs,
+            BinaryOpKind::BitwiseOr => lhs | rhs,
+            BinaryOpKind::BitwiseXor => lhs ^ rhs,
+            BinaryOpKind::BitwiseShiftLeft => match u64::try_from(rhs) {
+                Ok(rhs) => shift(rhs, |rhs| lhs.checked_shl(rhs), span)?,
+                Err(_) => return Err(overflow()),
+            },
+            BinaryOpKind::BitwiseShiftRight => match u64::try_from(rhs) {
+                Ok(rhs) => shift(rhs, |rhs| lhs.checked_shr(rhs), span)?,
+                Err(_) => return Err(overflow()),
+            },
+            _ => return Err(ConstEvalError::NotConstant(span)),
+        }),
+        (op, Float(lhs), Float(rhs)) => Float(match op {
+            BinaryOpKind::Add => lhs + rhs,
+            BinaryOpKind::Subtract => lhs - rhs,
+            BinaryOpKind::Multiply => lhs * rhs,
+            BinaryOpKind::Divide => lhs / rhs,
+            BinaryOpKind::Modulo => lhs % rhs,
+            BinaryOpKind::Exponentiate => lhs.powf(rhs),
+            _ => return Err(ConstEvalError::NotConstant(span)),
+        }),
+        (BinaryOpKind::Cons, head, List(mut tail)) => {
+            tail.insert(0, head);
+            List(tail)
+        }
+        (BinaryOpKind::Concat, List(mut lhs), List(rhs)) => {
+            lhs.extend(rhs);
+            List(lhs)
+        }
+        (BinaryOpKind::Concat, String(lhs), String(rhs)) => String(lhs + &rhs),
+        (BinaryOpKind::Concat, Bytes(mut lhs), Bytes(rhs)) => {
+            lhs.extend(rhs);
+            Bytes(lhs)
+        }
+        // operands of different types are a type error, reported by the type checker
+        _ => return Err(ConstEvalError::NotConstant(span)),
+    };
+    Ok(value)
+}
+
+/// Shifts a number by a number of bits, which overflows if it is not less than 64.
+fn shift<T>(
+    bits: u64,
+    shift: impl FnOnce(u32) -> Option<T>,
+    span: Span,
+) -> Result<T, ConstEvalError> {
+    u32::try_from(bits)
+        .ok()
+        .and_then(shift)
+        .ok_or(ConstEvalError::Overflow(span))
+}
+
+/// Evaluates a unary operation on a value.
+fn unary(op: UnaryOpKind, value: Value, span: Span) -> Result<Value, ConstEvalError> {
+    let value = match (op, value) {
+        (
+            UnaryOpKind::UnaryPlus,
+            value @ (Value::Natural(_) | Value::Integer(_) | Value::Float(_)),
+        ) => value,
+        (UnaryOpKind::Negate, Value::Integer(x)) => {
+            Value::Integer(x.checked_neg().ok_or(ConstEvalError::Overflow(span))?)
+        }
+        (UnaryOpKind::Negate, Value::Float(x)) => Value::Float(-x),
+        (UnaryOpKind::LogicalNot, Value::Bool(x)) => Value::Bool(!x),
+        (UnaryOpKind::BitwiseNot, Value::Natural(x)) => Value::Natural(!x),
+        (UnaryOpKind::BitwiseNot, Value::Integer(x)) => Value::Integer(!x),
+        _ => return Err(ConstEvalError::NotConstant(span)),
+    };
+    Ok(value)
+}
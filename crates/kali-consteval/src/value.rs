@@ -0,0 +1,92 @@
+//! The values constant expressions evaluate to.
+
+use std::{collections::BTreeMap, fmt};
+
+/// A value known at compile time.
+///
+/// Values are compared by their structure, as described in the specification: tuples, lists and
+/// records are ordered lexicographically, and records field by field in the order of their names.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Value {
+    /// A `nat`.
+    Natural(u64),
+    /// An `int`.
+    Integer(i64),
+    /// A `float`.
+    Float(f64),
+    /// A `bool`.
+    Bool(bool),
+    /// A `string`, as written in the source code.
+    String(String),
+    /// A byte string.
+    Bytes(Vec<u8>),
+    /// The unit value, `()`.
+    Unit,
+    /// A tuple of values.
+    Tuple(Vec<Value>),
+    /// A list of values.
+    List(Vec<Value>),
+    /// A record, whose fields are kept in the order of their names.
+    Record(BTreeMap<String, Value>),
+}
+
+impl fmt::Display for Value {
+    /// Formats the value as the literal expression it could be written as.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Natural(x) => write!(f, "{}", x),
+            Value::Integer(x) => write!(f, "{}", x),
+            Value::Float(x) if x.is_finite() && x.fract() == 0.0 => write!(f, "{:.1}", x),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Bool(x) => write!(f, "{}", x),
+            Value::String(x) => write!(f, "\"{}\"", x),
+            Value::Bytes(bytes) => {
+                f.write_str("b\"")?;
+                for byte in bytes {
+                    match byte {
+                        b'"' => f.write_str("\\\"")?,
+                        b'\\' => f.write_str("\\\\")?,
+                        b'\n' => f.write_str("\\n")?,
+                        b'\r' => f.write_str("\\r")?,
+                        b'\t' => f.write_str("\\t")?,
+                        b' '..=b'~' => write!(f, "{}", *byte as char)?,
+                        _ => write!(f, "\\x{:02x}", byte)?,
+                    }
+                }
+                f.write_str("\"")
+            }
+            Value::Unit => f.write_str("()"),
+            Value::Tuple(values) => {
+                f.write_str("(")?;
+                write_separated(f, values)?;
+                f.write_str(")")
+            }
+            Value::List(values) => {
+                f.write_str("[")?;
+                write_separated(f, values)?;
+                f.write_str("]")
+            }
+            Value::Record(fields) => {
+                f.write_str("{ ")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                f.write_str(" }")
+            }
+        }
+    }
+}
+
+/// Writes values separated by commas.
+fn write_separated(f: &mut fmt::Formatter<'_>, values: &[Value]) -> fmt::Result {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", value)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use kali_consteval::{ConstEvalError, Constants, Value, constants};
+use kali_parse::parse_str;
+
+/// Evaluates the constant definitions of `src`.
+fn evaluate(src: &str) -> Constants {
+    constants(&parse_str(src).unwrap())
+}
+
+/// Evaluates the constant definitions of `src`, returning the value of `x` as written in Kali.
+fn value(src: &str) -> String {
+    let constants = evaluate(src);
+    assert!(constants.errors.is_empty(), "{:?}", constants.errors);
+    constants.values["x"].to_string()
+}
+
+#[test]
+fn test_arithmetic() {
+    assert_eq!(value("let x = 1 + 2 * 3"), "7");
+    assert_eq!(value("let x = -7 / -2"), "3");
+    assert_eq!(value("let x = 2 ** 10 % 1000"), "24");
+    assert_eq!(value("let x = -7 % -2"), "-1");
+    assert_eq!(value("let x = (6 & 3) | 8"), "10");
+    assert_eq!(value("let x = ~0 == 18446744073709551615"), "true");
+}
+
+#[test]
+fn test_comparisons() {
+    assert_eq!(value("let x = (1, [true]) == (1, [true])"), "true");
+    assert_eq!(value("let x = [1, 2] < [1, 2, 3]"), "true");
+    assert_eq!(value("let x = (2, \"a\") >= (2, \"b\")"), "false");
+    // the right-hand side is not evaluated when the left-hand side decides
+    assert_eq!(value("let x = false && f(1)"), "false");
+}
+
+#[test]
+fn test_structures() {
+    assert_eq!(
+        value("let x = (1, [2, 3], ((), -1))"),
+        "(1, [2, 3], ((), -1))"
+    );
+    assert_eq!(value("let x = 0 :: [1, 2]"), "[0, 1, 2]");
+    assert_eq!(value("let x = [1, 2, 3][1]"), "2");
+    assert_eq!(value("let x = (b\"\\x00\", 1).0"), "b\"\\x00\"");
+    assert_eq!(
+        value("let x = if 1 > 2 { \"yes\" } else { \"no\" }"),
+        "\"no\""
+    );
+}
+
+#[test]
+fn test_match() {
+    let src = "let x = match [1, 2, 3] { [] -> 0, a :: b :: _ -> a + b }";
+    assert_eq!(value(src), "3");
+    let src = "let x = match \"GET /\" { \"POST \" ++ p -> p, \"GET \" ++ p -> p }";
+    assert_eq!(value(src), "\"/\"");
+    assert_eq!(value("let x = match 5 { 1..=4 -> 1, 5 | 6 -> 2 }"), "2");
+}
+
+#[test]
+fn test_definitions() {
+    let constants =
+        evaluate("let a = 2;\nlet (b, c) = (a * 3, a);\nlet f = y -> (y + a);\nlet d = f(1)");
+    let names: Vec<_> = constants.values.keys().collect();
+    assert_eq!(names, ["a", "b", "c"]);
+    assert_eq!(constants.values["b"], Value::Natural(6));
+    // a definition that is not constant forgets the constant it shadows
+    assert!(evaluate("let a = 1;\nlet a = g(1)").values.is_empty());
+}
+
+#[test]
+fn test_failures() {
+    let errors = evaluate(
+        "let a = 1 - 2;\nlet b = 1 / 0;\nlet c = [1][1];\nlet d = -9223372036854775807 + -2",
+    )
+    .errors;
+    assert!(matches!(errors[0], ConstEvalError::Overflow(_)));
+    assert!(matches!(errors[1], ConstEvalError::DivisionByZero(_)));
+    assert_eq!(
+        errors[2].to_string(),
+        "index 1 is out of bounds for a list of length 1"
+    );
+    // the error is reported at the index
+    assert_eq!(errors[2].span().into_range(), 42..43);
+    assert!(matches!(errors[3], ConstEvalError::Overflow(_)));
+    assert_eq!(errors.len(), 4);
+}
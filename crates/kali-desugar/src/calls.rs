@@ -0,0 +1,321 @@
+//! Desugaring of spreads, labelled arguments and default values.
+//!
+//! Desugared modules contain no [`ExprKind::Spread`] expressions:
+//!
+//! - a list literal containing spreads becomes a concatenation, so `[a, ..xs, b]` is
+//!   `[a] @ xs @ [b]`;
+//! - the arguments of a call from its first spread onwards are collected into a single list
+//!   argument, so `f(a, ..xs, b)` is `f(a, xs @ [b])`, and `f(..xs)` applies `f` to `xs`.
+//!
+//! Labelled arguments passed to a top-level function are reordered into positional arguments,
+//! so that given `let draw = x, y -> ...`, `draw(y: 2, x: 1)` is `draw(1, 2)`. Arguments left
+//! out of such calls are filled in with the default values of their parameters, so that given
+//! `let greet = name, greeting = "hello" -> ...`, `greet(name)` is `greet(name, "hello")`. Calls
+//! whose arguments do not match the parameters of the function are left as they are, for the
+//! type checker to report.
+
+use std::collections::HashMap;
+
+use kali_span::Span;
+use lasso::Spur;
+
+use kali_ast::{
+    BinaryOp, BinaryOpKind, Destructor, DestructorKind, Expr, ExprKind, ItemKind, Module, Pattern,
+    PatternKind,
+    arguments::{Parameter, match_arguments, needs_matching, parameters},
+};
+
+/// Desugars the spreads and arguments of every expression in a module.
+///
+/// # Arguments
+///
+/// * `module` - The module to desugar in place.
+pub fn desugar_calls(module: &mut Module) {
+    let mut lowerer = Lowerer::default();
+    for item in &mut module.items {
+        if let ItemKind::Definition(definition) = &mut item.kind {
+            // a definition shadows any function of the same name defined before it
+            let mut names = Vec::new();
+            destructor_names(&definition.name, &mut names);
+            for name in names {
+                lowerer.signatures.remove(&name);
+            }
+            if let (DestructorKind::Var(name), Some(mut parameters)) =
+                (&definition.name.kind, parameters(&definition.expr))
+            {
+                for default in parameters.iter_mut().filter_map(|p| p.default.as_mut()) {
+                    lowerer.expr(default);
+                }
+                lowerer.signatures.insert(name.key, parameters);
+            }
+            lowerer.expr(&mut definition.expr);
+        }
+    }
+}
+
+/// The state of the lowering of a module.
+#[derive(Default)]
+struct Lowerer {
+    /// The parameters of the top-level functions defined so far, by name, with their default
+    /// values already lowered.
+    signatures: HashMap<Spur, Vec<Parameter>>,
+    /// The variables bound by the enclosing lambdas and match arms, which shadow top-level
+    /// functions.
+    locals: Vec<Spur>,
+}
+
+impl Lowerer {
+    /// Lowers an expression and all of its subexpressions.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to lower in place.
+    fn expr(&mut self, expr: &mut Expr) {
+        let span = expr.span;
+        match &mut expr.kind {
+            ExprKind::Var(_) | ExprKind::Literal(_) | ExprKind::Hole(_) | ExprKind::Error => {}
+            ExprKind::BinaryExpr { lhs, rhs, .. }
+            | ExprKind::Range {
+                start: lhs,
+                end: rhs,
+                ..
+            }
+            | ExprKind::Index {
+                list: lhs,
+                index: rhs,
+            } => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            ExprKind::UnaryExpr { expr, .. }
+            | ExprKind::Section { operand: expr, .. }
+            | ExprKind::Spread(expr)
+            | ExprKind::Try(expr)
+            | ExprKind::Projection { tuple: expr, .. }
+            | ExprKind::Labelled { value: expr, .. } => self.expr(expr),
+            ExprKind::Tuple(exprs) | ExprKind::Set(exprs) | ExprKind::Sequence(exprs) => {
+                exprs.iter_mut().for_each(|expr| self.expr(expr))
+            }
+            ExprKind::List(exprs) => {
+                exprs.iter_mut().for_each(|expr| self.expr(expr));
+                if exprs.iter().any(is_spread) {
+                    *expr = concat(std::mem::take(exprs), span);
+                }
+            }
+            ExprKind::Map(entries) => entries.iter_mut().for_each(|(key, value)| {
+                self.expr(key);
+                self.expr(value);
+            }),
+            ExprKind::Record { fields } => fields.values_mut().for_each(|expr| self.expr(expr)),
+            ExprKind::Conditional {
+                condition,
+                body,
+                otherwise,
+            } => {
+                self.expr(condition);
+                self.expr(body);
+                if let Some(otherwise) = otherwise {
+                    self.expr(otherwise);
+                }
+            }
+            ExprKind::Match { value, arms } => {
+                self.expr(value);
+                for arm in arms {
+                    let len = self.locals.len();
+                    pattern_names(&arm.pattern, &mut self.locals);
+                    self.expr(&mut arm.expr);
+                    self.locals.truncate(len);
+                }
+            }
+            ExprKind::While { condition, body } => {
+                self.expr(condition);
+                self.expr(body);
+            }
+            ExprKind::For {
+                binding,
+                iterable,
+                body,
+            } => {
+                self.expr(iterable);
+                let len = self.locals.len();
+                destructor_names(binding, &mut self.locals);
+                self.expr(body);
+                self.locals.truncate(len);
+            }
+            ExprKind::Lambda { params, body, .. } => {
+                for default in params.iter_mut().filter_map(|param| param.default.as_mut()) {
+                    self.expr(default);
+                }
+                let len = self.locals.len();
+                for param in params.iter() {
+                    destructor_names(&param.parameter, &mut self.locals);
+                }
+                self.expr(body);
+                self.locals.truncate(len);
+            }
+            ExprKind::Call {
+                function,
+                arguments,
+            } => {
+                self.expr(function);
+                arguments.iter_mut().for_each(|expr| self.expr(expr));
+                self.match_call(function, arguments);
+                if let Some(index) = arguments.iter().position(is_spread) {
+                    let rest = arguments.split_off(index);
+                    let span = rest[0].span.union(rest[rest.len() - 1].span);
+                    arguments.push(concat(rest, span));
+                }
+            }
+        }
+    }
+
+    /// Rewrites the arguments of a call to a top-level function into positional form, reordering
+    /// labelled arguments and filling in those left out with their default values.
+    ///
+    /// # Arguments
+    ///
+    /// * `function` - The function being called.
+    /// * `arguments` - The arguments of the call, which are left unchanged if they do not match
+    ///   the parameters of the function.
+    fn match_call(&self, function: &Expr, arguments: &mut Vec<Expr>) {
+        let ExprKind::Var(name) = &function.kind else {
+            return;
+        };
+        if self.locals.contains(&name.key) {
+            return;
+        }
+        let Some(parameters) = self.signatures.get(&name.key) else {
+            return;
+        };
+        if !needs_matching(parameters, arguments) {
+            return;
+        }
+        let Ok(matched) = match_arguments(parameters, arguments) else {
+            return;
+        };
+        let missing = |(index, parameter): (&Option<usize>, &Parameter)| {
+            index.is_none() && parameter.default.is_none()
+        };
+        if matched.iter().zip(parameters).any(missing) {
+            return;
+        }
+        let mut unordered: Vec<_> = std::mem::take(arguments).into_iter().map(Some).collect();
+        *arguments = matched
+            .into_iter()
+            .zip(parameters)
+            .map(|(index, parameter)| match index {
+                Some(index) => {
+                    let argument = unordered[index]
+                        .take()
+                        .expect("each argument is passed to one parameter");
+                    match argument.kind {
+                        ExprKind::Labelled { value, .. } => *value,
+                        _ => argument,
+                    }
+                }
+                None => parameter
+                    .default
+                    .clone()
+                    .expect("parameters left out have a default value"),
+            })
+            .collect();
+    }
+}
+
+/// Collects the variables bound by a destructor.
+fn destructor_names(destructor: &Destructor, names: &mut Vec<Spur>) {
+    match &destructor.kind {
+        DestructorKind::Var(ident) => names.push(ident.key),
+        DestructorKind::Tuple(destructors) => destructors
+            .iter()
+            .for_each(|destructor| destructor_names(destructor, names)),
+        DestructorKind::Record(fields) => fields
+            .values()
+            .for_each(|destructor| destructor_names(destructor, names)),
+        DestructorKind::Cons { lhs, rhs } => {
+            destructor_names(lhs, names);
+            destructor_names(rhs, names);
+        }
+        DestructorKind::Rest => {}
+    }
+}
+
+/// Collects the variables bound by a pattern.
+fn pattern_names(pattern: &Pattern, names: &mut Vec<Spur>) {
+    match &pattern.kind {
+        PatternKind::Var(ident) => names.push(ident.key),
+        PatternKind::Tuple(patterns) => patterns
+            .iter()
+            .for_each(|pattern| pattern_names(pattern, names)),
+        PatternKind::Record(fields) => fields
+            .values()
+            .for_each(|pattern| pattern_names(pattern, names)),
+        PatternKind::Prefix { rest, .. } => pattern_names(rest, names),
+        PatternKind::Cons { lhs, rhs } | PatternKind::Or { lhs, rhs } => {
+            pattern_names(lhs, names);
+            pattern_names(rhs, names);
+        }
+        PatternKind::Literal(_)
+        | PatternKind::Wildcard
+        | PatternKind::EmptyList
+        | PatternKind::Range { .. }
+        | PatternKind::Rest => {}
+    }
+}
+
+fn is_spread(expr: &Expr) -> bool {
+    matches!(expr.kind, ExprKind::Spread(_))
+}
+
+/// Lowers the elements of a list literal containing spreads into a concatenation of lists.
+///
+/// # Arguments
+///
+/// * `elements` - The elements of the list, which have already been lowered.
+/// * `span` - The span of the list literal.
+///
+/// # Returns
+///
+/// An expression that evaluates to the same list, without any spreads.
+fn concat(elements: Vec<Expr>, span: Span) -> Expr {
+    let mut segments = Vec::new();
+    let mut run: Vec<Expr> = Vec::new();
+    for element in elements {
+        match element.kind {
+            ExprKind::Spread(list) => {
+                if !run.is_empty() {
+                    segments.push(list_of(std::mem::take(&mut run)));
+                }
+                segments.push(*list);
+            }
+            _ => run.push(element),
+        }
+    }
+    if !run.is_empty() {
+        segments.push(list_of(run));
+    }
+    // `@` is right-associative, so the segments are joined from the right
+    segments
+        .into_iter()
+        .rev()
+        .reduce(|rhs, lhs| Expr {
+            span: lhs.span.union(rhs.span),
+            kind: ExprKind::BinaryExpr {
+                op: BinaryOp {
+                    kind: BinaryOpKind::Concat,
+                    span,
+                },
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+        })
+        .expect("a list containing a spread has at least one segment")
+}
+
+/// Builds a list literal from a non-empty run of elements.
+fn list_of(elements: Vec<Expr>) -> Expr {
+    Expr {
+        span: elements[0].span.union(elements[elements.len() - 1].span),
+        kind: ExprKind::List(elements),
+    }
+}
@@ -0,0 +1,104 @@
+//! Desugaring of functions defined by clauses.
+//!
+//! A function defined by clauses is a lambda that matches its arguments against the patterns of
+//! each clause in turn, so
+//!
+//! ```text
+//! fn fib 0 = 0;
+//! fn fib 1 = 1;
+//! fn fib n = fib(n - 1) + fib(n - 2)
+//! ```
+//!
+//! is `let fib = $0 -> match $0 { 0 -> 0, 1 -> 1, n -> fib(n - 1) + fib(n - 2) }`. Functions of
+//! several arguments match a tuple of their arguments against a tuple of patterns per clause.
+//! The parameters are named `$0`, `$1`, ..., which cannot be written in source code, so they
+//! never capture a variable of the program.
+
+use kali_ast::{
+    Clause, Definition, Destructor, DestructorKind, Expr, ExprKind, Function, Ident, ItemKind,
+    LambdaParam, MatchArm, Module, Pattern, PatternKind,
+};
+use kali_span::Span;
+
+/// Turns every function defined by clauses in a module into a definition.
+///
+/// # Arguments
+///
+/// * `module` - The module to desugar in place.
+pub fn desugar_clauses(module: &mut Module) {
+    for item in &mut module.items {
+        if let ItemKind::Function(function) = &item.kind {
+            let definition = definition(function, item.span, &mut module.cache);
+            item.kind = ItemKind::Definition(definition);
+        }
+    }
+}
+
+/// Builds the definition of a lambda matching its arguments against the clauses of a function.
+fn definition(function: &Function, span: Span, cache: &mut lasso::Rodeo) -> Definition {
+    let arity = function.clauses[0].patterns.len();
+    let params: Vec<Ident> = (0..arity)
+        .map(|index| Ident {
+            key: cache.get_or_intern(format!("${}", index)),
+            span: function.name.span,
+        })
+        .collect();
+    let var = |ident: &Ident| Expr {
+        span: ident.span,
+        kind: ExprKind::Var(*ident),
+    };
+    let value = match params.as_slice() {
+        [param] => var(param),
+        params => Expr {
+            span: function.name.span,
+            kind: ExprKind::Tuple(params.iter().map(var).collect()),
+        },
+    };
+    let arms = function.clauses.iter().map(arm).collect();
+    Definition {
+        name: Destructor {
+            span: function.name.span,
+            kind: DestructorKind::Var(function.name),
+        },
+        expr: Expr {
+            span,
+            kind: ExprKind::Lambda {
+                params: params
+                    .iter()
+                    .map(|param| LambdaParam {
+                        parameter: Destructor {
+                            span: param.span,
+                            kind: DestructorKind::Var(*param),
+                        },
+                        ty: None,
+                        default: None,
+                    })
+                    .collect(),
+                ret_ty: None,
+                body: Box::new(Expr {
+                    span,
+                    kind: ExprKind::Match {
+                        value: Box::new(value),
+                        arms,
+                    },
+                }),
+            },
+        },
+    }
+}
+
+/// Builds the match arm of a clause.
+fn arm(clause: &Clause) -> MatchArm {
+    let pattern = match clause.patterns.as_slice() {
+        [pattern] => pattern.clone(),
+        patterns => Pattern {
+            span: patterns[0].span.union(patterns[patterns.len() - 1].span),
+            kind: PatternKind::Tuple(patterns.to_vec()),
+        },
+    };
+    MatchArm {
+        span: clause.span,
+        pattern,
+        expr: clause.body.clone(),
+    }
+}
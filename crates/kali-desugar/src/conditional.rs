@@ -0,0 +1,43 @@
+//! Desugaring of conditionals without an `else` branch.
+//!
+//! A conditional without an `else` branch evaluates to `()` when its condition is false, so
+//! `if c { f(x) }` is `if c { f(x) } else { () }`. Its body must then be of type `()` too.
+
+use std::convert::Infallible;
+
+use kali_ast::{
+    Expr, ExprKind, LiteralKind, Module,
+    visit::{WalkOrder, Walker},
+    visit_mut::VisitorMut,
+};
+
+/// Gives every conditional in a module an `else` branch.
+///
+/// # Arguments
+///
+/// * `module` - The module to desugar in place.
+pub fn desugar_conditionals(module: &mut Module) {
+    let Ok(()) = Walker::new(WalkOrder::PostOrder).walk_module_mut(&mut Conditionals, module);
+}
+
+/// Adds an `else` branch evaluating to `()` to each conditional without one.
+struct Conditionals;
+
+impl VisitorMut for Conditionals {
+    type Error = Infallible;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Infallible> {
+        let span = expr.span;
+        if let ExprKind::Conditional {
+            otherwise: otherwise @ None,
+            ..
+        } = &mut expr.kind
+        {
+            *otherwise = Some(Box::new(Expr {
+                span,
+                kind: ExprKind::Literal(LiteralKind::Unit),
+            }));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+//! Desugaring of the Kali surface syntax into a smaller core language.
+//!
+//! The parser produces an AST that mirrors the source code, with several ways of writing the
+//! same thing. Desugaring rewrites a module in place so that later passes, such as type
+//! checking, only need to handle the core forms:
+//!
+//! - functions defined by clauses become lambdas matching their arguments by [`clauses`];
+//! - pipelines become calls by [`pipe`];
+//! - operator sections become lambdas by [`section`];
+//! - spreads, labelled arguments and default values are removed by [`calls`];
+//! - conditionals without an `else` branch are given one that evaluates to `()` by
+//!   [`conditional`];
+//! - list literals become chains of `::` ending in the empty list `[]` by [`list`].
+//!
+//! Desugared nodes keep the spans of the syntax they were written as, so that errors found in
+//! the core language are reported against the source code.
+//!
+//! Other operators are not rewritten into calls, as the type checker gives each operator its own
+//! typing rule rather than a polymorphic function type.
+
+use kali_ast::{Module, pipe::Placement};
+
+pub mod calls;
+pub mod clauses;
+pub mod conditional;
+pub mod list;
+pub mod pipe;
+pub mod section;
+
+/// Desugars a module into the core language.
+///
+/// # Arguments
+///
+/// * `module` - The module to desugar in place.
+pub fn desugar(module: &mut Module) {
+    clauses::desugar_clauses(module);
+    pipe::desugar_pipes(module, Placement::default());
+    section::desugar_sections(module);
+    // spreads become concatenations of list literals, which are then turned into cons chains
+    calls::desugar_calls(module);
+    conditional::desugar_conditionals(module);
+    list::desugar_lists(module);
+}
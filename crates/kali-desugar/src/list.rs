@@ -0,0 +1,71 @@
+//! Desugaring of list literals.
+//!
+//! A list literal is a chain of `::` ending in the empty list, so `[a, b, c]` is
+//! `a :: b :: c :: []`. The empty list `[]` is the only list literal in the core language.
+
+use std::convert::Infallible;
+
+use kali_ast::{
+    BinaryOp, BinaryOpKind, Expr, ExprKind, Module,
+    visit::{WalkOrder, Walker},
+    visit_mut::VisitorMut,
+};
+use kali_span::Span;
+
+/// Rewrites every non-empty list literal in a module into a chain of `::`.
+///
+/// Spreads must already have been removed by [`desugar_calls`](crate::calls::desugar_calls),
+/// as a spread is not an element of the list it appears in.
+///
+/// # Arguments
+///
+/// * `module` - The module to desugar in place.
+pub fn desugar_lists(module: &mut Module) {
+    let Ok(()) = Walker::new(WalkOrder::PostOrder).walk_module_mut(&mut Lists, module);
+}
+
+/// Rewrites list literals into chains of `::`, after their elements have been rewritten.
+struct Lists;
+
+impl VisitorMut for Lists {
+    type Error = Infallible;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Infallible> {
+        if let ExprKind::List(elements) = &mut expr.kind
+            && !elements.is_empty()
+        {
+            *expr = cons_chain(std::mem::take(elements), expr.span);
+        }
+        Ok(())
+    }
+}
+
+/// Builds a chain of `::` from the elements of a list literal.
+///
+/// # Arguments
+///
+/// * `elements` - The elements of the list.
+/// * `span` - The span of the list literal, which the empty list at the end of the chain and
+///   each `::` are given.
+///
+/// # Returns
+///
+/// An expression that evaluates to the same list, where each `::` spans from its element to the
+/// end of the list literal.
+fn cons_chain(elements: Vec<Expr>, span: Span) -> Expr {
+    let nil = Expr {
+        span,
+        kind: ExprKind::List(Vec::new()),
+    };
+    elements.into_iter().rev().fold(nil, |tail, head| Expr {
+        span: Span::new(span.file_id, head.span.start..span.end),
+        kind: ExprKind::BinaryExpr {
+            op: BinaryOp {
+                kind: BinaryOpKind::Cons,
+                span,
+            },
+            lhs: Box::new(head),
+            rhs: Box::new(tail),
+        },
+    })
+}
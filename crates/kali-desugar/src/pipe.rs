@@ -0,0 +1,41 @@
+//! Desugaring of pipelines.
+//!
+//! A pipeline passes a value to a function, so `x |> f |> g y` is `g(y, f(x))`. See
+//! [`kali_ast::pipe`] for how the value is placed among the arguments of a call.
+
+use std::convert::Infallible;
+
+use kali_ast::{
+    BinaryOpKind, Expr, ExprKind, Module,
+    pipe::{Placement, pipe},
+    visit::{WalkOrder, Walker},
+    visit_mut::VisitorMut,
+};
+
+/// Rewrites every pipeline in a module into a call.
+///
+/// # Arguments
+///
+/// * `module` - The module to desugar in place.
+/// * `placement` - Where piped values are placed among the arguments of a call.
+pub fn desugar_pipes(module: &mut Module, placement: Placement) {
+    let Ok(()) = Walker::new(WalkOrder::PostOrder).walk_module_mut(&mut Pipes(placement), module);
+}
+
+/// Rewrites pipelines into calls, after their operands have been rewritten.
+struct Pipes(Placement);
+
+impl VisitorMut for Pipes {
+    type Error = Infallible;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Infallible> {
+        if let ExprKind::BinaryExpr { op, .. } = &expr.kind
+            && op.kind == BinaryOpKind::Pipe
+            && let ExprKind::BinaryExpr { lhs, rhs, .. } =
+                std::mem::replace(&mut expr.kind, ExprKind::Error)
+        {
+            *expr = pipe(*lhs, *rhs, self.0);
+        }
+        Ok(())
+    }
+}
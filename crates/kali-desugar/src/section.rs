@@ -0,0 +1,67 @@
+//! Desugaring of operator sections.
+//!
+//! A section is a binary operator given only one of its operands, and is a lambda of the other,
+//! so `(+ 1)` is `$0 -> $0 + 1` and `(2 *)` is `$0 -> 2 * $0`. The parameter is named `$0`, which
+//! cannot be written in source code, so it never captures a variable of the operand.
+
+use std::convert::Infallible;
+
+use kali_ast::{
+    Destructor, DestructorKind, Expr, ExprKind, Ident, LambdaParam, Module, Side,
+    visit::{WalkOrder, Walker},
+    visit_mut::VisitorMut,
+};
+
+/// Rewrites every operator section in a module into a lambda.
+///
+/// # Arguments
+///
+/// * `module` - The module to desugar in place.
+pub fn desugar_sections(module: &mut Module) {
+    let param = module.cache.get_or_intern_static("$0");
+    let Ok(()) = Walker::new(WalkOrder::PostOrder).walk_module_mut(&mut Sections(param), module);
+}
+
+/// Rewrites sections into lambdas of the given parameter, after their operands have been
+/// rewritten.
+struct Sections(lasso::Spur);
+
+impl VisitorMut for Sections {
+    type Error = Infallible;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Infallible> {
+        if let ExprKind::Section { .. } = &expr.kind
+            && let ExprKind::Section { op, operand, side } =
+                std::mem::replace(&mut expr.kind, ExprKind::Error)
+        {
+            let param = Ident {
+                key: self.0,
+                span: op.span,
+            };
+            let var = Box::new(Expr {
+                span: op.span,
+                kind: ExprKind::Var(param),
+            });
+            let (lhs, rhs) = match side {
+                Side::Left => (operand, var),
+                Side::Right => (var, operand),
+            };
+            expr.kind = ExprKind::Lambda {
+                params: vec![LambdaParam {
+                    parameter: Destructor {
+                        span: param.span,
+                        kind: DestructorKind::Var(param),
+                    },
+                    ty: None,
+                    default: None,
+                }],
+                ret_ty: None,
+                body: Box::new(Expr {
+                    span: expr.span,
+                    kind: ExprKind::BinaryExpr { op, lhs, rhs },
+                }),
+            };
+        }
+        Ok(())
+    }
+}
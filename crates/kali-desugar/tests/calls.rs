@@ -0,0 +1,87 @@
+use kali_desugar::calls::desugar_calls;
+use kali_parse::parse_str;
+use kali_testing::print;
+
+/// Desugars the calls in `src`, returning the printed result.
+fn lower(src: &str) -> String {
+    let mut module = parse_str(src).unwrap();
+    desugar_calls(&mut module);
+    print(&module).trim_end().to_string()
+}
+
+#[test]
+fn test_lower_list_spread() {
+    assert_eq!(lower("let a = [x, ..xs, y]"), "let a = [x] @ xs @ [y]");
+    assert_eq!(lower("let a = [..xs, ..ys]"), "let a = xs @ ys");
+    assert_eq!(lower("let a = [..xs]"), "let a = xs");
+    assert_eq!(lower("let a = [x, y, ..xs]"), "let a = [x, y] @ xs");
+    // lists without spreads are left as they are
+    assert_eq!(lower("let a = [x, y]"), "let a = [x, y]");
+}
+
+#[test]
+fn test_lower_spread_argument() {
+    assert_eq!(lower("let a = f(..xs)"), "let a = f(xs)");
+    assert_eq!(lower("let a = f(x, ..xs, y)"), "let a = f(x, xs @ [y])");
+    assert_eq!(lower("let a = f(x, y)"), "let a = f(x, y)");
+    // spreads are lowered wherever they appear
+    assert_eq!(
+        lower("let a = z -> g([..z], h(..z))"),
+        "let a = z -> g(z, h(z))"
+    );
+}
+
+#[test]
+fn test_lower_labelled_arguments() {
+    let draw = "let draw = x, y -> (x, y);\n";
+    let lowered = |src: &str| {
+        let lowered = lower(&format!("{}{}", draw, src));
+        lowered.lines().last().unwrap().to_string()
+    };
+    assert_eq!(lowered("let a = draw(y: 2, x: 1)"), "let a = draw(1, 2)");
+    assert_eq!(lowered("let a = draw(1, y: 2)"), "let a = draw(1, 2)");
+    assert_eq!(
+        lowered("let a = draw(x: f(y: 1), y: 2)"),
+        "let a = draw(f(y: 1), 2)"
+    );
+    // local bindings shadow the top-level function, so its labels no longer apply
+    assert_eq!(
+        lowered("let a = draw -> draw(y: 2, x: 1)"),
+        "let a = draw -> draw(y: 2, x: 1)"
+    );
+    // calls that do not match the parameters are left for the type checker to report
+    assert_eq!(lowered("let a = draw(z: 1)"), "let a = draw(z: 1)");
+    assert_eq!(lowered("let a = draw(y: 1)"), "let a = draw(y: 1)");
+}
+
+#[test]
+fn test_lower_default_arguments() {
+    let greet = "let greet = name, greeting = \"hello\", n = [..xs] -> name;\n";
+    let lowered = |src: &str| {
+        let lowered = lower(&format!("{}{}", greet, src));
+        lowered.lines().last().unwrap().to_string()
+    };
+    // default values are lowered along with the rest of the module
+    assert_eq!(
+        lowered("let a = greet(\"bob\")"),
+        "let a = greet(\"bob\", \"hello\", xs)"
+    );
+    assert_eq!(
+        lowered("let a = greet(n: [], name: \"bob\")"),
+        "let a = greet(\"bob\", \"hello\", [])"
+    );
+    assert_eq!(
+        lowered("let a = greet(\"bob\", \"hi\", [])"),
+        "let a = greet(\"bob\", \"hi\", [])"
+    );
+    // parameters without a default value cannot be left out
+    assert_eq!(lowered("let a = greet()"), "let a = greet()");
+}
+
+#[test]
+fn test_lower_partial_application() {
+    let src = "let draw = x, y, colour = \"black\" -> (x, y);\nlet a = draw(1)";
+    // partial applications pass the arguments they leave out to the closure they evaluate to,
+    // so are not given default values
+    assert_eq!(lower(src).lines().last().unwrap(), "let a = draw(1)");
+}
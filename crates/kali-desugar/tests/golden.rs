@@ -0,0 +1,56 @@
+//! Golden tests of the desugared output of the modules in `tests/golden`.
+//!
+//! Each `<name>.kali` file is parsed, desugared and printed, and the result is compared with
+//! `<name>.out`. Run with `KALI_BLESS=1` to overwrite the expected output with the actual output.
+
+use std::{fs, path::Path};
+
+use kali_desugar::desugar;
+use kali_parse::parse_str;
+use kali_testing::{module_diff, print};
+
+#[test]
+fn test_golden() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let bless = std::env::var_os("KALI_BLESS").is_some();
+    let mut inputs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "kali"))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no golden tests in {}", dir.display());
+
+    let mut failed = Vec::new();
+    for input in inputs {
+        let src = fs::read_to_string(&input).unwrap();
+        let mut module = parse_str(&src)
+            .unwrap_or_else(|errors| panic!("{} failed to parse: {:?}", input.display(), errors));
+        desugar(&mut module);
+        let actual = print(&module);
+
+        let expected_path = input.with_extension("out");
+        if bless {
+            fs::write(&expected_path, &actual).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+        if actual != expected {
+            eprintln!(
+                "{} does not match {}:\n{}",
+                input.display(),
+                expected_path.display(),
+                actual
+            );
+            // point at the first node that differs, unless the expected output is not valid code
+            if let Some(difference) = parse_str(&expected)
+                .ok()
+                .and_then(|expected| module_diff(&expected, &module))
+            {
+                eprintln!("{}", difference);
+            }
+            failed.push(input);
+        }
+    }
+    assert!(failed.is_empty(), "golden tests failed: {:?}", failed);
+}
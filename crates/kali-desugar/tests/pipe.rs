@@ -0,0 +1,30 @@
+use kali_ast::pipe::Placement;
+use kali_desugar::pipe::desugar_pipes;
+use kali_parse::parse_str;
+use kali_testing::print;
+
+/// Desugars the pipelines in `src`, returning the printed result.
+fn lower(src: &str, placement: Placement) -> String {
+    let mut module = parse_str(src).unwrap();
+    desugar_pipes(&mut module, placement);
+    print(&module).trim_end().to_string()
+}
+
+#[test]
+fn test_lower_pipe_last() {
+    let last = Placement::Last;
+    assert_eq!(lower("let a = x |> f", last), "let a = f(x)");
+    assert_eq!(lower("let a = x |> f |> g y", last), "let a = g(y, f(x))");
+    assert_eq!(lower("let a = x |> f(y, z)", last), "let a = f(y, z, x)");
+    // the piped value is any expression looser than the pipeline
+    assert_eq!(lower("let a = x + 1 |> f", last), "let a = f(x + 1)");
+    assert_eq!(lower("let a = x |> (y -> y)", last), "let a = (y -> y)(x)");
+}
+
+#[test]
+fn test_lower_pipe_first() {
+    let first = Placement::First;
+    assert_eq!(lower("let a = x |> f", first), "let a = f(x)");
+    assert_eq!(lower("let a = x |> f |> g y", first), "let a = g(f(x), y)");
+    assert_eq!(lower("let a = x |> f(y, z)", first), "let a = f(x, y, z)");
+}
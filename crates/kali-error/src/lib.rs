@@ -1,13 +1,431 @@
-//! Error types for Kali
+//! Diagnostics for Kali.
+//!
+//! Every pass of the compiler reports problems as [`Diagnostic`]s. Diagnostics raised by a
+//! [`Lint`] are warnings by default, and a [`LintConfig`] decides whether they are allowed,
+//! reported, or denied so that they fail the build.
 
-use ariadne::Report;
-pub enum Error {
-    /// A type error occurred.
-    TypeError(kali_type::TypeInferenceError),
+use std::{collections::HashMap, fmt, ops::Range};
+
+use ariadne::{Cache, Config, IndexType, Report, ReportKind};
+use chumsky::error::Rich;
+use kali_consteval::ConstEvalError;
+use kali_parse::mistakes::{Fix, Mistake};
+use kali_span::{SourceMap, Span};
+use kali_type::{TypeInferenceError, TypeUnificationError};
+use strum::IntoEnumIterator;
+
+/// The severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Severity {
+    /// Additional information that never fails the build.
+    Note,
+    /// Code that is likely to be a mistake.
+    Warning,
+    /// Code that is invalid, and fails the build.
+    Error,
+}
+
+/// A check for code that is valid, but likely to be a mistake.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::EnumString, strum::EnumIter,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Lint {
+    /// A variable is bound but never used.
+    UnusedVariable,
+    /// A name is imported but never used.
+    UnusedImport,
+    /// A binding has the same name as a binding in an enclosing scope.
+    Shadowing,
+    /// A match expression does not handle every possible value.
+    NonExhaustiveMatch,
+}
+
+/// How a [`Lint`] is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum LintLevel {
+    /// The lint is not reported.
+    Allow,
+    /// The lint is reported as a warning.
+    Warn,
+    /// The lint is reported as an error.
+    Deny,
+}
+
+/// The name that refers to every lint at once, e.g. in `--deny warnings`.
+pub const ALL_LINTS: &str = "warnings";
+
+/// An error raised when a lint name is not recognised.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown lint `{0}`")]
+pub struct UnknownLint(pub String);
+
+/// The level at which each [`Lint`] is reported.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    /// Levels that differ from the default, [`LintLevel::Warn`].
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintConfig {
+    /// Returns the level at which `lint` is reported.
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    /// Sets the level at which `lint` is reported.
+    pub fn set(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+
+    /// Sets the level of a lint by name, where [`ALL_LINTS`] sets the level of every lint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `UnknownLint` error if `name` does not name a lint.
+    pub fn set_named(&mut self, name: &str, level: LintLevel) -> Result<(), UnknownLint> {
+        if name == ALL_LINTS {
+            for lint in Lint::iter() {
+                self.set(lint, level);
+            }
+            return Ok(());
+        }
+        let lint = name.parse().map_err(|_| UnknownLint(name.to_string()))?;
+        self.set(lint, level);
+        Ok(())
+    }
+
+    /// Applies the configured levels to a set of diagnostics.
+    ///
+    /// Diagnostics of allowed lints are removed, and those of denied lints become errors. All
+    /// other diagnostics are returned unchanged.
+    pub fn apply(&self, diagnostics: impl IntoIterator<Item = Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                let Some(lint) = diagnostic.lint else {
+                    return Some(diagnostic);
+                };
+                match self.level(lint) {
+                    LintLevel::Allow => return None,
+                    LintLevel::Warn => {}
+                    LintLevel::Deny => diagnostic.severity = Severity::Error,
+                }
+                Some(diagnostic)
+            })
+            .collect()
+    }
+}
+
+/// A secondary span of source code related to a [`Diagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// The span of source code.
+    pub span: Span,
+    /// What the span has to do with the diagnostic.
+    pub message: String,
+}
+
+/// A change to the source code that fixes the problem of a [`Diagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// A description of the change.
+    pub message: String,
+    /// The spans to replace and their replacements, in order. Insertions have empty spans.
+    pub edits: Vec<(Span, String)>,
+}
+
+impl Suggestion {
+    /// Returns the lines of source code covered by the suggestion, with its edits applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - The source map that the spans of the suggestion refer to.
+    ///
+    /// # Returns
+    ///
+    /// The fixed lines, or `None` if the suggestion makes no edits.
+    pub fn preview(&self, sources: &SourceMap) -> Option<String> {
+        let (first, _) = self.edits.first()?;
+        let (last, _) = self.edits.last()?;
+        let src = sources.source(first.file_id);
+        let start = src[..first.start]
+            .rfind('\n')
+            .map_or(0, |offset| offset + 1);
+        let end = src[last.end..]
+            .find('\n')
+            .map_or(src.len(), |offset| last.end + offset);
+        let mut preview = String::new();
+        let mut offset = start;
+        for (span, replacement) in &self.edits {
+            preview.push_str(&src[offset..span.start]);
+            preview.push_str(replacement);
+            offset = span.end;
+        }
+        preview.push_str(&src[offset..end]);
+        Some(preview)
+    }
+}
+
+impl From<Fix> for Suggestion {
+    fn from(fix: Fix) -> Suggestion {
+        Suggestion {
+            message: fix.message.to_string(),
+            edits: fix
+                .edits
+                .into_iter()
+                .map(|(span, replacement)| (span, replacement.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// A problem found in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The severity of the diagnostic.
+    pub severity: Severity,
+    /// The lint that raised the diagnostic, if any.
+    pub lint: Option<Lint>,
+    /// A description of the problem.
+    pub message: String,
+    /// The span of the offending source code.
+    pub span: Span,
+    /// Other spans of source code related to the problem.
+    pub labels: Vec<Label>,
+    /// Additional information, e.g. how to fix the problem.
+    pub notes: Vec<String>,
+    /// Changes to the source code that fix the problem.
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic with the given severity and no lint.
+    pub fn new(severity: Severity, span: Span, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity,
+            lint: None,
+            message: message.into(),
+            span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Creates an error diagnostic.
+    pub fn error(span: Span, message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Error, span, message)
+    }
+
+    /// Creates a warning diagnostic raised by `lint`.
+    pub fn lint(lint: Lint, span: Span, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            lint: Some(lint),
+            ..Diagnostic::new(Severity::Warning, span, message)
+        }
+    }
+
+    /// Adds a secondary span to the diagnostic.
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    /// Adds a note to the diagnostic.
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Adds a suggested fix to the diagnostic.
+    pub fn with_suggestion(mut self, suggestion: impl Into<Suggestion>) -> Diagnostic {
+        self.suggestions.push(suggestion.into());
+        self
+    }
+
+    /// Returns whether the diagnostic fails the build.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Builds a report of the diagnostic for printing.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - The source map that the spans of the diagnostic refer to.
+    ///
+    /// # Returns
+    ///
+    /// A report whose spans are identified by the paths of their files, to be printed with the
+    /// cache returned by [`cache`].
+    pub fn report(&self, sources: &SourceMap) -> Report<'static, (String, Range<usize>)> {
+        let kind = match self.severity {
+            Severity::Error => ReportKind::Error,
+            Severity::Warning => ReportKind::Warning,
+            Severity::Note => ReportKind::Advice,
+        };
+        let locate = |span: Span| (sources.path(span.file_id).to_string(), span.into_range());
+        let mut report = Report::build(
+            kind,
+            sources.path(self.span.file_id).to_string(),
+            self.span.start,
+        )
+        // spans are byte offsets, whereas ariadne counts characters unless told otherwise
+        .with_config(Config::default().with_index_type(IndexType::Byte))
+        .with_message(&self.message)
+        .with_label(ariadne::Label::new(locate(self.span)));
+        for label in &self.labels {
+            report = report
+                .with_label(ariadne::Label::new(locate(label.span)).with_message(&label.message));
+        }
+        if let Some(lint) = self.lint {
+            let note = match self.severity {
+                Severity::Error => format!("`{}` is denied", lint),
+                _ => format!("`{}` is enabled", lint),
+            };
+            report = report.with_note(note);
+        }
+        for note in &self.notes {
+            report = report.with_note(note);
+        }
+        for suggestion in &self.suggestions {
+            report = match suggestion.preview(sources) {
+                Some(preview) => {
+                    report.with_help(format!("{}: `{}`", suggestion.message, preview.trim()))
+                }
+                None => report.with_help(&suggestion.message),
+            };
+        }
+        report.finish()
+    }
+
+    /// Serialises the diagnostic as a single line of JSON, for tools such as editors and CI.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - The source map that the span of the diagnostic refers to.
+    ///
+    /// # Returns
+    ///
+    /// An object with the `path` of the file, the `span` as byte offsets alongside the line and
+    /// column it starts at, the `severity`, the `code` of the lint that raised the diagnostic or
+    /// `null`, and the `message`.
+    pub fn to_json(&self, sources: &SourceMap) -> String {
+        let location = sources.lookup(self.span);
+        let code = match self.lint {
+            Some(lint) => json_string(&lint.to_string()),
+            None => "null".to_string(),
+        };
+        format!(
+            r#"{{"path":{},"span":{{"start":{},"end":{},"line":{},"column":{}}},"severity":"{}","code":{},"message":{}}}"#,
+            json_string(location.path),
+            self.span.start,
+            self.span.end,
+            location.line,
+            location.column,
+            self.severity,
+            code,
+            json_string(&self.message),
+        )
+    }
+
+    /// Converts a type inference error into diagnostics, one per underlying error.
+    pub fn from_type_error(error: TypeInferenceError) -> Vec<Diagnostic> {
+        match error {
+            TypeInferenceError::Multiple(errors) => errors
+                .into_iter()
+                .flat_map(Diagnostic::from_type_error)
+                .collect(),
+            TypeInferenceError::Spanned { span, error } => match *error {
+                // holes list what is in scope, to help fill them in
+                TypeInferenceError::Hole { ref bindings, .. } if !bindings.is_empty() => {
+                    let names = bindings
+                        .iter()
+                        .map(|(name, ty)| format!("  {}: {}", name, ty))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    vec![Diagnostic::error(span, error.to_string())
+                        .with_note(format!("in scope:\n{}", names))]
+                }
+                // options and results used as the value they may hold say how to unwrap them
+                TypeInferenceError::UnificationFailed(
+                    _,
+                    _,
+                    TypeUnificationError::Unwrap { combinator, .. },
+                ) => vec![
+                    Diagnostic::error(span, error.to_string()).with_note(format!(
+                        "`{}(default, x)` is the value `x` holds, or `default` if it holds none",
+                        combinator
+                    )),
+                ],
+                error => vec![Diagnostic::error(span, error.to_string())],
+            },
+            error => {
+                let span = error.span().unwrap_or(Span::default());
+                vec![Diagnostic::error(span, error.to_string())]
+            }
+        }
+    }
+}
+
+/// Quotes a string as a JSON string literal.
+pub fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Builds a cache of the files in a source map, for printing the reports of diagnostics.
+pub fn cache(sources: &SourceMap) -> impl Cache<String> {
+    let files: Vec<_> = sources
+        .files()
+        .map(|(_, file)| (file.path.clone(), file.source.clone()))
+        .collect();
+    ariadne::sources(files)
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.lint {
+            Some(lint) => write!(f, "{}[{}]: {}", self.severity, lint, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+impl From<Mistake> for Diagnostic {
+    fn from(mistake: Mistake) -> Diagnostic {
+        Diagnostic::error(mistake.span, mistake.message).with_suggestion(mistake.fix)
+    }
+}
+
+impl From<ConstEvalError> for Diagnostic {
+    fn from(error: ConstEvalError) -> Diagnostic {
+        Diagnostic::error(error.span(), error.to_string())
+            .with_note("top-level definitions are evaluated when the module is loaded")
+    }
 }
 
-impl Error {
-    pub fn into_report(self) -> Report<'static> {
-        todo!()
+impl<T: fmt::Display> From<Rich<'_, T, Span>> for Diagnostic {
+    fn from(error: Rich<'_, T, Span>) -> Diagnostic {
+        Diagnostic::error(*error.span(), error.reason().to_string())
     }
 }
@@ -0,0 +1,539 @@
+//! Lints for the Kali programming language.
+//!
+//! Lints flag code that is valid, but likely to be a mistake. Each lint can be silenced for a
+//! single item with an `#allow(lint)` attribute, or configured for the whole build with a
+//! [`LintConfig`].
+
+use std::collections::{HashMap, HashSet};
+
+use kali_ast::{
+    Clause, Destructor, DestructorKind, ExportKind, Expr, ExprKind, Ident, ImportTree,
+    ImportTreeKind, Item, ItemKind, LiteralKind, Module, Pattern, PatternKind, Type, TypeKind,
+};
+use kali_error::{Diagnostic, Lint, LintConfig, Severity};
+use kali_span::Span;
+
+/// The name of the attribute that silences lints for an item.
+const ALLOW: &str = "allow";
+
+/// Lints a module.
+///
+/// # Arguments
+///
+/// * `module` - The module to lint.
+/// * `config` - The level at which each lint is reported.
+///
+/// # Returns
+///
+/// The diagnostics raised by enabled lints, along with warnings about malformed attributes,
+/// ordered by their position in the source.
+pub fn lint(module: &Module, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        cache: &module.cache,
+        scopes: vec![HashMap::new()],
+        allowed: HashSet::new(),
+        diagnostics: Vec::new(),
+    };
+    for item in &module.items {
+        linter.item(item);
+    }
+    // imports may be used by any later item, so are only checked at the end of the module
+    linter.pop();
+    linter
+        .diagnostics
+        .sort_by_key(|diagnostic| diagnostic.span.start);
+    config.apply(linter.diagnostics)
+}
+
+/// The kinds of names that can be bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindingKind {
+    /// A top-level definition, which may be used by other modules.
+    Definition,
+    /// A local variable, such as a lambda parameter or a variable bound by a pattern.
+    Variable,
+    /// An imported name.
+    Import,
+}
+
+/// A name bound in a scope.
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    /// The kind of the binding.
+    kind: BindingKind,
+    /// Where the name was bound.
+    span: Span,
+    /// Whether the name has been referenced.
+    used: bool,
+    /// Whether the binding may go unused, as the item binding it allows that.
+    allowed: bool,
+}
+
+/// Walks a module, tracking the bindings in scope.
+struct Linter<'a> {
+    /// The interner that owns the keys of the module.
+    cache: &'a lasso::Rodeo,
+    /// The bindings in scope, innermost scope last.
+    scopes: Vec<HashMap<lasso::Spur, Binding>>,
+    /// The lints allowed by the attributes of the current item.
+    allowed: HashSet<Lint>,
+    /// The diagnostics raised so far.
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn name(&self, ident: &Ident) -> &str {
+        self.cache.resolve(&ident.key)
+    }
+
+    /// Records a diagnostic, unless the current item allows its lint.
+    fn report(&mut self, diagnostic: Diagnostic) {
+        if !diagnostic
+            .lint
+            .is_some_and(|lint| self.allowed.contains(&lint))
+        {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn item(&mut self, item: &Item) {
+        self.allowed = self.attributes(item);
+        match &item.kind {
+            ItemKind::Import(import_tree) => self.import(import_tree),
+            // exporting a name uses it, so that imports can be exported again
+            ItemKind::Export(ExportKind::List(items)) => {
+                for export in items {
+                    self.use_name(&export.name);
+                }
+            }
+            ItemKind::Export(ExportKind::Glob { .. }) => {}
+            ItemKind::TypeAlias(type_alias) => self.ty(&type_alias.ty),
+            ItemKind::Error => {}
+            // definitions are bound before their body, as they may be recursive
+            ItemKind::Definition(definition) => {
+                self.destructor(&definition.name, BindingKind::Definition);
+                self.expr(&definition.expr);
+            }
+            ItemKind::Function(function) => {
+                self.bind(&function.name, BindingKind::Definition);
+                if !exhaustive_clauses(&function.clauses) {
+                    let message = format!(
+                        "the clauses of `{}` do not cover every argument",
+                        self.name(&function.name)
+                    );
+                    self.report(
+                        Diagnostic::lint(Lint::NonExhaustiveMatch, item.span, message)
+                            .with_note("add a clause that matches any argument"),
+                    );
+                }
+                for clause in &function.clauses {
+                    self.scopes.push(HashMap::new());
+                    for pattern in &clause.patterns {
+                        self.pattern(pattern);
+                    }
+                    self.expr(&clause.body);
+                    self.pop();
+                }
+            }
+        }
+    }
+
+    /// Collects the lints allowed by the attributes of an item.
+    fn attributes(&mut self, item: &Item) -> HashSet<Lint> {
+        let mut allowed = HashSet::new();
+        for attribute in &item.attributes {
+            let name = self.name(&attribute.name);
+            if name != ALLOW {
+                let message = format!("unknown attribute `{}`", name);
+                self.diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    attribute.name.span,
+                    message,
+                ));
+                continue;
+            }
+            for argument in &attribute.arguments {
+                let name = self.name(argument);
+                match name.parse() {
+                    Ok(lint) => {
+                        allowed.insert(lint);
+                    }
+                    Err(_) => {
+                        let message = format!("unknown lint `{}`", name);
+                        self.diagnostics.push(Diagnostic::new(
+                            Severity::Warning,
+                            argument.span,
+                            message,
+                        ));
+                    }
+                }
+            }
+        }
+        allowed
+    }
+
+    /// Binds a name in the innermost scope.
+    fn bind(&mut self, ident: &Ident, kind: BindingKind) {
+        let previous = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.key).copied());
+        if let Some(previous) = previous {
+            let message = format!("`{}` shadows an earlier binding", self.name(ident));
+            self.report(
+                Diagnostic::lint(Lint::Shadowing, ident.span, message)
+                    .with_label(previous.span, "previously bound here"),
+            );
+        }
+        let binding = Binding {
+            kind,
+            span: ident.span,
+            used: false,
+            allowed: unused_lint(kind).is_some_and(|lint| self.allowed.contains(&lint)),
+        };
+        let replaced = self
+            .scopes
+            .last_mut()
+            .expect("the module scope is only popped once linting is done")
+            .insert(ident.key, binding);
+        // a binding replaced in the same scope can no longer be used
+        if let Some(replaced) = replaced {
+            self.unused(ident.key, replaced);
+        }
+    }
+
+    /// Marks the binding a name refers to as used.
+    fn use_name(&mut self, ident: &Ident) {
+        if let Some(binding) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(&ident.key))
+        {
+            binding.used = true;
+        }
+    }
+
+    /// Pops the innermost scope, reporting the bindings in it that were never used.
+    fn pop(&mut self) {
+        let scope = self.scopes.pop().expect("scopes are balanced");
+        for (key, binding) in scope {
+            self.unused(key, binding);
+        }
+    }
+
+    /// Reports a binding if it was never used.
+    fn unused(&mut self, key: lasso::Spur, binding: Binding) {
+        let Some(lint) = unused_lint(binding.kind) else {
+            return;
+        };
+        let name = self.cache.resolve(&key);
+        // names starting with an underscore are deliberately unused
+        if binding.used || binding.allowed || name.starts_with('_') {
+            return;
+        }
+        let diagnostic = match binding.kind {
+            BindingKind::Import => {
+                Diagnostic::lint(lint, binding.span, format!("unused import `{}`", name))
+            }
+            _ => Diagnostic::lint(lint, binding.span, format!("unused variable `{}`", name))
+                .with_note(format!(
+                    "if this is intentional, prefix it with an underscore: `_{}`",
+                    name
+                )),
+        };
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn import(&mut self, import_tree: &ImportTree) {
+        match &import_tree.kind {
+            ImportTreeKind::Item { name, alias } => {
+                self.bind(alias.as_ref().unwrap_or(name), BindingKind::Import)
+            }
+            ImportTreeKind::Segment { child, .. } => self.import(child),
+            ImportTreeKind::Glob => {}
+            ImportTreeKind::List(import_trees) => {
+                for import_tree in import_trees {
+                    self.import(import_tree);
+                }
+            }
+        }
+    }
+
+    fn ty(&mut self, ty: &Type) {
+        match &ty.kind {
+            TypeKind::Primitive(_) => {}
+            TypeKind::Named(name) => self.use_name(name),
+            TypeKind::Generic { name, arguments } => {
+                self.use_name(name);
+                for argument in arguments {
+                    self.ty(argument);
+                }
+            }
+            TypeKind::Tuple(types) => {
+                for ty in types {
+                    self.ty(ty);
+                }
+            }
+            TypeKind::List(ty) => self.ty(ty),
+            TypeKind::Record(fields) => {
+                for ty in fields.values() {
+                    self.ty(ty);
+                }
+            }
+            TypeKind::Fn(params, ret) => {
+                for ty in params {
+                    self.ty(ty);
+                }
+                self.ty(ret);
+            }
+            TypeKind::Intersection { lhs, rhs } | TypeKind::Union { lhs, rhs } => {
+                self.ty(lhs);
+                self.ty(rhs);
+            }
+        }
+    }
+
+    fn destructor(&mut self, destructor: &Destructor, kind: BindingKind) {
+        match &destructor.kind {
+            DestructorKind::Var(ident) => self.bind(ident, kind),
+            DestructorKind::Tuple(destructors) => {
+                for destructor in destructors {
+                    self.destructor(destructor, kind);
+                }
+            }
+            DestructorKind::Record(fields) => {
+                for destructor in fields.values() {
+                    self.destructor(destructor, kind);
+                }
+            }
+            DestructorKind::Cons { lhs, rhs } => {
+                self.destructor(lhs, kind);
+                self.destructor(rhs, kind);
+            }
+            DestructorKind::Rest => {}
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Var(ident) => self.bind(ident, BindingKind::Variable),
+            PatternKind::Tuple(patterns) => {
+                for pattern in patterns {
+                    self.pattern(pattern);
+                }
+            }
+            PatternKind::Record(fields) => {
+                for pattern in fields.values() {
+                    self.pattern(pattern);
+                }
+            }
+            PatternKind::Cons { lhs, rhs } => {
+                self.pattern(lhs);
+                self.pattern(rhs);
+            }
+            PatternKind::Prefix { rest, .. } => self.pattern(rest),
+            // both alternatives bind the same names
+            PatternKind::Or { lhs, .. } => self.pattern(lhs),
+            PatternKind::Literal(_)
+            | PatternKind::Wildcard
+            | PatternKind::EmptyList
+            | PatternKind::Range { .. }
+            | PatternKind::Rest => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Var(ident) => self.use_name(ident),
+            ExprKind::Literal(_) | ExprKind::Hole(_) | ExprKind::Error => {}
+            ExprKind::BinaryExpr { lhs, rhs, .. }
+            | ExprKind::Range {
+                start: lhs,
+                end: rhs,
+                ..
+            }
+            | ExprKind::Index {
+                list: lhs,
+                index: rhs,
+            } => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            ExprKind::UnaryExpr { expr, .. }
+            | ExprKind::Section { operand: expr, .. }
+            | ExprKind::Spread(expr)
+            | ExprKind::Try(expr)
+            | ExprKind::Projection { tuple: expr, .. }
+            | ExprKind::Labelled { value: expr, .. } => self.expr(expr),
+            ExprKind::Tuple(exprs)
+            | ExprKind::List(exprs)
+            | ExprKind::Set(exprs)
+            | ExprKind::Sequence(exprs) => {
+                for expr in exprs {
+                    self.expr(expr);
+                }
+            }
+            ExprKind::Map(entries) => {
+                for (key, value) in entries {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            ExprKind::Record { fields } => {
+                for expr in fields.values() {
+                    self.expr(expr);
+                }
+            }
+            ExprKind::Conditional {
+                condition,
+                body,
+                otherwise,
+            } => {
+                self.expr(condition);
+                self.expr(body);
+                if let Some(otherwise) = otherwise {
+                    self.expr(otherwise);
+                }
+            }
+            ExprKind::Match { value, arms } => {
+                self.expr(value);
+                let patterns: Vec<_> = arms.iter().map(|arm| &arm.pattern).collect();
+                if !exhaustive(&patterns) {
+                    self.report(
+                        Diagnostic::lint(
+                            Lint::NonExhaustiveMatch,
+                            expr.span,
+                            "non-exhaustive match",
+                        )
+                        .with_note("add a `_` arm to handle the remaining values"),
+                    );
+                }
+                for arm in arms {
+                    self.scopes.push(HashMap::new());
+                    self.pattern(&arm.pattern);
+                    self.expr(&arm.expr);
+                    self.pop();
+                }
+            }
+            ExprKind::While { condition, body } => {
+                self.expr(condition);
+                self.expr(body);
+            }
+            ExprKind::For {
+                binding,
+                iterable,
+                body,
+            } => {
+                self.expr(iterable);
+                self.scopes.push(HashMap::new());
+                self.destructor(binding, BindingKind::Variable);
+                self.expr(body);
+                self.pop();
+            }
+            ExprKind::Lambda {
+                params,
+                ret_ty,
+                body,
+            } => {
+                // default values are evaluated outside of the lambda, so cannot see its parameters
+                for default in params.iter().filter_map(|param| param.default.as_ref()) {
+                    self.expr(default);
+                }
+                self.scopes.push(HashMap::new());
+                for param in params {
+                    if let Some(ty) = &param.ty {
+                        self.ty(ty);
+                    }
+                    self.destructor(&param.parameter, BindingKind::Variable);
+                }
+                if let Some(ret_ty) = ret_ty {
+                    self.ty(ret_ty);
+                }
+                self.expr(body);
+                self.pop();
+            }
+            ExprKind::Call {
+                function,
+                arguments,
+            } => {
+                self.expr(function);
+                for argument in arguments {
+                    self.expr(argument);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the lint raised when a binding of the given kind is never used.
+fn unused_lint(kind: BindingKind) -> Option<Lint> {
+    match kind {
+        BindingKind::Definition => None,
+        BindingKind::Variable => Some(Lint::UnusedVariable),
+        BindingKind::Import => Some(Lint::UnusedImport),
+    }
+}
+
+/// Returns whether a pattern matches every value of its type.
+fn irrefutable(pattern: &Pattern) -> bool {
+    match &pattern.kind {
+        PatternKind::Var(_)
+        | PatternKind::Wildcard
+        | PatternKind::Rest
+        | PatternKind::Literal(LiteralKind::Unit) => true,
+        PatternKind::Tuple(patterns) => patterns.iter().all(irrefutable),
+        PatternKind::Record(fields) => fields.values().all(irrefutable),
+        PatternKind::Or { lhs, rhs } => irrefutable(lhs) || irrefutable(rhs),
+        PatternKind::Literal(_)
+        | PatternKind::Cons { .. }
+        | PatternKind::EmptyList
+        | PatternKind::Range { .. }
+        | PatternKind::Prefix { .. } => false,
+    }
+}
+
+/// Returns whether a set of patterns is known to match every value of their type.
+///
+/// This is conservative: beyond irrefutable patterns, only arms covering both booleans, or both
+/// the empty and non-empty list, are recognised as exhaustive.
+fn exhaustive(patterns: &[&Pattern]) -> bool {
+    let mut alternatives = Vec::new();
+    let mut stack = patterns.to_vec();
+    while let Some(pattern) = stack.pop() {
+        match &pattern.kind {
+            PatternKind::Or { lhs, rhs } => {
+                stack.push(lhs);
+                stack.push(rhs);
+            }
+            _ => alternatives.push(pattern),
+        }
+    }
+    let covers = |predicate: fn(&Pattern) -> bool| alternatives.iter().any(|p| predicate(p));
+    covers(irrefutable)
+        || (covers(|p| matches!(p.kind, PatternKind::Literal(LiteralKind::Bool(true))))
+            && covers(|p| matches!(p.kind, PatternKind::Literal(LiteralKind::Bool(false)))))
+        || (covers(|p| matches!(p.kind, PatternKind::EmptyList))
+            && covers(
+                |p| matches!(&p.kind, PatternKind::Cons { lhs, rhs } if irrefutable(lhs) && irrefutable(rhs)),
+            ))
+}
+
+/// Returns whether the clauses of a function match every combination of arguments.
+///
+/// The clauses of a function of one argument are checked like the arms of a match. Otherwise,
+/// only a clause whose patterns are all irrefutable is known to cover every combination.
+fn exhaustive_clauses(clauses: &[Clause]) -> bool {
+    match clauses.first().map(|clause| clause.patterns.len()) {
+        Some(1) => exhaustive(
+            &clauses
+                .iter()
+                .map(|clause| &clause.patterns[0])
+                .collect::<Vec<_>>(),
+        ),
+        _ => clauses
+            .iter()
+            .any(|clause| clause.patterns.iter().all(irrefutable)),
+    }
+}
@@ -0,0 +1,209 @@
+use kali_error::{Diagnostic, Lint, LintConfig, LintLevel, Severity};
+use kali_lint::lint;
+use kali_parse::parse_str;
+
+/// Lints `src` with the given configuration.
+fn lint_with(src: &str, config: &LintConfig) -> Vec<Diagnostic> {
+    let module = parse_str(src).unwrap();
+    lint(&module, config)
+}
+
+/// Lints `src` with every lint at its default level, returning the lints raised.
+fn lints(src: &str) -> Vec<Option<Lint>> {
+    lint_with(src, &LintConfig::default())
+        .into_iter()
+        .map(|diagnostic| diagnostic.lint)
+        .collect()
+}
+
+#[test]
+fn test_shadowing() {
+    let diagnostics = lint_with("let x = 1;\nlet f = x -> x", &LintConfig::default());
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].lint, Some(Lint::Shadowing));
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].span.into_range(), 19..20);
+    assert_eq!(diagnostics[0].labels[0].span.into_range(), 4..5);
+
+    assert_eq!(lints("let x = 1;\nlet x = 2"), [Some(Lint::Shadowing)]);
+    assert_eq!(
+        lints("let f = x -> match x { (y, x) -> x + y }"),
+        [Some(Lint::Shadowing)]
+    );
+    assert!(lints("let f = x -> x;\nlet g = x -> x").is_empty());
+    assert!(lints("let f = (x, _y) -> match x { a -> a }").is_empty());
+}
+
+#[test]
+fn test_non_exhaustive_match() {
+    assert_eq!(
+        lints("let f = x -> match x { 1 -> 2 }"),
+        [Some(Lint::NonExhaustiveMatch)]
+    );
+    assert_eq!(
+        lints("let f = x -> match x { [] -> 1 }"),
+        [Some(Lint::NonExhaustiveMatch)]
+    );
+    assert!(lints("let f = x -> match x { 1 -> 2, _ -> 3 }").is_empty());
+    assert!(lints("let f = x -> match x { true -> 1, false -> 2 }").is_empty());
+    assert!(lints("let f = x -> match x { true | false -> 1 }").is_empty());
+    assert!(lints("let f = x -> match x { [] -> 1, _ :: _ -> 2 }").is_empty());
+    assert!(lints("let f = x -> match x { (a, _) -> a }").is_empty());
+}
+
+#[test]
+fn test_non_exhaustive_clauses() {
+    assert_eq!(
+        lints("fn fib 0 = 0;\nfn fib 1 = 1"),
+        [Some(Lint::NonExhaustiveMatch)]
+    );
+    assert_eq!(
+        lints("fn zip [] ys = ys;\nfn zip xs [] = xs"),
+        [Some(Lint::NonExhaustiveMatch)]
+    );
+    assert!(lints("fn fib 0 = 0;\nfn fib n = n").is_empty());
+    assert!(lints("fn not true = false;\nfn not false = true").is_empty());
+    assert!(lints("fn zip [] ys = ys;\nfn zip xs _ = xs").is_empty());
+    // each clause binds its own variables
+    assert_eq!(
+        lints("fn f x 0 = x;\nfn f x y = y"),
+        [Some(Lint::UnusedVariable)]
+    );
+}
+
+#[test]
+fn test_allow_attribute() {
+    let src = "#allow(shadowing)\nlet x = 1;\n#allow(shadowing, non-exhaustive-match)\nlet x = match 1 { 1 -> 2 }";
+    assert!(lints(src).is_empty());
+
+    // the attribute only applies to the item it is attached to
+    let src = "#allow(shadowing)\nlet f = x -> x;\nlet g = f -> f";
+    assert_eq!(lints(src), [Some(Lint::Shadowing)]);
+
+    // ordinary comments are not attributes
+    let src = "# allow(shadowing)\nlet x = 1;\nlet x = 2";
+    assert_eq!(lints(src), [Some(Lint::Shadowing)]);
+}
+
+#[test]
+fn test_unknown_attributes() {
+    let diagnostics = lint_with(
+        "#allow(shadowin)\n#deny(shadowing)\nlet x = 1",
+        &LintConfig::default(),
+    );
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        messages,
+        [
+            "warning: unknown lint `shadowin`",
+            "warning: unknown attribute `deny`"
+        ]
+    );
+    assert_eq!(diagnostics[0].span.into_range(), 7..15);
+    assert_eq!(diagnostics[1].span.into_range(), 18..22);
+}
+
+#[test]
+fn test_lint_levels() {
+    let src = "let x = 1;\nlet x = match x { 1 -> 2 }";
+
+    let mut config = LintConfig::default();
+    config.set(Lint::Shadowing, LintLevel::Deny);
+    let severities: Vec<_> = lint_with(src, &config)
+        .iter()
+        .map(|d| (d.lint, d.severity))
+        .collect();
+    assert_eq!(
+        severities,
+        [
+            (Some(Lint::Shadowing), Severity::Error),
+            (Some(Lint::NonExhaustiveMatch), Severity::Warning)
+        ]
+    );
+
+    let mut config = LintConfig::default();
+    config.set_named("warnings", LintLevel::Allow).unwrap();
+    assert!(lint_with(src, &config).is_empty());
+    assert!(config.set_named("shadowin", LintLevel::Deny).is_err());
+
+    // `#allow` takes precedence over a denied lint
+    let mut config = LintConfig::default();
+    config.set_named("warnings", LintLevel::Deny).unwrap();
+    let src = "let x = 1;\n#allow(shadowing)\nlet x = 2";
+    assert!(lint_with(src, &config).is_empty());
+}
+
+#[test]
+fn test_unused_variable() {
+    let diagnostics = lint_with("let f = (x, y) -> x", &LintConfig::default());
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "warning[unused-variable]: unused variable `y`"
+    );
+    assert_eq!(diagnostics[0].span.into_range(), 12..13);
+    assert_eq!(
+        diagnostics[0].notes,
+        ["if this is intentional, prefix it with an underscore: `_y`"]
+    );
+
+    assert_eq!(
+        lints("let f = x -> match x { (a, b) -> a }"),
+        [Some(Lint::UnusedVariable)]
+    );
+    assert_eq!(
+        lints("let f = {a: x, b: y} -> 1"),
+        [Some(Lint::UnusedVariable), Some(Lint::UnusedVariable)]
+    );
+    // parameters used by nested lambdas are used
+    assert!(lints("let f = x -> y -> (x, y)").is_empty());
+    // top-level definitions may be used by other modules
+    assert!(lints("let x = 1;\nlet (a, b) = (1, 2)").is_empty());
+}
+
+#[test]
+fn test_underscore_prefix() {
+    assert!(lints("let f = (_x, y) -> y").is_empty());
+    assert!(lints("let f = x -> match x { (a, _b) -> a }").is_empty());
+}
+
+#[test]
+fn test_unused_import() {
+    let diagnostics = lint_with(
+        "import std::{io, fs};\nlet f = x -> io",
+        &LintConfig::default(),
+    );
+    assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "warning[unused-import]: unused import `fs`"
+    );
+    assert_eq!(diagnostics[0].span.into_range(), 17..19);
+    assert_eq!(diagnostics[1].lint, Some(Lint::UnusedVariable));
+
+    // imports are used by any later item, including through types
+    assert!(lints("import std::io;\nlet f = x io -> x").is_empty());
+    assert!(lints("import std::io;\ntype t = io").is_empty());
+    assert!(lints("import std::io as o;\nlet x = o").is_empty());
+    assert_eq!(
+        lints("import std::io as o;\nlet x = io"),
+        [Some(Lint::UnusedImport)]
+    );
+    assert!(lints("import std::*").is_empty());
+    // exporting an import uses it
+    assert!(lints("import std::io;\nexport {io as o}").is_empty());
+}
+
+#[test]
+fn test_allow_unused() {
+    assert!(lints("#allow(unused-variable)\nlet f = (x, y) -> 1").is_empty());
+    assert!(lints("#allow(unused-import)\nimport std::io").is_empty());
+    assert_eq!(
+        lints("#allow(unused-variable)\nlet f = x -> 1;\nlet g = y -> 1"),
+        [Some(Lint::UnusedVariable)]
+    );
+
+    let mut config = LintConfig::default();
+    config.set(Lint::UnusedImport, LintLevel::Allow);
+    assert!(lint_with("import std::io", &config).is_empty());
+}
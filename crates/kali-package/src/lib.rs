@@ -0,0 +1,324 @@
+//! Packages of Kali modules.
+//!
+//! A package is a directory with a `kali.toml` manifest, naming the package, the directories its
+//! modules are in, and the packages it depends on:
+//!
+//! ```toml
+//! [package]
+//! name = "shapes"
+//! version = "0.1.0"
+//! sources = ["src"]
+//!
+//! [dependencies]
+//! geometry = { path = "../geometry" }
+//!
+//! [format]
+//! max_width = 80
+//! ```
+//!
+//! Within the package, `import a::b::c` imports from the module `a/b.kali` in one of its source
+//! directories, and `import geometry::a::b` from `a.kali` in one of the source directories of the
+//! `geometry` dependency.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// The name of the manifest file of a package.
+pub const MANIFEST: &str = "kali.toml";
+
+/// The extension of Kali modules.
+const EXTENSION: &str = "kali";
+
+/// The module of a dependency imported by the name of the dependency alone.
+const LIB: &str = "lib";
+
+/// An error encountered while loading a package.
+#[derive(Debug, Error)]
+pub enum PackageError {
+    /// A file of the package could not be read.
+    #[error("could not read `{}`: {source}", path.display())]
+    Io {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying error.
+        source: std::io::Error,
+    },
+    /// A manifest is not valid.
+    #[error("invalid manifest `{}`: {source}", path.display())]
+    Manifest {
+        /// The path of the manifest.
+        path: PathBuf,
+        /// The underlying error.
+        source: toml::de::Error,
+    },
+    /// No manifest was found in a directory or any of its ancestors.
+    #[error("could not find `{MANIFEST}` in `{}` or any parent directory", .0.display())]
+    NotFound(PathBuf),
+    /// A package depends on itself, through the given chain of dependencies.
+    #[error("dependency cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// The contents of a `kali.toml` manifest.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// The package the manifest describes.
+    pub package: Metadata,
+    /// The packages the package depends on, by the name they are imported by.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+    /// How `kali fmt` formats the modules of the package.
+    #[serde(default)]
+    pub format: Format,
+}
+
+/// The `[package]` table of a manifest.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Metadata {
+    /// The name of the package.
+    pub name: String,
+    /// The version of the package.
+    pub version: String,
+    /// The directories the modules of the package are in, relative to the manifest.
+    #[serde(default = "default_sources")]
+    pub sources: Vec<PathBuf>,
+}
+
+/// A package depended on.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    /// The directory of the package, relative to the manifest depending on it.
+    pub path: PathBuf,
+}
+
+/// The `[format]` table of a manifest. Options left out keep the defaults of the formatter.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Format {
+    /// The width lines are kept within where possible.
+    pub max_width: Option<usize>,
+    /// Whether to indent with tabs rather than spaces.
+    pub tabs: Option<bool>,
+    /// The number of spaces to indent with, when not indenting with tabs.
+    pub indent_width: Option<usize>,
+    /// Whether the last of the elements laid out on lines of their own ends with a comma.
+    pub trailing_commas: Option<bool>,
+    /// Whether a formatted module ends with a newline.
+    pub final_newline: Option<bool>,
+}
+
+/// Modules are in `src` unless the manifest says otherwise.
+fn default_sources() -> Vec<PathBuf> {
+    vec![PathBuf::from("src")]
+}
+
+impl Manifest {
+    /// Parses the contents of a manifest.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The contents of the manifest.
+    pub fn parse(src: &str) -> Result<Manifest, toml::de::Error> {
+        toml::from_str(src)
+    }
+}
+
+/// A package, along with the packages it depends on.
+#[derive(Debug, Clone)]
+pub struct Package {
+    /// The directory of the package's manifest.
+    pub root: PathBuf,
+    /// The package's manifest.
+    pub manifest: Manifest,
+    /// The packages the package depends on, by the name they are imported by.
+    pub dependencies: BTreeMap<String, Package>,
+}
+
+impl Package {
+    /// Loads the package in a directory, along with its dependencies.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The directory containing the manifest.
+    pub fn load(root: &Path) -> Result<Package, PackageError> {
+        Self::load_from(root, &mut Vec::new())
+    }
+
+    /// Loads the package whose manifest is in a directory or the nearest of its ancestors.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to start looking from. An empty path, such as the parent of a bare
+    ///   file name, is the current directory.
+    pub fn find(dir: &Path) -> Result<Package, PackageError> {
+        let dir = match dir.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => dir,
+        };
+        let dir = std::path::absolute(dir).map_err(|source| PackageError::Io {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        let root = dir
+            .ancestors()
+            .find(|dir| dir.join(MANIFEST).is_file())
+            .ok_or_else(|| PackageError::NotFound(dir.clone()))?;
+        Self::load(root)
+    }
+
+    /// Loads a package, given the packages depending on it, to find dependency cycles.
+    fn load_from(
+        root: &Path,
+        loading: &mut Vec<(PathBuf, String)>,
+    ) -> Result<Package, PackageError> {
+        let io = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| PackageError::Io { path, source }
+        };
+        let root = root.canonicalize().map_err(io(root))?;
+        let path = root.join(MANIFEST);
+        let src = std::fs::read_to_string(&path).map_err(io(&path))?;
+        let manifest =
+            Manifest::parse(&src).map_err(|source| PackageError::Manifest { path, source })?;
+
+        if let Some(index) = loading.iter().position(|(dir, _)| *dir == root) {
+            let mut cycle: Vec<_> = loading[index..]
+                .iter()
+                .map(|(_, name)| name.clone())
+                .collect();
+            cycle.push(manifest.package.name);
+            return Err(PackageError::Cycle(cycle));
+        }
+        loading.push((root.clone(), manifest.package.name.clone()));
+        let dependencies = manifest
+            .dependencies
+            .iter()
+            .map(|(name, dependency)| {
+                let package = Self::load_from(&root.join(&dependency.path), loading)?;
+                Ok((name.clone(), package))
+            })
+            .collect::<Result<_, PackageError>>()?;
+        loading.pop();
+
+        Ok(Package {
+            root,
+            manifest,
+            dependencies,
+        })
+    }
+
+    /// Returns the name of the package.
+    pub fn name(&self) -> &str {
+        &self.manifest.package.name
+    }
+
+    /// Returns the directories the modules of the package are in.
+    pub fn sources(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.manifest
+            .package
+            .sources
+            .iter()
+            .map(|dir| self.root.join(dir))
+    }
+
+    /// Returns the paths of every module of the package, not including its dependencies, sorted.
+    pub fn modules(&self) -> Result<Vec<PathBuf>, PackageError> {
+        let mut modules = Vec::new();
+        for dir in self.sources() {
+            collect(&dir, &mut modules)?;
+        }
+        modules.sort();
+        Ok(modules)
+    }
+
+    /// Returns the package a module belongs to, either this package or one it depends on,
+    /// directly or otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the module.
+    pub fn owner(&self, file: &Path) -> Option<&Package> {
+        let file = file.canonicalize().ok()?;
+        self.owner_of(&file)
+    }
+
+    /// Returns the package a module belongs to, given its canonical path.
+    fn owner_of(&self, file: &Path) -> Option<&Package> {
+        if self.sources().any(|dir| file.starts_with(dir)) {
+            return Some(self);
+        }
+        self.dependencies
+            .values()
+            .find_map(|dependency| dependency.owner_of(file))
+    }
+
+    /// Returns the search path the modules of the package import from.
+    pub fn search_path(&self) -> SearchPath {
+        let mut roots: Vec<_> = self.sources().map(|dir| (None, dir)).collect();
+        for (name, dependency) in &self.dependencies {
+            roots.extend(dependency.sources().map(|dir| (Some(name.clone()), dir)));
+        }
+        SearchPath { roots }
+    }
+}
+
+/// Collects the modules in a directory and its subdirectories.
+fn collect(dir: &Path, modules: &mut Vec<PathBuf>) -> Result<(), PackageError> {
+    let entries = std::fs::read_dir(dir).map_err(|source| PackageError::Io {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect(&path, modules)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == EXTENSION)
+        {
+            modules.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The directories the modules of a package import from.
+#[derive(Debug, Clone, Default)]
+pub struct SearchPath {
+    /// The source directories of the package, then those of its dependencies along with the name
+    /// they are imported by.
+    roots: Vec<(Option<String>, PathBuf)>,
+}
+
+impl SearchPath {
+    /// Resolves the path of a module, e.g. `a::b`, to the file it is in.
+    ///
+    /// # Returns
+    /// The first existing file the path names, or `None` if there is none.
+    pub fn resolve(&self, module: &str) -> Option<PathBuf> {
+        let segments: Vec<_> = module.split("::").collect();
+        self.roots.iter().find_map(|(name, dir)| {
+            let segments = match name {
+                None => &segments[..],
+                Some(name) if segments[0] == name => match &segments[1..] {
+                    [] => &[LIB][..],
+                    rest => rest,
+                },
+                Some(_) => return None,
+            };
+            let file = segments
+                .iter()
+                .fold(dir.clone(), |file, segment| file.join(segment))
+                .with_extension(EXTENSION);
+            file.is_file().then_some(file)
+        })
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use kali_package::{Manifest, Package, PackageError};
+
+/// Creates a directory of files, given their paths relative to it and their contents.
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kali-package-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    for (path, contents) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+fn manifest(name: &str, dependencies: &str) -> String {
+    format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\n\n[dependencies]\n{dependencies}")
+}
+
+#[test]
+fn test_manifest() {
+    let manifest =
+        Manifest::parse(&manifest("shapes", "geometry = { path = \"../geometry\" }")).unwrap();
+    assert_eq!(manifest.package.name, "shapes");
+    assert_eq!(manifest.package.sources, [Path::new("src")]);
+    assert_eq!(
+        manifest.dependencies["geometry"].path,
+        Path::new("../geometry")
+    );
+
+    assert!(
+        Manifest::parse("[package]\nname = \"shapes\"\nversion = \"0.1.0\"\nedition = 1").is_err()
+    );
+    assert!(Manifest::parse("[package]\nname = \"shapes\"").is_err());
+}
+
+#[test]
+fn test_format() {
+    let default = Manifest::parse(&manifest("shapes", "")).unwrap();
+    assert_eq!(default.format, Default::default());
+
+    let src = format!(
+        "{}\n[format]\nmax_width = 80\ntabs = false",
+        manifest("shapes", "")
+    );
+    let parsed = Manifest::parse(&src).unwrap().format;
+    assert_eq!(parsed.max_width, Some(80));
+    assert_eq!(parsed.tabs, Some(false));
+    assert_eq!(parsed.indent_width, None);
+    assert!(Manifest::parse(&format!("{}\n[format]\nwidth = 80", manifest("shapes", ""))).is_err());
+}
+
+#[test]
+fn test_search_path() {
+    let dir = tree(
+        "search",
+        &[
+            (
+                "app/kali.toml",
+                &manifest("app", "geo = { path = \"../geometry\" }"),
+            ),
+            ("app/src/main.kali", ""),
+            ("app/src/shapes/circle.kali", ""),
+            ("geometry/kali.toml", &manifest("geometry", "")),
+            ("geometry/src/lib.kali", ""),
+            ("geometry/src/point.kali", ""),
+        ],
+    );
+    let package = Package::find(&dir.join("app/src/shapes")).unwrap();
+    assert_eq!(package.name(), "app");
+    assert_eq!(package.dependencies["geo"].name(), "geometry");
+
+    let modules: Vec<_> = package
+        .modules()
+        .unwrap()
+        .into_iter()
+        .map(|path| path.strip_prefix(&package.root).unwrap().to_path_buf())
+        .collect();
+    assert_eq!(
+        modules,
+        [
+            Path::new("src/main.kali"),
+            Path::new("src/shapes/circle.kali")
+        ]
+    );
+
+    let search = package.search_path();
+    let resolve = |module| {
+        search.resolve(module).map(|path| {
+            path.strip_prefix(dir.canonicalize().unwrap())
+                .unwrap()
+                .to_path_buf()
+        })
+    };
+    assert_eq!(
+        resolve("shapes::circle").unwrap(),
+        Path::new("app/src/shapes/circle.kali")
+    );
+    assert_eq!(
+        resolve("geo::point").unwrap(),
+        Path::new("geometry/src/point.kali")
+    );
+    assert_eq!(resolve("geo").unwrap(), Path::new("geometry/src/lib.kali"));
+    assert_eq!(resolve("geometry::point"), None);
+
+    let point = dir.join("geometry/src/point.kali");
+    assert_eq!(package.owner(&point).unwrap().name(), "geometry");
+}
+
+#[test]
+fn test_dependency_cycle() {
+    let dir = tree(
+        "cycle",
+        &[
+            ("a/kali.toml", &manifest("a", "b = { path = \"../b\" }")),
+            ("b/kali.toml", &manifest("b", "a = { path = \"../a\" }")),
+        ],
+    );
+    match Package::load(&dir.join("a")) {
+        Err(PackageError::Cycle(cycle)) => assert_eq!(cycle, ["a", "b", "a"]),
+        result => panic!("expected a cycle, found {:?}", result),
+    }
+
+    assert!(matches!(
+        Package::find(&std::env::temp_dir().join("kali-package-nowhere")),
+        Err(PackageError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_find_from_bare_file_name() {
+    // the parent of `main.kali` is the empty path, which is the current directory
+    let dir = Path::new("main.kali").parent().unwrap();
+    let Err(PackageError::NotFound(searched)) = Package::find(dir) else {
+        panic!("expected no package to be found");
+    };
+    assert_eq!(searched, std::env::current_dir().unwrap());
+}
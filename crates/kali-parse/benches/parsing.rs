@@ -0,0 +1,66 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use kali_parse::{parse_str, tokens};
+use kali_span::FileId;
+
+/// A single expression, a balanced tree of binary operators over `2^depth` leaves.
+fn expression(depth: usize) -> String {
+    fn tree(depth: usize, leaf: &mut usize) -> String {
+        if depth == 0 {
+            *leaf += 1;
+            return format!("x{}", *leaf);
+        }
+        let op = ["+", "*", "-", "&&", "|>"][depth % 5];
+        format!(
+            "({} {} {})",
+            tree(depth - 1, leaf),
+            op,
+            tree(depth - 1, leaf)
+        )
+    }
+    format!("let e = {}", tree(depth, &mut 0))
+}
+
+/// A long module of small functions calling one another.
+fn functions(count: usize) -> String {
+    (0..count)
+        .map(|i| match i {
+            0 => "let f0 = a, b -> if a { [b] } else { [] }".to_string(),
+            i => format!("let f{} = a, b nat -> f{}(!a, b + {})", i, i - 1, i),
+        })
+        .collect::<Vec<_>>()
+        .join(";\n")
+}
+
+/// Matches nested in the last arm of one another.
+fn matches(depth: usize) -> String {
+    let mut src = "0".to_string();
+    for i in (0..depth).rev() {
+        src = format!(
+            "match x{} {{ (a, b) :: _ -> a, 1..=9 | 10 -> {}, _ -> {} }}",
+            i, i, src
+        );
+    }
+    format!("let m = {}", src)
+}
+
+fn parsing(c: &mut Criterion) {
+    let sources = [
+        ("expression", expression(12)),
+        ("functions", functions(500)),
+        ("matches", matches(40)),
+    ];
+    let mut group = c.benchmark_group("parsing");
+    for (name, src) in &sources {
+        // fail early rather than benchmarking error recovery
+        parse_str(src).unwrap();
+        group.throughput(Throughput::Bytes(src.len() as u64));
+        group.bench_function(format!("lex/{}", name), |b| {
+            b.iter(|| tokens(FileId::default(), src))
+        });
+        group.bench_function(format!("parse/{}", name), |b| b.iter(|| parse_str(src)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parsing);
+criterion_main!(benches);
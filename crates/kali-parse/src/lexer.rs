@@ -1,7 +1,8 @@
 //! A lexer for the Kali programming language.
 
-use std::num::ParseIntError;
+use std::fmt;
 
+use kali_span::{FileId, Span};
 use logos::Logos;
 
 /// An enumeration of possbile tokens that can be lexed from source code.
@@ -18,6 +19,14 @@ pub enum Token<'src> {
     KeywordMatch,
     #[token("with")]
     KeywordWith,
+    #[token("while")]
+    KeywordWhile,
+    #[token("for")]
+    KeywordFor,
+    #[token("in")]
+    KeywordIn,
+    #[token("do")]
+    KeywordDo,
     #[token("let")]
     KeywordLet,
     #[token("fn")]
@@ -42,6 +51,8 @@ pub enum Token<'src> {
     TypeBool,
     #[token("string")]
     TypeString,
+    #[token("bytes")]
+    TypeBytes,
     #[token("=")]
     OpAssign,
     #[token("==")]
@@ -80,31 +91,32 @@ pub enum Token<'src> {
     OpLogicalOr,
     #[token("@")]
     OpConcat,
+    #[token("++")]
+    OpAppend,
     #[token("&")]
     OpBitwiseAnd,
     #[token("|")]
     OpBitwiseOr,
+    #[token("|>")]
+    OpPipe,
     #[token("^")]
     OpBitwiseXor,
     #[token("<<")]
     OpBitwiseShiftLeft,
-    #[token(">>")]
-    OpBitwiseShiftRight,
+    // `>>` is deliberately not a token, as it would close two generic argument lists at once in
+    // `List<List<int>>`. A right shift must instead be parsed as two adjacent `>` tokens.
     #[regex("(\\w|_)+", priority = 0)]
     Ident(&'src str),
+    /// A typed hole, such as `?todo`, holding its name without the `?`.
+    #[regex(r"\?(\w|_)+", |lex| &lex.slice()[1..])]
+    Hole(&'src str),
     // literals
-    #[regex("[0-9][0-9_]*", |lex| lex.slice().parse().ok(), priority = 1)]
-    #[regex("0x[0-9a-fA-F][0-9a-fA-F_]*", |lex| prefixed_natural(lex))]
-    #[regex("0b[01][01_]*", |lex| prefixed_natural(lex))]
-    #[regex("0o[0-7][0-7_]*", |lex| prefixed_natural(lex))]
-    #[regex("0d[0-9][0-9_]*", |lex| prefixed_natural(lex))]
+    #[regex("[0-9][0-9_]*", natural, priority = 1)]
+    #[regex("0x[0-9a-fA-F][0-9a-fA-F_]*", natural)]
+    #[regex("0b[01][01_]*", natural)]
+    #[regex("0o[0-7][0-7_]*", natural)]
+    #[regex("0d[0-9][0-9_]*", natural)]
     LitNatural(u64),
-    #[regex("-[0-9][0-9_]*", |lex| lex.slice().parse().ok())]
-    #[regex("-0x[0-9a-fA-F][0-9a-fA-F_]*", |lex| prefixed_integer(lex))]
-    #[regex("-0b[01][01_]*", |lex| prefixed_integer(lex))]
-    #[regex("-0o[0-7][0-7_]*", |lex| prefixed_integer(lex))]
-    #[regex("-0d[0-9][0-9_]*", |lex| prefixed_integer(lex))]
-    LitInteger(i64),
     #[token("true", |_| true)]
     #[token("false", |_| false)]
     LitBool(bool),
@@ -112,17 +124,27 @@ pub enum Token<'src> {
     LitUnit,
     #[regex(r#""[^"]*""#, |lex| &lex.slice()[1..lex.slice().len() - 1])]
     LitString(&'src str),
+    #[regex(r#"b"([^"\\]|\\.)*""#, byte_string)]
+    LitBytes(Vec<u8>),
     // symbols
     #[token("(")]
     SymLParen,
+    /// A `(` that directly follows an expression, opening an argument list. See [`lex`].
+    SymCallParen,
     #[token(")")]
     SymRParen,
     #[token("[")]
     SymLBracket,
+    /// A `[` that directly follows an expression, opening an index. See [`lex`].
+    SymIndexBracket,
     #[token("]")]
     SymRBracket,
     #[token("{")]
     SymLBrace,
+    #[token("#{")]
+    SymHashBrace,
+    #[token("#[")]
+    SymHashBracket,
     #[token("}")]
     SymRBrace,
     #[token(",")]
@@ -139,51 +161,290 @@ pub enum Token<'src> {
     SymSemicolon,
     #[token("...")]
     SymRest,
+    #[token("..")]
+    SymSpread,
+    #[token("..=")]
+    SymRangeInclusive,
+    #[token("?")]
+    SymQuestion,
+    #[token(".")]
+    SymDot,
 
     #[regex("[\n\t ]+", logos::skip)]
     Whitespace,
 
-    // `allow_greedy` is fine since we prefix it with `#`.
-    #[regex("#.*\n", logos::skip, allow_greedy = true)]
-    Comment,
+    // `allow_greedy` is fine since we prefix it with `#`. Comments of the form `#name(...)` are
+    // attributes, and all other comments are skipped. A `#{` opens a map and a `#[` a set instead.
+    #[regex("#([^{\\[\n].*)?\n", attribute, allow_greedy = true)]
+    Attribute(&'src str),
 
     Error(LexicalError),
 }
 
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::KeywordIf => f.write_str("if"),
+            Token::KeywordThen => f.write_str("then"),
+            Token::KeywordElse => f.write_str("else"),
+            Token::KeywordMatch => f.write_str("match"),
+            Token::KeywordWith => f.write_str("with"),
+            Token::KeywordWhile => f.write_str("while"),
+            Token::KeywordFor => f.write_str("for"),
+            Token::KeywordIn => f.write_str("in"),
+            Token::KeywordDo => f.write_str("do"),
+            Token::KeywordLet => f.write_str("let"),
+            Token::KeywordFn => f.write_str("fn"),
+            Token::KeywordType => f.write_str("type"),
+            Token::KeywordImport => f.write_str("import"),
+            Token::KeywordExport => f.write_str("export"),
+            Token::KeywordFrom => f.write_str("from"),
+            Token::KeywordAs => f.write_str("as"),
+            Token::TypeInteger => f.write_str("int"),
+            Token::TypeNatural => f.write_str("nat"),
+            Token::TypeFloat => f.write_str("float"),
+            Token::TypeBool => f.write_str("bool"),
+            Token::TypeString => f.write_str("string"),
+            Token::TypeBytes => f.write_str("bytes"),
+            Token::OpAssign => f.write_str("="),
+            Token::OpEqual => f.write_str("=="),
+            Token::OpNotEqual => f.write_str("!="),
+            Token::OpLessThan => f.write_str("<"),
+            Token::OpLessThanOrEqual => f.write_str("<="),
+            Token::OpGreaterThan => f.write_str(">"),
+            Token::OpGreaterThanOrEqual => f.write_str(">="),
+            Token::OpAdd => f.write_str("+"),
+            Token::OpSubtract => f.write_str("-"),
+            Token::OpMultiply => f.write_str("*"),
+            Token::OpDivide => f.write_str("/"),
+            Token::OpModulo => f.write_str("%"),
+            Token::OpExponentiate => f.write_str("**"),
+            Token::OpCons => f.write_str("::"),
+            Token::OpNegate => f.write_str("!"),
+            Token::OpBitwiseNot => f.write_str("~"),
+            Token::OpLogicalAnd => f.write_str("&&"),
+            Token::OpLogicalOr => f.write_str("||"),
+            Token::OpConcat => f.write_str("@"),
+            Token::OpAppend => f.write_str("++"),
+            Token::OpBitwiseAnd => f.write_str("&"),
+            Token::OpBitwiseOr => f.write_str("|"),
+            Token::OpPipe => f.write_str("|>"),
+            Token::OpBitwiseXor => f.write_str("^"),
+            Token::OpBitwiseShiftLeft => f.write_str("<<"),
+            Token::LitUnit => f.write_str("()"),
+            Token::SymLParen | Token::SymCallParen => f.write_str("("),
+            Token::SymRParen => f.write_str(")"),
+            Token::SymLBracket | Token::SymIndexBracket => f.write_str("["),
+            Token::SymRBracket => f.write_str("]"),
+            Token::SymLBrace => f.write_str("{"),
+            Token::SymRBrace => f.write_str("}"),
+            Token::SymComma => f.write_str(","),
+            Token::SymColon => f.write_str(":"),
+            Token::SymArrow => f.write_str("->"),
+            Token::SymArray => f.write_str("[]"),
+            Token::SymWildcard => f.write_str("_"),
+            Token::SymSemicolon => f.write_str(";"),
+            Token::SymHashBrace => f.write_str("#{"),
+            Token::SymHashBracket => f.write_str("#["),
+            Token::SymRest => f.write_str("..."),
+            Token::SymSpread => f.write_str(".."),
+            Token::SymRangeInclusive => f.write_str("..="),
+            Token::SymQuestion => f.write_str("?"),
+            Token::SymDot => f.write_str("."),
+            Token::Ident(ident) => write!(f, "{}", ident),
+            Token::Hole(name) => write!(f, "?{}", name),
+            Token::LitNatural(value) => write!(f, "{}", value),
+            Token::LitBool(value) => write!(f, "{}", value),
+            Token::LitString(value) => write!(f, "\"{}\"", value),
+            Token::LitBytes(_) => f.write_str("byte string"),
+            Token::Whitespace => f.write_str(" "),
+            Token::Attribute(attribute) => write!(f, "#{}", attribute),
+            Token::Error(_) => f.write_str("invalid token"),
+        }
+    }
+}
+
+impl Token<'_> {
+    /// Returns whether the token can end an expression.
+    fn ends_expression(&self) -> bool {
+        matches!(
+            self,
+            Token::Ident(_)
+                | Token::Hole(_)
+                | Token::LitNatural(_)
+                | Token::LitBool(_)
+                | Token::LitUnit
+                | Token::LitString(_)
+                | Token::LitBytes(_)
+                | Token::SymRParen
+                | Token::SymRBracket
+                | Token::SymRBrace
+                | Token::SymArray
+                | Token::SymQuestion
+        )
+    }
+}
+
+/// Lexes source code into a sequence of tokens and their spans.
+///
+/// A `(` that directly follows the end of an expression, without whitespace in between, opens an
+/// argument list and is lexed as [`Token::SymCallParen`]. This way `f(x, y)` calls `f` with two
+/// arguments, while `f (x, y)` applies `f` to a tuple. Likewise, a `[` that directly follows the
+/// end of an expression opens an index and is lexed as [`Token::SymIndexBracket`], so `xs[0]`
+/// indexes `xs`, while `f [0]` applies `f` to a list.
+pub(crate) fn lex(src: &str, file_id: FileId) -> Vec<(Token<'_>, Span)> {
+    let mut tokens: Vec<(Token, Span)> = Vec::new();
+    for (token, span) in Token::lexer(src).spanned() {
+        let token = token.unwrap_or_else(Token::Error);
+        let call = tokens
+            .last()
+            .is_some_and(|(last, last_span)| last_span.end == span.start && last.ends_expression());
+        match token {
+            Token::SymLParen if call => {
+                tokens.push((Token::SymCallParen, Span::new(file_id, span)))
+            }
+            Token::SymLBracket if call => {
+                tokens.push((Token::SymIndexBracket, Span::new(file_id, span)))
+            }
+            // `f()` is an empty argument list rather than a unit argument
+            Token::LitUnit if call => {
+                let open = Span::new(file_id, span.start..span.start + 1);
+                tokens.push((Token::SymCallParen, open));
+                tokens.push((
+                    Token::SymRParen,
+                    Span::new(file_id, span.start + 1..span.end),
+                ));
+            }
+            token => tokens.push((token, Span::new(file_id, span))),
+        }
+    }
+    tokens
+}
+
+/// Returns whether the source code covered by a token's span lexes back to that token.
+///
+/// Tokens made up by [`lex`] are compared with what their text lexes to on its own, so a
+/// [`Token::SymCallParen`] must cover a `(`, and a [`Token::SymIndexBracket`] a `[`.
+///
+/// # Arguments
+///
+/// * `src` - The source code the token was lexed from.
+/// * `token` - The token to check.
+/// * `span` - The span of the token.
+///
+/// # Returns
+///
+/// Whether the span covers exactly the text of the token.
+pub(crate) fn is_aligned(src: &str, token: &Token, span: Span) -> bool {
+    let Some(text) = src.get(span.into_range()) else {
+        return false;
+    };
+    let mut lexer = Token::lexer(text);
+    let relexed = match lexer.next() {
+        Some(token) => token.unwrap_or_else(Token::Error),
+        None => return false,
+    };
+    if lexer.next().is_some() {
+        return false;
+    }
+    match token {
+        Token::SymCallParen => relexed == Token::SymLParen,
+        Token::SymIndexBracket => relexed == Token::SymLBracket,
+        token => relexed == *token,
+    }
+}
+
+/// An error raised when the source code cannot be split into tokens.
 #[derive(Default, Debug, Clone, PartialEq)]
 pub enum LexicalError {
-    InvalidInteger(ParseIntError),
+    /// A natural literal greater than [`u64::MAX`], the largest `nat`.
+    NaturalOutOfRange,
+    /// A byte string literal containing an escape sequence that is not recognised.
+    InvalidEscape,
     #[default]
     InvalidToken,
 }
 
-fn prefixed_natural<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> Option<u64> {
-    let slice = lex.slice();
-    let radix = match &slice[..2] {
-        "0x" => 16,
-        "0b" => 2,
-        "0o" => 8,
-        "0d" => 10,
-        _ => unreachable!(),
+impl fmt::Display for LexicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexicalError::NaturalOutOfRange => {
+                f.write_str("literal out of range for `nat`, which is an unsigned 64-bit integer")
+            }
+            LexicalError::InvalidEscape => f.write_str(
+                "invalid escape in byte string, expected one of `\\xNN`, `\\n`, `\\r`, `\\t`, `\\0`, `\\\\` or `\\\"`",
+            ),
+            LexicalError::InvalidToken => f.write_str("invalid token"),
+        }
+    }
+}
+
+/// Emits a comment of the form `#name(arguments)` as an attribute, and skips any other comment.
+///
+/// The emitted text excludes the leading `#` and the trailing newline.
+fn attribute<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> logos::Filter<&'src str> {
+    let text = lex.slice()[1..].trim_end();
+    let is_attribute = text.ends_with(')')
+        && text.split_once('(').is_some_and(|(name, _)| {
+            !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        });
+    if is_attribute {
+        logos::Filter::Emit(text)
+    } else {
+        logos::Filter::Skip
+    }
+}
+
+/// Splits a numeric literal into its digits, without underscore separators, and its radix.
+///
+/// The literal may be prefixed with `0x`, `0b`, `0o` or `0d`, and is decimal otherwise.
+fn digits(literal: &str) -> (String, u32) {
+    let (radix, digits) = match literal.get(..2) {
+        Some("0x") => (16, &literal[2..]),
+        Some("0b") => (2, &literal[2..]),
+        Some("0o") => (8, &literal[2..]),
+        Some("0d") => (10, &literal[2..]),
+        _ => (10, literal),
     };
-    // remove underscore separators and parse
-    let slice = slice[2..].replace("_", "");
-    u64::from_str_radix(&slice, radix).ok()
+    (digits.replace('_', ""), radix)
+}
+
+/// Parses a natural literal, which must fit in a 64-bit unsigned integer.
+fn natural<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> Result<u64, LexicalError> {
+    let (digits, radix) = digits(lex.slice());
+    u64::from_str_radix(&digits, radix).map_err(|_| LexicalError::NaturalOutOfRange)
 }
 
-fn prefixed_integer<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> Option<i64> {
+/// Parses a byte string literal, such as `b"GIF89a\x01"`, into its bytes.
+///
+/// Characters stand for their UTF-8 encoding, and escape sequences for a single byte.
+fn byte_string<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> Result<Vec<u8>, LexicalError> {
     let slice = lex.slice();
-    let negative = slice.starts_with('-');
-    let radix = match &slice[1..3] {
-        "0x" => 16,
-        "0b" => 2,
-        "0o" => 8,
-        "0d" => 10,
-        _ => unreachable!(),
-    };
-    // remove underscore separators and parse
-    let slice = slice[3..].replace("_", "");
-    i64::from_str_radix(&slice, radix)
-        .ok()
-        .map(|n| if negative { -n } else { n })
+    let mut chars = slice[2..slice.len() - 1].chars();
+    let mut bytes = Vec::new();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('n') => b'\n',
+            Some('r') => b'\r',
+            Some('t') => b'\t',
+            Some('0') => b'\0',
+            Some('\\') => b'\\',
+            Some('"') => b'"',
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                if digits.len() != 2 {
+                    return Err(LexicalError::InvalidEscape);
+                }
+                u8::from_str_radix(&digits, 16).map_err(|_| LexicalError::InvalidEscape)?
+            }
+            _ => return Err(LexicalError::InvalidEscape),
+        };
+        bytes.push(byte);
+    }
+    Ok(bytes)
 }
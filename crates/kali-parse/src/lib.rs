@@ -1,37 +1,88 @@
+use std::collections::HashSet;
+
 use chumsky::{
     extra::SimpleState,
-    input::{Input, MapExtra, Stream, ValueInput},
-    pratt::{Associativity, infix, left, postfix, prefix, right},
+    input::{Emitter, Input, MapExtra, Stream, ValueInput},
+    pratt::{Associativity, infix, left, none, postfix, prefix, right},
     prelude::*,
 };
 use kali_ast::{
-    BinaryOp, BinaryOpKind, Definition, Destructor, DestructorKind, Expr, ExprKind, Ident,
-    ImportTree, ImportTreeKind, Item, ItemKind, LambdaParam, LiteralKind, MatchArm, Module,
-    Pattern, PatternKind, PrimitiveTypeKind, Type, TypeAlias, TypeKind, UnaryOp, UnaryOpKind,
-    Visibility,
+    Attribute, BinaryOp, BinaryOpKind, Clause, Definition, Destructor, DestructorKind, ExportItem,
+    ExportKind, Expr, ExprKind, Function, Ident, ImportTree, ImportTreeKind, Item, ItemKind,
+    LambdaParam, LiteralKind, MatchArm, Module, Pattern, PatternKind, PrimitiveTypeKind, Side,
+    Type, TypeAlias, TypeKind, UnaryOp, UnaryOpKind, Visibility,
 };
-use logos::Logos;
+use kali_span::{FileId, Span};
 
-use crate::lexer::Token;
+pub use crate::lexer::Token;
+use crate::{lexer::LexicalError, mistakes::Mistake};
 
 mod lexer;
+pub mod mistakes;
+mod nesting;
+pub mod trivia;
 
 /// Represents the state used during parsing, including a string interner for efficient string handling.
 #[derive(Default)]
 struct State {
     /// A `Rodeo` instance from the `lasso` crate, used for interning strings to reduce memory usage and improve performance.
     rodeo: lasso::Rodeo,
+    /// The remaining parse budget. See [`fuel`].
+    fuel: usize,
+    /// Whether the parse budget has run out.
+    exhausted: bool,
 }
 
+/// The parse budget granted per token of input.
+///
+/// Typical programs use a handful of units per token, so this only runs out on pathological
+/// input such as thousands of nested parentheses, which the grammar parses in quadratic time.
+const FUEL_PER_TOKEN: usize = 64;
+
+/// The message of the error reported when the parse budget runs out.
+const TOO_COMPLEX: &str = "parse too complex";
+
+/// The message of the error reported for source code nested more deeply than
+/// [`nesting::MAX_NESTING`], or whose syntax tree is deeper than [`nesting::MAX_DEPTH`].
+const TOO_DEEP: &str = "expression nested too deeply";
+
+/// The message of the error reported for a negative literal that does not fit in an `int`.
+const INTEGER_OUT_OF_RANGE: &str =
+    "literal out of range for `int`, which is a signed 64-bit integer";
+
 /// Concrete type for the parser extras.
-type Extras<'src> = extra::Full<Rich<'src, Token<'src>>, SimpleState<State>, ()>;
+type Extras<'src> = extra::Full<Rich<'src, Token<'src>, Span>, SimpleState<State>, ()>;
+
+/// Consumes a unit of the parse budget, failing once it has run out.
+///
+/// The budget is not restored on backtracking, so it bounds the total work done by the parser.
+/// Once it runs out every alternative fails immediately, and the parse finishes quickly with a
+/// [`TOO_COMPLEX`] error instead of hanging.
+fn fuel<'src, I>() -> impl Parser<'src, I, (), Extras<'src>> + Clone
+where
+    I: ValueInput<'src, Token = Token<'src>, Span = Span>,
+{
+    empty().try_map_with(|_, e| {
+        let state: &mut SimpleState<State> = e.state();
+        match state.fuel.checked_sub(1) {
+            Some(fuel) => {
+                state.fuel = fuel;
+                Ok(())
+            }
+            None => {
+                state.exhausted = true;
+                Err(Rich::custom(e.span(), TOO_COMPLEX))
+            }
+        }
+    })
+}
 
 /// Shorthand function to construct a [`BinaryOp`].
 fn binary_op<'src, I>(
     op: impl Parser<'src, I, BinaryOpKind, Extras<'src>> + 'src,
 ) -> impl Parser<'src, I, BinaryOp, Extras<'src>> + Clone
 where
-    I: ValueInput<'src, Token = Token<'src>, Span = SimpleSpan>,
+    I: ValueInput<'src, Token = Token<'src>, Span = Span>,
 {
     op.map_with(|kind, e| BinaryOp {
         kind,
@@ -48,7 +99,7 @@ fn binary_expr<'src, I>(
     e: &mut MapExtra<'src, '_, I, Extras<'src>>,
 ) -> Expr
 where
-    I: ValueInput<'src, Token = Token<'src>, Span = SimpleSpan>,
+    I: ValueInput<'src, Token = Token<'src>, Span = Span>,
 {
     Expr {
         kind: ExprKind::BinaryExpr {
@@ -60,9 +111,153 @@ where
     }
 }
 
+/// What follows the first expression in parentheses.
+enum Parenthesized {
+    /// The remaining elements of a tuple.
+    Tuple(Vec<Expr>),
+    /// The operator of a section with its right operand missing.
+    Section(BinaryOp),
+}
+
+/// What follows an atom, binding to it directly.
+#[derive(Clone)]
+enum Postfix {
+    /// An argument list, calling the atom.
+    Arguments(Vec<Expr>),
+    /// A `?`, propagating the error of the atom.
+    Try,
+    /// A `.` and an index, projecting an element of the atom.
+    Projection(usize),
+    /// An index between brackets, taking an element of the atom.
+    Index(Expr),
+}
+
+/// Parses the binary operator of an operator section. Pipelines have no sections.
+fn section_op<'src, I>() -> impl Parser<'src, I, BinaryOp, Extras<'src>> + Clone
+where
+    I: ValueInput<'src, Token = Token<'src>, Span = Span>,
+{
+    binary_op(select! {
+        Token::OpExponentiate => BinaryOpKind::Exponentiate,
+        Token::OpMultiply => BinaryOpKind::Multiply,
+        Token::OpDivide => BinaryOpKind::Divide,
+        Token::OpModulo => BinaryOpKind::Modulo,
+        Token::OpAdd => BinaryOpKind::Add,
+        Token::OpSubtract => BinaryOpKind::Subtract,
+        Token::OpBitwiseAnd => BinaryOpKind::BitwiseAnd,
+        Token::OpBitwiseXor => BinaryOpKind::BitwiseXor,
+        Token::OpBitwiseOr => BinaryOpKind::BitwiseOr,
+        Token::OpLessThanOrEqual => BinaryOpKind::LessThanOrEqual,
+        Token::OpGreaterThanOrEqual => BinaryOpKind::GreaterThanOrEqual,
+        Token::OpLessThan => BinaryOpKind::LessThan,
+        Token::OpGreaterThan => BinaryOpKind::GreaterThan,
+        Token::OpEqual => BinaryOpKind::Equal,
+        Token::OpNotEqual => BinaryOpKind::NotEqual,
+        Token::OpLogicalAnd => BinaryOpKind::LogicalAnd,
+        Token::OpLogicalOr => BinaryOpKind::LogicalOr,
+        Token::OpCons => BinaryOpKind::Cons,
+    })
+}
+
+/// Parses a parenthesised, comma-separated list of elements.
+///
+/// A single element without a comma is only grouped and is returned as-is, while two or more
+/// elements (with an optional trailing comma) are collected with `tuple`. Both forms share one
+/// parser so that the contents are never parsed twice, which would otherwise take time exponential
+/// in the nesting depth.
+fn tuple_or_grouped<'src, I, T>(
+    element: impl Parser<'src, I, T, Extras<'src>> + Clone + 'src,
+    tuple: impl Fn(Vec<T>, Span) -> T + Clone + 'src,
+) -> impl Parser<'src, I, T, Extras<'src>> + Clone
+where
+    I: ValueInput<'src, Token = Token<'src>, Span = Span>,
+    T: 'src,
+{
+    element
+        .clone()
+        .then(
+            just(Token::SymComma)
+                .ignore_then(element)
+                .repeated()
+                .at_least(1)
+                .collect::<Vec<_>>()
+                .then_ignore(just(Token::SymComma).or_not())
+                .or_not(),
+        )
+        .delimited_by(just(Token::SymLParen), just(Token::SymRParen))
+        .map_with(move |(first, rest), e| match rest {
+            Some(rest) => tuple(std::iter::once(first).chain(rest).collect(), e.span()),
+            None => first,
+        })
+        .boxed()
+}
+
+/// Skips a sequence of tokens in which delimiters are balanced, stopping before an unmatched
+/// closing delimiter. Used to recover from malformed expressions.
+fn balanced<'src, I>() -> impl Parser<'src, I, (), Extras<'src>> + Clone
+where
+    I: ValueInput<'src, Token = Token<'src>, Span = Span>,
+{
+    recursive(|balanced| {
+        choice((
+            balanced
+                .clone()
+                .delimited_by(just(Token::SymLParen), just(Token::SymRParen)),
+            balanced
+                .clone()
+                .delimited_by(just(Token::SymCallParen), just(Token::SymRParen)),
+            balanced
+                .clone()
+                .delimited_by(just(Token::SymLBracket), just(Token::SymRBracket)),
+            balanced
+                .clone()
+                .delimited_by(just(Token::SymIndexBracket), just(Token::SymRBracket)),
+            balanced
+                .clone()
+                .delimited_by(just(Token::SymHashBracket), just(Token::SymRBracket)),
+            balanced
+                .clone()
+                .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace)),
+            balanced.delimited_by(just(Token::SymHashBrace), just(Token::SymRBrace)),
+            none_of([
+                Token::SymLParen,
+                Token::SymCallParen,
+                Token::SymRParen,
+                Token::SymLBracket,
+                Token::SymIndexBracket,
+                Token::SymHashBracket,
+                Token::SymRBracket,
+                Token::SymLBrace,
+                Token::SymHashBrace,
+                Token::SymRBrace,
+            ])
+            .ignored(),
+        ))
+        .repeated()
+    })
+    .boxed()
+}
+
+/// Shorthand function to construct an [`ExprKind::Error`] spanning the parsed input.
+fn error_expr<'src, I>(
+    parser: impl Parser<'src, I, (), Extras<'src>> + Clone + 'src,
+) -> impl Parser<'src, I, Expr, Extras<'src>> + Clone
+where
+    I: ValueInput<'src, Token = Token<'src>, Span = Span>,
+{
+    // recovery is work too, so that it cannot rescan the input once the budget has run out
+    fuel()
+        .ignore_then(parser)
+        .map_with(|(), e| Expr {
+            kind: ExprKind::Error,
+            span: e.span(),
+        })
+        .boxed()
+}
+
 pub(crate) fn parser<'src, I>() -> impl Parser<'src, I, Vec<Item>, Extras<'src>>
 where
-    I: ValueInput<'src, Token = Token<'src>, Span = SimpleSpan>,
+    I: ValueInput<'src, Token = Token<'src>, Span = Span>,
 {
     // ident ::= Ident
     let ident = select! { Token::Ident(ident) => ident }
@@ -75,11 +270,10 @@ where
         })
         .labelled("identifier");
 
-    // literal_kind ::= LitBool | LitInteger | LitNatural | LitUnit | LitString
+    // literal_kind ::= LitBool | LitNatural | LitUnit | LitString | LitBytes
     let literal_kind = choice((
         select! {
             Token::LitBool(value) => LiteralKind::Bool(value),
-            Token::LitInteger(value) => LiteralKind::Integer(value),
             Token::LitNatural(value) => LiteralKind::Natural(value),
             // TODO: Floats
             // Token::LitFloat(value) => LiteralKind::Float(value),
@@ -94,34 +288,87 @@ where
             LiteralKind::String(state.rodeo.get_or_intern(value))
         })
         .labelled("string literal"),
+        select! {
+            Token::LitBytes(value) => LiteralKind::Bytes(value),
+        }
+        .labelled("byte string literal"),
+        // malformed literals are reported, and parsed as a placeholder of the same type so that
+        // the rest of the expression can still be parsed
+        select! {
+            Token::Error(error @ (LexicalError::NaturalOutOfRange
+                | LexicalError::InvalidEscape)) => error,
+        }
+        .validate(|error, e, emitter| {
+            emitter.emit(Rich::custom(e.span(), error.to_string()));
+            match error {
+                LexicalError::NaturalOutOfRange => LiteralKind::Natural(0),
+                _ => LiteralKind::Bytes(Vec::new()),
+            }
+        }),
     ));
 
-    // ty ::= primitive | named | tuple | list | record | (ty)
-    let ty = recursive(|ty| {
-        // primitive ::= TypeBool | TypeFloat | TypeInteger | TypeNatural | TypeString | LitUnit
+    // negative_literal ::= - LitNatural
+    // `-` is always lexed as an operator, and is folded into a natural literal that follows it,
+    // unless the literal is called, so that `-1(x)` negates the result of the call
+    let negated = select! {
+        Token::LitNatural(value) => Some(value),
+        Token::Error(LexicalError::NaturalOutOfRange) => None,
+    }
+    .then_ignore(just(Token::SymCallParen).not());
+    let negative_literal = just(Token::OpSubtract)
+        .ignore_then(negated.clone())
+        .validate(|value, e, emitter| {
+            match value.and_then(|value| 0i64.checked_sub_unsigned(value)) {
+                Some(value) => LiteralKind::Integer(value),
+                None => {
+                    emitter.emit(Rich::custom(e.span(), INTEGER_OUT_OF_RANGE));
+                    LiteralKind::Integer(0)
+                }
+            }
+        })
+        .labelled("literal");
+
+    // ty ::= function | ty_operand
+    let mut ty = Recursive::declare();
+
+    // ty_operand ::= primitive | named | tuple | list | record | (ty) | ty & ty | ty | ty
+    // lambda parameters are annotated with a type operand, as an arrow would end the parameters
+    let ty_operand = {
+        // primitive ::= TypeBool | TypeFloat | TypeInteger | TypeNatural | TypeString | TypeBytes | LitUnit
         let primitive = select! {
             Token::TypeBool => PrimitiveTypeKind::Bool,
             Token::TypeFloat => PrimitiveTypeKind::Float,
             Token::TypeInteger => PrimitiveTypeKind::Integer,
             Token::TypeNatural => PrimitiveTypeKind::Natural,
             Token::TypeString => PrimitiveTypeKind::String,
+            Token::TypeBytes => PrimitiveTypeKind::Bytes,
             Token::LitUnit => PrimitiveTypeKind::Unit
         }
         .map(TypeKind::Primitive)
         .labelled("primitive type");
 
-        // named ::= ident
-        let named = ident.clone().map(TypeKind::Named).labelled("named type");
+        // named ::= ident (< ty (, ty)* >)?
+        let named = ident
+            .then(
+                ty.clone()
+                    .separated_by(just(Token::SymComma))
+                    .at_least(1)
+                    .collect::<Vec<_>>()
+                    .delimited_by(just(Token::OpLessThan), just(Token::OpGreaterThan))
+                    .or_not(),
+            )
+            .map(|(name, arguments)| match arguments {
+                Some(arguments) => TypeKind::Generic { name, arguments },
+                None => TypeKind::Named(name),
+            })
+            .labelled("named type");
 
-        // tuple ::= (ty (, ty)*)
-        let tuple = ty
-            .clone()
-            .separated_by(just(Token::SymComma))
-            .at_least(1)
-            .collect::<Vec<_>>()
-            .delimited_by(just(Token::SymLParen), just(Token::SymRParen))
-            .map(TypeKind::Tuple)
-            .labelled("tuple type");
+        // tuple ::= (ty (, ty)+)
+        let tuple = tuple_or_grouped(ty.clone(), |types, span| Type {
+            kind: TypeKind::Tuple(types),
+            span,
+        })
+        .labelled("tuple type");
 
         // list ::= [ty]
         let list = ty
@@ -132,24 +379,26 @@ where
 
         // record ::= { ident : ty (, ident : ty)* }
         let record = ident
-            .clone()
             .then_ignore(just(Token::SymColon))
             .then(ty.clone())
             .separated_by(just(Token::SymComma))
             .allow_trailing()
             .collect::<Vec<_>>()
             .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace))
-            .map(|entries| TypeKind::Record(indexmap::IndexMap::from_iter(entries)))
+            .validate(|entries, e, emitter| {
+                let state: &mut SimpleState<State> = e.state();
+                TypeKind::Record(record_fields(entries, &state.rodeo, emitter))
+            })
             .labelled("record type");
 
-        let atom = choice((primitive, named, tuple, list, record))
-            .map_with(|kind, e| Type {
-                kind,
-                span: e.span(),
-            })
-            .or(ty
-                .clone()
-                .delimited_by(just(Token::SymLParen), just(Token::SymRParen)));
+        let atom = fuel().ignore_then(
+            choice((primitive, named, list, record))
+                .map_with(|kind, e| Type {
+                    kind,
+                    span: e.span(),
+                })
+                .or(tuple),
+        );
 
         // ty ::= ty & ty | ty | ty
         atom.pratt((
@@ -168,20 +417,75 @@ where
                 span: e.span(),
             }),
         ))
-    })
-    .labelled("type");
+        .labelled("type")
+        .boxed()
+    };
+
+    // function ::= params -> ty | ty_operand -> ty
+    // params ::= () | (ty (, ty)*)
+    // a parenthesised type is a parameter list only if an arrow follows it, so that `(a, b) -> c`
+    // takes two parameters and `((a, b)) -> c` takes a tuple
+    let params = just(Token::LitUnit).to(Vec::new()).or(ty
+        .clone()
+        .separated_by(just(Token::SymComma))
+        .at_least(1)
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(just(Token::SymLParen), just(Token::SymRParen)));
+    let params = just(Token::LitUnit)
+        .ignored()
+        .or(balanced().delimited_by(just(Token::SymLParen), just(Token::SymRParen)))
+        .then(just(Token::SymArrow))
+        .rewind()
+        .ignore_then(params)
+        .then_ignore(just(Token::SymArrow));
+    ty.define(
+        params
+            .then(ty.clone())
+            .map_with(|(params, ret), e| Type {
+                kind: TypeKind::Fn(params, Box::new(ret)),
+                span: e.span(),
+            })
+            .or(ty_operand
+                .clone()
+                .then(just(Token::SymArrow).ignore_then(ty.clone()).or_not())
+                .map_with(|(ty, ret), e| match ret {
+                    Some(ret) => Type {
+                        kind: TypeKind::Fn(vec![ty], Box::new(ret)),
+                        span: e.span(),
+                    },
+                    None => ty,
+                })),
+    );
+    let ty = ty.labelled("type");
+
+    // range_op ::= .. | ..=
+    // the operator of a range, which is whether it includes its end
+    let range_op = just(Token::SymSpread)
+        .to(false)
+        .or(just(Token::SymRangeInclusive).to(true));
 
-    // pattern ::= literal | variable | wildcard | tuple | record | empty_list | (pattern)
+    // pattern ::= literal | range | prefixed | variable | wildcard | tuple | record | empty_list
+    //           | (pattern)
     let pattern = recursive(|pattern| {
-        // literal ::= literal_kind
-        let atom_literal = literal_kind
+        // literal ::= literal_kind | negative_literal
+        // range ::= literal .. literal | literal ..= literal
+        let literal = literal_kind.or(negative_literal.clone());
+        let atom_literal = literal
             .clone()
-            .map(PatternKind::Literal)
+            .then(range_op.clone().then(literal).or_not())
+            .map(|(start, range)| match range {
+                Some((inclusive, end)) => PatternKind::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                None => PatternKind::Literal(start),
+            })
             .labelled("literal pattern");
 
         // variable ::= ident
         let atom_variable = ident
-            .clone()
             .map(PatternKind::Var)
             .labelled("variable pattern");
 
@@ -190,27 +494,25 @@ where
             .to(PatternKind::Wildcard)
             .labelled("wildcard pattern");
 
-        // tuple ::= (pattern (, pattern)*)
-        let atom_tuple = pattern
-            .clone()
-            .separated_by(just(Token::SymComma))
-            .at_least(2)
-            .allow_trailing()
-            .collect::<Vec<_>>()
-            .delimited_by(just(Token::SymLParen), just(Token::SymRParen))
-            .map(PatternKind::Tuple)
-            .labelled("tuple pattern");
+        // tuple ::= (pattern (, pattern)+)
+        let atom_tuple = tuple_or_grouped(pattern.clone(), |patterns, span| Pattern {
+            kind: PatternKind::Tuple(patterns),
+            span,
+        })
+        .labelled("tuple pattern");
 
         // record ::= { ident : pattern (, ident : pattern)* }
         let atom_record = ident
-            .clone()
             .then_ignore(just(Token::SymColon))
             .then(pattern.clone())
             .separated_by(just(Token::SymComma))
             .allow_trailing()
             .collect::<Vec<_>>()
             .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace))
-            .map(|entries| PatternKind::Record(indexmap::IndexMap::from_iter(entries)))
+            .validate(|entries, e, emitter| {
+                let state: &mut SimpleState<State> = e.state();
+                PatternKind::Record(record_fields(entries, &state.rodeo, emitter))
+            })
             .labelled("record pattern");
 
         // empty_list ::= []
@@ -218,23 +520,52 @@ where
             .to(PatternKind::EmptyList)
             .labelled("empty list pattern");
 
-        let atom = choice((
-            atom_literal,
-            atom_variable,
-            atom_wildcard,
-            atom_tuple,
-            atom_record,
-            atom_empty_list,
-        ))
-        .map_with(|kind, e| Pattern {
-            kind,
-            span: e.span(),
+        let atom = fuel()
+            .ignore_then(
+                choice((
+                    atom_literal,
+                    atom_variable,
+                    atom_wildcard,
+                    atom_record,
+                    atom_empty_list,
+                ))
+                .map_with(|kind, e| Pattern {
+                    kind,
+                    span: e.span(),
+                })
+                .or(atom_tuple),
+            )
+            .labelled("pattern");
+
+        // prefixed ::= string ++ prefixed | atom
+        // the rest of the string is matched by an atom, so `"a" ++ rest | b` is an or-pattern
+        let prefixed = select! {
+            Token::LitString(value) => value
+        }
+        .map_with(|value, e| {
+            let state: &mut SimpleState<State> = e.state();
+            (state.rodeo.get_or_intern(value), e.span())
+        })
+        .then_ignore(just(Token::OpAppend))
+        .repeated()
+        .collect::<Vec<_>>()
+        .then(atom)
+        .map(|(prefixes, rest)| {
+            prefixes
+                .into_iter()
+                .rev()
+                .fold(rest, |rest, (prefix, span): (_, Span)| Pattern {
+                    span: span.union(rest.span),
+                    kind: PatternKind::Prefix {
+                        prefix,
+                        rest: Box::new(rest),
+                    },
+                })
         })
-        .or(pattern.delimited_by(just(Token::SymLParen), just(Token::SymRParen)))
-        .labelled("pattern");
+        .labelled("prefix pattern");
 
         // pattern ::= pattern :: pattern | pattern | pattern
-        atom.pratt((
+        prefixed.pratt((
             infix(
                 Associativity::Right(1),
                 just(Token::OpCons),
@@ -265,68 +596,130 @@ where
     let destructor = recursive(|destructor| {
         // variable ::= ident
         let atom_variable = ident
-            .clone()
             .map(DestructorKind::Var)
             .labelled("variable destructor");
 
-        // tuple ::= (destructor (, destructor)*)
-        let atom_tuple = destructor
-            .clone()
-            .separated_by(just(Token::SymComma))
-            .at_least(2)
-            .allow_trailing()
-            .collect::<Vec<_>>()
-            .delimited_by(just(Token::SymLParen), just(Token::SymRParen))
-            .map(DestructorKind::Tuple)
-            .labelled("tuple destructor");
+        // tuple ::= (destructor (, destructor)+)
+        let atom_tuple = tuple_or_grouped(destructor.clone(), |destructors, span| Destructor {
+            kind: DestructorKind::Tuple(destructors),
+            span,
+        })
+        .labelled("tuple destructor");
 
         // record ::= { ident : destructor (, ident : destructor)* }
         let atom_record = ident
-            .clone()
             .then_ignore(just(Token::SymColon))
             .then(destructor.clone())
             .separated_by(just(Token::SymComma))
             .allow_trailing()
             .collect::<Vec<_>>()
             .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace))
-            .map(|entries| DestructorKind::Record(indexmap::IndexMap::from_iter(entries)))
+            .validate(|entries, e, emitter| {
+                let state: &mut SimpleState<State> = e.state();
+                DestructorKind::Record(record_fields(entries, &state.rodeo, emitter))
+            })
             .labelled("record destructor");
 
-        choice((atom_variable, atom_tuple, atom_record))
-            .map_with(|kind, e| Destructor {
-                kind,
-                span: e.span(),
-            })
-            .or(destructor.delimited_by(just(Token::SymLParen), just(Token::SymRParen)))
+        fuel().ignore_then(
+            choice((atom_variable, atom_record))
+                .map_with(|kind, e| Destructor {
+                    kind,
+                    span: e.span(),
+                })
+                .or(atom_tuple),
+        )
     })
     .labelled("destructor");
 
-    // expr ::= literal | variable | tuple | list | if_expr | match_expr | (expr)
+    // expr ::= literal | variable | hole | tuple | list | if_expr | match_expr | (expr)
     let expr = recursive(|expr| {
         // literal ::= literal_kind
         let atom_literal = literal_kind
-            .clone()
             .map(ExprKind::Literal)
             .labelled("literal expression");
 
         // variable ::= ident
         let atom_variable = ident
-            .clone()
             .map(ExprKind::Var)
             .labelled("variable expression");
 
-        // tuple ::= (expr (, expr)*)
+        // hole ::= Hole
+        let atom_hole = select! { Token::Hole(name) => name }
+            .map_with(|name, e| {
+                let state: &mut SimpleState<State> = e.state();
+                ExprKind::Hole(Ident {
+                    key: state.rodeo.get_or_intern(name),
+                    span: e.span(),
+                })
+            })
+            .labelled("hole");
+
+        // section ::= (op expr)
+        // `(- x)` is a negation, so `-` has no section with its left operand missing
+        let atom_section = section_op()
+            .filter(|op| op.kind != BinaryOpKind::Subtract)
+            .then(expr.clone())
+            .delimited_by(just(Token::SymLParen), just(Token::SymRParen))
+            .map_with(|(op, operand), e| Expr {
+                kind: ExprKind::Section {
+                    op,
+                    operand: Box::new(operand),
+                    side: Side::Right,
+                },
+                span: e.span(),
+            })
+            .labelled("operator section");
+
+        // tuple ::= (expr (, expr)+)
+        // section ::= (expr op)
+        // the contents are parsed once and then told apart by what follows the first expression
         let atom_tuple = expr
             .clone()
-            .separated_by(just(Token::SymComma))
-            .at_least(2)
-            .collect::<Vec<_>>()
+            .then(
+                choice((
+                    just(Token::SymComma)
+                        .ignore_then(expr.clone())
+                        .repeated()
+                        .at_least(1)
+                        .collect::<Vec<_>>()
+                        .then_ignore(just(Token::SymComma).or_not())
+                        .map(Parenthesized::Tuple),
+                    section_op().map(Parenthesized::Section),
+                ))
+                .or_not(),
+            )
             .delimited_by(just(Token::SymLParen), just(Token::SymRParen))
-            .map(ExprKind::Tuple)
+            .map_with(|(first, rest), e| {
+                let kind = match rest {
+                    Some(Parenthesized::Tuple(rest)) => {
+                        ExprKind::Tuple(std::iter::once(first).chain(rest).collect())
+                    }
+                    Some(Parenthesized::Section(op)) => ExprKind::Section {
+                        op,
+                        operand: Box::new(first),
+                        side: Side::Left,
+                    },
+                    None => return first,
+                };
+                Expr {
+                    kind,
+                    span: e.span(),
+                }
+            })
             .labelled("tuple expression");
 
-        // list ::= [expr (, expr)*] | []
-        let atom_list = expr
+        // element ::= .. expr | expr
+        let element = just(Token::SymSpread)
+            .ignore_then(expr.clone())
+            .map_with(|expr, e| Expr {
+                kind: ExprKind::Spread(Box::new(expr)),
+                span: e.span(),
+            })
+            .labelled("spread")
+            .or(expr.clone());
+
+        // list ::= [element (, element)*] | []
+        let atom_list = element
             .clone()
             .separated_by(just(Token::SymComma))
             .collect::<Vec<_>>()
@@ -335,25 +728,79 @@ where
             .map(ExprKind::List)
             .labelled("list expression");
 
-        // if_expr ::= if expr { expr } else { expr }
+        // set ::= #[ (expr (, expr)* ,?)? ]
+        let atom_set = expr
+            .clone()
+            .separated_by(just(Token::SymComma))
+            .allow_trailing()
+            .collect::<Vec<_>>()
+            .delimited_by(just(Token::SymHashBracket), just(Token::SymRBracket))
+            .map(ExprKind::Set)
+            .labelled("set expression");
+
+        // map ::= #{ (expr : expr (, expr : expr)* ,?)? }
+        let atom_map = expr
+            .clone()
+            .then_ignore(just(Token::SymColon))
+            .then(expr.clone())
+            .separated_by(just(Token::SymComma))
+            .allow_trailing()
+            .collect::<Vec<_>>()
+            .delimited_by(just(Token::SymHashBrace), just(Token::SymRBrace))
+            .map(ExprKind::Map)
+            .labelled("map expression");
+
+        // statements ::= expr (; expr)*
+        // a single expression is not a sequence, in the same way as it is not a tuple
+        let statements = expr
+            .clone()
+            .separated_by(just(Token::SymSemicolon))
+            .at_least(1)
+            .collect::<Vec<_>>()
+            .map_with(|mut exprs, e| match exprs.len() {
+                1 => exprs.pop().unwrap(),
+                _ => Expr {
+                    kind: ExprKind::Sequence(exprs),
+                    span: e.span(),
+                },
+            });
+
+        // block ::= { statements }
+        let block = statements
+            .clone()
+            .then_ignore(just(Token::SymRBrace).rewind())
+            .recover_with(via_parser(error_expr(balanced())))
+            .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace));
+
+        // do_block ::= do block
+        let atom_do = just(Token::KeywordDo)
+            .ignore_then(block.clone())
+            .map_with(|expr, e| match expr.kind {
+                ExprKind::Sequence(_) => Expr {
+                    span: e.span(),
+                    ..expr
+                },
+                _ => expr,
+            })
+            .labelled("do block");
+
+        // body ::= { statements } | expr
+        // the body of a loop may be a block without its `do`, which is not recovered from so that
+        // a lambda destructuring a record is still tried
+        let body = statements
+            .clone()
+            .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace))
+            .or(expr.clone());
+
+        // if_expr ::= if expr block else block
         let atom_if = just(Token::KeywordIf)
             .ignore_then(expr.clone())
-            .then(
-                expr.clone()
-                    .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace)),
-            )
-            .then(
-                just(Token::KeywordElse)
-                    .ignore_then(
-                        expr.clone()
-                            .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace)),
-                    )
-                    .or_not(),
-            )
+            .then(block.clone())
+            .then(just(Token::KeywordElse).ignore_then(block).or_not())
             .map(|((condition, body), otherwise)| ExprKind::Conditional {
                 condition: Box::new(condition),
                 body: Box::new(body),
-                otherwise: otherwise.map(|otherwise| Box::new(otherwise)),
+                otherwise: otherwise.map(Box::new),
             })
             .labelled("if expression");
 
@@ -365,6 +812,24 @@ where
                     .clone()
                     .then_ignore(just(Token::SymArrow))
                     .then(expr.clone())
+                    // in `p -> a | q -> b`, the `|` was read as a bitwise-or in the first body,
+                    // so the second arm is parsed here to report the mistake once
+                    .then(just(Token::SymArrow).ignore_then(expr.clone()).or_not())
+                    .validate(|((pattern, expr), next), _, emitter| {
+                        if next.is_some()
+                            && let ExprKind::BinaryExpr { op, .. } = &expr.kind
+                            && op.kind == BinaryOpKind::BitwiseOr
+                        {
+                            emitter.emit(Rich::custom(
+                                op.span,
+                                "match arms are separated by `,`, not `|`; wrap the arm body in \
+                                 parentheses for a bitwise-or",
+                            ));
+                        } else if next.is_some() {
+                            emitter.emit(Rich::custom(expr.span, "expected `,` after match arm"));
+                        }
+                        (pattern, expr)
+                    })
                     .map_with(|(pattern, expr), e| MatchArm {
                         pattern,
                         expr,
@@ -381,31 +846,140 @@ where
             })
             .labelled("match expression");
 
-        let atom = choice((
-            atom_literal,
-            atom_variable,
-            atom_tuple,
-            atom_list,
-            atom_if,
-            atom_match,
-        ))
-        .map_with(|kind, e| Expr {
-            kind,
+        // while_expr ::= while expr do body
+        let atom_while = just(Token::KeywordWhile)
+            .ignore_then(expr.clone())
+            .then_ignore(just(Token::KeywordDo))
+            .then(body.clone())
+            .map(|(condition, body)| ExprKind::While {
+                condition: Box::new(condition),
+                body: Box::new(body),
+            })
+            .labelled("while loop");
+
+        // for_expr ::= for destructor in expr do body
+        let atom_for = just(Token::KeywordFor)
+            .ignore_then(destructor.clone())
+            .then_ignore(just(Token::KeywordIn))
+            .then(expr.clone())
+            .then_ignore(just(Token::KeywordDo))
+            .then(body.clone())
+            .map(|((binding, iterable), body)| ExprKind::For {
+                binding,
+                iterable: Box::new(iterable),
+                body: Box::new(body),
+            })
+            .labelled("for loop");
+
+        let atom = fuel()
+            .ignore_then(
+                choice((
+                    atom_literal,
+                    atom_variable,
+                    atom_hole,
+                    atom_list,
+                    atom_map,
+                    atom_set,
+                    atom_if,
+                    atom_match,
+                    atom_while,
+                    atom_for,
+                ))
+                .map_with(|kind, e| Expr {
+                    kind,
+                    span: e.span(),
+                })
+                .or(atom_section)
+                .or(atom_tuple)
+                .or(atom_do),
+            )
+            .recover_with(via_parser(error_expr(
+                balanced().delimited_by(just(Token::SymLParen), just(Token::SymRParen)),
+            )))
+            .recover_with(via_parser(error_expr(
+                balanced().delimited_by(just(Token::SymLBracket), just(Token::SymRBracket)),
+            )));
+
+        // argument ::= ident : expr | element
+        let argument = ident
+            .then_ignore(just(Token::SymColon))
+            .then(expr.clone())
+            .map_with(|(label, value), e| Expr {
+                kind: ExprKind::Labelled {
+                    label,
+                    value: Box::new(value),
+                },
+                span: e.span(),
+            })
+            .labelled("labelled argument")
+            .or(element);
+
+        // arguments ::= ( (argument (, argument)*)? )
+        let arguments = argument
+            .separated_by(just(Token::SymComma))
+            .allow_trailing()
+            .collect::<Vec<_>>()
+            .delimited_by(just(Token::SymCallParen), just(Token::SymRParen))
+            .labelled("argument list");
+
+        // call ::= atom (arguments | ? | . LitNatural | [ expr ])*
+        // an argument list binds to the expression it directly follows, so `f x(y)` is `f (x(y))`,
+        // and so do a `?`, so that `f(x)?` propagates the error of the call, a projection and an
+        // index
+        let suffix = choice((
+            arguments.map(Postfix::Arguments),
+            just(Token::SymQuestion).to(Postfix::Try),
+            just(Token::SymDot)
+                .ignore_then(select! { Token::LitNatural(index) => index as usize })
+                .map(Postfix::Projection),
+            expr.clone()
+                .delimited_by(just(Token::SymIndexBracket), just(Token::SymRBracket))
+                .map(Postfix::Index),
+        ));
+        let atom = atom.foldl_with(suffix.repeated(), |expr, suffix, e| Expr {
+            kind: match suffix {
+                Postfix::Arguments(arguments) => ExprKind::Call {
+                    function: Box::new(expr),
+                    arguments,
+                },
+                Postfix::Try => ExprKind::Try(Box::new(expr)),
+                Postfix::Projection(index) => ExprKind::Projection {
+                    tuple: Box::new(expr),
+                    index,
+                },
+                Postfix::Index(index) => ExprKind::Index {
+                    list: Box::new(expr),
+                    index: Box::new(index),
+                },
+            },
             span: e.span(),
-        })
-        .or(expr
-            .clone()
-            .delimited_by(just(Token::SymLParen), just(Token::SymRParen)));
+        });
+
+        // operand ::= negative_literal | call
+        // a negative literal is not an atom, so that `f -1` subtracts from `f` rather than
+        // applying it to `-1`
+        let operand = negative_literal
+            .map_with(|literal, e| Expr {
+                kind: ExprKind::Literal(literal),
+                span: e.span(),
+            })
+            .or(atom.clone());
 
         // expr ::= lambda | unary_expr | call | binary_expr
-        atom.clone().pratt((
-            // lambda ::= (destructor (, destructor)* -> expr)
+        operand.clone().pratt((
+            // lambda ::= (param (, param)* -> expr)
+            // param ::= destructor type? (= operand)?
             prefix(
-                12,
+                16,
                 destructor
                     .clone()
-                    .then(ty.clone().or_not())
-                    .map(|(parameter, ty)| LambdaParam { parameter, ty })
+                    .then(ty_operand.clone().or_not())
+                    .then(just(Token::OpAssign).ignore_then(operand).or_not())
+                    .map(|((parameter, ty), default)| LambdaParam {
+                        parameter,
+                        ty,
+                        default,
+                    })
                     .separated_by(just(Token::SymComma))
                     .collect::<Vec<_>>()
                     .then_ignore(just(Token::SymArrow)),
@@ -420,13 +994,18 @@ where
             ),
             // unary_expr ::= op expr
             prefix(
-                11,
-                select! {
-                     Token::OpAdd => UnaryOpKind::UnaryPlus,
-                     Token::OpBitwiseNot => UnaryOpKind::BitwiseNot,
-                     Token::OpNegate => UnaryOpKind::LogicalNot,
-                     Token::OpSubtract => UnaryOpKind::Negate,
-                }
+                14,
+                choice((
+                    select! {
+                         Token::OpAdd => UnaryOpKind::UnaryPlus,
+                         Token::OpBitwiseNot => UnaryOpKind::BitwiseNot,
+                         Token::OpNegate => UnaryOpKind::LogicalNot,
+                    },
+                    // prefix operators are tried before operands, so leave `-1` to be folded
+                    just(Token::OpSubtract)
+                        .then_ignore(negated.not())
+                        .to(UnaryOpKind::Negate),
+                ))
                 .map_with(|kind, e| UnaryOp {
                     kind,
                     span: e.span(),
@@ -439,14 +1018,17 @@ where
                     span: e.span(),
                 },
             ),
-            // call ::= expr (expr (, expr)*)
+            // application ::= expr call (, call)*
+            // calls bind tighter than prefix operators, so `-f x` negates the result of the call
+            // a `do` block is not applied to, as the `do` of a loop may be followed by a block
             postfix(
-                10,
-                just(Token::LitUnit).to(vec![]).or(atom
-                    .clone()
+                15,
+                just(Token::KeywordDo)
+                    .not()
+                    .ignore_then(atom.clone())
                     .separated_by(just(Token::SymComma))
                     .at_least(1)
-                    .collect::<Vec<_>>()),
+                    .collect::<Vec<_>>(),
                 |function, arguments, e| Expr {
                     kind: ExprKind::Call {
                         function: Box::new(function),
@@ -457,14 +1039,14 @@ where
             ),
             // binary_expr ::= expr op expr
             infix(
-                right(9),
+                right(13),
                 binary_op(select! {
                     Token::OpExponentiate => BinaryOpKind::Exponentiate
                 }),
                 binary_expr,
             ),
             infix(
-                left(8),
+                left(12),
                 binary_op(select! {
                     Token::OpMultiply => BinaryOpKind::Multiply,
                     Token::OpDivide => BinaryOpKind::Divide,
@@ -473,7 +1055,7 @@ where
                 binary_expr,
             ),
             infix(
-                left(7),
+                left(11),
                 binary_op(select! {
                     Token::OpAdd => BinaryOpKind::Add,
                     Token::OpSubtract => BinaryOpKind::Subtract,
@@ -481,7 +1063,29 @@ where
                 binary_expr,
             ),
             infix(
-                left(6),
+                left(10),
+                binary_op(select! {
+                    Token::OpBitwiseAnd => BinaryOpKind::BitwiseAnd,
+                }),
+                binary_expr,
+            ),
+            infix(
+                left(9),
+                binary_op(select! {
+                    Token::OpBitwiseXor => BinaryOpKind::BitwiseXor,
+                }),
+                binary_expr,
+            ),
+            // `|` is bitwise-or here, but an or-pattern in patterns and a union in types
+            infix(
+                left(8),
+                binary_op(select! {
+                    Token::OpBitwiseOr => BinaryOpKind::BitwiseOr,
+                }),
+                binary_expr,
+            ),
+            infix(
+                left(7),
                 binary_op(select! {
                     Token::OpLessThanOrEqual => BinaryOpKind::LessThanOrEqual,
                     Token::OpGreaterThanOrEqual => BinaryOpKind::GreaterThanOrEqual,
@@ -491,7 +1095,7 @@ where
                 binary_expr,
             ),
             infix(
-                left(5),
+                left(6),
                 binary_op(select! {
                     Token::OpEqual => BinaryOpKind::Equal,
                     Token::OpNotEqual => BinaryOpKind::NotEqual,
@@ -499,19 +1103,29 @@ where
                 binary_expr,
             ),
             infix(
-                left(4),
+                left(5),
                 binary_op(select! {
                     Token::OpLogicalAnd => BinaryOpKind::LogicalAnd,
                 }),
                 binary_expr,
             ),
             infix(
-                left(3),
+                left(4),
                 binary_op(select! {
                     Token::OpLogicalOr => BinaryOpKind::LogicalOr,
                 }),
                 binary_expr,
             ),
+            // range ::= expr .. expr | expr ..= expr
+            // ranges do not chain, so `a..b..c` is an error
+            infix(none(3), range_op, |start, inclusive, end, e| Expr {
+                kind: ExprKind::Range {
+                    start: Box::new(start),
+                    end: Box::new(end),
+                    inclusive,
+                },
+                span: e.span(),
+            }),
             infix(
                 right(2),
                 binary_op(select! {
@@ -519,13 +1133,20 @@ where
                 }),
                 binary_expr,
             ),
+            infix(
+                left(1),
+                binary_op(select! {
+                    Token::OpPipe => BinaryOpKind::Pipe,
+                }),
+                binary_expr,
+            ),
         ))
     })
     .labelled("expression");
 
     // item_type_alias ::= type ident = ty
     let item_type_alias = just(Token::KeywordType)
-        .ignore_then(ident.clone())
+        .ignore_then(ident)
         .then_ignore(just(Token::OpAssign))
         .then(ty.clone())
         .map(|(name, ty)| ItemKind::TypeAlias(TypeAlias { name, ty }))
@@ -536,14 +1157,12 @@ where
         .ignore_then(recursive(|import_tree| {
             // item ::= ident (as ident)?
             let item = ident
-                .clone()
-                .then(just(Token::KeywordAs).ignore_then(ident.clone()).or_not())
+                .then(just(Token::KeywordAs).ignore_then(ident).or_not())
                 .map(|(name, alias)| ImportTreeKind::Item { name, alias })
                 .labelled("import item");
 
             // segment ::= ident :: import_tree
             let segment = ident
-                .clone()
                 .then_ignore(just(Token::OpCons))
                 .then(import_tree.clone())
                 .map(|(name, child)| ImportTreeKind::Segment {
@@ -567,7 +1186,8 @@ where
                 .map(ImportTreeKind::List)
                 .labelled("import list");
 
-            choice((item, segment, glob, list)).map_with(|kind, e| ImportTree {
+            // segments must be tried before items, which would otherwise accept the leading ident
+            choice((segment, item, glob, list)).map_with(|kind, e| ImportTree {
                 kind,
                 span: e.span(),
             })
@@ -575,6 +1195,31 @@ where
         .map(ItemKind::Import)
         .labelled("import tree");
 
+    // item_export ::= export { export_item (, export_item)* } | export * from LitString
+    let item_export = just(Token::KeywordExport)
+        .ignore_then(choice((
+            // export_item ::= ident (as ident)?
+            ident
+                .then(just(Token::KeywordAs).ignore_then(ident).or_not())
+                .map(|(name, alias)| ExportItem { name, alias })
+                .separated_by(just(Token::SymComma))
+                .allow_trailing()
+                .collect::<Vec<_>>()
+                .delimited_by(just(Token::SymLBrace), just(Token::SymRBrace))
+                .map(ExportKind::List),
+            just(Token::OpMultiply)
+                .ignore_then(just(Token::KeywordFrom))
+                .ignore_then(select! { Token::LitString(path) => path })
+                .map_with(|path, e| {
+                    let state: &mut SimpleState<State> = e.state();
+                    ExportKind::Glob {
+                        path: state.rodeo.get_or_intern(path),
+                    }
+                }),
+        )))
+        .map(ItemKind::Export)
+        .labelled("export");
+
     // item_definition ::= let destructor = expr
     let item_definition = just(Token::KeywordLet)
         .ignore_then(destructor.clone())
@@ -583,21 +1228,219 @@ where
         .map(|(name, expr)| ItemKind::Definition(Definition { name, expr }))
         .labelled("definition");
 
-    // item ::= item_type_alias | item_import_tree | item_definition
-    let item = choice((item_type_alias, item_import_tree, item_definition))
-        .map_with(|kind, e| Item {
-            visibility: Visibility::Inherited,
+    // item_clause ::= fn ident pattern+ = expr
+    let item_clause = just(Token::KeywordFn)
+        .ignore_then(ident)
+        .then(pattern.clone().repeated().at_least(1).collect::<Vec<_>>())
+        .then_ignore(just(Token::OpAssign))
+        .then(expr.clone())
+        .map_with(|((name, patterns), body), e| {
+            ItemKind::Function(Function {
+                name,
+                clauses: vec![Clause {
+                    span: e.span(),
+                    patterns,
+                    body,
+                }],
+            })
+        })
+        .labelled("function clause");
+
+    // attribute ::= Attribute
+    let attribute = select! { Token::Attribute(text) => text }
+        .map_with(|text, e| {
+            // the text of the attribute starts after the `#`
+            let span: Span = e.span();
+            let file_id = span.file_id;
+            let start = span.start + 1;
+            let span = Span::new(file_id, span.start..start + text.len());
+            let state: &mut SimpleState<State> = e.state();
+            let mut ident = |offset: usize, name: &str| Ident {
+                key: state.rodeo.get_or_intern(name),
+                span: Span::new(file_id, start + offset..start + offset + name.len()),
+            };
+            let (name, rest) = text
+                .split_once('(')
+                .expect("the lexer only emits attributes with an argument list");
+            let mut offset = name.len() + 1;
+            let name = ident(0, name);
+            let mut arguments = Vec::new();
+            for argument in rest[..rest.len() - 1].split(',') {
+                let trimmed = argument.trim_start();
+                let leading = argument.len() - trimmed.len();
+                let trimmed = trimmed.trim_end();
+                if !trimmed.is_empty() {
+                    arguments.push(ident(offset + leading, trimmed));
+                }
+                offset += argument.len() + 1;
+            }
+            Attribute {
+                span,
+                name,
+                arguments,
+            }
+        })
+        .labelled("attribute");
+
+    // item ::= attribute* (item_export | export? (item_type_alias | item_import_tree
+    //     | item_definition | item_clause))
+    let item = attribute
+        .repeated()
+        .collect::<Vec<_>>()
+        .then(choice((
+            item_export.map(|kind| (Visibility::Inherited, kind)),
+            just(Token::KeywordExport)
+                .to(Visibility::Exported)
+                .or_not()
+                .map(Option::unwrap_or_default)
+                .then(choice((
+                    item_type_alias,
+                    item_import_tree,
+                    item_definition,
+                    item_clause,
+                ))),
+        )))
+        .map_with(|(attributes, (visibility, kind)), e| Item {
+            visibility,
             kind,
+            attributes,
             span: e.span(),
         })
         .labelled("item");
 
+    // items must be followed by a separator, so that trailing tokens are recovered from too
+    let item = item
+        .then_ignore(just(Token::SymSemicolon).ignored().or(end()).rewind())
+        .recover_with(via_parser(
+            any()
+                .and_is(just(Token::SymSemicolon).not())
+                .repeated()
+                .at_least(1)
+                .map_with(|_, e| Item {
+                    visibility: Visibility::Inherited,
+                    kind: ItemKind::Error,
+                    attributes: Vec::new(),
+                    span: e.span(),
+                }),
+        ));
+
     // module ::= item (; item)*
     item.separated_by(just(Token::SymSemicolon))
         .collect::<Vec<_>>()
+        .validate(|items, e, emitter| {
+            let state: &mut SimpleState<State> = e.state();
+            merge_clauses(items, &state.rodeo, emitter)
+        })
         .labelled("module")
 }
 
+/// Merges the clauses of each function into a single item.
+///
+/// The clauses of a function must be adjacent, and take the same number of arguments. Clauses
+/// that break either rule are reported and left out.
+///
+/// # Arguments
+///
+/// * `items` - The items of a module, where each clause is a function of its own.
+/// * `rodeo` - The interner owning the names of the functions.
+/// * `emitter` - Where to report clauses that cannot be merged.
+///
+/// # Returns
+///
+/// The items of the module, with one item per function.
+fn merge_clauses<'src>(
+    items: Vec<Item>,
+    rodeo: &lasso::Rodeo,
+    emitter: &mut Emitter<Rich<'src, Token<'src>, Span>>,
+) -> Vec<Item> {
+    let mut merged: Vec<Item> = Vec::with_capacity(items.len());
+    let mut defined = HashSet::new();
+    for mut item in items {
+        let ItemKind::Function(function) = &mut item.kind else {
+            merged.push(item);
+            continue;
+        };
+        let name = rodeo.resolve(&function.name.key);
+        if let Some(Item {
+            kind: ItemKind::Function(previous),
+            span,
+            attributes,
+            visibility,
+        }) = merged.last_mut()
+            && previous.name.key == function.name.key
+        {
+            let expected = previous.clauses[0].patterns.len();
+            let clause = function.clauses.remove(0);
+            if clause.patterns.len() != expected {
+                emitter.emit(Rich::custom(
+                    clause.span,
+                    format!(
+                        "this clause of `{}` takes {} argument(s), but its first clause takes {}",
+                        name,
+                        clause.patterns.len(),
+                        expected
+                    ),
+                ));
+                continue;
+            }
+            *span = span.union(item.span);
+            attributes.append(&mut item.attributes);
+            // a function is exported if any of its clauses is
+            if item.visibility == Visibility::Exported {
+                *visibility = Visibility::Exported;
+            }
+            previous.clauses.push(clause);
+            continue;
+        }
+        if !defined.insert(function.name.key) {
+            emitter.emit(Rich::custom(
+                item.span,
+                format!(
+                    "the clauses of `{}` must be written together, without other items between them",
+                    name
+                ),
+            ));
+            continue;
+        }
+        merged.push(item);
+    }
+    merged
+}
+
+/// Collects the fields of a record, reporting every field that is given more than once.
+///
+/// # Arguments
+///
+/// * `entries` - The fields of the record, in the order they are written.
+/// * `rodeo` - The interner the field names were interned in.
+/// * `emitter` - Where to report repeated fields.
+///
+/// # Returns
+///
+/// The fields of the record in the order they are written, keeping the first of any repeated
+/// field.
+fn record_fields<'src, T>(
+    entries: Vec<(Ident, T)>,
+    rodeo: &lasso::Rodeo,
+    emitter: &mut Emitter<Rich<'src, Token<'src>, Span>>,
+) -> indexmap::IndexMap<Ident, T> {
+    let mut fields = indexmap::IndexMap::with_capacity(entries.len());
+    for (name, value) in entries {
+        if fields.contains_key(&name) {
+            emitter.emit(Rich::custom(
+                name.span,
+                format!(
+                    "the field `{}` is given more than once",
+                    rodeo.resolve(&name.key)
+                ),
+            ));
+        } else {
+            fields.insert(name, value);
+        }
+    }
+    fields
+}
+
 /// Parses the given source code into a `Module` representation.
 ///
 /// # Arguments
@@ -612,29 +1455,201 @@ where
 /// # Example
 ///
 /// ```
-/// let source = "let x = 42;";
+/// use kali_parse::parse_str;
+///
+/// let source = "let x = 42";
 /// let result = parse_str(source);
 /// match result {
 ///     Ok(module) => println!("Parsed successfully: {:?}", module),
 ///     Err(errors) => println!("Parsing failed with errors: {:?}", errors),
 /// }
 /// ```
-pub fn parse_str<'src>(src: &'src str) -> Result<Module, Vec<Rich<'src, Token<'src>>>> {
-    let token_iter = Token::lexer(src).spanned().map(|(tok, span)| match tok {
-        Ok(tok) => (tok, span.into()),
-        Err(e) => (Token::Error(e), span.into()),
-    });
-    let token_stream =
-        Stream::from_iter(token_iter).map((0..src.len()).into(), |(t, s): (_, _)| (t, s));
+pub fn parse_str<'src>(src: &'src str) -> Result<Module, Vec<Rich<'src, Token<'src>, Span>>> {
+    let (module, errors) = parse_str_recovering(src);
+    if errors.is_empty() {
+        Ok(module)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parses the given source code into a `Module` representation, recovering from syntax errors.
+///
+/// Items and delimited expressions that fail to parse are replaced with [`ItemKind::Error`] and
+/// [`ExprKind::Error`] nodes respectively, so that later passes can still operate on the rest of
+/// the module.
+///
+/// # Arguments
+///
+/// * `src` - A string slice containing the source code to be parsed.
+///
+/// # Returns
+///
+/// * `(Module, Vec<Rich<Token>>)` - The (possibly partial) module, along with every error encountered.
+pub fn parse_str_recovering<'src>(src: &'src str) -> (Module, Vec<Rich<'src, Token<'src>, Span>>) {
+    parse_file_recovering(FileId::default(), src)
+}
+
+/// Parses the source code of a file into a `Module` representation.
+///
+/// # Arguments
+///
+/// * `file_id` - The ID of the file in its source map, attached to every span of the module.
+/// * `src` - A string slice containing the source code to be parsed.
+///
+/// # Returns
+///
+/// * `Ok(Module)` - If the parsing is successful, returns the parsed module.
+/// * `Err(Vec<Rich<Token>>)` - If the parsing fails, returns every error encountered.
+pub fn parse_file<'src>(
+    file_id: FileId,
+    src: &'src str,
+) -> Result<Module, Vec<Rich<'src, Token<'src>, Span>>> {
+    let (module, errors) = parse_file_recovering(file_id, src);
+    if errors.is_empty() {
+        Ok(module)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parses the source code of a file into a `Module` representation, recovering from syntax
+/// errors. See [`parse_str_recovering`].
+///
+/// # Arguments
+///
+/// * `file_id` - The ID of the file in its source map, attached to every span of the module.
+/// * `src` - A string slice containing the source code to be parsed.
+///
+/// # Returns
+///
+/// * `(Module, Vec<Rich<Token>>)` - The (possibly partial) module, along with every error encountered.
+pub fn parse_file_recovering<'src>(
+    file_id: FileId,
+    src: &'src str,
+) -> (Module, Vec<Rich<'src, Token<'src>, Span>>) {
+    let (module, mut errors, mistakes) = parse_file_with_mistakes(file_id, src);
+    errors.extend(
+        mistakes
+            .into_iter()
+            .map(|mistake| Rich::custom(mistake.span, mistake.message)),
+    );
+    errors.sort_by_key(|error| error.span().start);
+    (module, errors)
+}
+
+/// Parses the source code of a file into a `Module` representation, recovering from syntax
+/// errors, and reports the errors caused by a common mistake as the [`Mistake`] itself, so that
+/// its fix is kept. See [`parse_file_recovering`].
+///
+/// # Arguments
+///
+/// * `file_id` - The ID of the file in its source map, attached to every span of the module.
+/// * `src` - A string slice containing the source code to be parsed.
+///
+/// # Returns
+///
+/// * `(Module, Vec<Rich<Token>>, Vec<Mistake>)` - The (possibly partial) module, along with every
+///   error not caused by a common mistake, and the mistakes that caused the others.
+pub fn parse_file_with_mistakes<'src>(
+    file_id: FileId,
+    src: &'src str,
+) -> (Module, Vec<Rich<'src, Token<'src>, Span>>, Vec<Mistake>) {
+    let tokens = lexer::lex(src, file_id);
+    // deeply nested code is not parsed at all, as it would overflow the stack
+    if let Some(span) = nesting::too_deep(&tokens) {
+        let module = Module {
+            items: Vec::new(),
+            cache: lasso::Rodeo::default(),
+        };
+        return (module, vec![Rich::custom(span, TOO_DEEP)], Vec::new());
+    }
+    let fuel = tokens.len().saturating_mul(FUEL_PER_TOKEN);
+    let eoi = Span::new(file_id, 0..src.len());
+    let mut mistakes = mistakes::find(&tokens);
+    let token_stream = Stream::from_iter(tokens).map(eoi, |(t, s): (_, _)| (t, s));
 
-    let mut state = State::default().into();
-    parser()
+    let mut state = SimpleState(State {
+        fuel,
+        ..State::default()
+    });
+    let (items, mut errors) = parser()
         .parse_with_state(token_stream, &mut state)
-        .into_result()
-        .map(move |items| Module {
-            items,
-            cache: state.0.rodeo,
-        })
+        .into_output_errors();
+    // once the budget has run out, the remaining errors are only noise from failing alternatives
+    if state.0.exhausted {
+        errors = vec![Rich::custom(eoi, TOO_COMPLEX)];
+        mistakes.clear();
+    } else if errors.is_empty() {
+        mistakes.clear();
+    } else {
+        // the errors caused by a common mistake are replaced by a description of the mistake
+        errors.retain(|error| {
+            !mistakes
+                .iter()
+                .any(|mistake| mistake.explains(*error.span()))
+        });
+    }
+    let module = Module {
+        items: items.unwrap_or_default(),
+        cache: state.0.rodeo,
+    };
+    (module, errors, mistakes)
+}
+
+/// Lexes the source code of a file into tokens, for inspecting the lexer.
+///
+/// # Arguments
+///
+/// * `file_id` - The ID of the file in its source map, attached to every span.
+/// * `src` - A string slice containing the source code to be lexed.
+///
+/// # Returns
+///
+/// The tokens the parser sees and their spans, in source order.
+pub fn tokens(file_id: FileId, src: &str) -> Vec<(Token<'_>, Span)> {
+    lexer::lex(src, file_id)
+}
+
+/// Finds the tokens whose spans do not cover their own text.
+///
+/// Every token is checked by lexing the source code its span covers on its own, which must give
+/// back the same token. Tokens that fail this check have drifted from the source, and would be
+/// reported at the wrong place.
+///
+/// # Arguments
+///
+/// * `file_id` - The ID of the file in its source map, attached to every span.
+/// * `src` - A string slice containing the source code to be lexed.
+///
+/// # Returns
+///
+/// The misaligned tokens and their spans, in source order.
+pub fn misaligned_tokens(file_id: FileId, src: &str) -> Vec<(Token<'_>, Span)> {
+    lexer::lex(src, file_id)
+        .into_iter()
+        .filter(|(token, span)| !lexer::is_aligned(src, token, *span))
+        .collect()
+}
+
+/// Lexes arbitrary bytes, discarding the resulting tokens.
+///
+/// This is an entry point for fuzzing the lexer. Input that is not valid UTF-8 is ignored; all
+/// other input must be lexed without panicking.
+pub fn fuzz_lex(data: &[u8]) {
+    if let Ok(src) = std::str::from_utf8(data) {
+        drop(lexer::lex(src, FileId::default()));
+    }
+}
+
+/// Parses arbitrary bytes, discarding the resulting module or errors.
+///
+/// This is an entry point for fuzzing the parser. Input that is not valid UTF-8 is ignored; all
+/// other input must be parsed without panicking.
+pub fn fuzz_parse(data: &[u8]) {
+    if let Ok(src) = std::str::from_utf8(data) {
+        let _ = parse_str(src);
+    }
 }
 
 /// The `kali!` macro is a utility for parsing Rust-like syntax into a `Module` representation.
@@ -651,15 +1666,15 @@ pub fn parse_str<'src>(src: &'src str) -> Result<Module, Vec<Rich<'src, Token<'s
 /// # Example
 ///
 /// ```
-/// use your_crate_name::kali;
+/// use kali_parse::kali;
 ///
 /// let module = kali! {
-///     let x = 42;
+///     let x = 42
 /// };
 /// println!("{:?}", module);
 /// ```
 ///
-/// In this example, the `kali!` macro parses the input source code `let x = 42;` and returns a `Module` object.
+/// In this example, the `kali!` macro parses the input source code `let x = 42` and returns a `Module` object.
 ///
 /// # Panics
 ///
@@ -0,0 +1,228 @@
+//! Diagnosis of common mistakes made by newcomers to Kali.
+//!
+//! Some syntax errors are almost always made by someone used to another language, such as
+//! binding a name with `==` or comparing values with `=`. The parser reports these as unexpected
+//! tokens, which does not say what was meant. Mistakes are found by scanning the tokens of a
+//! file, and replace the errors the parser reports for them with a precise message and a [`Fix`].
+
+use kali_span::Span;
+
+use crate::lexer::Token;
+
+/// A common mistake found in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mistake {
+    /// The span of the offending tokens.
+    pub span: Span,
+    /// A description of the mistake.
+    pub message: &'static str,
+    /// How to fix the mistake.
+    pub fix: Fix,
+    /// The span of the source code the parser reports errors caused by the mistake in.
+    cause: Span,
+}
+
+impl Mistake {
+    /// Returns whether the mistake explains a syntax error reported at `span`.
+    ///
+    /// An error at the end of the input has an empty span just after the last token, so an empty
+    /// span at the end of the cause is explained by it too.
+    pub(crate) fn explains(&self, span: Span) -> bool {
+        let overlaps =
+            span.start < self.cause.end && self.cause.start < span.end.max(span.start + 1);
+        overlaps || (span.start == span.end && span.start == self.cause.end)
+    }
+}
+
+/// A change to the source code that fixes a [`Mistake`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// A description of the change.
+    pub message: &'static str,
+    /// The spans to replace and their replacements, in order. Insertions have empty spans.
+    pub edits: Vec<(Span, &'static str)>,
+}
+
+impl Fix {
+    /// Creates a fix that replaces a single span.
+    fn replace(message: &'static str, span: Span, replacement: &'static str) -> Fix {
+        Fix {
+            message,
+            edits: vec![(span, replacement)],
+        }
+    }
+
+    /// Applies the fix to a source file.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source code the spans of the fix refer to.
+    ///
+    /// # Returns
+    ///
+    /// The fixed source code.
+    pub fn apply(&self, src: &str) -> String {
+        let mut fixed = String::with_capacity(src.len());
+        let mut offset = 0;
+        for (span, replacement) in &self.edits {
+            fixed.push_str(&src[offset..span.start]);
+            fixed.push_str(replacement);
+            offset = span.end;
+        }
+        fixed.push_str(&src[offset..]);
+        fixed
+    }
+}
+
+/// Finds the common mistakes in a sequence of tokens.
+///
+/// # Arguments
+///
+/// * `tokens` - The tokens of a file, as returned by the lexer.
+pub(crate) fn find(tokens: &[(Token, Span)]) -> Vec<Mistake> {
+    let mut mistakes = Vec::new();
+    for (index, (token, span)) in tokens.iter().enumerate() {
+        let rest = &tokens[index + 1..];
+        let mistake = match token {
+            Token::KeywordLet => binding(rest),
+            Token::KeywordIf => condition(rest),
+            Token::KeywordThen => Some(then(*span, rest)),
+            // the parser reports the end of the input, which covers the `;`
+            Token::SymSemicolon if rest.is_empty() => Some(Mistake {
+                span: *span,
+                message: "the last item is followed by `;`, which only separates items",
+                fix: Fix::replace("remove the `;`", *span, ""),
+                cause: *span,
+            }),
+            _ => None,
+        };
+        mistakes.extend(mistake);
+    }
+    mistakes
+}
+
+/// Finds a `==` used in place of the `=` of a definition, given the tokens after its `let`.
+fn binding(tokens: &[(Token, Span)]) -> Option<Mistake> {
+    let (token, span) = at_depth_zero(tokens)
+        .find(|(token, _)| matches!(token, Token::OpAssign | Token::OpEqual))?;
+    (*token == Token::OpEqual).then(|| Mistake {
+        span: *span,
+        message: "definitions bind their value with `=`, not `==`",
+        fix: Fix::replace("use `=` to bind the value", *span, "="),
+        cause: *span,
+    })
+}
+
+/// Finds a `=` used in place of `==` in the condition of an `if`, given the tokens after it.
+fn condition(tokens: &[(Token, Span)]) -> Option<Mistake> {
+    let mut condition = at_depth_zero(tokens);
+    let (_, span) = condition
+        .by_ref()
+        .take_while(|(token, _)| !matches!(token, Token::SymLBrace | Token::KeywordThen))
+        .find(|(token, _)| *token == Token::OpAssign)?;
+    // `x = 1` reads as a parameter with a default value, so the parser fails after it, at the
+    // latest on the `{` that follows the condition
+    let end = condition
+        .find(|(token, _)| matches!(token, Token::SymLBrace | Token::KeywordThen))
+        .map_or(span.end, |(_, brace)| brace.end);
+    Some(Mistake {
+        span: *span,
+        message: "values are compared with `==`, not `=`",
+        fix: Fix::replace("use `==` to compare the values", *span, "=="),
+        cause: Span::new(span.file_id, span.start..end),
+    })
+}
+
+/// Describes a `then` written after the condition of an `if`, given the tokens after it.
+///
+/// The fix wraps the branches in braces, so that `if c then a else b` becomes
+/// `if c { a } else { b }`.
+fn then(span: Span, tokens: &[(Token, Span)]) -> Mistake {
+    let mut edits = vec![(span, "{")];
+    let body = branch(tokens);
+    match tokens.get(body) {
+        Some((Token::KeywordElse, else_span)) => {
+            edits.push((empty(span, else_span.start), "} "));
+            let otherwise = &tokens[body + 1..];
+            if !matches!(
+                otherwise.first(),
+                Some((Token::SymLBrace | Token::KeywordIf, _))
+            ) {
+                let end = branch(otherwise);
+                edits.push((empty(span, else_span.end), " {"));
+                edits.push((empty(span, end_of(&otherwise[..end], else_span.end)), " }"));
+            }
+        }
+        _ => edits.push((empty(span, end_of(&tokens[..body], span.end)), " }")),
+    }
+    Mistake {
+        span,
+        message: "`if` expressions write their branches in braces, not after `then`",
+        fix: Fix {
+            message: "wrap the branches in braces",
+            edits,
+        },
+        cause: span,
+    }
+}
+
+/// Returns the number of tokens in a branch, which ends at an `else`, a `;` or a closing
+/// delimiter outside of any brackets.
+fn branch(tokens: &[(Token, Span)]) -> usize {
+    let mut depth = 0usize;
+    for (index, (token, _)) in tokens.iter().enumerate() {
+        match token {
+            Token::SymLParen
+            | Token::SymCallParen
+            | Token::SymLBracket
+            | Token::SymIndexBracket
+            | Token::SymLBrace
+            | Token::SymHashBrace
+            | Token::SymHashBracket => depth += 1,
+            Token::SymRParen | Token::SymRBracket | Token::SymRBrace if depth == 0 => {
+                return index;
+            }
+            Token::SymRParen | Token::SymRBracket | Token::SymRBrace => depth -= 1,
+            Token::KeywordElse | Token::SymSemicolon if depth == 0 => return index,
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+/// Returns the tokens that are not nested in brackets, up to the end of the item.
+fn at_depth_zero<'a, 'src>(
+    tokens: &'a [(Token<'src>, Span)],
+) -> impl Iterator<Item = &'a (Token<'src>, Span)> {
+    let mut depth = 0usize;
+    tokens
+        .iter()
+        .take_while(|(token, _)| *token != Token::SymSemicolon)
+        .filter(move |(token, _)| {
+            let outer = depth == 0;
+            match token {
+                Token::SymLParen
+                | Token::SymCallParen
+                | Token::SymLBracket
+                | Token::SymIndexBracket
+                | Token::SymLBrace
+                | Token::SymHashBrace
+                | Token::SymHashBracket => depth += 1,
+                Token::SymRParen | Token::SymRBracket | Token::SymRBrace => {
+                    depth = depth.saturating_sub(1)
+                }
+                _ => {}
+            }
+            outer
+        })
+}
+
+/// Returns the end of the last of a run of tokens, or `default` if there are none.
+fn end_of(tokens: &[(Token, Span)], default: usize) -> usize {
+    tokens.last().map_or(default, |(_, span)| span.end)
+}
+
+/// Returns an empty span at an offset in the file of `span`, where an edit inserts text.
+fn empty(span: Span, offset: usize) -> Span {
+    Span::new(span.file_id, offset..offset)
+}
@@ -0,0 +1,171 @@
+//! Bounds on how deeply the source code of a file is nested, checked before it is parsed.
+//!
+//! The parser and every later pass recurse into nested code, so deeply nested code would overflow
+//! the stack. The tokens of a file are scanned before it is parsed, estimating two depths:
+//!
+//! - How deeply its code is nested, which the parser recurses on. Each delimiter, prefix operator,
+//!   right-associative operator and lambda body is a level deeper than the tokens before it in
+//!   the same expression. The condition of an `if` or `while` and the value of a `match` end
+//!   where their body starts, so neither a chain of operators nor an `else { if .. }` chain is
+//!   nested deeper than its parts.
+//! - How deep its syntax tree is, which the later passes recurse on. Every delimiter, operator
+//!   and keyword is a level deeper than the tokens before it, including those chained one after
+//!   another, though `if`, `match`, `while`, `for` and `else` only count through their bodies.
+//!
+//! The expressions inside a delimiter start from its depths. Both are estimated from the tokens
+//! alone, so may be off by a level or so, which the limits leave room for.
+
+use kali_span::Span;
+
+use crate::lexer::Token;
+
+/// How deeply the code of a file may be nested.
+pub(crate) const MAX_NESTING: usize = 256;
+
+/// How deep the syntax tree of a file may be.
+pub(crate) const MAX_DEPTH: usize = 512;
+
+/// A delimiter, or the condition of an `if`, `match`, `while` or `for`, that has not been closed.
+struct Open {
+    /// The depth of the syntax tree inside it.
+    depth: usize,
+    /// How deeply the code before it is nested.
+    nesting: usize,
+    /// Whether it is a condition, which is closed by the body that follows it.
+    condition: bool,
+}
+
+/// Finds the first token nested deeper than [`MAX_NESTING`], or whose syntax tree is deeper than
+/// [`MAX_DEPTH`], if any.
+///
+/// # Arguments
+///
+/// * `tokens` - The tokens of a file, as returned by the lexer.
+pub(crate) fn too_deep(tokens: &[(Token, Span)]) -> Option<Span> {
+    let mut open: Vec<Open> = Vec::new();
+    let (mut depth, mut nesting) = (0, 0);
+    // whether the previous token ends an operand, so that an operator after it is binary
+    let mut operand = false;
+    for (token, span) in tokens {
+        match token {
+            Token::SymRParen | Token::SymRBracket | Token::SymRBrace => {
+                close_conditions(&mut open);
+                if let Some(delimiter) = open.pop() {
+                    (depth, nesting) = (delimiter.depth, delimiter.nesting);
+                }
+                operand = true;
+            }
+            Token::SymComma | Token::SymSemicolon => {
+                close_conditions(&mut open);
+                (depth, nesting) = open
+                    .last()
+                    .map_or((0, 0), |delimiter| (delimiter.depth, delimiter.nesting + 1));
+                operand = false;
+            }
+            Token::Ident(_)
+            | Token::Hole(_)
+            | Token::LitNatural(_)
+            | Token::LitBool(_)
+            | Token::LitUnit
+            | Token::LitString(_)
+            | Token::LitBytes(_)
+            | Token::TypeInteger
+            | Token::TypeNatural
+            | Token::TypeFloat
+            | Token::TypeBool
+            | Token::TypeString
+            | Token::TypeBytes
+            | Token::SymArray
+            | Token::SymWildcard => operand = true,
+            Token::SymLParen
+            | Token::SymCallParen
+            | Token::SymLBracket
+            | Token::SymIndexBracket
+            | Token::SymLBrace
+            | Token::SymHashBrace
+            | Token::SymHashBracket => {
+                // the body of an `if` or `match` closes its condition
+                if *token == Token::SymLBrace && open.last().is_some_and(|open| open.condition) {
+                    let condition = open.pop().unwrap();
+                    (depth, nesting) = (condition.depth, condition.nesting);
+                }
+                depth += 1;
+                open.push(Open {
+                    depth,
+                    nesting,
+                    condition: false,
+                });
+                nesting += 1;
+                operand = false;
+            }
+            // the body of a condition is a level deeper, so the condition itself need not be
+            Token::KeywordIf | Token::KeywordMatch | Token::KeywordWhile | Token::KeywordFor => {
+                open.push(Open {
+                    depth,
+                    nesting,
+                    condition: true,
+                });
+                nesting += 1;
+                operand = false;
+            }
+            // the body of a `while` or `for` closes its condition, but is not delimited
+            Token::KeywordDo => {
+                if open.last().is_some_and(|open| open.condition) {
+                    let condition = open.pop().unwrap();
+                    (depth, nesting) = (condition.depth, condition.nesting);
+                }
+                depth += 1;
+                nesting += 1;
+                operand = false;
+            }
+            // the block after an `else` is a level deeper already
+            Token::KeywordElse => {}
+            // a postfix operator ends an operand as well
+            Token::SymQuestion => depth += 1,
+            Token::SymArrow | Token::OpCons | Token::OpExponentiate => {
+                depth += 1;
+                nesting += 1;
+                operand = false;
+            }
+            _ if operand => {
+                depth += 1;
+                operand = false;
+            }
+            _ => {
+                depth += 1;
+                // keywords other than those above are followed by a delimited body, if any
+                if !is_keyword(token) {
+                    nesting += 1;
+                }
+            }
+        }
+        if nesting > MAX_NESTING || depth > MAX_DEPTH {
+            return Some(*span);
+        }
+    }
+    None
+}
+
+/// Closes the conditions left open by an `if`, `match`, `while` or `for` without a body.
+fn close_conditions(open: &mut Vec<Open>) {
+    while open.last().is_some_and(|open| open.condition) {
+        open.pop();
+    }
+}
+
+/// Returns whether a token is a keyword.
+fn is_keyword(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::KeywordThen
+            | Token::KeywordWith
+            | Token::KeywordIn
+            | Token::KeywordLet
+            | Token::KeywordFn
+            | Token::KeywordType
+            | Token::KeywordImport
+            | Token::KeywordExport
+            | Token::KeywordFrom
+            | Token::KeywordAs
+    )
+}
@@ -0,0 +1,121 @@
+//! Comments and blank lines, which the lexer skips, for tools that preserve them.
+//!
+//! The parser never sees trivia, so it is collected separately from the source code: everything
+//! between two tokens is whitespace or a comment. Trivia is attached to syntax tree nodes by their
+//! spans, as the comments on their own lines before a node, and the comment after it on the same
+//! line.
+
+use kali_span::{FileId, Span};
+
+use crate::lexer;
+
+/// A comment, which is skipped by the lexer unless it is an attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment<'src> {
+    /// The text of the comment, without the leading `#`.
+    pub text: &'src str,
+    /// The span of the comment, from its `#` up to the end of its line.
+    pub span: Span,
+    /// The number of blank lines between the comment and the code or comment before it.
+    pub blank_lines: usize,
+    /// Whether the comment follows code on the same line, rather than being on a line of its own.
+    pub trailing: bool,
+}
+
+/// The trivia of a file, in source order.
+#[derive(Debug, Clone)]
+pub struct Trivia<'src> {
+    /// The source code of the file.
+    src: &'src str,
+    /// The comments of the file, in source order.
+    comments: Vec<Comment<'src>>,
+}
+
+impl<'src> Trivia<'src> {
+    /// Collects the trivia of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_id` - The ID of the file in its source map, attached to every span.
+    /// * `src` - A string slice containing the source code of the file.
+    pub fn collect(file_id: FileId, src: &'src str) -> Trivia<'src> {
+        let mut comments = Vec::new();
+        let mut gap = 0;
+        let ends = lexer::lex(src, file_id)
+            .into_iter()
+            .map(|(_, span)| (span.start, span.end))
+            .chain([(src.len(), src.len())]);
+        for (start, end) in ends {
+            let mut offset = gap;
+            // every `#` between two tokens starts a comment running to the end of its line
+            while let Some(hash) = src.get(offset..start).and_then(|gap| gap.find('#')) {
+                let hash = offset + hash;
+                let end = src[hash..start].find('\n').map_or(start, |len| hash + len);
+                let line = src[..hash].rfind('\n').map_or(0, |newline| newline + 1);
+                comments.push(Comment {
+                    text: src[hash + 1..end].trim_end(),
+                    span: Span::new(file_id, hash..end),
+                    blank_lines: blank_lines(&src[..hash]),
+                    trailing: !src[line..hash].trim().is_empty(),
+                });
+                offset = end;
+            }
+            gap = gap.max(end);
+        }
+        Trivia { src, comments }
+    }
+
+    /// Returns every comment of the file, in source order.
+    pub fn comments(&self) -> &[Comment<'src>] {
+        &self.comments
+    }
+
+    /// Returns the comments on their own lines between an offset and the start of a node, such
+    /// as those documenting an item.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - The end of the code before the node, e.g. of the previous item.
+    /// * `span` - The span of the node.
+    pub fn leading(&self, after: usize, span: Span) -> &[Comment<'src>] {
+        let first = self
+            .comments
+            .partition_point(|comment| comment.span.start < after);
+        let last = self
+            .comments
+            .partition_point(|comment| comment.span.start < span.start);
+        let mut leading = &self.comments[first..last.max(first)];
+        // a comment on the line the code before ends on trails that code instead
+        if let Some((comment, rest)) = leading.split_first()
+            && comment.trailing
+        {
+            leading = rest;
+        }
+        leading
+    }
+
+    /// Returns the comment following a node on the line it ends on, if any.
+    pub fn trailing(&self, span: Span) -> Option<&Comment<'src>> {
+        let next = self
+            .comments
+            .partition_point(|comment| comment.span.start < span.end);
+        self.comments.get(next).filter(|comment| {
+            comment.trailing && !self.src[span.end..comment.span.start].contains('\n')
+        })
+    }
+
+    /// Returns the number of blank lines between an offset and the code or comment before it.
+    pub fn blank_lines(&self, offset: usize) -> usize {
+        blank_lines(&self.src[..offset])
+    }
+}
+
+/// Counts the blank lines at the end of some source code, up to its last line with any text.
+fn blank_lines(src: &str) -> usize {
+    let whitespace = src.len() - src.trim_end().len();
+    let newlines = src[src.len() - whitespace..]
+        .chars()
+        .filter(|&c| c == '\n')
+        .count();
+    newlines.saturating_sub(1)
+}
@@ -0,0 +1,57 @@
+use kali_ast::{ItemKind, Visibility};
+use kali_parse::{parse_str, parse_str_recovering};
+
+/// Returns the messages of the syntax errors in `src`.
+fn errors(src: &str) -> Vec<String> {
+    parse_str(src)
+        .unwrap_err()
+        .iter()
+        .map(|error| error.to_string())
+        .collect()
+}
+
+#[test]
+fn test_clauses_are_merged() {
+    let src = "fn fib 0 = 0;\nfn fib 1 = 1;\nfn fib n = fib(n - 1) + fib(n - 2);\nlet a = fib(10)";
+    let module = parse_str(src).unwrap();
+    assert_eq!(module.items.len(), 2);
+    let ItemKind::Function(function) = &module.items[0].kind else {
+        panic!("expected a function");
+    };
+    assert_eq!(module.cache.resolve(&function.name.key), "fib");
+    assert_eq!(function.clauses.len(), 3);
+    assert_eq!(module.items[0].span.into_range(), 0..62);
+    assert_eq!(function.clauses[1].span.into_range(), 14..26);
+
+    // clauses of different functions are kept apart
+    let module = parse_str("fn f 0 = 0;\nfn g 0 = 0;\nfn g x = x").unwrap();
+    assert_eq!(module.items.len(), 2);
+
+    // a function is exported if any of its clauses is
+    let module = parse_str("fn f 0 = 0;\nexport fn f x = x").unwrap();
+    assert_eq!(module.items[0].visibility, Visibility::Exported);
+}
+
+#[test]
+fn test_clause_arity() {
+    let src = "fn f 0 = 0;\nfn f x y = x";
+    assert_eq!(
+        errors(src),
+        ["this clause of `f` takes 2 argument(s), but its first clause takes 1"]
+    );
+    // the clause is left out, and the rest of the function is kept
+    let (module, errors) = parse_str_recovering(src);
+    assert_eq!(errors.len(), 1);
+    let ItemKind::Function(function) = &module.items[0].kind else {
+        panic!("expected a function");
+    };
+    assert_eq!(function.clauses.len(), 1);
+}
+
+#[test]
+fn test_separated_clauses() {
+    assert_eq!(
+        errors("fn f 0 = 0;\nlet a = 1;\nfn f x = x"),
+        ["the clauses of `f` must be written together, without other items between them"]
+    );
+}
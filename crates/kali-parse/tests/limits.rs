@@ -0,0 +1,108 @@
+use chumsky::error::RichReason;
+use kali_parse::parse_str;
+
+/// The error reported for input nested too deeply.
+const TOO_DEEP: &str = "expression nested too deeply";
+
+/// Builds sources nested `depth` levels deep in each way an expression can be nested.
+fn nestings(depth: usize) -> Vec<String> {
+    vec![
+        format!("let a = {}a{}", "(".repeat(depth), ")".repeat(depth)),
+        format!("let a = {}a{}", "[".repeat(depth), "]".repeat(depth)),
+        format!("let a = {}a", "x -> ".repeat(depth)),
+        format!("let a = {}a", "-".repeat(depth)),
+        format!("let a = {}a", "!".repeat(depth)),
+        format!("let a = a{} :: []", " :: a".repeat(depth)),
+    ]
+}
+
+/// Builds sources chaining `length` expressions one after another, without nesting them.
+fn chains(length: usize) -> Vec<String> {
+    vec![
+        format!("let a = a{}", " + a".repeat(length)),
+        format!("let a = a{}", " |> f".repeat(length)),
+        format!("let a = f{}", "(a)".repeat(length)),
+        format!("let a = a{}", "[0]".repeat(length)),
+    ]
+}
+
+/// Asserts that parsing `src` fails with a single [`TOO_DEEP`] error.
+fn assert_too_deep(src: &str) {
+    let errors = parse_str(src).unwrap_err();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(
+        matches!(
+            errors[0].reason(),
+            RichReason::Custom(message) if message == TOO_DEEP
+        ),
+        "{:?}",
+        errors
+    );
+}
+
+#[test]
+fn test_deep_nesting() {
+    for src in nestings(100).into_iter().chain(chains(100)) {
+        assert!(parse_str(&src).is_ok(), "failed to parse `{}`", src);
+    }
+}
+
+#[test]
+fn test_wide_nesting() {
+    // the elements of a list are not nested in one another
+    let src = format!("let a = [{}]", vec!["-(a + a)"; 10_000].join(", "));
+    assert!(parse_str(&src).is_ok());
+}
+
+#[test]
+fn test_nested_too_deeply() {
+    for depth in [1000, 100_000] {
+        for src in nestings(depth) {
+            assert_too_deep(&src);
+        }
+    }
+}
+
+#[test]
+fn test_long_chains() {
+    // chained operators and calls are parsed one after another, so are not nested in each other
+    for src in chains(400) {
+        assert!(parse_str(&src).is_ok(), "failed to parse `{}`", src);
+    }
+    // nor is each `if` of an `else` chain nested in the condition of the one before it
+    let length = 200;
+    let src = format!(
+        "let a = {}a{}",
+        "if a { a } else { ".repeat(length),
+        " }".repeat(length)
+    );
+    assert!(parse_str(&src).is_ok());
+}
+
+#[test]
+fn test_chained_too_deeply() {
+    for src in chains(100_000) {
+        assert_too_deep(&src);
+    }
+}
+
+#[test]
+fn test_nested_too_deeply_in_patterns_and_types() {
+    let depth = 1000;
+    let pattern = format!(
+        "let a = match x {{ {}_{} -> 1 }}",
+        "(".repeat(depth),
+        ")".repeat(depth)
+    );
+    let ty = format!("type t = {}nat", "nat -> ".repeat(depth));
+    for src in [pattern, ty] {
+        assert_too_deep(&src);
+    }
+}
+
+#[test]
+fn test_long_module() {
+    let src =
+        vec!["let f = (a, b) -> match a { (x, y) :: _ -> [x, (y), b], _ -> [] }"; 500].join(";\n");
+    assert!(parse_str(&src).is_ok());
+}
@@ -0,0 +1,91 @@
+use kali_ast::{ExprKind, ItemKind, LiteralKind};
+use kali_parse::{parse_str, parse_str_recovering};
+
+/// Parses `let x = <literal>`, returning the value of the literal.
+fn literal(literal: &str) -> LiteralKind {
+    let module = parse_str(&format!("let x = {}", literal)).unwrap();
+    let ItemKind::Definition(definition) = &module.items[0].kind else {
+        panic!("expected a definition");
+    };
+    match &definition.expr.kind {
+        ExprKind::Literal(literal) => literal.clone(),
+        kind => panic!("expected a literal, found {:?}", kind),
+    }
+}
+
+/// Returns the messages of the syntax errors in `src`.
+fn errors(src: &str) -> Vec<String> {
+    parse_str(src)
+        .unwrap_err()
+        .iter()
+        .map(|error| error.to_string())
+        .collect()
+}
+
+#[test]
+fn test_integer_bounds() {
+    assert!(matches!(
+        literal("18446744073709551615"),
+        LiteralKind::Natural(u64::MAX)
+    ));
+    assert!(matches!(
+        literal("0xffff_ffff_ffff_ffff"),
+        LiteralKind::Natural(u64::MAX)
+    ));
+    assert!(matches!(
+        literal("-9223372036854775808"),
+        LiteralKind::Integer(i64::MIN)
+    ));
+    assert!(matches!(
+        literal("-0x8000_0000_0000_0000"),
+        LiteralKind::Integer(i64::MIN)
+    ));
+    assert!(matches!(literal("1_000"), LiteralKind::Natural(1000)));
+    assert!(matches!(literal("- 1_000"), LiteralKind::Integer(-1000)));
+}
+
+#[test]
+fn test_out_of_range() {
+    assert_eq!(
+        errors("let x = 18446744073709551616"),
+        ["literal out of range for `nat`, which is an unsigned 64-bit integer"]
+    );
+    assert_eq!(
+        errors("let x = -0x8000_0000_0000_0001"),
+        ["literal out of range for `int`, which is a signed 64-bit integer"]
+    );
+    assert_eq!(
+        errors("let x = -18446744073709551616"),
+        ["literal out of range for `int`, which is a signed 64-bit integer"]
+    );
+    // the rest of the module is still parsed
+    let (module, errors) = parse_str_recovering(
+        "let x = 0b1_0000000000000000000000000000000000000000000000000000000000000000 + 1;\nlet y = 1",
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(module.items.len(), 2);
+}
+
+#[test]
+fn test_byte_string() {
+    let LiteralKind::Bytes(bytes) = literal(r#"b"GIF\x89\0\n\\\"é""#) else {
+        panic!("expected a byte string");
+    };
+    assert_eq!(bytes, b"GIF\x89\0\n\\\"\xc3\xa9");
+    assert!(matches!(literal(r#"b"""#), LiteralKind::Bytes(bytes) if bytes.is_empty()));
+
+    for src in [
+        r#"let x = b"\q""#,
+        r#"let x = b"\x8""#,
+        r#"let x = b"\xzz""#,
+    ] {
+        assert_eq!(
+            errors(src),
+            [
+                "invalid escape in byte string, expected one of `\\xNN`, `\\n`, `\\r`, `\\t`, `\\0`, `\\\\` or `\\\"`"
+            ],
+            "{}",
+            src
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use kali_parse::{parse_file_with_mistakes, parse_str};
+use kali_span::FileId;
+
+/// Returns the messages of the syntax errors in `src`.
+fn errors(src: &str) -> Vec<String> {
+    parse_str(src)
+        .unwrap_err()
+        .iter()
+        .map(|error| error.to_string())
+        .collect()
+}
+
+/// Returns `src` with the fix of each of its mistakes applied.
+fn fixes(src: &str) -> Vec<String> {
+    parse_file_with_mistakes(FileId::default(), src)
+        .2
+        .iter()
+        .map(|mistake| mistake.fix.apply(src))
+        .collect()
+}
+
+#[test]
+fn test_binding_with_equality() {
+    let src = "let x == 1";
+    assert_eq!(
+        errors(src),
+        ["definitions bind their value with `=`, not `==`"]
+    );
+    assert_eq!(fixes(src), ["let x = 1"]);
+    // `==` is only a mistake before the `=` of the definition
+    assert!(fixes("let x = y == 1").is_empty());
+}
+
+#[test]
+fn test_condition_with_assignment() {
+    let src = "let a = x -> if x = 1 { 2 } else { 3 }";
+    assert_eq!(errors(src), ["values are compared with `==`, not `=`"]);
+    assert_eq!(fixes(src), ["let a = x -> if x == 1 { 2 } else { 3 }"]);
+    assert!(fixes("let a = x -> if f(y = 1) { 2 }").is_empty());
+}
+
+#[test]
+fn test_then() {
+    let src = "let a = x -> if x then 1 else 2";
+    assert_eq!(
+        errors(src),
+        ["`if` expressions write their branches in braces, not after `then`"]
+    );
+    assert_eq!(fixes(src), ["let a = x -> if x { 1 } else { 2 }"]);
+    assert_eq!(
+        fixes("let a = x -> if x then f(x);\nlet b = 1"),
+        ["let a = x -> if x { f(x) };\nlet b = 1"]
+    );
+    assert_eq!(
+        fixes("let a = x -> if x then 1 else { 2 }"),
+        ["let a = x -> if x { 1 } else { 2 }"]
+    );
+}
+
+#[test]
+fn test_trailing_semicolon() {
+    let src = "let a = 1;\nlet b = 2;";
+    assert_eq!(
+        errors(src),
+        ["the last item is followed by `;`, which only separates items"]
+    );
+    assert_eq!(fixes(src), ["let a = 1;\nlet b = 2"]);
+}
+
+#[test]
+fn test_other_errors_are_kept() {
+    // errors that are not caused by a mistake are still reported alongside it
+    let errors = errors("let x == 1;\nlet = 2");
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0], "definitions bind their value with `=`, not `==`");
+}
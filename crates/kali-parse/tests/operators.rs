@@ -0,0 +1,253 @@
+use chumsky::error::RichReason;
+use kali_ast::{
+    BinaryOpKind, Expr, ExprKind, ItemKind, LiteralKind, Module, PatternKind, Side, UnaryOpKind,
+};
+use kali_parse::parse_str;
+
+fn definition(module: &Module) -> &Expr {
+    match &module.items[0].kind {
+        ItemKind::Definition(definition) => &definition.expr,
+        kind => panic!("expected a definition, found {:?}", kind),
+    }
+}
+
+fn binary(expr: &Expr) -> (BinaryOpKind, &Expr, &Expr) {
+    match &expr.kind {
+        ExprKind::BinaryExpr { op, lhs, rhs } => (op.kind, lhs, rhs),
+        kind => panic!("expected a binary expression, found {:?}", kind),
+    }
+}
+
+#[test]
+fn test_bitwise_precedence() {
+    let module = parse_str("let e = a | b ^ c & d == e").unwrap();
+    let (op, lhs, _) = binary(definition(&module));
+    assert_eq!(op, BinaryOpKind::Equal);
+
+    let (op, _, rhs) = binary(lhs);
+    assert_eq!(op, BinaryOpKind::BitwiseOr);
+    let (op, _, rhs) = binary(rhs);
+    assert_eq!(op, BinaryOpKind::BitwiseXor);
+    let (op, _, _) = binary(rhs);
+    assert_eq!(op, BinaryOpKind::BitwiseAnd);
+}
+
+#[test]
+fn test_or_pattern() {
+    let module = parse_str("let e = match a { 1 | 2 -> b | c }").unwrap();
+    let ExprKind::Match { arms, .. } = &definition(&module).kind else {
+        panic!("expected a match expression");
+    };
+    assert_eq!(arms.len(), 1);
+    assert!(matches!(arms[0].pattern.kind, PatternKind::Or { .. }));
+    assert_eq!(binary(&arms[0].expr).0, BinaryOpKind::BitwiseOr);
+}
+
+#[test]
+fn test_pipe_separated_arms() {
+    let errors = parse_str("let e = match a { 1 -> b | 2 -> c }").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0].reason(),
+        RichReason::Custom(message) if message.contains("separated by `,`")
+    ));
+    assert_eq!(errors[0].span().into_range(), 25..26);
+}
+
+#[test]
+fn test_pipe_precedence() {
+    // pipelines bind looser than every other operator, and associate to the left
+    let module = parse_str("let e = x :: xs |> map f |> g y || z").unwrap();
+    let (op, lhs, rhs) = binary(definition(&module));
+    assert_eq!(op, BinaryOpKind::Pipe);
+    assert_eq!(binary(rhs).0, BinaryOpKind::LogicalOr);
+
+    let (op, lhs, rhs) = binary(lhs);
+    assert_eq!(op, BinaryOpKind::Pipe);
+    assert_eq!(binary(lhs).0, BinaryOpKind::Cons);
+    assert!(matches!(rhs.kind, ExprKind::Call { .. }));
+}
+
+#[test]
+fn test_range_precedence() {
+    // ranges bind looser than arithmetic and logic, but tighter than `::` and `|>`
+    let module = parse_str("let e = 1..n + 1 |> collect").unwrap();
+    let (op, lhs, _) = binary(definition(&module));
+    assert_eq!(op, BinaryOpKind::Pipe);
+    let ExprKind::Range {
+        start,
+        end,
+        inclusive,
+    } = &lhs.kind
+    else {
+        panic!("expected a range, found {:?}", lhs.kind);
+    };
+    assert!(!inclusive);
+    assert!(matches!(
+        start.kind,
+        ExprKind::Literal(LiteralKind::Natural(1))
+    ));
+    assert_eq!(binary(end).0, BinaryOpKind::Add);
+
+    let module = parse_str("let e = -1..=a || b").unwrap();
+    assert!(matches!(
+        definition(&module).kind,
+        ExprKind::Range {
+            inclusive: true,
+            ..
+        }
+    ));
+
+    // ranges do not chain
+    assert!(parse_str("let e = a..b..c").is_err());
+}
+
+#[test]
+fn test_range_pattern() {
+    let module = parse_str("let e = match a { 1..=9 -> b, -3..0 | 10 -> c }").unwrap();
+    let ExprKind::Match { arms, .. } = &definition(&module).kind else {
+        panic!("expected a match expression");
+    };
+    assert!(matches!(
+        arms[0].pattern.kind,
+        PatternKind::Range {
+            start: LiteralKind::Natural(1),
+            end: LiteralKind::Natural(9),
+            inclusive: true,
+        }
+    ));
+    let PatternKind::Or { lhs, .. } = &arms[1].pattern.kind else {
+        panic!("expected an or-pattern, found {:?}", arms[1].pattern.kind);
+    };
+    assert!(matches!(
+        lhs.kind,
+        PatternKind::Range {
+            start: LiteralKind::Integer(-3),
+            end: LiteralKind::Natural(0),
+            inclusive: false,
+        }
+    ));
+}
+
+#[test]
+fn test_prefix_pattern() {
+    let module =
+        parse_str("let e = match s { \"GET \" ++ \"/\" ++ path | \"\" -> path, \"\" ++ _ -> s }")
+            .unwrap();
+    let ExprKind::Match { arms, .. } = &definition(&module).kind else {
+        panic!("expected a match expression");
+    };
+    // `++` binds tighter than `|`, and chains to the right
+    let PatternKind::Or { lhs, .. } = &arms[0].pattern.kind else {
+        panic!("expected an or-pattern, found {:?}", arms[0].pattern.kind);
+    };
+    let PatternKind::Prefix { rest, .. } = &lhs.kind else {
+        panic!("expected a prefix pattern, found {:?}", lhs.kind);
+    };
+    let PatternKind::Prefix { rest, .. } = &rest.kind else {
+        panic!("expected a prefix pattern, found {:?}", rest.kind);
+    };
+    assert!(matches!(rest.kind, PatternKind::Var(_)));
+    assert!(matches!(arms[1].pattern.kind, PatternKind::Prefix { .. }));
+
+    // only a string literal can be a prefix
+    assert!(parse_str("let e = match s { a ++ b -> b }").is_err());
+}
+
+fn section(expr: &Expr) -> (BinaryOpKind, &Expr, Side) {
+    match &expr.kind {
+        ExprKind::Section { op, operand, side } => (op.kind, operand, *side),
+        kind => panic!("expected an operator section, found {:?}", kind),
+    }
+}
+
+#[test]
+fn test_sections() {
+    let module = parse_str("let e = (+ 1)").unwrap();
+    let (op, operand, side) = section(definition(&module));
+    assert_eq!((op, side), (BinaryOpKind::Add, Side::Right));
+    assert!(matches!(operand.kind, ExprKind::Literal(_)));
+
+    let module = parse_str("let e = (2 *)").unwrap();
+    let (op, _, side) = section(definition(&module));
+    assert_eq!((op, side), (BinaryOpKind::Multiply, Side::Left));
+
+    // the operand is a whole expression
+    let module = parse_str("let e = (x + y ::)").unwrap();
+    let (op, operand, _) = section(definition(&module));
+    assert_eq!(op, BinaryOpKind::Cons);
+    assert_eq!(binary(operand).0, BinaryOpKind::Add);
+
+    let module = parse_str("let e = (* f x)").unwrap();
+    let (_, operand, _) = section(definition(&module));
+    assert!(matches!(operand.kind, ExprKind::Call { .. }));
+}
+
+#[test]
+fn test_section_disambiguation() {
+    // `(- x)` is a negation, but `(x -)` is a section
+    let module = parse_str("let e = (- x)").unwrap();
+    assert!(matches!(
+        &definition(&module).kind,
+        ExprKind::UnaryExpr { op, .. } if op.kind == UnaryOpKind::Negate
+    ));
+    let module = parse_str("let e = (x -)").unwrap();
+    assert_eq!(section(definition(&module)).0, BinaryOpKind::Subtract);
+
+    // parentheses around a complete expression only group
+    let module = parse_str("let e = (x - y)").unwrap();
+    assert_eq!(binary(definition(&module)).0, BinaryOpKind::Subtract);
+    let module = parse_str("let e = (x, y)").unwrap();
+    assert!(matches!(definition(&module).kind, ExprKind::Tuple(_)));
+
+    // sections are atoms, so they can be passed as arguments
+    let module = parse_str("let e = map (* 2), xs").unwrap();
+    let ExprKind::Call { arguments, .. } = &definition(&module).kind else {
+        panic!("expected a call");
+    };
+    assert_eq!(section(&arguments[0]).0, BinaryOpKind::Multiply);
+
+    // pipelines have no sections
+    assert!(parse_str("let e = (|> f)").is_err());
+    assert!(parse_str("let e = (x |>)").is_err());
+}
+
+#[test]
+fn test_minus() {
+    // `-` between operands subtracts, however it is spaced
+    for src in ["let e = a - 1", "let e = a -1", "let e = a-1"] {
+        let module = parse_str(src).unwrap();
+        let (op, lhs, rhs) = binary(definition(&module));
+        assert_eq!(op, BinaryOpKind::Subtract, "{}", src);
+        assert!(matches!(lhs.kind, ExprKind::Var(_)), "{}", src);
+        assert!(
+            matches!(rhs.kind, ExprKind::Literal(LiteralKind::Natural(1))),
+            "{}",
+            src
+        );
+    }
+
+    // and otherwise negates, folding into a literal that follows it
+    let module = parse_str("let e = -a").unwrap();
+    assert!(matches!(
+        &definition(&module).kind,
+        ExprKind::UnaryExpr { op, .. } if op.kind == UnaryOpKind::Negate
+    ));
+    for src in ["let e = -1", "let e = - 1"] {
+        let module = parse_str(src).unwrap();
+        assert!(
+            matches!(
+                definition(&module).kind,
+                ExprKind::Literal(LiteralKind::Integer(-1))
+            ),
+            "{}",
+            src
+        );
+    }
+    let module = parse_str("let e = a - -1").unwrap();
+    let (_, _, rhs) = binary(definition(&module));
+    assert!(matches!(
+        rhs.kind,
+        ExprKind::Literal(LiteralKind::Integer(-1))
+    ));
+}
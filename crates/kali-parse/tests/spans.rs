@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use kali_ast::{DestructorKind, ItemKind};
+use kali_parse::{misaligned_tokens, parse_file, parse_str};
+use kali_span::{FileId, SourceMap};
+
+#[test]
+fn test_file_spans() {
+    let mut sources = SourceMap::new();
+    sources.add("a.kali", "let a = 1");
+    let file_id = sources.add("b.kali", "let b = c");
+    let module = parse_file(file_id, sources.source(file_id)).unwrap();
+
+    let ItemKind::Definition(definition) = &module.items[0].kind else {
+        panic!("expected a definition");
+    };
+    let DestructorKind::Var(name) = &definition.name.kind else {
+        panic!("expected a variable");
+    };
+    assert_eq!(name.span.file_id, file_id);
+    assert_eq!(sources.slice(name.span), "b");
+    assert_eq!(sources.slice(definition.expr.span), "c");
+    assert_eq!(module.items[0].span.file_id, file_id);
+}
+
+#[test]
+fn test_error_spans() {
+    let mut sources = SourceMap::new();
+    sources.add("a.kali", "");
+    let file_id = sources.add("b.kali", "let = 1");
+    let errors = parse_file(file_id, sources.source(file_id)).unwrap_err();
+    assert!(errors.iter().all(|error| error.span().file_id == file_id));
+
+    // sources parsed without a source map belong to the default file
+    let module = parse_str("let a = 1").unwrap();
+    assert_eq!(module.items[0].span.file_id, FileId::default());
+}
+
+#[test]
+fn test_token_spans() {
+    // argument lists are made up by the lexer, and `f()` splits a single `()` token in two
+    let src = "let a = f() + g(x, \"é\") + h (1, 2)\n#inline\nlet b = 0x1F";
+    assert!(misaligned_tokens(FileId::default(), src).is_empty());
+    assert!(misaligned_tokens(FileId::default(), "let = £ 99999999999999999999999").is_empty());
+}
+
+#[test]
+fn test_token_spans_over_corpus() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let mut checked = 0;
+    for dir in ["examples", "crates/kali-desugar/tests/golden"] {
+        for entry in std::fs::read_dir(root.join(dir)).unwrap() {
+            let path = entry.unwrap().path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "kali")
+            {
+                let src = std::fs::read_to_string(&path).unwrap();
+                let misaligned = misaligned_tokens(FileId::default(), &src);
+                assert!(
+                    misaligned.is_empty(),
+                    "{}: {:?}",
+                    path.display(),
+                    misaligned
+                );
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 0);
+}
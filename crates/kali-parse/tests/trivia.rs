@@ -0,0 +1,56 @@
+use kali_parse::{parse_str, trivia::Trivia};
+use kali_span::FileId;
+
+/// Returns the text of each comment in `src`, and whether it trails code.
+fn comments(src: &str) -> Vec<(&str, bool)> {
+    Trivia::collect(FileId::default(), src)
+        .comments()
+        .iter()
+        .map(|comment| (comment.text, comment.trailing))
+        .collect()
+}
+
+#[test]
+fn test_comments() {
+    let src = "# leading\nlet x = 1 # trailing\n\n  # indented\nlet y = \"# not a comment\"\n";
+    assert_eq!(
+        comments(src),
+        [
+            (" leading", false),
+            (" trailing", true),
+            (" indented", false)
+        ]
+    );
+    // maps, sets and attributes are not comments
+    assert!(comments("#inline()\nlet m = #{ 1: 2 };\nlet s = #[1]\n").is_empty());
+}
+
+#[test]
+fn test_blank_lines() {
+    let src = "let x = 1;\n\n\n# two\n# none\nlet y = 2;\n\nlet z = 3";
+    let trivia = Trivia::collect(FileId::default(), src);
+    let blank: Vec<_> = trivia.comments().iter().map(|c| c.blank_lines).collect();
+    assert_eq!(blank, [2, 0]);
+    let z = src.find("let z").unwrap();
+    assert_eq!(trivia.blank_lines(z), 1);
+}
+
+#[test]
+fn test_attached_to_items() {
+    let src = "let x = 1; # x\n# about y\n# more\nlet y = 2 # y\n";
+    let module = parse_str(src).unwrap();
+    let trivia = Trivia::collect(FileId::default(), src);
+    let (x, y) = (&module.items[0], &module.items[1]);
+
+    let text = |comments: &[kali_parse::trivia::Comment]| -> Vec<String> {
+        comments.iter().map(|c| c.text.to_string()).collect()
+    };
+    assert!(trivia.leading(0, x.span).is_empty());
+    assert_eq!(trivia.trailing(x.span).unwrap().text, " x");
+    // the comment trailing `x` does not lead `y`
+    assert_eq!(
+        text(trivia.leading(x.span.end, y.span)),
+        [" about y", " more"]
+    );
+    assert_eq!(trivia.trailing(y.span).unwrap().text, " y");
+}
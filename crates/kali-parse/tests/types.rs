@@ -0,0 +1,255 @@
+use kali_ast::{BinaryOpKind, ExprKind, ItemKind, Module, PrimitiveTypeKind, Type, TypeKind};
+use kali_parse::parse_str;
+
+fn alias(module: &Module) -> &Type {
+    match &module.items[0].kind {
+        ItemKind::TypeAlias(alias) => &alias.ty,
+        kind => panic!("expected a type alias, found {:?}", kind),
+    }
+}
+
+fn generic<'a>(module: &'a Module, ty: &'a Type) -> (&'a str, &'a [Type]) {
+    match &ty.kind {
+        TypeKind::Generic { name, arguments } => (module.cache.resolve(&name.key), arguments),
+        kind => panic!("expected a generic type, found {:?}", kind),
+    }
+}
+
+#[test]
+fn test_comparison_chain() {
+    let module = parse_str("let d = a < b > c").unwrap();
+    let ItemKind::Definition(definition) = &module.items[0].kind else {
+        panic!("expected a definition");
+    };
+    let ExprKind::BinaryExpr { op, lhs, .. } = &definition.expr.kind else {
+        panic!("expected a binary expression");
+    };
+    assert_eq!(op.kind, BinaryOpKind::GreaterThan);
+    assert!(matches!(
+        &lhs.kind,
+        ExprKind::BinaryExpr { op, .. } if op.kind == BinaryOpKind::LessThan
+    ));
+}
+
+#[test]
+fn test_generic_type() {
+    let module = parse_str("type a = List<int>").unwrap();
+    let (name, arguments) = generic(&module, alias(&module));
+    assert_eq!(name, "List");
+    assert!(matches!(
+        arguments,
+        [Type {
+            kind: TypeKind::Primitive(PrimitiveTypeKind::Integer),
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_nested_generic_type() {
+    let module = parse_str("type a = Map<string, List<List<int>>>").unwrap();
+    let (name, arguments) = generic(&module, alias(&module));
+    assert_eq!(name, "Map");
+    assert_eq!(arguments.len(), 2);
+
+    let (name, arguments) = generic(&module, &arguments[1]);
+    assert_eq!(name, "List");
+    let (name, _) = generic(&module, &arguments[0]);
+    assert_eq!(name, "List");
+}
+
+#[test]
+fn test_generic_lambda_parameter() {
+    let module = parse_str("let f = x List<int> -> x").unwrap();
+    let ItemKind::Definition(definition) = &module.items[0].kind else {
+        panic!("expected a definition");
+    };
+    let ExprKind::Lambda { params, .. } = &definition.expr.kind else {
+        panic!("expected a lambda");
+    };
+    let ty = params[0].ty.as_ref().expect("expected a type annotation");
+    assert_eq!(generic(&module, ty).0, "List");
+}
+
+fn function(ty: &Type) -> (&[Type], &Type) {
+    match &ty.kind {
+        TypeKind::Fn(params, ret) => (params, ret),
+        kind => panic!("expected a function type, found {:?}", kind),
+    }
+}
+
+#[test]
+fn test_function_type() {
+    let module = parse_str("type a = int -> bool").unwrap();
+    let (params, ret) = function(alias(&module));
+    assert!(matches!(
+        params,
+        [Type {
+            kind: TypeKind::Primitive(PrimitiveTypeKind::Integer),
+            ..
+        }]
+    ));
+    assert!(matches!(
+        ret.kind,
+        TypeKind::Primitive(PrimitiveTypeKind::Bool)
+    ));
+}
+
+#[test]
+fn test_function_type_parameters() {
+    let module = parse_str("type a = (int, bool) -> int").unwrap();
+    assert_eq!(function(alias(&module)).0.len(), 2);
+
+    // a function of a single tuple
+    let module = parse_str("type a = ((int, bool)) -> int").unwrap();
+    let (params, _) = function(alias(&module));
+    assert!(matches!(
+        params,
+        [Type {
+            kind: TypeKind::Tuple(_),
+            ..
+        }]
+    ));
+
+    let module = parse_str("type a = () -> int").unwrap();
+    assert!(function(alias(&module)).0.is_empty());
+
+    // a tuple not followed by an arrow is not a parameter list
+    let module = parse_str("type a = (int, bool)").unwrap();
+    assert!(matches!(alias(&module).kind, TypeKind::Tuple(_)));
+}
+
+#[test]
+fn test_function_type_associativity() {
+    // arrows are right-associative
+    let module = parse_str("type a = int -> int -> int").unwrap();
+    let (params, ret) = function(alias(&module));
+    assert!(matches!(
+        params,
+        [Type {
+            kind: TypeKind::Primitive(_),
+            ..
+        }]
+    ));
+    assert!(matches!(ret.kind, TypeKind::Fn(..)));
+
+    let module = parse_str("type a = (int -> int) -> int").unwrap();
+    let (params, ret) = function(alias(&module));
+    assert!(matches!(
+        params,
+        [Type {
+            kind: TypeKind::Fn(..),
+            ..
+        }]
+    ));
+    assert!(matches!(ret.kind, TypeKind::Primitive(_)));
+}
+
+#[test]
+fn test_function_type_precedence() {
+    // arrows bind looser than union and intersection, on both sides
+    let module = parse_str("type a = int | bool -> int & bool").unwrap();
+    let (params, ret) = function(alias(&module));
+    assert!(matches!(
+        params,
+        [Type {
+            kind: TypeKind::Union { .. },
+            ..
+        }]
+    ));
+    assert!(matches!(ret.kind, TypeKind::Intersection { .. }));
+
+    let module = parse_str("type a = (int) | bool -> int").unwrap();
+    let (params, _) = function(alias(&module));
+    assert!(matches!(
+        params,
+        [Type {
+            kind: TypeKind::Union { .. },
+            ..
+        }]
+    ));
+
+    let module = parse_str("type a = int | (bool -> int)").unwrap();
+    let TypeKind::Union { rhs, .. } = &alias(&module).kind else {
+        panic!("expected a union type");
+    };
+    function(rhs);
+}
+
+#[test]
+fn test_function_type_nested() {
+    let module =
+        parse_str("type a = { f: (int) -> int, g: [() -> bool] } -> List<(int) -> int>").unwrap();
+    let (params, ret) = function(alias(&module));
+    let TypeKind::Record(fields) = &params[0].kind else {
+        panic!("expected a record type");
+    };
+    for ty in fields.values() {
+        match &ty.kind {
+            TypeKind::List(ty) => function(ty),
+            _ => function(ty),
+        };
+    }
+    let (_, arguments) = generic(&module, ret);
+    function(&arguments[0]);
+}
+
+#[test]
+fn test_lambda_parameter_function_type() {
+    // the arrow of an unparenthesised annotation ends the parameters
+    let module = parse_str("let f = x int -> x").unwrap();
+    let ItemKind::Definition(definition) = &module.items[0].kind else {
+        panic!("expected a definition");
+    };
+    let ExprKind::Lambda { params, .. } = &definition.expr.kind else {
+        panic!("expected a lambda");
+    };
+    assert!(matches!(
+        params[0].ty,
+        Some(Type {
+            kind: TypeKind::Primitive(_),
+            ..
+        })
+    ));
+
+    let module = parse_str("let f = g ((int) -> int) -> g(1)").unwrap();
+    let ItemKind::Definition(definition) = &module.items[0].kind else {
+        panic!("expected a definition");
+    };
+    let ExprKind::Lambda { params, .. } = &definition.expr.kind else {
+        panic!("expected a lambda");
+    };
+    function(params[0].ty.as_ref().expect("expected a type annotation"));
+}
+
+#[test]
+fn test_record_field_order() {
+    // fields keep the order they are written in
+    let module = parse_str("type a = { y: int, x: bool }").unwrap();
+    let TypeKind::Record(fields) = &alias(&module).kind else {
+        panic!("expected a record type");
+    };
+    let names: Vec<_> = fields
+        .keys()
+        .map(|name| module.cache.resolve(&name.key))
+        .collect();
+    assert_eq!(names, ["y", "x"]);
+}
+
+#[test]
+fn test_repeated_record_field() {
+    for src in [
+        "type a = { x: int, y: int, x: bool }",
+        "let f = { x: a, x: b } -> a",
+        "let e = match p { { x: 1, x: 2 } -> p }",
+    ] {
+        let errors = parse_str(src).unwrap_err();
+        assert_eq!(errors.len(), 1, "{}: {:?}", src, errors);
+        assert_eq!(
+            errors[0].to_string(),
+            "the field `x` is given more than once",
+            "{}",
+            src
+        );
+    }
+}
@@ -3,9 +3,51 @@
 use std::io::Write;
 
 use kali_ast::{
-    BinaryExpr, Call, Conditional, Expr, Identifier, Lambda, Literal, LiteralKind, Match, Module,
-    UnaryExpr,
+    Attribute, BinaryOpKind, Clause, Definition, Destructor, DestructorKind, ExportItem,
+    ExportKind, Expr, ExprKind, Function, Ident, ImportTree, ImportTreeKind, Item, ItemKind,
+    LambdaParam, LiteralKind, MatchArm, Module, Pattern, PatternKind, PrimitiveTypeKind, Side,
+    Type, TypeAlias, TypeKind, UnaryOpKind, Visibility,
 };
+use kali_parse::trivia::Trivia;
+use kali_span::Span;
+
+/// The number of columns a tab counts for when measuring lines.
+const TAB_WIDTH: usize = 4;
+
+/// How the printer lays out code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatConfig {
+    /// The width lines are kept within where possible, by laying out argument lists, lists and
+    /// records that would not fit with each element on a line of its own.
+    pub max_width: usize,
+    /// How each level of indentation is written.
+    pub indent: Indent,
+    /// Whether the last of the elements laid out on lines of their own ends with a comma, where
+    /// the grammar allows one.
+    pub trailing_commas: bool,
+    /// Whether a module ends with a newline.
+    pub final_newline: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        FormatConfig {
+            max_width: 100,
+            indent: Indent::Tabs,
+            trailing_commas: true,
+            final_newline: true,
+        }
+    }
+}
+
+/// How a level of indentation is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// A tab, counted as four columns.
+    Tabs,
+    /// A number of spaces.
+    Spaces(usize),
+}
 
 /// `Context` holds a mutable reference to a writer implementing `std::io::Write`.
 /// It is used to manage output buffers for printing operations.
@@ -14,6 +56,20 @@ pub struct Context<'a> {
     depth: usize,
     /// The buffer to which output will be written.
     buf: &'a mut (dyn Write + 'a),
+    /// The interner used to resolve identifiers and string literals.
+    rodeo: &'a lasso::Rodeo,
+    /// The source the nodes were parsed from, used to reproduce nodes that failed to parse.
+    source: Option<&'a str>,
+    /// The comments of the source the nodes were parsed from, re-emitted alongside them.
+    trivia: Option<&'a Trivia<'a>>,
+    /// The index of the first comment that has not been printed yet.
+    comment: usize,
+    /// How code is laid out.
+    config: FormatConfig,
+    /// The column the next character is written at.
+    column: usize,
+    /// Whether the output is only measured, so every sequence is printed on a single line.
+    measuring: bool,
 }
 
 impl<'a> Context<'a> {
@@ -22,8 +78,52 @@ impl<'a> Context<'a> {
     /// # Arguments
     ///
     /// * `buf` - A mutable reference to a type implementing `std::io::Write`.
-    pub fn new(buf: &mut dyn Write) -> Context<'_> {
-        Context { depth: 0, buf }
+    /// * `rodeo` - The interner that owns the keys of the nodes being printed.
+    pub fn new(buf: &'a mut dyn Write, rodeo: &'a lasso::Rodeo) -> Context<'a> {
+        Context {
+            depth: 0,
+            buf,
+            rodeo,
+            source: None,
+            trivia: None,
+            comment: 0,
+            config: FormatConfig::default(),
+            column: 0,
+            measuring: false,
+        }
+    }
+
+    /// Attaches the source the nodes were parsed from, so that nodes which failed to parse are
+    /// printed verbatim.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source code that was parsed.
+    pub fn with_source(mut self, source: &'a str) -> Context<'a> {
+        self.source = Some(source);
+        self
+    }
+
+    /// Attaches the comments of the source the nodes were parsed from, so that they are printed
+    /// where they were written: on their own lines before items and match arms, or after them on
+    /// the same line. Comments elsewhere are moved after the item they are in.
+    ///
+    /// # Arguments
+    ///
+    /// * `trivia` - The trivia of the source code that was parsed.
+    pub fn with_trivia(mut self, trivia: &'a Trivia<'a>) -> Context<'a> {
+        self.trivia = Some(trivia);
+        self
+    }
+
+    /// Sets how code is laid out.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The layout options.
+    pub fn with_config(mut self, config: FormatConfig) -> Context<'a> {
+        self.config = config;
+        self
     }
 
     /// Increases the current indentation depth by one.
@@ -42,17 +142,180 @@ impl<'a> Context<'a> {
     ///
     /// Returns an `Error` if writing to the buffer fails.
     pub fn newline(&mut self) -> Result<()> {
-        write!(self, "\n")?;
+        writeln!(self)?;
         for _ in 0..self.depth {
-            write!(self, "\t")?;
+            match self.config.indent {
+                Indent::Tabs => write!(self, "\t")?,
+                Indent::Spaces(width) => write!(self, "{:width$}", "")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Measures how wide something would be printed on a single line from the current column.
+    ///
+    /// # Returns
+    /// The number of columns it takes up, or `None` if it spans several lines regardless.
+    fn measure(&mut self, print: impl FnOnce(&mut Context) -> Result<()>) -> Result<Option<usize>> {
+        let mut buf = Vec::new();
+        let mut ctx = Context::new(&mut buf, self.rodeo).with_config(self.config.clone());
+        ctx.source = self.source;
+        ctx.depth = self.depth;
+        ctx.column = self.column;
+        ctx.measuring = true;
+        print(&mut ctx)?;
+        let column = ctx.column;
+        // the column starts over on each new line
+        Ok((!buf.contains(&b'\n')).then(|| column - self.column))
+    }
+
+    /// Resolves an interned string.
+    pub fn resolve(&self, key: &lasso::Spur) -> &'a str {
+        self.rodeo.resolve(key)
+    }
+
+    /// Prints the comments not printed yet that start before an offset, each on its own line
+    /// followed by a newline, keeping a blank line where there was one. The offset is
+    /// `usize::MAX` for the comments of a file without code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if writing to the buffer fails.
+    fn leading_comments(&mut self, offset: usize) -> Result<()> {
+        let Some(trivia) = self.trivia else {
+            return Ok(());
+        };
+        let first = self.comment;
+        while let Some(comment) = trivia.comments().get(self.comment)
+            && comment.span.start < offset
+        {
+            if comment.blank_lines > 0 && self.comment > first {
+                self.newline()?;
+            }
+            write!(self, "#{}", comment.text)?;
+            self.newline()?;
+            self.comment += 1;
+        }
+        if self.comment > first && offset != usize::MAX && trivia.blank_lines(offset) > 0 {
+            self.newline()?;
         }
         Ok(())
     }
+
+    /// Prints the comments on their own lines after an item, each on a new line.
+    ///
+    /// The comments before the next item follow the previous one if no blank line comes before
+    /// them but one comes after them, and lead the next item otherwise. At the end of the file,
+    /// without a next item, every comment left follows the last item.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if writing to the buffer fails.
+    fn following_comments(&mut self, next: Option<usize>) -> Result<()> {
+        let Some(trivia) = self.trivia else {
+            return Ok(());
+        };
+        let comments = trivia.comments();
+        let end = match next {
+            Some(next) => {
+                let before = comments
+                    .partition_point(|comment| comment.span.start < next)
+                    .max(self.comment);
+                let run = comments[self.comment..before]
+                    .iter()
+                    .take_while(|comment| comment.blank_lines == 0)
+                    .count();
+                let blank_after = match self.comment + run {
+                    after if after < before => comments[after].blank_lines > 0,
+                    _ => trivia.blank_lines(next) > 0,
+                };
+                if blank_after {
+                    self.comment + run
+                } else {
+                    self.comment
+                }
+            }
+            None => comments.len(),
+        };
+        while self.comment < end {
+            let comment = &comments[self.comment];
+            if comment.blank_lines > 0 {
+                self.newline()?;
+            }
+            self.newline()?;
+            write!(self, "#{}", comment.text)?;
+            self.comment += 1;
+        }
+        Ok(())
+    }
+
+    /// Prints the comment following a node on the line it ends on, if any, and then the comments
+    /// within the node that were not printed yet, each on a line of its own.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if writing to the buffer fails.
+    fn trailing_comments(&mut self, span: Span) -> Result<()> {
+        let Some(trivia) = self.trivia else {
+            return Ok(());
+        };
+        let comments = trivia.comments();
+        let trailing = comments.partition_point(|comment| comment.span.start < span.end);
+        let trailing = trivia
+            .trailing(span)
+            .filter(|_| trailing >= self.comment)
+            .map(|comment| (trailing, comment));
+        if let Some((_, comment)) = trailing {
+            write!(self, " #{}", comment.text)?;
+        }
+        while let Some(comment) = comments.get(self.comment)
+            && comment.span.start < span.end
+        {
+            self.newline()?;
+            write!(self, "#{}", comment.text)?;
+            self.comment += 1;
+        }
+        if let Some((index, _)) = trailing {
+            self.comment = index + 1;
+        }
+        Ok(())
+    }
+
+    /// Writes the source text of a node that failed to parse.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if no source is attached, or if writing to the buffer fails.
+    fn write_source(&mut self, start: usize, end: usize) -> Result<()> {
+        let text = self
+            .source
+            .and_then(|source| source.get(start..end))
+            .ok_or(Error::MissingSource(start, end))?;
+        write!(self, "{}", text)?;
+        Ok(())
+    }
 }
 
 impl Write for Context<'_> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.buf.write(buf)
+        let written = self.buf.write(buf)?;
+        let buf = &buf[..written];
+        let line = match buf.iter().rposition(|&byte| byte == b'\n') {
+            Some(newline) => {
+                self.column = 0;
+                &buf[newline + 1..]
+            }
+            None => buf,
+        };
+        // count characters rather than bytes, skipping the continuation bytes of UTF-8
+        for &byte in line {
+            match byte {
+                b'\t' => self.column += TAB_WIDTH,
+                _ if byte & 0xC0 != 0x80 => self.column += 1,
+                _ => {}
+            }
+        }
+        Ok(written)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
@@ -68,6 +331,8 @@ type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     #[error("{0}")]
     Io(#[from] std::io::Error),
+    #[error("cannot print the malformed node at {0}..{1} without its source")]
+    MissingSource(usize, usize),
 }
 
 /// Trait for types that can be printed to a writer via a `Context`.
@@ -84,112 +349,903 @@ pub trait Print {
     fn print(&self, ctx: &mut Context) -> Result<()>;
 }
 
-impl<Meta> Print for Module<Meta> {
+/// Prints a delimited sequence of elements on a single line if it fits within the maximum width,
+/// or otherwise with each element on a line of its own, e.g. the arguments of a call.
+///
+/// # Arguments
+///
+/// * `open` - The opening delimiter.
+/// * `elements` - The elements of the sequence.
+/// * `close` - The closing delimiter.
+/// * `trailing_comma` - Whether the grammar allows a comma after the last element.
+/// * `print` - Prints an element.
+fn print_delimited<T>(
+    ctx: &mut Context,
+    open: &str,
+    elements: &[T],
+    close: &str,
+    trailing_comma: bool,
+    print: impl Fn(&mut Context, &T) -> Result<()>,
+) -> Result<()> {
+    let inline = |ctx: &mut Context| -> Result<()> {
+        write!(ctx, "{}", open)?;
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                write!(ctx, ", ")?;
+            }
+            print(ctx, element)?;
+        }
+        write!(ctx, "{}", close)?;
+        Ok(())
+    };
+    if ctx.measuring || elements.is_empty() {
+        return inline(ctx);
+    }
+    let fits = ctx
+        .measure(inline)?
+        .is_some_and(|width| ctx.column + width <= ctx.config.max_width);
+    if fits {
+        return inline(ctx);
+    }
+    write!(ctx, "{}", open)?;
+    ctx.increase();
+    for (i, element) in elements.iter().enumerate() {
+        ctx.newline()?;
+        print(ctx, element)?;
+        if i + 1 < elements.len() || (trailing_comma && ctx.config.trailing_commas) {
+            write!(ctx, ",")?;
+        }
+    }
+    ctx.decrease();
+    ctx.newline()?;
+    write!(ctx, "{}", close)?;
+    Ok(())
+}
+
+/// Prints a sequence of values separated by `sep`.
+fn print_separated<'a, T: Print + 'a>(
+    ctx: &mut Context,
+    items: impl IntoIterator<Item = &'a T>,
+    sep: &str,
+) -> Result<()> {
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            write!(ctx, "{}", sep)?;
+        }
+        item.print(ctx)?;
+    }
+    Ok(())
+}
+
+impl Print for Module {
     fn print(&self, ctx: &mut Context) -> Result<()> {
-        todo!()
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                write!(ctx, ";")?;
+                ctx.trailing_comments(self.items[i - 1].span)?;
+                ctx.following_comments(Some(item.span.start))?;
+                ctx.newline()?;
+                ctx.newline()?;
+            }
+            ctx.leading_comments(item.span.start)?;
+            item.print(ctx)?;
+        }
+        let Some(last) = self.items.last() else {
+            return ctx.leading_comments(usize::MAX);
+        };
+        ctx.trailing_comments(last.span)?;
+        ctx.following_comments(None)?;
+        if ctx.config.final_newline {
+            ctx.newline()?;
+        }
+        Ok(())
     }
 }
 
-impl<Meta> Print for Expr<Meta> {
+impl Print for Item {
     fn print(&self, ctx: &mut Context) -> Result<()> {
-        match &self {
-            Expr::Literal(literal) => literal.print(ctx),
-            Expr::Ident(identifier) => identifier.print(ctx),
-            Expr::BinaryExpr(binary_expr) => binary_expr.print(ctx),
-            Expr::UnaryExpr(unary_expr) => unary_expr.print(ctx),
-            Expr::Conditional(conditional) => conditional.print(ctx),
-            Expr::Lambda(lambda) => lambda.print(ctx),
-            Expr::Match(match_expr) => match_expr.print(ctx),
-            Expr::Call(call) => call.print(ctx),
+        for attribute in &self.attributes {
+            attribute.print(ctx)?;
+            ctx.newline()?;
+        }
+        if self.visibility == Visibility::Exported {
+            write!(ctx, "export ")?;
+        }
+        match &self.kind {
+            ItemKind::Import(import_tree) => {
+                write!(ctx, "import ")?;
+                import_tree.print(ctx)
+            }
+            ItemKind::Export(ExportKind::List(items)) => {
+                write!(ctx, "export {{")?;
+                print_separated(ctx, items, ", ")?;
+                write!(ctx, "}}")?;
+                Ok(())
+            }
+            ItemKind::Export(ExportKind::Glob { path }) => {
+                write!(ctx, "export * from \"{}\"", ctx.resolve(path))?;
+                Ok(())
+            }
+            ItemKind::TypeAlias(type_alias) => type_alias.print(ctx),
+            ItemKind::Definition(definition) => definition.print(ctx),
+            ItemKind::Function(function) => function.print(ctx),
+            ItemKind::Error => ctx.write_source(self.span.start, self.span.end),
         }
     }
 }
 
-impl<Meta> Print for Literal<Meta> {
+impl Print for Attribute {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        write!(ctx, "#")?;
+        self.name.print(ctx)?;
+        write!(ctx, "(")?;
+        print_separated(ctx, &self.arguments, ", ")?;
+        write!(ctx, ")")?;
+        Ok(())
+    }
+}
+
+impl Print for Ident {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        write!(ctx, "{}", ctx.resolve(&self.key))?;
+        Ok(())
+    }
+}
+
+impl Print for ImportTree {
     fn print(&self, ctx: &mut Context) -> Result<()> {
         match &self.kind {
-            LiteralKind::Natural(x) => write!(ctx, "{}", x)?,
-            LiteralKind::Integer(x) => write!(ctx, "{}", x)?,
-            LiteralKind::Float(x) => write!(ctx, "{}", x)?,
-            LiteralKind::Bool(x) => write!(ctx, "{}", x)?,
-            LiteralKind::String(x) => write!(ctx, r#""{}""#, x)?,
-            LiteralKind::Unit => write!(ctx, "()")?,
-            LiteralKind::Array(exprs) => {
+            ImportTreeKind::Item { name, alias } => {
+                name.print(ctx)?;
+                if let Some(alias) = alias {
+                    write!(ctx, " as ")?;
+                    alias.print(ctx)?;
+                }
+            }
+            ImportTreeKind::Segment { name, child } => {
+                name.print(ctx)?;
+                write!(ctx, "::")?;
+                child.print(ctx)?;
+            }
+            ImportTreeKind::Glob => write!(ctx, "*")?,
+            ImportTreeKind::List(trees) => {
+                write!(ctx, "{{")?;
+                print_separated(ctx, trees, ", ")?;
+                write!(ctx, "}}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Print for ExportItem {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        self.name.print(ctx)?;
+        if let Some(alias) = &self.alias {
+            write!(ctx, " as ")?;
+            alias.print(ctx)?;
+        }
+        Ok(())
+    }
+}
+
+impl Print for TypeAlias {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        write!(ctx, "type ")?;
+        self.name.print(ctx)?;
+        write!(ctx, " = ")?;
+        self.ty.print(ctx)
+    }
+}
+
+impl Print for Definition {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        write!(ctx, "let ")?;
+        self.name.print(ctx)?;
+        write!(ctx, " = ")?;
+        self.expr.print(ctx)
+    }
+}
+
+impl Print for Function {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        for (i, clause) in self.clauses.iter().enumerate() {
+            if i > 0 {
+                write!(ctx, ";")?;
+                ctx.newline()?;
+            }
+            write!(ctx, "fn ")?;
+            self.name.print(ctx)?;
+            write!(ctx, " ")?;
+            clause.print(ctx)?;
+        }
+        Ok(())
+    }
+}
+
+impl Print for Clause {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        for pattern in &self.patterns {
+            // each pattern is an operand, so `x :: xs` is wrapped to keep it one argument
+            print_pattern_operand(ctx, pattern, PatternPrecedence::Atom)?;
+            write!(ctx, " ")?;
+        }
+        write!(ctx, "= ")?;
+        self.body.print(ctx)
+    }
+}
+
+/// Precedence levels of type operators, from loosest to tightest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum TypePrecedence {
+    Function,
+    Intersection,
+    Union,
+    Atom,
+}
+
+fn type_precedence(ty: &Type) -> TypePrecedence {
+    match &ty.kind {
+        TypeKind::Fn(..) => TypePrecedence::Function,
+        TypeKind::Intersection { .. } => TypePrecedence::Intersection,
+        TypeKind::Union { .. } => TypePrecedence::Union,
+        _ => TypePrecedence::Atom,
+    }
+}
+
+/// Prints a type, wrapping it in parentheses if it binds looser than `min`.
+fn print_type_operand(ctx: &mut Context, ty: &Type, min: TypePrecedence) -> Result<()> {
+    if type_precedence(ty) < min {
+        write!(ctx, "(")?;
+        ty.print(ctx)?;
+        write!(ctx, ")")?;
+        Ok(())
+    } else {
+        ty.print(ctx)
+    }
+}
+
+impl Print for Type {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        match &self.kind {
+            TypeKind::Primitive(primitive) => {
+                let name = match primitive {
+                    PrimitiveTypeKind::Integer => "int",
+                    PrimitiveTypeKind::Natural => "nat",
+                    PrimitiveTypeKind::Float => "float",
+                    PrimitiveTypeKind::Bool => "bool",
+                    PrimitiveTypeKind::String => "string",
+                    PrimitiveTypeKind::Bytes => "bytes",
+                    PrimitiveTypeKind::Unit => "()",
+                };
+                write!(ctx, "{}", name)?;
+            }
+            TypeKind::Named(ident) => ident.print(ctx)?,
+            TypeKind::Generic { name, arguments } => {
+                name.print(ctx)?;
+                write!(ctx, "<")?;
+                print_separated(ctx, arguments, ", ")?;
+                write!(ctx, ">")?;
+            }
+            TypeKind::Tuple(types) => {
+                write!(ctx, "(")?;
+                print_separated(ctx, types, ", ")?;
+                write!(ctx, ")")?;
+            }
+            TypeKind::List(ty) => {
                 write!(ctx, "[")?;
-                for (i, expr) in exprs.iter().enumerate() {
+                ty.print(ctx)?;
+                write!(ctx, "]")?;
+            }
+            TypeKind::Record(fields) => {
+                let fields: Vec<_> = fields.iter().collect();
+                print_delimited(ctx, "{", &fields, "}", true, |ctx, (name, ty)| {
+                    name.print(ctx)?;
+                    write!(ctx, ": ")?;
+                    ty.print(ctx)
+                })?;
+            }
+            TypeKind::Fn(params, ret) => {
+                write!(ctx, "(")?;
+                print_separated(ctx, params, ", ")?;
+                write!(ctx, ") -> ")?;
+                ret.print(ctx)?;
+            }
+            // both operators are right-associative
+            TypeKind::Intersection { lhs, rhs } => {
+                print_type_operand(ctx, lhs, TypePrecedence::Union)?;
+                write!(ctx, " & ")?;
+                print_type_operand(ctx, rhs, TypePrecedence::Intersection)?;
+            }
+            TypeKind::Union { lhs, rhs } => {
+                print_type_operand(ctx, lhs, TypePrecedence::Atom)?;
+                write!(ctx, " | ")?;
+                print_type_operand(ctx, rhs, TypePrecedence::Union)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Precedence levels of expressions, from loosest to tightest. These mirror the binding powers
+/// of the Pratt parser in `kali-parse`, except that calls are always printed with an argument
+/// list, which binds as tightly as an atom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Lambda,
+    Pipe,
+    Cons,
+    Range,
+    LogicalOr,
+    LogicalAnd,
+    Equality,
+    Comparison,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseAnd,
+    Additive,
+    Multiplicative,
+    Exponent,
+    Unary,
+    Atom,
+}
+
+impl Precedence {
+    /// Returns the next-tightest precedence level.
+    fn next(self) -> Precedence {
+        match self {
+            Precedence::Lambda => Precedence::Pipe,
+            Precedence::Pipe => Precedence::Cons,
+            Precedence::Cons => Precedence::Range,
+            Precedence::Range => Precedence::LogicalOr,
+            Precedence::LogicalOr => Precedence::LogicalAnd,
+            Precedence::LogicalAnd => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::BitwiseOr,
+            Precedence::BitwiseOr => Precedence::BitwiseXor,
+            Precedence::BitwiseXor => Precedence::BitwiseAnd,
+            Precedence::BitwiseAnd => Precedence::Additive,
+            Precedence::Additive => Precedence::Multiplicative,
+            Precedence::Multiplicative => Precedence::Exponent,
+            Precedence::Exponent => Precedence::Unary,
+            Precedence::Unary | Precedence::Atom => Precedence::Atom,
+        }
+    }
+}
+
+/// Returns the precedence and right-associativity of a binary operator. Operators that the
+/// grammar does not yet accept borrow the level of their closest counterpart.
+fn binary_precedence(op: BinaryOpKind) -> (Precedence, bool) {
+    match op {
+        BinaryOpKind::Exponentiate => (Precedence::Exponent, true),
+        BinaryOpKind::Multiply | BinaryOpKind::Divide | BinaryOpKind::Modulo => {
+            (Precedence::Multiplicative, false)
+        }
+        BinaryOpKind::Add
+        | BinaryOpKind::Subtract
+        | BinaryOpKind::BitwiseShiftLeft
+        | BinaryOpKind::BitwiseShiftRight => (Precedence::Additive, false),
+        BinaryOpKind::LessThan
+        | BinaryOpKind::LessThanOrEqual
+        | BinaryOpKind::GreaterThan
+        | BinaryOpKind::GreaterThanOrEqual => (Precedence::Comparison, false),
+        BinaryOpKind::Equal | BinaryOpKind::NotEqual => (Precedence::Equality, false),
+        BinaryOpKind::BitwiseAnd => (Precedence::BitwiseAnd, false),
+        BinaryOpKind::BitwiseXor => (Precedence::BitwiseXor, false),
+        BinaryOpKind::BitwiseOr => (Precedence::BitwiseOr, false),
+        BinaryOpKind::LogicalAnd => (Precedence::LogicalAnd, false),
+        BinaryOpKind::LogicalOr => (Precedence::LogicalOr, false),
+        BinaryOpKind::Cons | BinaryOpKind::Concat => (Precedence::Cons, true),
+        BinaryOpKind::Pipe => (Precedence::Pipe, false),
+    }
+}
+
+fn precedence(expr: &Expr) -> Precedence {
+    match &expr.kind {
+        // loop bodies extend as far to the right as they can, like lambdas
+        ExprKind::Lambda { .. } | ExprKind::While { .. } | ExprKind::For { .. } => {
+            Precedence::Lambda
+        }
+        ExprKind::BinaryExpr { op, .. } => binary_precedence(op.kind).0,
+        ExprKind::Range { .. } => Precedence::Range,
+        // negative literals are folded from a `-` and a natural literal
+        ExprKind::UnaryExpr { .. } | ExprKind::Literal(LiteralKind::Integer(_)) => {
+            Precedence::Unary
+        }
+        _ => Precedence::Atom,
+    }
+}
+
+/// Returns whether the printed form of an expression ends in something that would absorb a
+/// following comma, i.e. a lambda.
+fn is_open_ended(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Lambda { .. } | ExprKind::While { .. } | ExprKind::For { .. } => true,
+        ExprKind::UnaryExpr { expr, .. } => is_open_ended(expr),
+        ExprKind::BinaryExpr { op, rhs, .. } => {
+            let (prec, right) = binary_precedence(op.kind);
+            let min = if right { prec } else { prec.next() };
+            precedence(rhs) >= min && is_open_ended(rhs)
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether the printed form of an expression ends in the operand of an operator, where
+/// a lambda may start, and which is not already wrapped by [`print_element`].
+fn ends_in_operand(expr: &Expr) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::BinaryExpr { .. } | ExprKind::Range { .. } | ExprKind::UnaryExpr { .. }
+    ) && !is_open_ended(expr)
+}
+
+/// Returns whether the printed form of an expression starts with a number literal, which a `-`
+/// before it would be folded into.
+fn starts_with_literal(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Literal(LiteralKind::Natural(_) | LiteralKind::Float(_)) => true,
+        ExprKind::Call { function: expr, .. }
+        | ExprKind::Try(expr)
+        | ExprKind::Projection { tuple: expr, .. }
+        | ExprKind::Index { list: expr, .. } => starts_with_literal(expr),
+        _ => false,
+    }
+}
+
+/// Returns whether a pattern can also be read as a destructor, such as the parameter of a lambda.
+fn is_destructor(pattern: &Pattern) -> bool {
+    match &pattern.kind {
+        PatternKind::Var(_) => true,
+        PatternKind::Tuple(patterns) => patterns.iter().all(is_destructor),
+        PatternKind::Record(fields) => fields.values().all(is_destructor),
+        _ => false,
+    }
+}
+
+/// Prints the body of a block, which is written between braces, separating the expressions of
+/// a sequence with `;`.
+fn print_block(ctx: &mut Context, expr: &Expr) -> Result<()> {
+    write!(ctx, "{{ ")?;
+    match &expr.kind {
+        ExprKind::Sequence(exprs) => print_separated(ctx, exprs, "; ")?,
+        _ => expr.print(ctx)?,
+    }
+    write!(ctx, " }}")?;
+    Ok(())
+}
+
+/// Prints the body of a loop, which leaves out the `do` of a block.
+fn print_body(ctx: &mut Context, body: &Expr) -> Result<()> {
+    match &body.kind {
+        ExprKind::Sequence(_) => print_block(ctx, body),
+        _ => body.print(ctx),
+    }
+}
+
+/// Prints an expression, wrapping it in parentheses if it binds looser than `min`.
+fn print_operand(ctx: &mut Context, expr: &Expr, min: Precedence) -> Result<()> {
+    if precedence(expr) < min {
+        write!(ctx, "(")?;
+        expr.print(ctx)?;
+        write!(ctx, ")")?;
+        Ok(())
+    } else {
+        expr.print(ctx)
+    }
+}
+
+/// Prints an expression that is followed by a comma, such as a tuple element or match arm.
+fn print_element(ctx: &mut Context, expr: &Expr) -> Result<()> {
+    if is_open_ended(expr) {
+        write!(ctx, "(")?;
+        expr.print(ctx)?;
+        write!(ctx, ")")?;
+        Ok(())
+    } else {
+        expr.print(ctx)
+    }
+}
+
+impl Print for Expr {
+    fn print(&self, ctx: &mut Context) -> Result<()> {
+        match &self.kind {
+            ExprKind::Var(ident) => ident.print(ctx)?,
+            ExprKind::Literal(literal) => literal.print(ctx)?,
+            ExprKind::BinaryExpr { op, lhs, rhs } => {
+                let (prec, right) = binary_precedence(op.kind);
+                let (lhs_min, rhs_min) = if right {
+                    (prec.next(), prec)
+                } else {
+                    (prec, prec.next())
+                };
+                print_operand(ctx, lhs, lhs_min)?;
+                write!(ctx, " {} ", op.kind)?;
+                print_operand(ctx, rhs, rhs_min)?;
+            }
+            // ranges do not chain, so neither end may be a range itself
+            ExprKind::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                print_operand(ctx, start, Precedence::Range.next())?;
+                write!(ctx, "{}", if *inclusive { "..=" } else { ".." })?;
+                print_operand(ctx, end, Precedence::Range.next())?;
+            }
+            ExprKind::UnaryExpr { op, expr } => {
+                write!(ctx, "{}", op.kind)?;
+                // `-1` is parsed as a single negative literal, so a negated expression starting
+                // with a literal is wrapped
+                if op.kind == UnaryOpKind::Negate && starts_with_literal(expr) {
+                    write!(ctx, "(")?;
                     expr.print(ctx)?;
-                    if i != exprs.len() - 1 {
-                        write!(ctx, ", ")?;
+                    write!(ctx, ")")?;
+                } else {
+                    print_operand(ctx, expr, Precedence::Unary)?;
+                }
+            }
+            ExprKind::Section { op, operand, side } => {
+                let (prec, right) = binary_precedence(op.kind);
+                write!(ctx, "(")?;
+                match side {
+                    Side::Left => {
+                        print_operand(ctx, operand, if right { prec.next() } else { prec })?;
+                        write!(ctx, " {})", op.kind)?;
+                    }
+                    Side::Right => {
+                        write!(ctx, "{} ", op.kind)?;
+                        print_operand(ctx, operand, if right { prec } else { prec.next() })?;
+                        write!(ctx, ")")?;
                     }
                 }
-                write!(ctx, "]")?;
             }
-            LiteralKind::Tuple(exprs) => {
+            ExprKind::Tuple(exprs) => {
                 write!(ctx, "(")?;
                 for (i, expr) in exprs.iter().enumerate() {
-                    expr.print(ctx)?;
-                    if i != exprs.len() - 1 {
+                    if i > 0 {
                         write!(ctx, ", ")?;
                     }
+                    print_element(ctx, expr)?;
                 }
                 write!(ctx, ")")?;
             }
-            LiteralKind::Struct(btree_map) => {
-                todo!()
+            ExprKind::List(exprs) => {
+                print_delimited(ctx, "[", exprs, "]", false, print_element)?;
             }
-        };
+            ExprKind::Set(exprs) => {
+                write!(ctx, "#[")?;
+                for (i, expr) in exprs.iter().enumerate() {
+                    if i > 0 {
+                        write!(ctx, ", ")?;
+                    }
+                    print_element(ctx, expr)?;
+                }
+                write!(ctx, "]")?;
+            }
+            ExprKind::Map(entries) => {
+                write!(ctx, "#{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(ctx, ", ")?;
+                    }
+                    print_element(ctx, key)?;
+                    write!(ctx, ": ")?;
+                    print_element(ctx, value)?;
+                }
+                write!(ctx, "}}")?;
+            }
+            ExprKind::Record { fields } => {
+                let fields: Vec<_> = fields.iter().collect();
+                print_delimited(ctx, "{", &fields, "}", true, |ctx, (name, expr)| {
+                    name.print(ctx)?;
+                    write!(ctx, ": ")?;
+                    print_element(ctx, expr)
+                })?;
+            }
+            ExprKind::Conditional {
+                condition,
+                body,
+                otherwise,
+            } => {
+                write!(ctx, "if ")?;
+                condition.print(ctx)?;
+                write!(ctx, " ")?;
+                print_block(ctx, body)?;
+                if let Some(otherwise) = otherwise {
+                    write!(ctx, " else ")?;
+                    print_block(ctx, otherwise)?;
+                }
+            }
+            ExprKind::Match { value, arms } => {
+                write!(ctx, "match ")?;
+                value.print(ctx)?;
+                write!(ctx, " {{")?;
+                ctx.increase();
+                for (i, arm) in arms.iter().enumerate() {
+                    ctx.newline()?;
+                    ctx.leading_comments(arm.span.start)?;
+                    // the operand after an operator may start a lambda, which would take the
+                    // pattern of the next arm as its parameters
+                    let absorbs_next = arms
+                        .get(i + 1)
+                        .is_some_and(|next| is_destructor(&next.pattern));
+                    if absorbs_next && ends_in_operand(&arm.expr) {
+                        arm.pattern.print(ctx)?;
+                        write!(ctx, " -> (")?;
+                        arm.expr.print(ctx)?;
+                        write!(ctx, ")")?;
+                    } else {
+                        arm.print(ctx)?;
+                    }
+                    if i + 1 < arms.len() || ctx.config.trailing_commas {
+                        write!(ctx, ",")?;
+                    }
+                    ctx.trailing_comments(arm.span)?;
+                }
+                ctx.decrease();
+                if !arms.is_empty() {
+                    ctx.newline()?;
+                }
+                write!(ctx, "}}")?;
+            }
+            ExprKind::While { condition, body } => {
+                write!(ctx, "while ")?;
+                condition.print(ctx)?;
+                write!(ctx, " do ")?;
+                print_body(ctx, body)?;
+            }
+            ExprKind::For {
+                binding,
+                iterable,
+                body,
+            } => {
+                write!(ctx, "for ")?;
+                binding.print(ctx)?;
+                write!(ctx, " in ")?;
+                iterable.print(ctx)?;
+                write!(ctx, " do ")?;
+                print_body(ctx, body)?;
+            }
+            ExprKind::Sequence(_) => {
+                write!(ctx, "do ")?;
+                print_block(ctx, self)?;
+            }
+            ExprKind::Lambda { params, body, .. } => {
+                print_separated(ctx, params, ", ")?;
+                write!(ctx, " -> ")?;
+                // lambda bodies bind tighter than calls and any infix operator
+                if matches!(
+                    body.kind,
+                    ExprKind::Lambda { .. } | ExprKind::UnaryExpr { .. }
+                ) {
+                    body.print(ctx)?;
+                } else {
+                    print_operand(ctx, body, Precedence::Atom)?;
+                }
+            }
+            ExprKind::Call {
+                function,
+                arguments,
+            } => {
+                // the argument list must directly follow the function, as `f (x, y)` would
+                // apply `f` to a tuple
+                print_operand(ctx, function, Precedence::Atom)?;
+                match arguments.as_slice() {
+                    [argument] => {
+                        write!(ctx, "(")?;
+                        argument.print(ctx)?;
+                        write!(ctx, ")")?;
+                    }
+                    arguments => print_delimited(ctx, "(", arguments, ")", true, print_element)?,
+                }
+            }
+            ExprKind::Try(expr) => {
+                print_operand(ctx, expr, Precedence::Atom)?;
+                write!(ctx, "?")?;
+            }
+            ExprKind::Projection { tuple, index } => {
+                print_operand(ctx, tuple, Precedence::Atom)?;
+                write!(ctx, ".{}", index)?;
+            }
+            ExprKind::Index { list, index } => {
+                print_operand(ctx, list, Precedence::Atom)?;
+                write!(ctx, "[")?;
+                index.print(ctx)?;
+                write!(ctx, "]")?;
+            }
+            ExprKind::Spread(expr) => {
+                write!(ctx, "..")?;
+                print_element(ctx, expr)?;
+            }
+            ExprKind::Labelled { label, value } => {
+                label.print(ctx)?;
+                write!(ctx, ": ")?;
+                print_element(ctx, value)?;
+            }
+            ExprKind::Hole(name) => {
+                write!(ctx, "?")?;
+                name.print(ctx)?;
+            }
+            ExprKind::Error => ctx.write_source(self.span.start, self.span.end)?,
+        }
         Ok(())
     }
 }
 
-impl<Meta> Print for Identifier<Meta> {
+impl Print for LiteralKind {
     fn print(&self, ctx: &mut Context) -> Result<()> {
-        write!(ctx, "{}", self.value)?;
+        match self {
+            LiteralKind::Natural(x) => write!(ctx, "{}", x)?,
+            LiteralKind::Integer(x) => write!(ctx, "{}", x)?,
+            LiteralKind::Float(x) => write!(ctx, "{}", x)?,
+            LiteralKind::Bool(x) => write!(ctx, "{}", x)?,
+            LiteralKind::String(x) => write!(ctx, r#""{}""#, ctx.resolve(x))?,
+            LiteralKind::Bytes(bytes) => {
+                write!(ctx, "b\"")?;
+                for byte in bytes {
+                    match byte {
+                        b'"' => write!(ctx, "\\\"")?,
+                        b'\\' => write!(ctx, "\\\\")?,
+                        b'\n' => write!(ctx, "\\n")?,
+                        b'\r' => write!(ctx, "\\r")?,
+                        b'\t' => write!(ctx, "\\t")?,
+                        b' '..=b'~' => write!(ctx, "{}", *byte as char)?,
+                        // other bytes are escaped, as they need not be valid UTF-8
+                        _ => write!(ctx, "\\x{:02x}", byte)?,
+                    }
+                }
+                write!(ctx, "\"")?;
+            }
+            LiteralKind::Unit => write!(ctx, "()")?,
+        };
         Ok(())
     }
 }
 
-impl<Meta> Print for BinaryExpr<Meta> {
+impl Print for MatchArm {
     fn print(&self, ctx: &mut Context) -> Result<()> {
-        self.lhs.print(ctx)?;
-        write!(ctx, " {} ", self.operator)?;
-        self.rhs.print(ctx)
+        self.pattern.print(ctx)?;
+        write!(ctx, " -> ")?;
+        print_element(ctx, &self.expr)
     }
 }
 
-impl<Meta> Print for UnaryExpr<Meta> {
+impl Print for LambdaParam {
     fn print(&self, ctx: &mut Context) -> Result<()> {
-        write!(ctx, "{}", self.operator)?;
-        self.inner.print(ctx)
+        self.parameter.print(ctx)?;
+        if let Some(ty) = &self.ty {
+            write!(ctx, " ")?;
+            // an arrow in the type would end the parameters
+            print_type_operand(ctx, ty, TypePrecedence::Intersection)?;
+        }
+        if let Some(default) = &self.default {
+            write!(ctx, " = ")?;
+            print_operand(ctx, default, Precedence::Atom)?;
+        }
+        Ok(())
     }
 }
 
-impl<Meta> Print for Conditional<Meta> {
-    fn print(&self, ctx: &mut Context) -> Result<()> {
-        write!(ctx, "if ")?;
-        self.condition.print(ctx)?;
-        write!(ctx, " ")?;
-        self.body.print(ctx)?;
-        write!(ctx, " else ")?;
-        self.otherwise.print(ctx)?;
-        Ok(())
+/// Precedence levels of pattern operators, from loosest to tightest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PatternPrecedence {
+    Cons,
+    Or,
+    Prefix,
+    Atom,
+}
+
+fn pattern_precedence(pattern: &Pattern) -> PatternPrecedence {
+    match &pattern.kind {
+        PatternKind::Cons { .. } => PatternPrecedence::Cons,
+        PatternKind::Or { .. } => PatternPrecedence::Or,
+        PatternKind::Prefix { .. } => PatternPrecedence::Prefix,
+        _ => PatternPrecedence::Atom,
     }
 }
 
-impl<Meta> Print for Lambda<Meta> {
-    fn print(&self, ctx: &mut Context) -> Result<()> {
-        todo!()
+/// Prints a pattern, wrapping it in parentheses if it binds looser than `min`.
+fn print_pattern_operand(
+    ctx: &mut Context,
+    pattern: &Pattern,
+    min: PatternPrecedence,
+) -> Result<()> {
+    if pattern_precedence(pattern) < min {
+        write!(ctx, "(")?;
+        pattern.print(ctx)?;
+        write!(ctx, ")")?;
+        Ok(())
+    } else {
+        pattern.print(ctx)
     }
 }
 
-impl<Meta> Print for Match<Meta> {
+impl Print for Pattern {
     fn print(&self, ctx: &mut Context) -> Result<()> {
+        match &self.kind {
+            PatternKind::Literal(literal) => literal.print(ctx)?,
+            PatternKind::Var(ident) => ident.print(ctx)?,
+            PatternKind::Tuple(patterns) => {
+                write!(ctx, "(")?;
+                print_separated(ctx, patterns, ", ")?;
+                write!(ctx, ")")?;
+            }
+            PatternKind::Wildcard => write!(ctx, "_")?,
+            PatternKind::Record(fields) => {
+                write!(ctx, "{{")?;
+                for (i, (name, pattern)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(ctx, ", ")?;
+                    }
+                    name.print(ctx)?;
+                    write!(ctx, ": ")?;
+                    pattern.print(ctx)?;
+                }
+                write!(ctx, "}}")?;
+            }
+            // `::` is right-associative
+            PatternKind::Cons { lhs, rhs } => {
+                print_pattern_operand(ctx, lhs, PatternPrecedence::Or)?;
+                write!(ctx, " :: ")?;
+                print_pattern_operand(ctx, rhs, PatternPrecedence::Cons)?;
+            }
+            PatternKind::EmptyList => write!(ctx, "[]")?,
+            PatternKind::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                start.print(ctx)?;
+                write!(ctx, "{}", if *inclusive { "..=" } else { ".." })?;
+                end.print(ctx)?;
+            }
+            // `|` is left-associative
+            PatternKind::Or { lhs, rhs } => {
+                print_pattern_operand(ctx, lhs, PatternPrecedence::Or)?;
+                write!(ctx, " | ")?;
+                print_pattern_operand(ctx, rhs, PatternPrecedence::Prefix)?;
+            }
+            // `++` is right-associative, and its left operand is always a string
+            PatternKind::Prefix { prefix, rest } => {
+                LiteralKind::String(*prefix).print(ctx)?;
+                write!(ctx, " ++ ")?;
+                print_pattern_operand(ctx, rest, PatternPrecedence::Prefix)?;
+            }
+            PatternKind::Rest => write!(ctx, "...")?,
+        }
         Ok(())
     }
 }
 
-impl<Meta> Print for Call<Meta> {
+impl Print for Destructor {
     fn print(&self, ctx: &mut Context) -> Result<()> {
+        match &self.kind {
+            DestructorKind::Var(ident) => ident.print(ctx)?,
+            DestructorKind::Tuple(destructors) => {
+                write!(ctx, "(")?;
+                print_separated(ctx, destructors, ", ")?;
+                write!(ctx, ")")?;
+            }
+            DestructorKind::Record(fields) => {
+                write!(ctx, "{{")?;
+                for (i, (name, destructor)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(ctx, ", ")?;
+                    }
+                    name.print(ctx)?;
+                    write!(ctx, ": ")?;
+                    destructor.print(ctx)?;
+                }
+                write!(ctx, "}}")?;
+            }
+            DestructorKind::Cons { lhs, rhs } => {
+                lhs.print(ctx)?;
+                write!(ctx, " :: ")?;
+                rhs.print(ctx)?;
+            }
+            DestructorKind::Rest => write!(ctx, "...")?,
+        }
         Ok(())
     }
 }
@@ -197,7 +1253,8 @@ impl<Meta> Print for Call<Meta> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use kali_ast::{Identifier, LiteralKind};
+    use kali_ast::{BinaryOp, LiteralKind};
+    use kali_span::Span;
 
     struct TestWriter {
         pub output: Vec<u8>,
@@ -213,134 +1270,143 @@ mod tests {
         }
     }
 
-    fn print_to_string<T: Print>(value: &T) -> String {
+    fn print_to_string<T: Print>(value: &T, rodeo: &lasso::Rodeo) -> String {
         let mut writer = TestWriter { output: Vec::new() };
-        let mut ctx = Context::new(&mut writer);
+        let mut ctx = Context::new(&mut writer, rodeo);
         value.print(&mut ctx).unwrap();
         String::from_utf8(writer.output).unwrap()
     }
 
+    fn span() -> Span {
+        Span::default()
+    }
+
+    fn literal(kind: LiteralKind) -> Expr {
+        Expr {
+            span: span(),
+            kind: ExprKind::Literal(kind),
+        }
+    }
+
+    fn var(rodeo: &mut lasso::Rodeo, name: &str) -> Expr {
+        Expr {
+            span: span(),
+            kind: ExprKind::Var(Ident {
+                key: rodeo.get_or_intern(name),
+                span: span(),
+            }),
+        }
+    }
+
+    fn binary(kind: BinaryOpKind, lhs: Expr, rhs: Expr) -> Expr {
+        Expr {
+            span: span(),
+            kind: ExprKind::BinaryExpr {
+                op: BinaryOp { kind, span: span() },
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+        }
+    }
+
     #[test]
     fn test_print_literal_natural() {
-        let lit = Literal {
-            kind: LiteralKind::Natural(42),
-            meta: (),
-        };
-        assert_eq!(print_to_string(&lit), "42");
+        let lit = literal(LiteralKind::Natural(42));
+        assert_eq!(print_to_string(&lit, &lasso::Rodeo::new()), "42");
     }
 
     #[test]
     fn test_print_literal_integer() {
-        let lit = Literal {
-            kind: LiteralKind::Integer(-7),
-            meta: (),
-        };
-        assert_eq!(print_to_string(&lit), "-7");
+        let lit = literal(LiteralKind::Integer(-7));
+        assert_eq!(print_to_string(&lit, &lasso::Rodeo::new()), "-7");
     }
 
     #[test]
     fn test_print_literal_float() {
-        let lit = Literal {
-            kind: LiteralKind::Float(3.14),
-            meta: (),
-        };
-        assert_eq!(print_to_string(&lit), "3.14");
+        let lit = literal(LiteralKind::Float(3.5));
+        assert_eq!(print_to_string(&lit, &lasso::Rodeo::new()), "3.5");
     }
 
     #[test]
     fn test_print_literal_bool() {
-        let lit_true = Literal {
-            kind: LiteralKind::Bool(true),
-            meta: (),
-        };
-        let lit_false = Literal {
-            kind: LiteralKind::Bool(false),
-            meta: (),
-        };
-        assert_eq!(print_to_string(&lit_true), "true");
-        assert_eq!(print_to_string(&lit_false), "false");
+        let lit_true = literal(LiteralKind::Bool(true));
+        let lit_false = literal(LiteralKind::Bool(false));
+        assert_eq!(print_to_string(&lit_true, &lasso::Rodeo::new()), "true");
+        assert_eq!(print_to_string(&lit_false, &lasso::Rodeo::new()), "false");
     }
 
     #[test]
     fn test_print_literal_string() {
-        let lit = Literal {
-            kind: LiteralKind::String("hello".to_string()),
-            meta: (),
-        };
-        assert_eq!(print_to_string(&lit), r#""hello""#);
+        let mut rodeo = lasso::Rodeo::new();
+        let lit = literal(LiteralKind::String(rodeo.get_or_intern("hello")));
+        assert_eq!(print_to_string(&lit, &rodeo), r#""hello""#);
     }
 
     #[test]
     fn test_print_literal_unit() {
-        let lit = Literal {
-            kind: LiteralKind::Unit,
-            meta: (),
-        };
-        assert_eq!(print_to_string(&lit), "()");
+        let lit = literal(LiteralKind::Unit);
+        assert_eq!(print_to_string(&lit, &lasso::Rodeo::new()), "()");
     }
 
     #[test]
     fn test_print_identifier() {
-        let ident = Identifier {
-            value: "foo".to_string(),
-            meta: (),
-        };
-        assert_eq!(print_to_string(&ident), "foo");
+        let mut rodeo = lasso::Rodeo::new();
+        let ident = var(&mut rodeo, "foo");
+        assert_eq!(print_to_string(&ident, &rodeo), "foo");
     }
 
     #[test]
     fn test_print_conditional() {
-        let cond = Conditional {
-            condition: Box::new(Expr::Literal(Literal {
-                kind: LiteralKind::Bool(true),
-                meta: (),
-            })),
-            body: Box::new(Expr::Literal(Literal {
-                kind: LiteralKind::Natural(1),
-                meta: (),
-            })),
-            otherwise: Box::new(Expr::Literal(Literal {
-                kind: LiteralKind::Natural(0),
-                meta: (),
-            })),
-            meta: (),
+        let cond = Expr {
+            span: span(),
+            kind: ExprKind::Conditional {
+                condition: Box::new(literal(LiteralKind::Bool(true))),
+                body: Box::new(literal(LiteralKind::Natural(1))),
+                otherwise: Some(Box::new(literal(LiteralKind::Natural(0)))),
+            },
         };
-        assert_eq!(print_to_string(&cond), "if true 1 else 0");
+        assert_eq!(
+            print_to_string(&cond, &lasso::Rodeo::new()),
+            "if true { 1 } else { 0 }"
+        );
     }
 
     #[test]
     fn test_print_literal_array() {
-        let arr = Literal {
-            kind: LiteralKind::Array(vec![
-                Expr::Literal(Literal {
-                    kind: LiteralKind::Natural(1),
-                    meta: (),
-                }),
-                Expr::Literal(Literal {
-                    kind: LiteralKind::Natural(2),
-                    meta: (),
-                }),
+        let arr = Expr {
+            span: span(),
+            kind: ExprKind::List(vec![
+                literal(LiteralKind::Natural(1)),
+                literal(LiteralKind::Natural(2)),
             ]),
-            meta: (),
         };
-        assert_eq!(print_to_string(&arr), "[1, 2]");
+        assert_eq!(print_to_string(&arr, &lasso::Rodeo::new()), "[1, 2]");
     }
 
     #[test]
     fn test_print_literal_tuple() {
-        let tup = Literal {
-            kind: LiteralKind::Tuple(vec![
-                Expr::Literal(Literal {
-                    kind: LiteralKind::Natural(1),
-                    meta: (),
-                }),
-                Expr::Literal(Literal {
-                    kind: LiteralKind::Natural(2),
-                    meta: (),
-                }),
+        let tup = Expr {
+            span: span(),
+            kind: ExprKind::Tuple(vec![
+                literal(LiteralKind::Natural(1)),
+                literal(LiteralKind::Natural(2)),
             ]),
-            meta: (),
         };
-        assert_eq!(print_to_string(&tup), "(1, 2)");
+        assert_eq!(print_to_string(&tup, &lasso::Rodeo::new()), "(1, 2)");
+    }
+
+    #[test]
+    fn test_print_binary_precedence() {
+        let mut rodeo = lasso::Rodeo::new();
+        let sum = binary(
+            BinaryOpKind::Add,
+            var(&mut rodeo, "a"),
+            var(&mut rodeo, "b"),
+        );
+        let product = binary(BinaryOpKind::Multiply, sum.clone(), var(&mut rodeo, "c"));
+        assert_eq!(print_to_string(&product, &rodeo), "(a + b) * c");
+
+        let nested = binary(BinaryOpKind::Subtract, var(&mut rodeo, "a"), sum);
+        assert_eq!(print_to_string(&nested, &rodeo), "a - (a + b)");
     }
 }
@@ -0,0 +1,272 @@
+//! Regression tests for the ambiguities of call syntax.
+//!
+//! Functions are called either by juxtaposition, `f x`, or with an argument list that directly
+//! follows the callee without whitespace, `f(x)`.
+//!
+//! | Source         | Parses as             | Why                                              |
+//! |----------------|-----------------------|--------------------------------------------------|
+//! | `f x y`        | `(f x) y`             | calls are curried and left-associative           |
+//! | `f x, y`       | `f(x, y)`             | commas separate the arguments of a single call   |
+//! | `f(x, y)`      | `f(x, y)`             | an argument list directly follows the callee     |
+//! | `f (x, y)`     | `f((x, y))`           | a parenthesised tuple is one argument            |
+//! | `f (x)`        | `f(x)`                | parentheses only group                           |
+//! | `f()`          | `f()`                 | an empty argument list                           |
+//! | `f ()`         | `f(())`               | `()` after whitespace is the unit argument       |
+//! | `f(x)(y)`      | `(f(x))(y)`           | argument lists chain, left to right              |
+//! | `f x(y)`       | `f(x(y))`             | an argument list binds to what it follows        |
+//! | `f x.0`        | `f(x.0)`              | and so does a projection                         |
+//! | `f xs[0]`      | `f(xs[0])`            | and so does an index                             |
+//! | `f [0]`        | `f([0])`              | `[` after whitespace starts a list argument      |
+//! | `f -x`         | `f - x`               | `-` between operands is always binary            |
+//! | `f -1`         | `f - 1`               | even when `-` is followed by a number            |
+//! | `f (-1)`       | `f(-1)`               | `-` before a number is folded into the literal   |
+//! | `f (+ x)`      | `f(y -> y + x)`       | an operator missing an operand is a section      |
+//! | `f (- x)`      | `f(-x)`               | `(- x)` is a negation, not a section             |
+//! | `-f x`         | `-(f x)`              | calls bind tighter than prefix operators         |
+//! | `f x + y`      | `(f x) + y`           | calls bind tighter than infix operators          |
+//! | `f x, g y`     | `(f(x, g)) y`         | arguments are atoms, so `g y` must be wrapped    |
+//! | `f !x`         | error                 | prefix operators cannot start an argument        |
+//! | `f x -> x`     | `(f: x) -> x`         | a parameter followed by a type is a lambda       |
+//! | `f(a, ..xs)`   | `f(a, xs)`            | arguments from the first spread form one list    |
+//! | `f ..xs`       | `f..xs`               | `..` between operands is always a range          |
+//! | `f(x: 1)`      | `f(x: 1)`             | arguments may be passed by parameter name        |
+//! | `f x: 1`       | error                 | only argument lists accept labels                |
+
+use kali_ast::{Expr, ExprKind, ItemKind, LiteralKind, Module};
+use kali_parse::parse_str;
+use kali_testing::{assert_parse_ok, print};
+
+/// Renders an expression as an s-expression, making its structure explicit.
+fn sexpr(module: &Module, expr: &Expr) -> String {
+    let list = |head: String, exprs: &[&Expr]| {
+        let mut out = format!("({}", head);
+        for expr in exprs {
+            out.push(' ');
+            out.push_str(&sexpr(module, expr));
+        }
+        out.push(')');
+        out
+    };
+    match &expr.kind {
+        ExprKind::Var(ident) => module.cache.resolve(&ident.key).to_string(),
+        ExprKind::Literal(LiteralKind::Natural(x)) => x.to_string(),
+        ExprKind::Literal(LiteralKind::Integer(x)) => x.to_string(),
+        ExprKind::Literal(LiteralKind::Unit) => "()".to_string(),
+        ExprKind::BinaryExpr { op, lhs, rhs } => list(op.kind.to_string(), &[lhs, rhs]),
+        ExprKind::UnaryExpr { op, expr } => list(op.kind.to_string(), &[expr]),
+        ExprKind::Section { op, operand, side } => {
+            list(format!("section/{:?} {}", side, op.kind), &[operand])
+        }
+        ExprKind::Tuple(exprs) => list("tuple".to_string(), &exprs.iter().collect::<Vec<_>>()),
+        ExprKind::List(exprs) => list("list".to_string(), &exprs.iter().collect::<Vec<_>>()),
+        ExprKind::Spread(expr) => list("..".to_string(), &[expr]),
+        ExprKind::Projection { tuple, index } => list(format!(".{}", index), &[tuple]),
+        ExprKind::Index { list: xs, index } => list("index".to_string(), &[xs, index]),
+        ExprKind::Range {
+            start,
+            end,
+            inclusive,
+        } => list(
+            if *inclusive { "range=" } else { "range" }.to_string(),
+            &[start, end],
+        ),
+        ExprKind::Labelled { label, value } => {
+            list(format!("{}:", module.cache.resolve(&label.key)), &[value])
+        }
+        ExprKind::Call {
+            function,
+            arguments,
+        } => {
+            let mut exprs = vec![&**function];
+            exprs.extend(arguments);
+            list("call".to_string(), &exprs)
+        }
+        ExprKind::Lambda { params, body, .. } => list(format!("lambda/{}", params.len()), &[body]),
+        kind => panic!("unexpected expression {:?}", kind),
+    }
+}
+
+/// Asserts that `src` parses to the given s-expression, and survives a printer round trip.
+fn assert_parses_as(src: &str, expected: &str) {
+    let src = format!("let e = {}", src);
+    let module = assert_parse_ok!(&src);
+    let ItemKind::Definition(definition) = &module.items[0].kind else {
+        panic!("expected a definition");
+    };
+    assert_eq!(sexpr(&module, &definition.expr), expected, "{}", src);
+
+    let formatted = print(&module);
+    assert_parse_ok!(&formatted, &module);
+}
+
+#[test]
+fn test_curried_call() {
+    assert_parses_as("f x y", "(call (call f x) y)");
+    assert_parses_as("(f x) y", "(call (call f x) y)");
+    assert_parses_as("f (x) (y)", "(call (call f x) y)");
+}
+
+#[test]
+fn test_comma_separated_arguments() {
+    assert_parses_as("f x, y", "(call f x y)");
+    assert_parses_as("f x, y, z", "(call f x y z)");
+}
+
+#[test]
+fn test_tuple_argument() {
+    assert_parses_as("f (x, y)", "(call f (tuple x y))");
+    assert_parses_as("f ((x, y))", "(call f (tuple x y))");
+    assert_parses_as("f (x, y) z", "(call (call f (tuple x y)) z)");
+    assert_parses_as("(f, g) x", "(call (tuple f g) x)");
+}
+
+#[test]
+fn test_grouped_argument() {
+    assert_parses_as("f (x)", "(call f x)");
+    assert_parses_as("f (x + y)", "(call f (+ x y))");
+    assert_parses_as("f (g x)", "(call f (call g x))");
+}
+
+#[test]
+fn test_argument_list() {
+    assert_parses_as("f(x, y)", "(call f x y)");
+    assert_parses_as("f(x, y,)", "(call f x y)");
+    assert_parses_as("f(x)", "(call f x)");
+    assert_parses_as("f((x, y))", "(call f (tuple x y))");
+    assert_parses_as("f(x + y, g z)", "(call f (+ x y) (call g z))");
+    assert_parses_as("(f)(x)", "(call f x)");
+    assert_parses_as("f(x -> x)", "(call f (lambda/1 x))");
+    assert_parses_as("f(a, (x -> x))", "(call f a (lambda/1 x))");
+}
+
+#[test]
+fn test_chained_argument_lists() {
+    assert_parses_as("f(x)(y)", "(call (call f x) y)");
+    assert_parses_as("f(x)(y)(z)", "(call (call (call f x) y) z)");
+    assert_parses_as("f(x) y", "(call (call f x) y)");
+    assert_parses_as("f x(y)", "(call f (call x y))");
+    assert_parses_as("f x(y), z", "(call f (call x y) z)");
+    assert_parses_as("f (x)(y)", "(call f (call x y))");
+    assert_parses_as("-f(x)", "(- (call f x))");
+    assert_parses_as("f(x) + g(y)", "(+ (call f x) (call g y))");
+}
+
+#[test]
+fn test_unit_argument() {
+    assert_parses_as("f()", "(call f)");
+    assert_parses_as("f ()", "(call f ())");
+    assert_parses_as("f(())", "(call f ())");
+    assert_parses_as("f (())", "(call f ())");
+    assert_parses_as("f x ()", "(call (call f x) ())");
+    assert_parses_as("f()()", "(call (call f))");
+}
+
+#[test]
+fn test_minus() {
+    assert_parses_as("f -x", "(- f x)");
+    assert_parses_as("f - x", "(- f x)");
+    assert_parses_as("f -1", "(- f 1)");
+    assert_parses_as("f - 1", "(- f 1)");
+    assert_parses_as("f-1", "(- f 1)");
+    assert_parses_as("f (-x)", "(call f (- x))");
+    assert_parses_as("f (-1)", "(call f -1)");
+    assert_parses_as("f - -1", "(- f -1)");
+    assert_parses_as("-x", "(- x)");
+    assert_parses_as("-1", "-1");
+    assert_parses_as("- 1", "-1");
+    assert_parses_as("-(1)", "(- 1)");
+    assert_parses_as("-1(x)", "(- (call 1 x))");
+    assert_parses_as("-1 ** x", "(** -1 x)");
+}
+
+#[test]
+fn test_section_argument() {
+    assert_parses_as("f (+ x)", "(call f (section/Right + x))");
+    assert_parses_as("f (x +)", "(call f (section/Left + x))");
+    assert_parses_as("f (- x)", "(call f (- x))");
+    assert_parses_as("f (x -)", "(call f (section/Left - x))");
+}
+
+#[test]
+fn test_prefix_operator_on_call() {
+    assert_parses_as("-f x", "(- (call f x))");
+    assert_parses_as("!f x, y", "(! (call f x y))");
+    assert_parses_as("(-f) x", "(call (- f) x)");
+    assert_parses_as("(-f x, y)", "(- (call f x y))");
+    assert_parses_as("(-(f x), y)", "(tuple (- (call f x)) y)");
+    assert_parses_as("-f x ** 2", "(** (- (call f x)) 2)");
+}
+
+#[test]
+fn test_infix_operator_after_call() {
+    assert_parses_as("f x + y", "(+ (call f x) y)");
+    assert_parses_as("f x + g y", "(+ (call f x) (call g y))");
+    assert_parses_as("f x :: y", "(:: (call f x) y)");
+}
+
+#[test]
+fn test_call_argument() {
+    assert_parses_as("f x, g y", "(call (call f x g) y)");
+    assert_parses_as("f x, (g y)", "(call f x (call g y))");
+}
+
+#[test]
+fn test_prefix_operator_argument() {
+    assert!(parse_str("let e = f !x").is_err());
+    assert!(parse_str("let e = f ~x").is_err());
+    assert_parses_as("f (!x)", "(call f (! x))");
+}
+
+#[test]
+fn test_lambda() {
+    assert_parses_as("f x -> x", "(lambda/1 x)");
+    assert_parses_as("f (x -> x)", "(call f (lambda/1 x))");
+    assert_parses_as("(x -> x) y", "(call (lambda/1 x) y)");
+    assert_parses_as("x -> -f x", "(lambda/1 (- (call f x)))");
+    assert_parses_as("x -> f(x)", "(lambda/1 (call f x))");
+}
+
+#[test]
+fn test_projection() {
+    assert_parses_as("f x.0", "(call f (.0 x))");
+    assert_parses_as("f(x).1", "(.1 (call f x))");
+    assert_parses_as("t.0.1", "(.1 (.0 t))");
+    assert_parses_as("-t.0", "(- (.0 t))");
+    assert_parses_as("(f x).0", "(.0 (call f x))");
+    assert!(parse_str("let e = t.a").is_err());
+}
+
+#[test]
+fn test_index() {
+    assert_parses_as("xs[0]", "(index xs 0)");
+    assert_parses_as("f xs[i + 1]", "(call f (index xs (+ i 1)))");
+    assert_parses_as("f [0]", "(call f (list 0))");
+    assert_parses_as("xs[0][1]", "(index (index xs 0) 1)");
+    assert_parses_as("f(x)[0].1", "(.1 (index (call f x) 0))");
+    assert_parses_as("[a][0]", "(index (list a) 0)");
+    assert_parses_as("-xs[0]", "(- (index xs 0))");
+}
+
+#[test]
+fn test_spread_argument() {
+    assert_parses_as("f(..xs)", "(call f (.. xs))");
+    assert_parses_as("f(a, ..xs, b)", "(call f a (.. xs) b)");
+    assert_parses_as("f(..xs, ..ys)", "(call f (.. xs) (.. ys))");
+    assert_parses_as("f(..g x)", "(call f (.. (call g x)))");
+    assert_parses_as("f(..x + y)", "(call f (.. (+ x y)))");
+    assert_parses_as("[a, ..xs, b]", "(list a (.. xs) b)");
+    assert_parses_as("[..[a], ..xs(y)]", "(list (.. (list a)) (.. (call xs y)))");
+    assert_parses_as("f ..xs", "(range f xs)");
+    assert!(parse_str("let e = (..xs)").is_err());
+}
+
+#[test]
+fn test_labelled_argument() {
+    assert_parses_as("f(x: 1)", "(call f (x: 1))");
+    assert_parses_as("f(a, y: b, x: g z)", "(call f a (y: b) (x: (call g z)))");
+    assert_parses_as("f(x: y -> y)", "(call f (x: (lambda/1 y)))");
+    assert_parses_as("f(x: (y -> y), z: 1)", "(call f (x: (lambda/1 y)) (z: 1))");
+    assert!(parse_str("let e = f x: 1").is_err());
+    assert!(parse_str("let e = (x: 1)").is_err());
+    assert!(parse_str("let e = f(x: ..xs)").is_err());
+}
@@ -0,0 +1,44 @@
+use kali_parse::{parse_str, trivia::Trivia};
+use kali_print::{Context, Print};
+use kali_span::FileId;
+
+/// Formats `src`, keeping its comments.
+fn format(src: &str) -> String {
+    let module = parse_str(src).unwrap();
+    let trivia = Trivia::collect(FileId::default(), src);
+    let mut out = Vec::new();
+    let mut ctx = Context::new(&mut out, &module.cache)
+        .with_source(src)
+        .with_trivia(&trivia);
+    module.print(&mut ctx).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_item_comments() {
+    let src =
+        "# the answer\nlet x = 42; # trailing\n\n# first\n\n# second\n\nlet y = x\n# the end\n";
+    assert_eq!(
+        format(src),
+        "# the answer\nlet x = 42; # trailing\n\n# first\n\n# second\n\nlet y = x\n# the end\n"
+    );
+}
+
+#[test]
+fn test_arm_comments() {
+    let src = "let y = match x {\n# zero\n0 -> 1, # one\n_ -> 2 }";
+    assert_eq!(
+        format(src),
+        "let y = match x {\n\t# zero\n\t0 -> 1, # one\n\t_ -> 2,\n}\n"
+    );
+}
+
+#[test]
+fn test_no_comment_is_lost() {
+    // comments within an expression move after the item they are in
+    let src = "let x = [\n1, # one\n2\n];\nlet y = x";
+    assert_eq!(format(src), "let x = [1, 2];\n# one\n\nlet y = x\n");
+    // formatting is idempotent
+    let formatted = format(src);
+    assert_eq!(format(&formatted), formatted);
+}
@@ -1,74 +1,199 @@
-use std::io::Write;
-
-use kali_ast::{Eraser, Rewriter};
-use kali_parse::{kali_expr, parse_expr_str};
+use kali_parse::{parse_str, parse_str_recovering};
 use kali_print::{Context, Print};
+use kali_testing::{assert_parse_ok, print};
 
-struct TestWriter {
-    pub output: Vec<u8>,
-}
-
-impl Write for TestWriter {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.output.extend_from_slice(buf);
-        Ok(buf.len())
-    }
-    fn flush(&mut self) -> std::io::Result<()> {
-        Ok(())
-    }
-}
-
-fn print_to_string<T: Print>(value: &T) -> String {
-    let mut writer = TestWriter { output: Vec::new() };
-    let mut ctx = Context::new(&mut writer);
-    value.print(&mut ctx).unwrap();
-    String::from_utf8(writer.output).unwrap()
+fn assert_round_trip(src: &str) {
+    let module = assert_parse_ok!(src);
+    let formatted = print(&module);
+    assert_parse_ok!(&formatted, &module);
 }
 
 #[test]
 fn test_e2e_binary_expr() {
-    let expr = Eraser::rewrite(&mut (), kali_expr! { x + 1 }).unwrap();
-    let formatted = print_to_string(&expr);
-    let parsed = Eraser::rewrite(&mut (), parse_expr_str(&formatted).unwrap()).unwrap();
-    assert_eq!(expr, parsed);
+    assert_round_trip("let y = x + 1");
 }
 
 #[test]
 fn test_e2e_unary_expr() {
-    let expr = Eraser::rewrite(&mut (), kali_expr! { -x }).unwrap();
-    let formatted = print_to_string(&expr);
-    let parsed = Eraser::rewrite(&mut (), parse_expr_str(&formatted).unwrap()).unwrap();
-    assert_eq!(expr, parsed);
+    assert_round_trip("let y = -x");
 }
 
 #[test]
 fn test_e2e_literal_expr() {
-    let expr = Eraser::rewrite(&mut (), kali_expr! { 42 }).unwrap();
-    let formatted = print_to_string(&expr);
-    let parsed = Eraser::rewrite(&mut (), parse_expr_str(&formatted).unwrap()).unwrap();
-    assert_eq!(expr, parsed);
+    assert_round_trip("let y = 42");
+}
+
+#[test]
+fn test_e2e_byte_string() {
+    assert_round_trip(r#"let y = b"\x00\x89PNG\r\n\t\"\\ é""#);
+    assert_round_trip("type a = (bytes, string)");
 }
 
 #[test]
 fn test_e2e_paren_expr() {
-    let expr = Eraser::rewrite(&mut (), kali_expr! { (x + 1) }).unwrap();
-    let formatted = print_to_string(&expr);
-    let parsed = Eraser::rewrite(&mut (), parse_expr_str(&formatted).unwrap()).unwrap();
-    assert_eq!(expr, parsed);
+    assert_round_trip("let y = (x + 1) * 2");
 }
 
 #[test]
 fn test_e2e_call_expr() {
-    let expr = Eraser::rewrite(&mut (), kali_expr! { foo(x, 2) }).unwrap();
-    let formatted = print_to_string(&expr);
-    let parsed = Eraser::rewrite(&mut (), parse_expr_str(&formatted).unwrap()).unwrap();
-    assert_eq!(expr, parsed);
+    assert_round_trip("let y = foo x, 2");
+}
+
+#[test]
+fn test_e2e_pipe_expr() {
+    assert_round_trip("let y = x |> f |> g y");
+    assert_round_trip("let y = x |> (z |> f)");
+    assert_round_trip("let y = (x |> f) :: xs");
+    assert_round_trip("let y = x |> (z -> z)");
+}
+
+#[test]
+fn test_e2e_section_expr() {
+    assert_round_trip("let y = (+ 1)");
+    assert_round_trip("let y = (2 *)");
+    assert_round_trip("let y = (x -)");
+    assert_round_trip("let y = (x + y *)");
+    assert_round_trip("let y = (** x ** y)");
+    assert_round_trip("let y = (:: (z -> z))");
+    assert_round_trip("let y = map (== x), xs");
+}
+
+#[test]
+fn test_e2e_hole_expr() {
+    assert_round_trip("let y = ?todo");
+    assert_round_trip("let y = f(?x, 1) + ?y");
+    assert_round_trip("let y = ?f(x)");
 }
 
 #[test]
 fn test_e2e_if_expr() {
-    let expr = Eraser::rewrite(&mut (), kali_expr! { if x 1 else 2 }).unwrap();
-    let formatted = print_to_string(&expr);
-    let parsed = Eraser::rewrite(&mut (), parse_expr_str(&formatted).unwrap()).unwrap();
-    assert_eq!(expr, parsed);
+    assert_round_trip("let y = if x { 1 } else { 2 }");
+}
+
+#[test]
+fn test_e2e_loop_expr() {
+    assert_round_trip("let y = while get(r) < 10 do set(r, get(r) + 1)");
+    assert_round_trip("let y = for (a, b) in zip(xs, ys) do a + b");
+    assert_round_trip("let y = (for x in xs do x) :: ys");
+}
+
+#[test]
+fn test_e2e_block_expr() {
+    assert_round_trip("let y = do { set(r, 1); get(r) }");
+    assert_round_trip("let y = if x { f(a); b } else { c }");
+    assert_round_trip("let y = for x in xs do { f(x); x }");
+    assert_round_trip("let y = do { while x do a; b } :: ys");
+    // a trailing `;` would leave the block without a value
+    assert!(parse_str("let y = do { a; }").is_err());
+}
+
+#[test]
+fn test_e2e_set_expr() {
+    assert_round_trip("let y = #[1, x -> x, #[]]");
+    assert_round_trip("let y = #[a, b,]");
+    // a `#[` opens a set rather than a comment, but `# [` is still a comment
+    assert_round_trip("# [not a set\nlet y = #[#{ 1: #[2] }]");
+}
+
+#[test]
+fn test_e2e_range_expr() {
+    assert_round_trip("let y = 1..10");
+    assert_round_trip("let y = -1..=n + 1 :: (a..b) |> collect");
+    assert_round_trip("let y = (x -> x)..(0..1)");
+    assert_round_trip("let y = for i in 0..n do [..xs, ..a..b]");
+    assert_round_trip("let y = match x { 1..=9 -> a, -3..0 :: _ -> b }");
+    // the next arm is not read as the parameters of a lambda ending the range
+    assert_round_trip("let y = match x { 1 -> a..b, (c, d) -> e, f -> -g, h -> i }");
+}
+
+#[test]
+fn test_e2e_prefix_pattern() {
+    assert_round_trip("let y = match s { \"GET \" ++ path -> path, _ -> \"\" }");
+    assert_round_trip("let y = match s { \"a\" ++ (\"b\" | \"c\") | \"d\" ++ _ :: xs -> s }");
+    assert_round_trip("let y = match s { x | \"a\" ++ \"b\" ++ rest -> rest }");
+}
+
+#[test]
+fn test_e2e_map_expr() {
+    assert_round_trip(r#"let y = #{ "a": 1, "b": x -> x, }"#);
+    assert_round_trip("let y = #{}");
+    // a `#{` opens a map rather than a comment
+    assert_round_trip("# a comment\nlet y = #{ (1, 2): #{} } # another\n;let z = y");
+}
+
+#[test]
+fn test_e2e_try_expr() {
+    assert_round_trip("let y = f(x)? + g x?");
+    assert_round_trip("let y = (-1)?");
+    assert_round_trip("let y = (a + b)??(c)");
+}
+
+#[test]
+fn test_e2e_projection_expr() {
+    assert_round_trip("let y = t.0 + f(x).1");
+    assert_round_trip("let y = -t.0.1 :: (a, b).1");
+    assert_round_trip("let y = (f x).0 + g x.2?");
+    assert_round_trip("let y = t.0..t.1");
+}
+
+#[test]
+fn test_e2e_index_expr() {
+    assert_round_trip("let y = xs[0] + f(x)[i + 1]");
+    assert_round_trip("let y = f xs[0] [1]");
+    assert_round_trip("let y = (-1)[0][xs[0]] :: (f x)[0]");
+    // a `-` before a literal would be folded into it
+    assert_round_trip("let y = -(1.0) + -(1[0]?)");
+}
+
+#[test]
+fn test_e2e_default_parameter() {
+    assert_round_trip(r#"let greet = name, greeting string = "hello" -> (greeting, name)"#);
+    assert_round_trip("let f = x = -1, y = g(x), z = (1 + 2) -> x");
+    // default values are atoms, so that they end before the next parameter
+    assert!(parse_str("let f = x = a + 1 -> x").is_err());
+}
+
+#[test]
+fn test_e2e_export() {
+    assert_round_trip("export let a = 1");
+    assert_round_trip("#inline()\nexport fn f x = x");
+    assert_round_trip("export {a, b as c}");
+    assert_round_trip("export {}");
+    assert_round_trip(r#"export * from "std/list""#);
+}
+
+#[test]
+fn test_e2e_import() {
+    assert_round_trip("import std::{list::*, io::print as p}");
+}
+
+#[test]
+fn test_e2e_function_clauses() {
+    assert_round_trip("fn fib 0 = 0;\nfn fib 1 = 1;\nfn fib n = fib(n - 1) + fib(n - 2)");
+    assert_round_trip("fn zip [] _ = [];\nfn zip (x :: xs) (y :: ys, _) = (x, y) :: zip(xs, ys)");
+}
+
+#[test]
+fn test_e2e_generic_type() {
+    assert_round_trip("type a = Map<string, List<List<int>>>");
+}
+
+#[test]
+fn test_e2e_function_type() {
+    assert_round_trip("type a = (int, bool) -> int");
+    assert_round_trip("type a = ((int, bool)) -> () -> int | bool");
+    assert_round_trip("let f = g ((int) -> int) -> g(1)");
+}
+
+#[test]
+fn test_e2e_recovery() {
+    let src = "let a = 1;\n\nlet b = (1 + *) * 2;\n\nlet c = 2 2 2 =;\n\nlet d = if a { -> } else { b }\n";
+    let (module, errors) = parse_str_recovering(src);
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert_eq!(module.items.len(), 4);
+
+    let mut buf = Vec::new();
+    let mut ctx = Context::new(&mut buf, &module.cache).with_source(src);
+    module.print(&mut ctx).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), src);
 }
@@ -0,0 +1,77 @@
+use kali_parse::parse_str;
+use kali_print::{Context, FormatConfig, Indent, Print};
+use kali_testing::assert_parse_ok;
+
+/// Formats `src` with a configuration.
+fn format(src: &str, config: FormatConfig) -> String {
+    let module = parse_str(src).unwrap();
+    let mut out = Vec::new();
+    let mut ctx = Context::new(&mut out, &module.cache).with_config(config);
+    module.print(&mut ctx).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// A configuration keeping lines within `max_width` columns.
+fn width(max_width: usize) -> FormatConfig {
+    FormatConfig {
+        max_width,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_break_arguments() {
+    let src = "let y = f(first, second, third)";
+    assert_eq!(format(src, width(40)), "let y = f(first, second, third)\n");
+    assert_eq!(
+        format(src, width(20)),
+        "let y = f(\n\tfirst,\n\tsecond,\n\tthird,\n)\n"
+    );
+    // only the sequences that do not fit are broken
+    let nested = "let y = f(g(a, b), [1, 2, 3, 4, 5, 6, 7, 8, 9])";
+    assert_eq!(
+        format(nested, width(32)),
+        "let y = f(\n\tg(a, b),\n\t[1, 2, 3, 4, 5, 6, 7, 8, 9],\n)\n"
+    );
+}
+
+#[test]
+fn test_break_records_and_lists() {
+    let src = "let f = p { width: nat, height: nat } -> [p, p, p]";
+    assert_eq!(
+        format(src, width(30)),
+        "let f = p {\n\twidth: nat,\n\theight: nat,\n} -> [p, p, p]\n"
+    );
+    // lists have no trailing comma
+    assert_eq!(
+        format("let xs = [first, second]", width(16)),
+        "let xs = [\n\tfirst,\n\tsecond\n]\n"
+    );
+}
+
+#[test]
+fn test_options() {
+    let src = "let y = f(first, second);\nlet z = match y { 0 -> 1, _ -> 2 }";
+    let config = FormatConfig {
+        max_width: 20,
+        indent: Indent::Spaces(2),
+        trailing_commas: false,
+        final_newline: false,
+    };
+    assert_eq!(
+        format(src, config),
+        "let y = f(\n  first,\n  second\n);\n\nlet z = match y {\n  0 -> 1,\n  _ -> 2\n}"
+    );
+}
+
+#[test]
+fn test_e2e_broken_lines() {
+    // broken sequences parse back to the same module
+    let src = "let f = p { width: nat, height: nat } -> g(p, [1, 2, 3], p, h(p, p));\n\
+               let g = x -> [match x { 0 -> 1, _ -> 2 }, 3]";
+    for max_width in [10, 30, 60] {
+        let module = assert_parse_ok!(src);
+        let formatted = format(src, width(max_width));
+        assert_parse_ok!(&formatted, &module);
+    }
+}
@@ -0,0 +1,126 @@
+use std::convert::Infallible;
+
+use kali_ast::{
+    BinaryOpKind, Destructor, Expr, ExprKind, Ident, LiteralKind, MatchArm, Pattern,
+    visit::{Visitor, WalkOrder, Walker},
+    visit_mut::VisitorMut,
+};
+use kali_parse::parse_str;
+use kali_testing::print;
+
+/// Records the nodes it visits, in order.
+#[derive(Default)]
+struct Trace<'a> {
+    cache: Option<&'a lasso::Rodeo>,
+    events: Vec<String>,
+}
+
+impl Visitor for Trace<'_> {
+    type Error = Infallible;
+
+    fn visit_ident(&mut self, ident: &Ident) -> Result<(), Infallible> {
+        let name = self.cache.unwrap().resolve(&ident.key);
+        self.events.push(name.to_string());
+        Ok(())
+    }
+
+    fn visit_match_arm(&mut self, _: &MatchArm) -> Result<(), Infallible> {
+        self.events.push("arm".to_string());
+        Ok(())
+    }
+
+    fn visit_pattern(&mut self, _: &Pattern) -> Result<(), Infallible> {
+        self.events.push("pattern".to_string());
+        Ok(())
+    }
+
+    fn visit_destructor(&mut self, _: &Destructor) -> Result<(), Infallible> {
+        self.events.push("destructor".to_string());
+        Ok(())
+    }
+}
+
+/// Walks `src` in the given order, returning the nodes visited.
+fn trace(src: &str, order: WalkOrder) -> String {
+    let module = parse_str(src).unwrap();
+    let mut trace = Trace {
+        cache: Some(&module.cache),
+        ..Trace::default()
+    };
+    Walker::new(order).walk_module(&mut trace, &module).unwrap();
+    trace.events.join(" ")
+}
+
+/// Folds additions of natural number literals.
+struct Fold;
+
+impl VisitorMut for Fold {
+    type Error = Infallible;
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> Result<(), Infallible> {
+        if let ExprKind::BinaryExpr { op, lhs, rhs } = &expr.kind
+            && op.kind == BinaryOpKind::Add
+            && let ExprKind::Literal(LiteralKind::Natural(lhs)) = lhs.kind
+            && let ExprKind::Literal(LiteralKind::Natural(rhs)) = rhs.kind
+        {
+            expr.kind = ExprKind::Literal(LiteralKind::Natural(lhs + rhs));
+        }
+        Ok(())
+    }
+}
+
+/// Renames every identifier with one key to another.
+struct Rename(lasso::Spur, lasso::Spur);
+
+impl VisitorMut for Rename {
+    type Error = Infallible;
+
+    fn visit_ident_mut(&mut self, ident: &mut Ident) -> Result<(), Infallible> {
+        if ident.key == self.0 {
+            ident.key = self.1;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_fold() {
+    let mut module = parse_str("let a = x -> (1 + 2 + (3 + x))").unwrap();
+    // children are folded before their parents, so nested additions fold completely
+    Walker::new(WalkOrder::PostOrder)
+        .walk_module_mut(&mut Fold, &mut module)
+        .unwrap();
+    assert_eq!(print(&module).trim_end(), "let a = x -> (3 + (3 + x))");
+}
+
+#[test]
+fn test_rename() {
+    let mut module = parse_str("type t = { x: x };\nlet a = f(x, y)").unwrap();
+    let from = module.cache.get("x").unwrap();
+    let to = module.cache.get_or_intern("z");
+    Walker::new(WalkOrder::PreOrder)
+        .walk_module_mut(&mut Rename(from, to), &mut module)
+        .unwrap();
+    assert_eq!(
+        print(&module).trim_end(),
+        "type t = {z: z};\n\nlet a = f(z, y)"
+    );
+}
+
+#[test]
+fn test_walk_patterns() {
+    // `i :: j | k` is `i :: (j | k)`
+    let src = "let {a: (b, c)} = match d { (e, {f: g}) -> h, i :: j | k -> l }";
+    assert_eq!(
+        trace(src, WalkOrder::PreOrder),
+        "destructor a destructor destructor b destructor c d \
+         arm pattern pattern e pattern f pattern g h \
+         arm pattern pattern i pattern pattern j pattern k l"
+    );
+    assert_eq!(
+        trace(src, WalkOrder::PostOrder),
+        "a b destructor c destructor destructor destructor d \
+         e pattern f g pattern pattern pattern h arm \
+         i pattern j pattern k pattern pattern pattern l arm"
+    );
+}
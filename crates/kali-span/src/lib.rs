@@ -0,0 +1,438 @@
+//! Source locations shared by every crate of the Kali compiler.
+//!
+//! A [`Span`] is a range of bytes in a single source file, identified by a [`FileId`]. Files are
+//! registered with a [`SourceMap`], which maps their IDs back to paths and source text, so that
+//! spans produced by one crate can be reported by another. The source map also converts byte
+//! offsets into the lines and columns that are shown to the user, and into the UTF-16 positions
+//! used by editors.
+
+use std::{fmt, ops::Range};
+
+use unicode_width::UnicodeWidthStr;
+
+/// Identifies a source file registered with a [`SourceMap`].
+///
+/// The default ID refers to the first file registered, so that code parsing a single source
+/// without a source map produces the same spans as code that registers it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+impl FileId {
+    /// Returns the index of the file in its source map.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for FileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A range of bytes in a source file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The file the span refers to.
+    pub file_id: FileId,
+    /// The offset of the first byte of the span.
+    pub start: usize,
+    /// The offset of the byte after the end of the span.
+    pub end: usize,
+}
+
+impl Span {
+    /// Creates a span covering `range` in the given file.
+    pub fn new(file_id: FileId, range: Range<usize>) -> Span {
+        Span {
+            file_id,
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    /// Returns the range of bytes covered by the span.
+    pub fn into_range(self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns the length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns whether the span covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns whether `other` lies within the span, in the same file.
+    pub fn contains(&self, other: Span) -> bool {
+        self.file_id == other.file_id && self.start <= other.start && other.end <= self.end
+    }
+
+    /// Returns the smallest span covering both `self` and `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the spans refer to different files.
+    pub fn union(self, other: Span) -> Span {
+        assert_eq!(
+            self.file_id, other.file_id,
+            "cannot join spans of different files"
+        );
+        Span {
+            file_id: self.file_id,
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+impl From<Range<usize>> for Span {
+    /// Creates a span covering `range` in the default file.
+    fn from(range: Range<usize>) -> Span {
+        Span::new(FileId::default(), range)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}..{}", self.file_id, self.start, self.end)
+    }
+}
+
+impl chumsky::span::Span for Span {
+    type Context = FileId;
+    type Offset = usize;
+
+    fn new(context: FileId, range: Range<usize>) -> Span {
+        Span::new(context, range)
+    }
+
+    fn context(&self) -> FileId {
+        self.file_id
+    }
+
+    fn start(&self) -> usize {
+        self.start
+    }
+
+    fn end(&self) -> usize {
+        self.end
+    }
+}
+
+/// A source file registered with a [`SourceMap`].
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// The path the file was read from.
+    pub path: String,
+    /// The contents of the file.
+    pub source: String,
+    /// The offset of the first byte of each line.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    /// Creates a source file, computing the offsets of its lines.
+    pub fn new(path: impl Into<String>, source: impl Into<String>) -> SourceFile {
+        let source = source.into();
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        SourceFile {
+            path: path.into(),
+            source,
+            line_starts,
+        }
+    }
+
+    /// Returns the number of lines in the file.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the line and column of a byte offset, both counted from one.
+    ///
+    /// Columns count characters rather than bytes, so that they match what an editor shows.
+    /// An offset past the end of the file is clamped to the end.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The byte offset into the file.
+    ///
+    /// # Returns
+    ///
+    /// The line and column of the offset.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let (line, start, offset) = self.line_prefix(offset);
+        (line + 1, self.source[start..offset].chars().count() + 1)
+    }
+
+    /// Returns the column a byte offset is displayed at in a terminal, counted from one.
+    ///
+    /// Unlike [`SourceFile::line_col`], wide characters such as `你` take two columns and
+    /// combining characters take none, so that carets line up under the offending code.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The byte offset into the file.
+    ///
+    /// # Returns
+    ///
+    /// The display column of the offset.
+    pub fn display_column(&self, offset: usize) -> usize {
+        let (_, start, offset) = self.line_prefix(offset);
+        self.source[start..offset].width() + 1
+    }
+
+    /// Returns the position of a byte offset in UTF-16 code units, as used by the Language
+    /// Server Protocol.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The byte offset into the file.
+    ///
+    /// # Returns
+    ///
+    /// The line and the column in UTF-16 code units, both counted from zero.
+    pub fn utf16_position(&self, offset: usize) -> (usize, usize) {
+        let (line, start, offset) = self.line_prefix(offset);
+        let column = self.source[start..offset].encode_utf16().count();
+        (line, column)
+    }
+
+    /// Returns the byte offset of a position in UTF-16 code units.
+    ///
+    /// Lines past the end of the file are clamped to the end, and columns past the end of a line
+    /// to the end of that line. A column inside a character points at that character.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line, counted from zero.
+    /// * `column` - The column in UTF-16 code units, counted from zero.
+    ///
+    /// # Returns
+    ///
+    /// The byte offset of the position.
+    pub fn utf16_offset(&self, line: usize, column: usize) -> usize {
+        let Some(&start) = self.line_starts.get(line) else {
+            return self.source.len();
+        };
+        let text = self.source[start..].split('\n').next().unwrap_or_default();
+        let mut units = 0;
+        for (index, c) in text.char_indices() {
+            units += c.len_utf16();
+            if units > column {
+                return start + index;
+            }
+        }
+        start + text.len()
+    }
+
+    /// Returns the line of a byte offset, the offset its line starts at, and the offset rounded
+    /// down to the start of the character it is in. Offsets past the end are clamped to the end.
+    fn line_prefix(&self, offset: usize) -> (usize, usize, usize) {
+        let mut offset = offset.min(self.source.len());
+        // offsets inside a multi-byte character point at that character
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        // the first line always starts at zero, so there is at least one line at or before it
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        (line, self.line_starts[line], offset)
+    }
+}
+
+/// A position in a source file, as shown to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location<'a> {
+    /// The path of the file.
+    pub path: &'a str,
+    /// The line, counted from one.
+    pub line: usize,
+    /// The column, counted in characters from one.
+    pub column: usize,
+}
+
+impl fmt::Display for Location<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path, self.line, self.column)
+    }
+}
+
+/// The source files of a compilation, indexed by [`FileId`].
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    /// Creates an empty source map.
+    pub fn new() -> SourceMap {
+        SourceMap::default()
+    }
+
+    /// Registers a source file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path the file was read from, used when reporting spans in the file.
+    /// * `source` - The contents of the file.
+    ///
+    /// # Returns
+    ///
+    /// The ID to attach to spans in the file.
+    pub fn add(&mut self, path: impl Into<String>, source: impl Into<String>) -> FileId {
+        let id = FileId(
+            u32::try_from(self.files.len()).expect("a source map holds at most u32::MAX files"),
+        );
+        self.files.push(SourceFile::new(path, source));
+        id
+    }
+
+    /// Returns the file with the given ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file was not registered with this source map.
+    pub fn file(&self, file_id: FileId) -> &SourceFile {
+        &self.files[file_id.index()]
+    }
+
+    /// Returns the path of the file with the given ID.
+    pub fn path(&self, file_id: FileId) -> &str {
+        &self.file(file_id).path
+    }
+
+    /// Returns the contents of the file with the given ID.
+    pub fn source(&self, file_id: FileId) -> &str {
+        &self.file(file_id).source
+    }
+
+    /// Returns the source text covered by a span.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the span does not lie within its file.
+    pub fn slice(&self, span: Span) -> &str {
+        &self.source(span.file_id)[span.into_range()]
+    }
+
+    /// Returns where a span starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `span` - The span to look up.
+    ///
+    /// # Returns
+    ///
+    /// The path of the file the span refers to, along with the line and column it starts at.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file of the span was not registered with this source map.
+    pub fn lookup(&self, span: Span) -> Location<'_> {
+        let file = self.file(span.file_id);
+        let (line, column) = file.line_col(span.start);
+        Location {
+            path: &file.path,
+            line,
+            column,
+        }
+    }
+
+    /// Returns an iterator over the registered files and their IDs.
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        (0..).map(FileId).zip(&self.files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_map() {
+        let mut map = SourceMap::new();
+        let a = map.add("a.kali", "let x = 1");
+        let b = map.add("b.kali", "let y = x");
+        assert_eq!(a, FileId::default());
+        assert_ne!(a, b);
+        assert_eq!(map.path(b), "b.kali");
+        assert_eq!(map.slice(Span::new(b, 4..5)), "y");
+        assert_eq!(map.slice(Span::from(8..9)), "1");
+        assert_eq!(map.files().count(), 2);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut map = SourceMap::new();
+        map.add("a.kali", "");
+        let file_id = map.add("b.kali", "let x = 1;\nlet y = x;\n\nlet ζ = \"π\" y");
+        let location = map.lookup(Span::new(file_id, 15..16));
+        assert_eq!((location.line, location.column), (2, 5));
+        assert_eq!(location.to_string(), "b.kali:2:5");
+
+        let file = map.file(file_id);
+        assert_eq!(file.line_count(), 4);
+        assert_eq!(file.line_col(0), (1, 1));
+        assert_eq!(file.line_col(10), (1, 11));
+        assert_eq!(file.line_col(11), (2, 1));
+        assert_eq!(file.line_col(22), (3, 1));
+        // columns count characters, so `=` is the seventh column despite `ζ` taking two bytes
+        assert_eq!(file.line_col(30), (4, 7));
+        assert_eq!(file.line_col(37), (4, 13));
+        // offsets inside a character point at it, and offsets past the end are clamped
+        assert_eq!(file.line_col(28), (4, 5));
+        assert_eq!(file.line_col(1000), (4, 14));
+    }
+
+    #[test]
+    fn test_unicode_columns() {
+        let file = SourceFile::new("a.kali", "let 你好 = 1;\nlet привет = \"😀\" x");
+        // `你` and `好` take three bytes, one UTF-16 unit and two display columns each
+        assert_eq!(file.line_col(11), (1, 8));
+        assert_eq!(file.display_column(11), 10);
+        assert_eq!(file.utf16_position(11), (0, 7));
+        // `😀` takes four bytes and two UTF-16 units
+        let x = file.source.rfind('x').unwrap();
+        assert_eq!(file.line_col(x), (2, 18));
+        assert_eq!(file.display_column(x), 19);
+        assert_eq!(file.utf16_position(x), (1, 18));
+        // offsets inside a character point at it
+        assert_eq!(file.utf16_position(5), (0, 4));
+        assert_eq!(file.display_column(5), 5);
+
+        assert_eq!(file.utf16_offset(0, 7), 11);
+        assert_eq!(file.utf16_offset(1, 18), x);
+        // a column inside a surrogate pair points at the character
+        assert_eq!(file.utf16_offset(1, 15), x - 6);
+        // positions past the end of a line or the file are clamped
+        assert_eq!(file.utf16_offset(0, 100), 15);
+        assert_eq!(file.utf16_offset(5, 0), file.source.len());
+    }
+
+    #[test]
+    fn test_union() {
+        let file = FileId::default();
+        assert_eq!(
+            Span::new(file, 4..6).union(Span::new(file, 1..2)),
+            Span::new(file, 1..6)
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut map = SourceMap::new();
+        let a = map.add("a.kali", "let x = 1");
+        let b = map.add("b.kali", "let y = x");
+        let span = Span::new(a, 4..9);
+        assert!(span.contains(Span::new(a, 4..5)));
+        assert!(span.contains(span));
+        assert!(span.contains(Span::new(a, 9..9)));
+        assert!(!span.contains(Span::new(a, 3..5)));
+        assert!(!span.contains(Span::new(b, 4..5)));
+    }
+}
@@ -0,0 +1,629 @@
+//! Structural differences between syntax trees.
+//!
+//! When two trees are not [`SyntaxEq`], [`module_diff`] finds the first node at which they
+//! differ, so that a failing test reports the path to that node and the two subtrees rooted
+//! there rather than the `Debug` output of both trees.
+
+use std::fmt;
+
+use kali_ast::{
+    Attribute, Clause, Definition, Destructor, DestructorKind, ExportItem, ExportKind, Expr,
+    ExprKind, Function, Ident, ImportTree, ImportTreeKind, Item, ItemKind, LambdaParam,
+    LiteralKind, MatchArm, Module, Pattern, PatternKind, Type, TypeAlias, TypeKind,
+};
+use kali_print::{Context, Print};
+
+use crate::{Interners, SyntaxEq};
+
+/// The first node at which two syntax trees differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// The path from the root of the trees to the node, e.g. `items[0].expr.lhs`.
+    pub path: String,
+    /// The subtree of the expected tree, printed as source code.
+    pub expected: String,
+    /// The subtree of the tree that was found, printed as source code.
+    pub found: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "trees differ at `{}`", self.path)?;
+        writeln!(f, "expected: {}", self.expected)?;
+        write!(f, "   found: {}", self.found)
+    }
+}
+
+/// Returns the first node at which two modules differ, resolving keys against each module's own
+/// cache, or `None` if they are structurally equal.
+///
+/// Nodes are visited in source order, and a node is only reported if none of its children
+/// differ, so the difference is as small as possible.
+pub fn module_diff(expected: &Module, found: &Module) -> Option<Difference> {
+    let mut differ = Differ {
+        interners: Interners {
+            lhs: &expected.cache,
+            rhs: &found.cache,
+        },
+        path: String::from("items"),
+    };
+    expected.items.syntax_diff(&found.items, &mut differ)
+}
+
+/// The state of a search for the first difference between two trees.
+struct Differ<'a> {
+    /// The interners of the two trees.
+    interners: Interners<'a>,
+    /// The path to the nodes being compared.
+    path: String,
+}
+
+impl Differ<'_> {
+    /// Compares two children of the nodes being compared.
+    ///
+    /// # Arguments
+    ///
+    /// * `segment` - The segment appended to the path, e.g. `.lhs` or `[0]`.
+    /// * `lhs` - The child of the expected node.
+    /// * `rhs` - The child of the found node.
+    fn child<T: SyntaxDiff + ?Sized>(
+        &mut self,
+        segment: &str,
+        lhs: &T,
+        rhs: &T,
+    ) -> Option<Difference> {
+        let len = self.path.len();
+        self.path.push_str(segment);
+        let difference = lhs.syntax_diff(rhs, self);
+        self.path.truncate(len);
+        difference
+    }
+
+    /// Describes a difference at the nodes being compared.
+    fn difference<T: SyntaxDiff + ?Sized>(&self, lhs: &T, rhs: &T) -> Difference {
+        Difference {
+            path: self.path.clone(),
+            expected: lhs.render(self.interners.lhs),
+            found: rhs.render(self.interners.rhs),
+        }
+    }
+}
+
+/// Structural difference between syntax trees.
+trait SyntaxDiff: SyntaxEq {
+    /// Returns the first difference between the children of two nodes, if the nodes are of the
+    /// same kind. Differences in the nodes themselves are not reported.
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference>;
+
+    /// Prints the node as source code.
+    fn render(&self, rodeo: &lasso::Rodeo) -> String;
+
+    /// Returns the first difference between two nodes, or `None` if they are equal.
+    fn syntax_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        if self.syntax_eq(other, differ.interners) {
+            return None;
+        }
+        self.children_diff(other, differ)
+            .or_else(|| Some(differ.difference(self, other)))
+    }
+}
+
+/// Prints a node as source code, on a single line where possible.
+fn printed<T: Print + ?Sized>(node: &T, rodeo: &lasso::Rodeo) -> String {
+    let mut buf = Vec::new();
+    match node.print(&mut Context::new(&mut buf, rodeo)) {
+        Ok(()) => String::from_utf8_lossy(&buf).trim().to_string(),
+        Err(err) => format!("<{}>", err),
+    }
+}
+
+impl<T: SyntaxDiff> SyntaxDiff for Vec<T> {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        // elements present in both lists are compared, even if the lengths differ
+        self.iter()
+            .zip(other)
+            .enumerate()
+            .find_map(|(index, (a, b))| differ.child(&format!("[{}]", index), a, b))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        let elements: Vec<_> = self.iter().map(|element| element.render(rodeo)).collect();
+        format!("[{}]", elements.join(", "))
+    }
+}
+
+impl<T: SyntaxDiff> SyntaxDiff for Box<T> {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        (**self).children_diff(other, differ)
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        (**self).render(rodeo)
+    }
+}
+
+impl<T: SyntaxDiff> SyntaxDiff for Option<T> {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        match (self, other) {
+            (Some(a), Some(b)) => a.children_diff(b, differ),
+            _ => None,
+        }
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        match self {
+            Some(node) => node.render(rodeo),
+            None => String::from("<none>"),
+        }
+    }
+}
+
+impl<T: SyntaxDiff> SyntaxDiff for indexmap::IndexMap<Ident, T> {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        self.iter()
+            .zip(other)
+            .enumerate()
+            .find_map(|(index, ((ka, va), (kb, vb)))| {
+                differ.child(&format!("[{}]", index), ka, kb).or_else(|| {
+                    let key = differ.interners.lhs.resolve(&ka.key);
+                    differ.child(&format!(".{}", key), va, vb)
+                })
+            })
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        let fields: Vec<_> = self
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key.render(rodeo), value.render(rodeo)))
+            .collect();
+        format!("{{{}}}", fields.join(", "))
+    }
+}
+
+impl SyntaxDiff for Ident {
+    fn children_diff(&self, _: &Self, _: &mut Differ) -> Option<Difference> {
+        None
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        rodeo.resolve(&self.key).to_string()
+    }
+}
+
+impl SyntaxDiff for Item {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".attributes", &self.attributes, &other.attributes)
+            .or_else(|| match (&self.kind, &other.kind) {
+                (ItemKind::Import(a), ItemKind::Import(b)) => differ.child(".import", a, b),
+                (ItemKind::Export(ExportKind::List(a)), ItemKind::Export(ExportKind::List(b))) => {
+                    a.children_diff(b, differ)
+                }
+                (ItemKind::TypeAlias(a), ItemKind::TypeAlias(b)) => a.children_diff(b, differ),
+                (ItemKind::Definition(a), ItemKind::Definition(b)) => a.children_diff(b, differ),
+                (ItemKind::Function(a), ItemKind::Function(b)) => a.children_diff(b, differ),
+                _ => None,
+            })
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for Attribute {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".name", &self.name, &other.name)
+            .or_else(|| differ.child(".arguments", &self.arguments, &other.arguments))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for ImportTree {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        match (&self.kind, &other.kind) {
+            (
+                ImportTreeKind::Item { name, alias },
+                ImportTreeKind::Item {
+                    name: other_name,
+                    alias: other_alias,
+                },
+            ) => differ
+                .child(".name", name, other_name)
+                .or_else(|| differ.child(".alias", alias, other_alias)),
+            (
+                ImportTreeKind::Segment { name, child },
+                ImportTreeKind::Segment {
+                    name: other_name,
+                    child: other_child,
+                },
+            ) => differ
+                .child(".name", name, other_name)
+                .or_else(|| differ.child(".child", child, other_child)),
+            (ImportTreeKind::List(a), ImportTreeKind::List(b)) => a.children_diff(b, differ),
+            _ => None,
+        }
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for ExportItem {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".name", &self.name, &other.name)
+            .or_else(|| differ.child(".alias", &self.alias, &other.alias))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for TypeAlias {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".name", &self.name, &other.name)
+            .or_else(|| differ.child(".ty", &self.ty, &other.ty))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for Type {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        match (&self.kind, &other.kind) {
+            (TypeKind::Named(a), TypeKind::Named(b)) => a.children_diff(b, differ),
+            (
+                TypeKind::Generic { name, arguments },
+                TypeKind::Generic {
+                    name: other_name,
+                    arguments: other_arguments,
+                },
+            ) => differ
+                .child(".name", name, other_name)
+                .or_else(|| differ.child(".arguments", arguments, other_arguments)),
+            (TypeKind::Tuple(a), TypeKind::Tuple(b)) => a.children_diff(b, differ),
+            (TypeKind::List(a), TypeKind::List(b)) => differ.child(".element", a, b),
+            (TypeKind::Record(a), TypeKind::Record(b)) => a.children_diff(b, differ),
+            (TypeKind::Fn(a_params, a_ret), TypeKind::Fn(b_params, b_ret)) => differ
+                .child(".params", a_params, b_params)
+                .or_else(|| differ.child(".ret", a_ret, b_ret)),
+            (
+                TypeKind::Intersection { lhs, rhs },
+                TypeKind::Intersection {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            )
+            | (
+                TypeKind::Union { lhs, rhs },
+                TypeKind::Union {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            ) => differ
+                .child(".lhs", lhs, other_lhs)
+                .or_else(|| differ.child(".rhs", rhs, other_rhs)),
+            _ => None,
+        }
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for Definition {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".name", &self.name, &other.name)
+            .or_else(|| differ.child(".expr", &self.expr, &other.expr))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for Function {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".name", &self.name, &other.name)
+            .or_else(|| differ.child(".clauses", &self.clauses, &other.clauses))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for Clause {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".patterns", &self.patterns, &other.patterns)
+            .or_else(|| differ.child(".body", &self.body, &other.body))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for LiteralKind {
+    fn children_diff(&self, _: &Self, _: &mut Differ) -> Option<Difference> {
+        None
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for Expr {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        match (&self.kind, &other.kind) {
+            (
+                ExprKind::BinaryExpr { op, lhs, rhs },
+                ExprKind::BinaryExpr {
+                    op: other_op,
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            ) if op.kind == other_op.kind => differ
+                .child(".lhs", lhs, other_lhs)
+                .or_else(|| differ.child(".rhs", rhs, other_rhs)),
+            (
+                ExprKind::UnaryExpr { op, expr },
+                ExprKind::UnaryExpr {
+                    op: other_op,
+                    expr: other_expr,
+                },
+            ) if op.kind == other_op.kind => differ.child(".expr", expr, other_expr),
+            (
+                ExprKind::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                ExprKind::Range {
+                    start: other_start,
+                    end: other_end,
+                    inclusive: other_inclusive,
+                },
+            ) if inclusive == other_inclusive => differ
+                .child(".start", start, other_start)
+                .or_else(|| differ.child(".end", end, other_end)),
+            (
+                ExprKind::Section { op, operand, side },
+                ExprKind::Section {
+                    op: other_op,
+                    operand: other_operand,
+                    side: other_side,
+                },
+            ) if op.kind == other_op.kind && side == other_side => {
+                differ.child(".operand", operand, other_operand)
+            }
+            (ExprKind::Tuple(a), ExprKind::Tuple(b))
+            | (ExprKind::List(a), ExprKind::List(b))
+            | (ExprKind::Set(a), ExprKind::Set(b))
+            | (ExprKind::Sequence(a), ExprKind::Sequence(b)) => a.children_diff(b, differ),
+            (ExprKind::Map(entries), ExprKind::Map(other)) => entries
+                .iter()
+                .zip(other)
+                .enumerate()
+                .find_map(|(index, ((key, value), (other_key, other_value)))| {
+                    differ
+                        .child(&format!("[{}].key", index), key, other_key)
+                        .or_else(|| differ.child(&format!("[{}].value", index), value, other_value))
+                }),
+            (ExprKind::Record { fields }, ExprKind::Record { fields: other }) => {
+                fields.children_diff(other, differ)
+            }
+            (
+                ExprKind::Conditional {
+                    condition,
+                    body,
+                    otherwise,
+                },
+                ExprKind::Conditional {
+                    condition: other_condition,
+                    body: other_body,
+                    otherwise: other_otherwise,
+                },
+            ) => differ
+                .child(".condition", condition, other_condition)
+                .or_else(|| differ.child(".body", body, other_body))
+                .or_else(|| differ.child(".otherwise", otherwise, other_otherwise)),
+            (
+                ExprKind::Match { value, arms },
+                ExprKind::Match {
+                    value: other_value,
+                    arms: other_arms,
+                },
+            ) => differ
+                .child(".value", value, other_value)
+                .or_else(|| differ.child(".arms", arms, other_arms)),
+            (
+                ExprKind::While { condition, body },
+                ExprKind::While {
+                    condition: other_condition,
+                    body: other_body,
+                },
+            ) => differ
+                .child(".condition", condition, other_condition)
+                .or_else(|| differ.child(".body", body, other_body)),
+            (
+                ExprKind::For {
+                    binding,
+                    iterable,
+                    body,
+                },
+                ExprKind::For {
+                    binding: other_binding,
+                    iterable: other_iterable,
+                    body: other_body,
+                },
+            ) => differ
+                .child(".binding", binding, other_binding)
+                .or_else(|| differ.child(".iterable", iterable, other_iterable))
+                .or_else(|| differ.child(".body", body, other_body)),
+            (
+                ExprKind::Lambda {
+                    params,
+                    ret_ty,
+                    body,
+                },
+                ExprKind::Lambda {
+                    params: other_params,
+                    ret_ty: other_ret_ty,
+                    body: other_body,
+                },
+            ) => differ
+                .child(".params", params, other_params)
+                .or_else(|| differ.child(".ret_ty", ret_ty, other_ret_ty))
+                .or_else(|| differ.child(".body", body, other_body)),
+            (
+                ExprKind::Call {
+                    function,
+                    arguments,
+                },
+                ExprKind::Call {
+                    function: other_function,
+                    arguments: other_arguments,
+                },
+            ) => differ
+                .child(".function", function, other_function)
+                .or_else(|| differ.child(".arguments", arguments, other_arguments)),
+            (ExprKind::Spread(expr), ExprKind::Spread(other))
+            | (ExprKind::Try(expr), ExprKind::Try(other)) => differ.child(".expr", expr, other),
+            (
+                ExprKind::Projection { tuple, index },
+                ExprKind::Projection {
+                    tuple: other_tuple,
+                    index: other_index,
+                },
+            ) if index == other_index => differ.child(".tuple", tuple, other_tuple),
+            (
+                ExprKind::Index { list, index },
+                ExprKind::Index {
+                    list: other_list,
+                    index: other_index,
+                },
+            ) => differ
+                .child(".list", list, other_list)
+                .or_else(|| differ.child(".index", index, other_index)),
+            (
+                ExprKind::Labelled { label, value },
+                ExprKind::Labelled {
+                    label: other_label,
+                    value: other_value,
+                },
+            ) => differ
+                .child(".label", label, other_label)
+                .or_else(|| differ.child(".value", value, other_value)),
+            _ => None,
+        }
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for MatchArm {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".pattern", &self.pattern, &other.pattern)
+            .or_else(|| differ.child(".expr", &self.expr, &other.expr))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for Pattern {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        match (&self.kind, &other.kind) {
+            (PatternKind::Tuple(a), PatternKind::Tuple(b)) => a.children_diff(b, differ),
+            (PatternKind::Record(a), PatternKind::Record(b)) => a.children_diff(b, differ),
+            (
+                PatternKind::Cons { lhs, rhs },
+                PatternKind::Cons {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            )
+            | (
+                PatternKind::Or { lhs, rhs },
+                PatternKind::Or {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            ) => differ
+                .child(".lhs", lhs, other_lhs)
+                .or_else(|| differ.child(".rhs", rhs, other_rhs)),
+            (
+                PatternKind::Prefix { prefix, rest },
+                PatternKind::Prefix {
+                    prefix: other_prefix,
+                    rest: other_rest,
+                },
+            ) if differ.interners.lhs.resolve(prefix)
+                == differ.interners.rhs.resolve(other_prefix) =>
+            {
+                differ.child(".rest", rest, other_rest)
+            }
+            _ => None,
+        }
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for LambdaParam {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        differ
+            .child(".parameter", &self.parameter, &other.parameter)
+            .or_else(|| differ.child(".ty", &self.ty, &other.ty))
+            .or_else(|| differ.child(".default", &self.default, &other.default))
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
+
+impl SyntaxDiff for Destructor {
+    fn children_diff(&self, other: &Self, differ: &mut Differ) -> Option<Difference> {
+        match (&self.kind, &other.kind) {
+            (DestructorKind::Tuple(a), DestructorKind::Tuple(b)) => a.children_diff(b, differ),
+            (DestructorKind::Record(a), DestructorKind::Record(b)) => a.children_diff(b, differ),
+            (
+                DestructorKind::Cons { lhs, rhs },
+                DestructorKind::Cons {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            ) => differ
+                .child(".lhs", lhs, other_lhs)
+                .or_else(|| differ.child(".rhs", rhs, other_rhs)),
+            _ => None,
+        }
+    }
+
+    fn render(&self, rodeo: &lasso::Rodeo) -> String {
+        printed(self, rodeo)
+    }
+}
@@ -0,0 +1,496 @@
+//! Structural equality of syntax trees.
+
+use kali_ast::{
+    Attribute, BinaryOp, Clause, Definition, Destructor, DestructorKind, ExportItem, ExportKind,
+    Expr, ExprKind, Function, Ident, ImportTree, ImportTreeKind, Item, ItemKind, LambdaParam,
+    LiteralKind, MatchArm, Module, Pattern, PatternKind, Type, TypeAlias, TypeKind, UnaryOp,
+};
+
+/// The interners owning the keys of the two trees being compared.
+#[derive(Clone, Copy)]
+pub struct Interners<'a> {
+    /// The interner of the left-hand tree.
+    pub lhs: &'a lasso::Rodeo,
+    /// The interner of the right-hand tree.
+    pub rhs: &'a lasso::Rodeo,
+}
+
+/// Structural equality between syntax trees.
+///
+/// Spans are ignored, and interned strings are compared by their resolved text, so two trees
+/// produced by different parses of equivalent source compare equal.
+pub trait SyntaxEq {
+    /// Returns whether `self` and `other` are structurally equal.
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool;
+}
+
+/// Returns whether two modules are structurally equal, resolving keys against each module's
+/// own cache.
+pub fn modules_eq(lhs: &Module, rhs: &Module) -> bool {
+    let interners = Interners {
+        lhs: &lhs.cache,
+        rhs: &rhs.cache,
+    };
+    lhs.items.syntax_eq(&rhs.items, interners)
+}
+
+impl<T: SyntaxEq> SyntaxEq for Vec<T> {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other)
+                .all(|(a, b)| a.syntax_eq(b, interners))
+    }
+}
+
+impl<T: SyntaxEq> SyntaxEq for Box<T> {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        (**self).syntax_eq(other, interners)
+    }
+}
+
+impl<T: SyntaxEq> SyntaxEq for Option<T> {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.syntax_eq(b, interners),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: SyntaxEq> SyntaxEq for indexmap::IndexMap<Ident, T> {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.len() == other.len()
+            && self.iter().zip(other).all(|((ka, va), (kb, vb))| {
+                ka.syntax_eq(kb, interners) && va.syntax_eq(vb, interners)
+            })
+    }
+}
+
+impl SyntaxEq for Ident {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        interners.lhs.resolve(&self.key) == interners.rhs.resolve(&other.key)
+    }
+}
+
+impl SyntaxEq for Item {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.visibility == other.visibility
+            && self.attributes.syntax_eq(&other.attributes, interners)
+            && match (&self.kind, &other.kind) {
+                (ItemKind::Import(a), ItemKind::Import(b)) => a.syntax_eq(b, interners),
+                (ItemKind::Export(ExportKind::List(a)), ItemKind::Export(ExportKind::List(b))) => {
+                    a.syntax_eq(b, interners)
+                }
+                (
+                    ItemKind::Export(ExportKind::Glob { path }),
+                    ItemKind::Export(ExportKind::Glob { path: other }),
+                ) => interners.lhs.resolve(path) == interners.rhs.resolve(other),
+                (ItemKind::TypeAlias(a), ItemKind::TypeAlias(b)) => a.syntax_eq(b, interners),
+                (ItemKind::Definition(a), ItemKind::Definition(b)) => a.syntax_eq(b, interners),
+                (ItemKind::Function(a), ItemKind::Function(b)) => a.syntax_eq(b, interners),
+                (ItemKind::Error, ItemKind::Error) => true,
+                _ => false,
+            }
+    }
+}
+
+impl SyntaxEq for Attribute {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.name.syntax_eq(&other.name, interners)
+            && self.arguments.syntax_eq(&other.arguments, interners)
+    }
+}
+
+impl SyntaxEq for ImportTree {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        match (&self.kind, &other.kind) {
+            (
+                ImportTreeKind::Item { name, alias },
+                ImportTreeKind::Item {
+                    name: other_name,
+                    alias: other_alias,
+                },
+            ) => name.syntax_eq(other_name, interners) && alias.syntax_eq(other_alias, interners),
+            (
+                ImportTreeKind::Segment { name, child },
+                ImportTreeKind::Segment {
+                    name: other_name,
+                    child: other_child,
+                },
+            ) => name.syntax_eq(other_name, interners) && child.syntax_eq(other_child, interners),
+            (ImportTreeKind::Glob, ImportTreeKind::Glob) => true,
+            (ImportTreeKind::List(a), ImportTreeKind::List(b)) => a.syntax_eq(b, interners),
+            _ => false,
+        }
+    }
+}
+
+impl SyntaxEq for ExportItem {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.name.syntax_eq(&other.name, interners) && self.alias.syntax_eq(&other.alias, interners)
+    }
+}
+
+impl SyntaxEq for TypeAlias {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.name.syntax_eq(&other.name, interners) && self.ty.syntax_eq(&other.ty, interners)
+    }
+}
+
+impl SyntaxEq for Type {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        match (&self.kind, &other.kind) {
+            (TypeKind::Primitive(a), TypeKind::Primitive(b)) => a == b,
+            (TypeKind::Named(a), TypeKind::Named(b)) => a.syntax_eq(b, interners),
+            (
+                TypeKind::Generic { name, arguments },
+                TypeKind::Generic {
+                    name: other_name,
+                    arguments: other_arguments,
+                },
+            ) => {
+                name.syntax_eq(other_name, interners)
+                    && arguments.syntax_eq(other_arguments, interners)
+            }
+            (TypeKind::Tuple(a), TypeKind::Tuple(b)) => a.syntax_eq(b, interners),
+            (TypeKind::List(a), TypeKind::List(b)) => a.syntax_eq(b, interners),
+            (TypeKind::Record(a), TypeKind::Record(b)) => a.syntax_eq(b, interners),
+            (TypeKind::Fn(a_params, a_ret), TypeKind::Fn(b_params, b_ret)) => {
+                a_params.syntax_eq(b_params, interners) && a_ret.syntax_eq(b_ret, interners)
+            }
+            (
+                TypeKind::Intersection { lhs, rhs },
+                TypeKind::Intersection {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            )
+            | (
+                TypeKind::Union { lhs, rhs },
+                TypeKind::Union {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            ) => lhs.syntax_eq(other_lhs, interners) && rhs.syntax_eq(other_rhs, interners),
+            _ => false,
+        }
+    }
+}
+
+impl SyntaxEq for Definition {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.name.syntax_eq(&other.name, interners) && self.expr.syntax_eq(&other.expr, interners)
+    }
+}
+
+impl SyntaxEq for Function {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.name.syntax_eq(&other.name, interners)
+            && self.clauses.syntax_eq(&other.clauses, interners)
+    }
+}
+
+impl SyntaxEq for Clause {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.patterns.syntax_eq(&other.patterns, interners)
+            && self.body.syntax_eq(&other.body, interners)
+    }
+}
+
+impl SyntaxEq for LiteralKind {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        match (self, other) {
+            (LiteralKind::Natural(a), LiteralKind::Natural(b)) => a == b,
+            (LiteralKind::Integer(a), LiteralKind::Integer(b)) => a == b,
+            (LiteralKind::Float(a), LiteralKind::Float(b)) => a.to_bits() == b.to_bits(),
+            (LiteralKind::Bool(a), LiteralKind::Bool(b)) => a == b,
+            (LiteralKind::String(a), LiteralKind::String(b)) => {
+                interners.lhs.resolve(a) == interners.rhs.resolve(b)
+            }
+            (LiteralKind::Bytes(a), LiteralKind::Bytes(b)) => a == b,
+            (LiteralKind::Unit, LiteralKind::Unit) => true,
+            _ => false,
+        }
+    }
+}
+
+impl SyntaxEq for BinaryOp {
+    fn syntax_eq(&self, other: &Self, _: Interners) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl SyntaxEq for UnaryOp {
+    fn syntax_eq(&self, other: &Self, _: Interners) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl SyntaxEq for Expr {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        match (&self.kind, &other.kind) {
+            (ExprKind::Var(a), ExprKind::Var(b)) => a.syntax_eq(b, interners),
+            (ExprKind::Literal(a), ExprKind::Literal(b)) => a.syntax_eq(b, interners),
+            (
+                ExprKind::BinaryExpr { op, lhs, rhs },
+                ExprKind::BinaryExpr {
+                    op: other_op,
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            ) => {
+                op.syntax_eq(other_op, interners)
+                    && lhs.syntax_eq(other_lhs, interners)
+                    && rhs.syntax_eq(other_rhs, interners)
+            }
+            (
+                ExprKind::UnaryExpr { op, expr },
+                ExprKind::UnaryExpr {
+                    op: other_op,
+                    expr: other_expr,
+                },
+            ) => op.syntax_eq(other_op, interners) && expr.syntax_eq(other_expr, interners),
+            (
+                ExprKind::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                ExprKind::Range {
+                    start: other_start,
+                    end: other_end,
+                    inclusive: other_inclusive,
+                },
+            ) => {
+                inclusive == other_inclusive
+                    && start.syntax_eq(other_start, interners)
+                    && end.syntax_eq(other_end, interners)
+            }
+            (
+                ExprKind::Section { op, operand, side },
+                ExprKind::Section {
+                    op: other_op,
+                    operand: other_operand,
+                    side: other_side,
+                },
+            ) => {
+                side == other_side
+                    && op.syntax_eq(other_op, interners)
+                    && operand.syntax_eq(other_operand, interners)
+            }
+            (ExprKind::Tuple(a), ExprKind::Tuple(b))
+            | (ExprKind::List(a), ExprKind::List(b))
+            | (ExprKind::Set(a), ExprKind::Set(b))
+            | (ExprKind::Sequence(a), ExprKind::Sequence(b)) => a.syntax_eq(b, interners),
+            (ExprKind::Map(entries), ExprKind::Map(other)) => {
+                entries.len() == other.len()
+                    && entries
+                        .iter()
+                        .zip(other)
+                        .all(|((key, value), (other_key, other_value))| {
+                            key.syntax_eq(other_key, interners)
+                                && value.syntax_eq(other_value, interners)
+                        })
+            }
+            (ExprKind::Record { fields }, ExprKind::Record { fields: other }) => {
+                fields.syntax_eq(other, interners)
+            }
+            (
+                ExprKind::Conditional {
+                    condition,
+                    body,
+                    otherwise,
+                },
+                ExprKind::Conditional {
+                    condition: other_condition,
+                    body: other_body,
+                    otherwise: other_otherwise,
+                },
+            ) => {
+                condition.syntax_eq(other_condition, interners)
+                    && body.syntax_eq(other_body, interners)
+                    && otherwise.syntax_eq(other_otherwise, interners)
+            }
+            (
+                ExprKind::Match { value, arms },
+                ExprKind::Match {
+                    value: other_value,
+                    arms: other_arms,
+                },
+            ) => value.syntax_eq(other_value, interners) && arms.syntax_eq(other_arms, interners),
+            (
+                ExprKind::While { condition, body },
+                ExprKind::While {
+                    condition: other_condition,
+                    body: other_body,
+                },
+            ) => {
+                condition.syntax_eq(other_condition, interners)
+                    && body.syntax_eq(other_body, interners)
+            }
+            (
+                ExprKind::For {
+                    binding,
+                    iterable,
+                    body,
+                },
+                ExprKind::For {
+                    binding: other_binding,
+                    iterable: other_iterable,
+                    body: other_body,
+                },
+            ) => {
+                binding.syntax_eq(other_binding, interners)
+                    && iterable.syntax_eq(other_iterable, interners)
+                    && body.syntax_eq(other_body, interners)
+            }
+            (
+                ExprKind::Lambda {
+                    params,
+                    ret_ty,
+                    body,
+                },
+                ExprKind::Lambda {
+                    params: other_params,
+                    ret_ty: other_ret_ty,
+                    body: other_body,
+                },
+            ) => {
+                params.syntax_eq(other_params, interners)
+                    && ret_ty.syntax_eq(other_ret_ty, interners)
+                    && body.syntax_eq(other_body, interners)
+            }
+            (
+                ExprKind::Call {
+                    function,
+                    arguments,
+                },
+                ExprKind::Call {
+                    function: other_function,
+                    arguments: other_arguments,
+                },
+            ) => {
+                function.syntax_eq(other_function, interners)
+                    && arguments.syntax_eq(other_arguments, interners)
+            }
+            (ExprKind::Spread(expr), ExprKind::Spread(other))
+            | (ExprKind::Try(expr), ExprKind::Try(other)) => expr.syntax_eq(other, interners),
+            (
+                ExprKind::Projection { tuple, index },
+                ExprKind::Projection {
+                    tuple: other_tuple,
+                    index: other_index,
+                },
+            ) => index == other_index && tuple.syntax_eq(other_tuple, interners),
+            (
+                ExprKind::Index { list, index },
+                ExprKind::Index {
+                    list: other_list,
+                    index: other_index,
+                },
+            ) => list.syntax_eq(other_list, interners) && index.syntax_eq(other_index, interners),
+            (
+                ExprKind::Labelled { label, value },
+                ExprKind::Labelled {
+                    label: other_label,
+                    value: other_value,
+                },
+            ) => label.syntax_eq(other_label, interners) && value.syntax_eq(other_value, interners),
+            (ExprKind::Hole(a), ExprKind::Hole(b)) => a.syntax_eq(b, interners),
+            (ExprKind::Error, ExprKind::Error) => true,
+            _ => false,
+        }
+    }
+}
+
+impl SyntaxEq for MatchArm {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.pattern.syntax_eq(&other.pattern, interners)
+            && self.expr.syntax_eq(&other.expr, interners)
+    }
+}
+
+impl SyntaxEq for Pattern {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        match (&self.kind, &other.kind) {
+            (PatternKind::Literal(a), PatternKind::Literal(b)) => a.syntax_eq(b, interners),
+            (PatternKind::Var(a), PatternKind::Var(b)) => a.syntax_eq(b, interners),
+            (PatternKind::Tuple(a), PatternKind::Tuple(b)) => a.syntax_eq(b, interners),
+            (PatternKind::Record(a), PatternKind::Record(b)) => a.syntax_eq(b, interners),
+            (
+                PatternKind::Cons { lhs, rhs },
+                PatternKind::Cons {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            )
+            | (
+                PatternKind::Or { lhs, rhs },
+                PatternKind::Or {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            ) => lhs.syntax_eq(other_lhs, interners) && rhs.syntax_eq(other_rhs, interners),
+            (
+                PatternKind::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                PatternKind::Range {
+                    start: other_start,
+                    end: other_end,
+                    inclusive: other_inclusive,
+                },
+            ) => {
+                inclusive == other_inclusive
+                    && start.syntax_eq(other_start, interners)
+                    && end.syntax_eq(other_end, interners)
+            }
+            (
+                PatternKind::Prefix { prefix, rest },
+                PatternKind::Prefix {
+                    prefix: other_prefix,
+                    rest: other_rest,
+                },
+            ) => {
+                interners.lhs.resolve(prefix) == interners.rhs.resolve(other_prefix)
+                    && rest.syntax_eq(other_rest, interners)
+            }
+            (PatternKind::Wildcard, PatternKind::Wildcard)
+            | (PatternKind::EmptyList, PatternKind::EmptyList)
+            | (PatternKind::Rest, PatternKind::Rest) => true,
+            _ => false,
+        }
+    }
+}
+
+impl SyntaxEq for LambdaParam {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        self.parameter.syntax_eq(&other.parameter, interners)
+            && self.ty.syntax_eq(&other.ty, interners)
+            && self.default.syntax_eq(&other.default, interners)
+    }
+}
+
+impl SyntaxEq for Destructor {
+    fn syntax_eq(&self, other: &Self, interners: Interners) -> bool {
+        match (&self.kind, &other.kind) {
+            (DestructorKind::Var(a), DestructorKind::Var(b)) => a.syntax_eq(b, interners),
+            (DestructorKind::Tuple(a), DestructorKind::Tuple(b)) => a.syntax_eq(b, interners),
+            (DestructorKind::Record(a), DestructorKind::Record(b)) => a.syntax_eq(b, interners),
+            (
+                DestructorKind::Cons { lhs, rhs },
+                DestructorKind::Cons {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                },
+            ) => lhs.syntax_eq(other_lhs, interners) && rhs.syntax_eq(other_rhs, interners),
+            (DestructorKind::Rest, DestructorKind::Rest) => true,
+            _ => false,
+        }
+    }
+}
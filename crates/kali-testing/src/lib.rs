@@ -0,0 +1,77 @@
+//! Shared testing utilities for the Kali compiler crates.
+//!
+//! This crate provides [`proptest`] strategies that generate syntax trees, along with a
+//! structural equality check that ignores spans, so that properties such as
+//! `parse(print(ast)) == ast` can be asserted over arbitrary programs. When two trees are not
+//! equal, [`module_diff`] reports where they differ, which [`assert_modules_eq!`] and
+//! [`assert_parse_ok!`] include in their panic messages.
+
+use kali_ast::Module;
+use kali_print::{Context, Print};
+
+mod diff;
+mod eq;
+pub mod strategy;
+
+pub use diff::*;
+pub use eq::*;
+
+/// Prints a module to a string.
+///
+/// # Panics
+///
+/// Panics if the printer fails to write to the buffer.
+pub fn print(module: &Module) -> String {
+    let mut buf = Vec::new();
+    let mut ctx = Context::new(&mut buf, &module.cache);
+    module
+        .print(&mut ctx)
+        .expect("printing to a buffer cannot fail");
+    String::from_utf8(buf).expect("printer emitted invalid UTF-8")
+}
+
+/// Parses source code, panicking with every syntax error if it fails to parse.
+#[track_caller]
+pub fn parse_ok(src: &str) -> Module {
+    match kali_parse::parse_str(src) {
+        Ok(module) => module,
+        Err(errors) => {
+            let errors: Vec<_> = errors.iter().map(|error| error.to_string()).collect();
+            panic!("failed to parse:\n{}\n{}", src, errors.join("\n"))
+        }
+    }
+}
+
+/// Asserts that two modules are structurally equal, panicking with the first node at which they
+/// differ if they are not.
+///
+/// An optional format string and arguments are included in the panic message.
+#[macro_export]
+macro_rules! assert_modules_eq {
+    ($expected:expr, $found:expr $(,)?) => {
+        if let Some(difference) = $crate::module_diff($expected, $found) {
+            panic!("{}", difference);
+        }
+    };
+    ($expected:expr, $found:expr, $($arg:tt)+) => {
+        if let Some(difference) = $crate::module_diff($expected, $found) {
+            panic!("{}\n{}", format_args!($($arg)+), difference);
+        }
+    };
+}
+
+/// Asserts that source code parses without errors, evaluating to the parsed module.
+///
+/// Given an expected module, also asserts that the parsed module is structurally equal to it.
+#[macro_export]
+macro_rules! assert_parse_ok {
+    ($src:expr $(,)?) => {
+        $crate::parse_ok($src)
+    };
+    ($src:expr, $expected:expr $(,)?) => {{
+        let src: &str = $src;
+        let parsed = $crate::parse_ok(src);
+        $crate::assert_modules_eq!($expected, &parsed, "{}", src);
+        parsed
+    }};
+}
@@ -0,0 +1,521 @@
+//! [`proptest`] strategies for generating syntax trees.
+//!
+//! The generated trees are restricted to shapes the grammar can express: for example, no float
+//! literals, no record expressions, and no non-negative `Integer` literals (which print as, and
+//! re-parse to, naturals). Keys are drawn from the fixed [`IDENTS`] and [`STRINGS`] pools, which
+//! [`interner`] interns in a known order.
+
+use indexmap::IndexMap;
+use kali_ast::{
+    Attribute, BinaryOp, BinaryOpKind, Clause, Definition, Destructor, DestructorKind, ExportItem,
+    ExportKind, Expr, ExprKind, Function, Ident, ImportTree, ImportTreeKind, Item, ItemKind,
+    LambdaParam, LiteralKind, MatchArm, Module, Pattern, PatternKind, PrimitiveTypeKind, Side,
+    Type, TypeAlias, TypeKind, UnaryOp, UnaryOpKind, Visibility,
+};
+use kali_span::Span;
+use lasso::Key;
+use proptest::{collection::vec, option, prelude::*};
+
+/// The identifiers that generated trees may reference.
+pub const IDENTS: &[&str] = &["a", "b", "c", "foo", "bar", "baz", "x1", "_tmp"];
+
+/// The string literals that generated trees may contain.
+pub const STRINGS: &[&str] = &["", "hello", "hello world", "こんにちは", "#not a comment"];
+
+/// Binary operators accepted by the expression grammar.
+const BINARY_OPS: &[BinaryOpKind] = &[
+    BinaryOpKind::Add,
+    BinaryOpKind::Subtract,
+    BinaryOpKind::Multiply,
+    BinaryOpKind::Divide,
+    BinaryOpKind::Exponentiate,
+    BinaryOpKind::Modulo,
+    BinaryOpKind::Equal,
+    BinaryOpKind::NotEqual,
+    BinaryOpKind::LessThan,
+    BinaryOpKind::LessThanOrEqual,
+    BinaryOpKind::GreaterThan,
+    BinaryOpKind::GreaterThanOrEqual,
+    BinaryOpKind::BitwiseAnd,
+    BinaryOpKind::BitwiseXor,
+    BinaryOpKind::BitwiseOr,
+    BinaryOpKind::LogicalAnd,
+    BinaryOpKind::LogicalOr,
+    BinaryOpKind::Cons,
+    BinaryOpKind::Pipe,
+];
+
+/// Unary operators accepted by the expression grammar.
+///
+/// Unary plus is left out: the printer wraps operands in parentheses, where `(+x)` reads as an
+/// operator section.
+const UNARY_OPS: &[UnaryOpKind] = &[
+    UnaryOpKind::Negate,
+    UnaryOpKind::LogicalNot,
+    UnaryOpKind::BitwiseNot,
+];
+
+/// Creates an interner containing [`IDENTS`] followed by [`STRINGS`].
+pub fn interner() -> lasso::Rodeo {
+    let mut rodeo = lasso::Rodeo::new();
+    for s in IDENTS.iter().chain(STRINGS) {
+        rodeo.get_or_intern_static(s);
+    }
+    rodeo
+}
+
+/// The span given to every generated node.
+fn span() -> Span {
+    Span::default()
+}
+
+fn key(idx: usize) -> lasso::Spur {
+    lasso::Spur::try_from_usize(idx).expect("pool index out of range")
+}
+
+/// Generates an identifier from [`IDENTS`].
+pub fn ident() -> impl Strategy<Value = Ident> + Clone {
+    (0..IDENTS.len()).prop_map(|idx| Ident {
+        key: key(idx),
+        span: span(),
+    })
+}
+
+/// Generates a literal.
+pub fn literal() -> impl Strategy<Value = LiteralKind> + Clone {
+    prop_oneof![
+        any::<u64>().prop_map(LiteralKind::Natural),
+        (i64::MIN..0).prop_map(LiteralKind::Integer),
+        any::<bool>().prop_map(LiteralKind::Bool),
+        (0..STRINGS.len()).prop_map(|idx| LiteralKind::String(key(IDENTS.len() + idx))),
+        vec(any::<u8>(), 0..8).prop_map(LiteralKind::Bytes),
+        Just(LiteralKind::Unit),
+    ]
+}
+
+fn fields<T: std::fmt::Debug>(
+    inner: impl Strategy<Value = T>,
+) -> impl Strategy<Value = IndexMap<Ident, T>> {
+    vec((ident(), inner), 0..3).prop_map(IndexMap::from_iter)
+}
+
+fn ty_of(kind: TypeKind) -> Type {
+    Type { span: span(), kind }
+}
+
+/// Generates a type.
+pub fn ty() -> BoxedStrategy<Type> {
+    let leaf = prop_oneof![
+        prop_oneof![
+            Just(PrimitiveTypeKind::Integer),
+            Just(PrimitiveTypeKind::Natural),
+            Just(PrimitiveTypeKind::Float),
+            Just(PrimitiveTypeKind::Bool),
+            Just(PrimitiveTypeKind::String),
+            Just(PrimitiveTypeKind::Bytes),
+            Just(PrimitiveTypeKind::Unit),
+        ]
+        .prop_map(TypeKind::Primitive),
+        ident().prop_map(TypeKind::Named),
+    ]
+    .prop_map(ty_of);
+
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 2..4).prop_map(TypeKind::Tuple),
+            (ident(), vec(inner.clone(), 1..3))
+                .prop_map(|(name, arguments)| TypeKind::Generic { name, arguments }),
+            inner.clone().prop_map(|ty| TypeKind::List(Box::new(ty))),
+            fields(inner.clone()).prop_map(TypeKind::Record),
+            (vec(inner.clone(), 0..3), inner.clone())
+                .prop_map(|(params, ret)| TypeKind::Fn(params, Box::new(ret))),
+            (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)| TypeKind::Union {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            }),
+            (inner.clone(), inner).prop_map(|(lhs, rhs)| TypeKind::Intersection {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            }),
+        ]
+        .prop_map(ty_of)
+    })
+    .boxed()
+}
+
+fn pattern_of(kind: PatternKind) -> Pattern {
+    Pattern { span: span(), kind }
+}
+
+/// Generates a match pattern.
+pub fn pattern() -> BoxedStrategy<Pattern> {
+    let leaf = prop_oneof![
+        literal().prop_map(PatternKind::Literal),
+        ident().prop_map(PatternKind::Var),
+        Just(PatternKind::Wildcard),
+        Just(PatternKind::EmptyList),
+        (literal(), literal(), any::<bool>()).prop_map(|(start, end, inclusive)| {
+            PatternKind::Range {
+                start,
+                end,
+                inclusive,
+            }
+        }),
+    ]
+    .prop_map(pattern_of);
+
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 2..4).prop_map(PatternKind::Tuple),
+            fields(inner.clone()).prop_map(PatternKind::Record),
+            (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)| PatternKind::Cons {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            }),
+            (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)| PatternKind::Or {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            }),
+            (0..STRINGS.len(), inner).prop_map(|(idx, rest)| PatternKind::Prefix {
+                prefix: key(IDENTS.len() + idx),
+                rest: Box::new(rest),
+            }),
+        ]
+        .prop_map(pattern_of)
+    })
+    .boxed()
+}
+
+fn destructor_of(kind: DestructorKind) -> Destructor {
+    Destructor { span: span(), kind }
+}
+
+/// Generates a destructor.
+pub fn destructor() -> BoxedStrategy<Destructor> {
+    ident()
+        .prop_map(|ident| destructor_of(DestructorKind::Var(ident)))
+        .prop_recursive(3, 8, 3, |inner| {
+            prop_oneof![
+                vec(inner.clone(), 2..4).prop_map(DestructorKind::Tuple),
+                fields(inner).prop_map(DestructorKind::Record),
+            ]
+            .prop_map(destructor_of)
+        })
+        .boxed()
+}
+
+fn expr_of(kind: ExprKind) -> Expr {
+    Expr { span: span(), kind }
+}
+
+/// Returns whether an expression could also be read as a list of lambda parameters.
+fn is_destructor_like_expr(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Var(_) => true,
+        ExprKind::Tuple(exprs) => exprs.iter().all(is_destructor_like_expr),
+        _ => false,
+    }
+}
+
+/// Returns whether an expression could also be read as a lambda parameter with a type.
+///
+/// A call without arguments, such as `a()`, reads as the parameter `a` of the unit type.
+fn is_param_like_expr(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Call {
+            function,
+            arguments,
+        } if arguments.is_empty() => is_destructor_like_expr(function),
+        _ => is_destructor_like_expr(expr),
+    }
+}
+
+/// Returns whether a pattern could also be read as a lambda parameter.
+fn is_destructor_like_pattern(pattern: &Pattern) -> bool {
+    match &pattern.kind {
+        PatternKind::Var(_) => true,
+        PatternKind::Tuple(patterns) => patterns.iter().all(is_destructor_like_pattern),
+        PatternKind::Record(fields) => fields.values().all(is_destructor_like_pattern),
+        _ => false,
+    }
+}
+
+/// Returns the operand at the end of an expression's printed form.
+fn trailing_operand(expr: &Expr) -> &Expr {
+    match &expr.kind {
+        ExprKind::BinaryExpr { rhs: operand, .. } | ExprKind::UnaryExpr { expr: operand, .. } => {
+            trailing_operand(operand)
+        }
+        _ => expr,
+    }
+}
+
+/// Returns whether printing these arms would be misread by the parser.
+///
+/// An arm whose body ends in something that looks like a destructor, followed by an arm whose
+/// pattern does too, reads as a single arm with a lambda: `p -> a, b -> c` is `p -> (a, b -> c)`.
+fn arms_are_ambiguous(arms: &[MatchArm]) -> bool {
+    arms.windows(2).any(|pair| {
+        is_param_like_expr(trailing_operand(&pair[0].expr))
+            && is_destructor_like_pattern(&pair[1].pattern)
+    })
+}
+
+/// Generates an expression.
+pub fn expr() -> BoxedStrategy<Expr> {
+    let leaf = prop_oneof![
+        ident().prop_map(ExprKind::Var),
+        literal().prop_map(ExprKind::Literal),
+        ident().prop_map(ExprKind::Hole),
+    ]
+    .prop_map(expr_of);
+
+    leaf.prop_recursive(4, 48, 4, |inner| {
+        let binary = (
+            proptest::sample::select(BINARY_OPS),
+            inner.clone(),
+            inner.clone(),
+        )
+            .prop_map(|(kind, lhs, rhs)| ExprKind::BinaryExpr {
+                op: BinaryOp { kind, span: span() },
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            });
+        let unary =
+            (proptest::sample::select(UNARY_OPS), inner.clone()).prop_map(|(kind, expr)| {
+                ExprKind::UnaryExpr {
+                    op: UnaryOp { kind, span: span() },
+                    expr: Box::new(expr),
+                }
+            });
+        // `(- x)` is a negation, and pipelines have no sections
+        let section = (
+            proptest::sample::select(BINARY_OPS),
+            inner.clone(),
+            prop_oneof![Just(Side::Left), Just(Side::Right)],
+        )
+            .prop_filter("not a section", |(kind, _, side)| {
+                *kind != BinaryOpKind::Pipe
+                    && !(*kind == BinaryOpKind::Subtract && *side == Side::Right)
+            })
+            .prop_map(|(kind, operand, side)| ExprKind::Section {
+                op: BinaryOp { kind, span: span() },
+                operand: Box::new(operand),
+                side,
+            });
+        let range =
+            (inner.clone(), inner.clone(), any::<bool>()).prop_map(|(start, end, inclusive)| {
+                ExprKind::Range {
+                    start: Box::new(start),
+                    end: Box::new(end),
+                    inclusive,
+                }
+            });
+        let conditional = (inner.clone(), inner.clone(), option::of(inner.clone())).prop_map(
+            |(condition, body, otherwise)| ExprKind::Conditional {
+                condition: Box::new(condition),
+                body: Box::new(body),
+                otherwise: otherwise.map(Box::new),
+            },
+        );
+        let arm = (pattern(), inner.clone()).prop_map(|(pattern, expr)| MatchArm {
+            span: span(),
+            pattern,
+            expr,
+        });
+        // `match a {}` reads as a lambda parameter `a` of the empty record type.
+        let match_expr = (inner.clone(), vec(arm, 1..4))
+            .prop_filter("ambiguous match arms", |(_, arms)| {
+                !arms_are_ambiguous(arms)
+            })
+            .prop_map(|(value, arms)| ExprKind::Match {
+                value: Box::new(value),
+                arms,
+            });
+        let while_loop =
+            (inner.clone(), inner.clone()).prop_map(|(condition, body)| ExprKind::While {
+                condition: Box::new(condition),
+                body: Box::new(body),
+            });
+        let for_loop =
+            (destructor(), inner.clone(), inner.clone()).prop_map(|(binding, iterable, body)| {
+                ExprKind::For {
+                    binding,
+                    iterable: Box::new(iterable),
+                    body: Box::new(body),
+                }
+            });
+        let param = (
+            destructor(),
+            option::of(ty()),
+            option::weighted(0.2, inner.clone()),
+        )
+            .prop_map(|(parameter, ty, default)| LambdaParam {
+                parameter,
+                ty,
+                default,
+            });
+        let lambda =
+            (vec(param, 1..3), inner.clone()).prop_map(|(params, body)| ExprKind::Lambda {
+                params,
+                ret_ty: None,
+                body: Box::new(body),
+            });
+        // the elements of lists and argument lists may be spreads
+        let element = prop_oneof![
+            3 => inner.clone(),
+            1 => inner.clone().prop_map(|expr| expr_of(ExprKind::Spread(Box::new(expr)))),
+        ];
+        // and arguments may also be passed by label
+        let argument = prop_oneof![
+            3 => element.clone(),
+            1 => (ident(), inner.clone()).prop_map(|(label, value)| expr_of(ExprKind::Labelled {
+                label,
+                value: Box::new(value),
+            })),
+        ];
+        let call =
+            (inner.clone(), vec(argument, 0..3)).prop_map(|(function, arguments)| ExprKind::Call {
+                function: Box::new(function),
+                arguments,
+            });
+
+        prop_oneof![
+            vec(inner.clone(), 2..4).prop_map(ExprKind::Tuple),
+            vec(element, 0..4).prop_map(ExprKind::List),
+            vec(inner.clone(), 0..4).prop_map(ExprKind::Set),
+            vec((inner.clone(), inner.clone()), 0..3).prop_map(ExprKind::Map),
+            vec(inner.clone(), 2..4).prop_map(ExprKind::Sequence),
+            inner.clone().prop_map(|expr| ExprKind::Try(Box::new(expr))),
+            (inner.clone(), 0..4usize).prop_map(|(tuple, index)| ExprKind::Projection {
+                tuple: Box::new(tuple),
+                index,
+            }),
+            (inner.clone(), inner.clone()).prop_map(|(list, index)| ExprKind::Index {
+                list: Box::new(list),
+                index: Box::new(index),
+            }),
+            binary,
+            unary,
+            section,
+            range,
+            conditional,
+            match_expr,
+            while_loop,
+            for_loop,
+            lambda,
+            call,
+        ]
+        .prop_map(expr_of)
+    })
+    .boxed()
+}
+
+/// Generates an import tree.
+pub fn import_tree() -> BoxedStrategy<ImportTree> {
+    let leaf = prop_oneof![
+        (ident(), option::of(ident()))
+            .prop_map(|(name, alias)| ImportTreeKind::Item { name, alias }),
+        Just(ImportTreeKind::Glob),
+    ]
+    .prop_map(|kind| ImportTree { kind, span: span() });
+
+    leaf.prop_recursive(3, 8, 3, |inner| {
+        prop_oneof![
+            (ident(), inner.clone()).prop_map(|(name, child)| ImportTreeKind::Segment {
+                name,
+                child: Box::new(child),
+            }),
+            vec(inner, 0..3).prop_map(ImportTreeKind::List),
+        ]
+        .prop_map(|kind| ImportTree { kind, span: span() })
+    })
+    .boxed()
+}
+
+/// Generates an item attribute.
+pub fn attribute() -> impl Strategy<Value = Attribute> {
+    (ident(), vec(ident(), 0..3)).prop_map(|(name, arguments)| Attribute {
+        span: span(),
+        name,
+        arguments,
+    })
+}
+
+/// Generates the kind of an export statement.
+pub fn export() -> impl Strategy<Value = ExportKind> {
+    prop_oneof![
+        vec(
+            (ident(), option::of(ident())).prop_map(|(name, alias)| ExportItem { name, alias }),
+            0..3
+        )
+        .prop_map(ExportKind::List),
+        (0..STRINGS.len()).prop_map(|idx| ExportKind::Glob {
+            path: key(IDENTS.len() + idx)
+        }),
+    ]
+}
+
+/// Generates a function whose clauses all take the same number of arguments.
+pub fn function() -> impl Strategy<Value = Function> {
+    (1..3usize)
+        .prop_flat_map(|arity| {
+            let clause = (vec(pattern(), arity), expr()).prop_map(|(patterns, body)| Clause {
+                span: span(),
+                patterns,
+                body,
+            });
+            (ident(), vec(clause, 1..3))
+        })
+        .prop_map(|(name, clauses)| Function { name, clauses })
+}
+
+/// Generates a module item.
+pub fn item() -> impl Strategy<Value = Item> {
+    let kind = prop_oneof![
+        import_tree().prop_map(ItemKind::Import),
+        (ident(), ty()).prop_map(|(name, ty)| ItemKind::TypeAlias(TypeAlias { name, ty })),
+        (destructor(), expr())
+            .prop_map(|(name, expr)| ItemKind::Definition(Definition { name, expr })),
+        function().prop_map(ItemKind::Function),
+        export().prop_map(ItemKind::Export),
+    ];
+    (kind, any::<bool>(), vec(attribute(), 0..2)).prop_map(|(kind, exported, attributes)| Item {
+        span: span(),
+        // export statements cannot themselves be exported
+        visibility: match kind {
+            ItemKind::Export(_) => Visibility::Inherited,
+            _ if exported => Visibility::Exported,
+            _ => Visibility::Inherited,
+        },
+        kind,
+        attributes,
+    })
+}
+
+/// Returns the name of an item if it is a function.
+fn function_name(item: &Item) -> Option<lasso::Spur> {
+    match &item.kind {
+        ItemKind::Function(function) => Some(function.name.key),
+        _ => None,
+    }
+}
+
+/// Generates a module whose cache is [`interner`].
+///
+/// Functions are given distinct names, as the parser merges the clauses of functions with the
+/// same name.
+pub fn module() -> impl Strategy<Value = Module> {
+    vec(item(), 0..4)
+        .prop_filter("functions must have distinct names", |items| {
+            let mut names = items.iter().filter_map(function_name).collect::<Vec<_>>();
+            let count = names.len();
+            names.sort();
+            names.dedup();
+            names.len() == count
+        })
+        .prop_map(|items| Module {
+            items,
+            cache: interner(),
+        })
+}
@@ -0,0 +1,57 @@
+use kali_testing::{assert_modules_eq, assert_parse_ok, module_diff, parse_ok};
+
+/// Returns the first difference between the modules parsed from two sources, as displayed.
+fn diff(expected: &str, found: &str) -> Option<String> {
+    module_diff(&parse_ok(expected), &parse_ok(found)).map(|difference| difference.to_string())
+}
+
+#[test]
+fn test_equal_modules() {
+    // spans are ignored
+    assert_eq!(diff("let a = (x + 1)", "let a =    x+1"), None);
+    assert_modules_eq!(&parse_ok("let a = 1"), &parse_ok("let a = 1"));
+}
+
+#[test]
+fn test_innermost_difference() {
+    assert_eq!(
+        diff("let a = f(x + 1, y)", "let a = f(x + 2, y)").unwrap(),
+        "trees differ at `items[0].expr.arguments[0].rhs`\nexpected: 1\n   found: 2"
+    );
+    // nodes of different kinds differ themselves, rather than in their children
+    assert_eq!(
+        diff("let a = x -> (x + 1)", "let a = x -> (x - 1)").unwrap(),
+        "trees differ at `items[0].expr.body`\nexpected: x + 1\n   found: x - 1"
+    );
+    assert_eq!(
+        diff("let a = match x { [] -> 1 }", "let a = match x { _ -> 1 }").unwrap(),
+        "trees differ at `items[0].expr.arms[0].pattern`\nexpected: []\n   found: _"
+    );
+}
+
+#[test]
+fn test_length_difference() {
+    assert_eq!(
+        diff("let a = [1, 2]", "let a = [1, 2, 3]").unwrap(),
+        "trees differ at `items[0].expr`\nexpected: [1, 2]\n   found: [1, 2, 3]"
+    );
+    assert_eq!(
+        diff("let a = 1", "let a = 1;\nlet b = 2").unwrap(),
+        "trees differ at `items`\nexpected: [let a = 1]\n   found: [let a = 1, let b = 2]"
+    );
+}
+
+#[test]
+fn test_record_difference() {
+    assert_eq!(
+        diff("let {x: a, y: b} = r", "let {x: a, y: c} = r").unwrap(),
+        "trees differ at `items[0].name.y`\nexpected: b\n   found: c"
+    );
+}
+
+#[test]
+#[should_panic(expected = "trees differ at `items[0].expr.rhs`")]
+fn test_assert_parse_ok() {
+    let expected = assert_parse_ok!("let a = x * y");
+    assert_parse_ok!("let a = x * z", &expected);
+}
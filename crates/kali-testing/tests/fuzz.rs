@@ -0,0 +1,102 @@
+use std::thread;
+
+use proptest::{collection::vec, prelude::*, sample::select};
+
+/// Fragments of source text the lexer recognises, so that generated input reaches the parser.
+const FRAGMENTS: &[&str] = &[
+    "let", "type", "import", "match", "if", "else", "as", "int", "a", "b", "0", "-1", "0x",
+    "\"s\"", "()", "[]", "(", ")", "[", "]", "{", "}", ",", ":", ";", "::", "->", "=", "==", "<",
+    ">", "+", "-", "*", "**", "!", "~", "&", "|", "&&", "_", "#", " ", "\n",
+];
+
+/// Fragments that nest the source text after them a level deeper.
+const NESTERS: &[&str] = &[
+    "(", "[", "{", "#[", "f(", "a[", "-", "!", "~", "x ->", "a +", "a ::", "a |>", "if a {",
+];
+
+/// Generates source text made of [`FRAGMENTS`].
+fn token_soup() -> impl Strategy<Value = String> {
+    vec(select(FRAGMENTS), 0..48).prop_map(|fragments| fragments.join(" "))
+}
+
+/// Generates a definition nested up to a thousand levels deep, followed by source text made of
+/// [`FRAGMENTS`], which may close some of the levels.
+fn deep_soup() -> impl Strategy<Value = String> {
+    (vec(select(NESTERS), 0..1000), token_soup())
+        .prop_map(|(nesters, rest)| format!("let a = {} a {}", nesters.join(" "), rest))
+}
+
+/// Builds definitions nested `depth` levels deep in each way the passes of the compiler recurse.
+fn nestings(depth: usize) -> Vec<String> {
+    vec![
+        format!("let a = {}a{}", "(".repeat(depth), ")".repeat(depth)),
+        format!("let a = {}a{}", "[".repeat(depth), "]".repeat(depth)),
+        format!("let a = {}a{}", "{ ".repeat(depth), " }".repeat(depth)),
+        format!("let a = {}a", "x -> ".repeat(depth)),
+        format!("let a = {}a", "- ".repeat(depth)),
+        format!("let a = a{}", " - a".repeat(depth)),
+        format!("let a = a{} :: []", " :: a".repeat(depth)),
+        format!("let a = f{}", "(a)".repeat(depth)),
+        format!("let {}a{} = a", "(".repeat(depth), ")".repeat(depth)),
+        format!("type a = {}int", "int -> ".repeat(depth)),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn lex_never_panics(data in any::<Vec<u8>>(), src in token_soup()) {
+        kali_parse::fuzz_lex(&data);
+        kali_parse::fuzz_lex(src.as_bytes());
+    }
+
+    #[test]
+    fn parse_never_panics(data in any::<Vec<u8>>(), src in token_soup()) {
+        kali_parse::fuzz_parse(&data);
+        kali_parse::fuzz_parse(src.as_bytes());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn deep_input_compiles(src in deep_soup()) {
+        kali::Compiler::new().compile_str(&src);
+    }
+}
+
+#[test]
+fn nested_parentheses_parse() {
+    let depth = 64;
+    for src in [
+        format!("let a = {}a{}", "(".repeat(depth), ")".repeat(depth)),
+        format!("let {}a{} = a", "(".repeat(depth), ")".repeat(depth)),
+        format!("type a = {}int{}", "(".repeat(depth), ")".repeat(depth)),
+        format!(
+            "let a = match a {{ {}_{} -> a }}",
+            "(".repeat(depth),
+            ")".repeat(depth)
+        ),
+    ] {
+        assert!(kali_parse::parse_str(&src).is_ok(), "failed to parse {src}");
+    }
+}
+
+#[test]
+fn deep_nesting_compiles() {
+    // `kali check` compiles files on threads with small stacks, so every pass must either stay
+    // within them or reject the input before recursing into it
+    let compile = || {
+        for depth in [200, 1000] {
+            for src in nestings(depth) {
+                kali::Compiler::new().compile_str(&src);
+            }
+        }
+    };
+    thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(compile)
+        .unwrap()
+        .join()
+        .unwrap();
+}
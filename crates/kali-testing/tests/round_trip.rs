@@ -0,0 +1,16 @@
+use kali_testing::{module_diff, print, strategy};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn parse_print_round_trip(module in strategy::module()) {
+        let src = print(&module);
+        let parsed = kali_parse::parse_str(&src)
+            .map_err(|errors| TestCaseError::fail(format!("{src}\n{errors:?}")))?;
+        if let Some(difference) = module_diff(&module, &parsed) {
+            return Err(TestCaseError::fail(format!("round trip changed the tree:\n{src}\n{difference}")));
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use kali_parse::parse_str;
+use kali_type::TypeInferenceEngine;
+
+/// Definitions that each refer to the one after, so that every type is inferred through a chain
+/// of inference variables.
+fn chain(length: usize) -> String {
+    let mut src: Vec<_> = (0..length)
+        .map(|i| format!("let a{} = a{}", i, i + 1))
+        .collect();
+    src.push(format!("let a{} = 1", length));
+    src.join(";\n")
+}
+
+/// A lambda of many parameters, whose body combines all of them.
+fn nested(depth: usize) -> String {
+    let params: Vec<_> = (0..depth).map(|i| format!("x{}", i)).collect();
+    format!("let f = {} -> {}", params.join(", "), params.join(" + "))
+}
+
+/// Functions matching on a union, each arm of which is narrowed.
+fn narrowing(count: usize) -> String {
+    (0..count)
+        .map(|i| {
+            format!(
+                "let f{} = v (nat, nat) | [nat] | string -> match v {{ (a, b) -> [a, b], [] -> [], x :: xs -> xs, s -> [] }}",
+                i
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(";\n")
+}
+
+/// A long module of small functions calling one another.
+fn module(length: usize) -> String {
+    (0..length)
+        .map(|i| match i {
+            0 => "let f0 = (a, b) -> match a { (x, y) :: _ -> [x, (y), b], _ -> [] }".to_string(),
+            i => format!("let f{} = (a, b) -> f{}(a, b)", i, i - 1),
+        })
+        .collect::<Vec<_>>()
+        .join(";\n")
+}
+
+/// Matches on a list nested in the last arm of one another, each narrowing the list further.
+fn matches(depth: usize) -> String {
+    let mut src = "x".to_string();
+    for i in (0..depth).rev() {
+        src = format!("match xs {{ [] -> {}, x :: xs -> {} }}", i, src);
+    }
+    format!("let m = xs -> {}", src)
+}
+
+fn inference(c: &mut Criterion) {
+    let sources = [
+        ("chain", chain(1000)),
+        ("nested", nested(200)),
+        ("narrowing", narrowing(200)),
+        ("module", module(500)),
+        ("matches", matches(40)),
+    ];
+    for (name, src) in sources {
+        let module = parse_str(&src).unwrap();
+        c.bench_function(name, |b| b.iter(|| TypeInferenceEngine::infer(&module)));
+    }
+}
+
+criterion_group!(benches, inference);
+criterion_main!(benches);
@@ -1,478 +1,1349 @@
 //! Implements the type inferrence engine.
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    convert::Infallible,
+};
 
 use kali_ast::{
-    BinaryExpr, BinaryOp, Call, Conditional, Decl, Export, FuncDecl, FuncDeclParam, Identifier,
-    Import, ImportKind, Lambda, Literal, LiteralKind, Match, Module, Pattern, PatternKind,
-    Rewriter, TypeExpr, UnaryExpr,
+    self as ast, BinaryOpKind, Definition, Destructor, DestructorKind, ExportKind, Expr, ExprKind,
+    Ident, ImportTree, ImportTreeKind, Item, ItemKind, LiteralKind, Module, Pattern, PatternKind,
+    PrimitiveTypeKind, Side, TypeAlias, TypeKind, UnaryOpKind,
+    arguments::{
+        ArgumentError, Parameter, is_labelled, match_arguments, needs_matching, parameters,
+    },
+    pipe::{Placement, pipe},
+    visit::{Visitor, WalkOrder, Walker},
 };
-use kali_parse::Span;
+use kali_span::Span;
 use tracing::trace;
 
-use crate::{iter::TypeRefIterator, Constant, Context, Type, TypeInferenceError};
+use crate::{
+    Constant, Context, INTRINSICS, Kind, MAP, ModuleInterface, RANGE, SET, Suggestion, Type,
+    TypeInferenceError, closest, intrinsic, intrinsic_kind, map_of, range_of, result, set_of,
+    verify::verify,
+};
 
 /// The type inferrence engine.
-pub struct TypeInferenceEngine {
-    limit: usize,
+///
+/// Errors are collected rather than returned as soon as they occur. A node that fails to type
+/// check is given [`Type::Error`], which unifies with any other type, so that a single mistake
+/// does not cascade into further errors in the surrounding expression.
+pub struct TypeInferenceEngine<'a> {
+    /// The string cache of the module being inferred.
+    cache: &'a lasso::Rodeo,
+    /// The interfaces of the modules that may be imported, by path.
+    interfaces: &'a HashMap<String, ModuleInterface>,
+    /// The inference context.
+    ctx: Context,
+    /// The type aliases of the module, by name, lowered when they are first used.
+    definitions: HashMap<lasso::Spur, &'a TypeAlias>,
+    /// The type aliases lowered so far.
+    aliases: HashMap<lasso::Spur, Type>,
+    /// The kinds of the type constructors used so far, by name, which are fixed by their first
+    /// use.
+    kinds: HashMap<lasso::Spur, Kind>,
+    /// The type aliases being lowered, innermost last.
+    lowering: Vec<lasso::Spur>,
+    /// The type aliases being lowered that have been found to refer to themselves.
+    recursive: HashSet<lasso::Spur>,
+    /// Whether a glob import may have brought names into scope that we cannot see.
+    opaque: bool,
+    /// The variables standing for the shared inference variables of imported interfaces, by the
+    /// path of the module and the variable.
+    imported: HashMap<(String, usize), Type>,
+    /// The return types of the enclosing functions, innermost last, which `?` returns errors
+    /// from.
+    returns: Vec<Type>,
+    /// The types of the expressions inferred so far, innermost first, to check for ambiguity.
+    expressions: Vec<(Span, Type)>,
+    /// The errors encountered so far.
+    errors: Vec<TypeInferenceError>,
 }
 
-impl TypeInferenceEngine {
-    /// Infer the types of a module.
-    pub fn infer(module: Module<Span>) -> Result<Module<Meta>, TypeInferenceError> {
-        let mut context = Context::new();
-        Self::rewrite(&mut context, module)
-    }
+/// The inferred types of a module.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleTypes {
+    /// The types of the top-level bindings of the module, by name.
+    pub bindings: BTreeMap<String, Type>,
+    /// The types the type aliases of the module stand for, by name.
+    pub aliases: BTreeMap<String, Type>,
 }
 
-/// Meta information for a node.
-#[derive(Debug, Clone)]
-pub struct Meta {
-    /// The span of the node.
-    pub span: Span,
-    /// The type of the node.
-    pub ty: Type,
+impl ModuleTypes {
+    /// Returns the inference variables left unsolved in the types of more than one binding, which
+    /// must stand for the same type in each, so are not generalised.
+    pub fn shared(&self) -> BTreeSet<usize> {
+        let mut bindings: HashMap<usize, usize> = HashMap::new();
+        for ty in self.bindings.values() {
+            for id in ty.variables() {
+                *bindings.entry(id).or_default() += 1;
+            }
+        }
+        bindings
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .map(|(id, _)| id)
+            .collect()
+    }
 }
 
-impl Meta {
-    /// Create a new meta.
-    pub fn new(span: Span) -> Self {
-        Self {
-            span,
-            ty: Type::Never,
+impl TypeInferenceEngine<'_> {
+    /// Infer the types of a module.
+    ///
+    /// # Returns
+    /// The types of the module, or every error encountered. More than one error is reported as
+    /// [`TypeInferenceError::Multiple`].
+    pub fn infer(module: &Module) -> Result<ModuleTypes, TypeInferenceError> {
+        let (types, errors) = Self::infer_recovering(module);
+        match errors.into_iter().reduce(TypeInferenceError::combine) {
+            Some(error) => Err(error),
+            None => Ok(types),
         }
     }
 
-    /// Add a type to this meta.
-    pub fn with_ty(self, ty: Type) -> Self {
-        Self { ty, ..self }
+    /// Infer the types of a module, continuing past errors.
+    ///
+    /// # Returns
+    /// The types of the module, where bindings that failed to type check are [`Type::Error`],
+    /// alongside every error encountered in source order. Every hole is reported as an error,
+    /// [`TypeInferenceError::Hole`].
+    pub fn infer_recovering(module: &Module) -> (ModuleTypes, Vec<TypeInferenceError>) {
+        Self::infer_recovering_with(module, &HashMap::new())
     }
-}
 
-impl Rewriter<BinaryExpr<Span>, BinaryExpr<Meta>, Context, TypeInferenceError>
-    for TypeInferenceEngine
-{
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: BinaryExpr<Span>,
-    ) -> Result<BinaryExpr<Meta>, TypeInferenceError> {
-        trace!("Rewriting BinaryExpr");
-
-        let lhs = Self::rewrite(ctx, node.lhs)?;
-        let rhs = Self::rewrite(ctx, node.rhs)?;
-
-        // split on comparison operators
-        let ty = if matches!(
-            node.operator,
-            BinaryOp::Equal
-                | BinaryOp::NotEqual
-                | BinaryOp::LessThan
-                | BinaryOp::LessThanOrEqual
-                | BinaryOp::GreaterThan
-                | BinaryOp::GreaterThanOrEqual
-        ) {
-            Type::Constant(Constant::Bool)
-        } else {
-            lhs.meta().ty.unify(&rhs.meta().ty, ctx).map_err(|err| {
-                TypeInferenceError::UnificationFailed(
-                    lhs.meta().ty.clone(),
-                    rhs.meta().ty.clone(),
-                    err,
-                )
-            })?
+    /// Infer the types of a module, continuing past errors, given the interfaces of the modules
+    /// it may import by path, e.g. `a::b`. Names imported from other modules are of unknown type.
+    ///
+    /// # Returns
+    /// The types of the module and every error encountered, as with
+    /// [`infer_recovering`](Self::infer_recovering).
+    pub fn infer_recovering_with(
+        module: &Module,
+        interfaces: &HashMap<String, ModuleInterface>,
+    ) -> (ModuleTypes, Vec<TypeInferenceError>) {
+        let mut engine = TypeInferenceEngine {
+            cache: &module.cache,
+            interfaces,
+            ctx: Context::new(),
+            definitions: HashMap::new(),
+            aliases: HashMap::new(),
+            kinds: HashMap::new(),
+            lowering: Vec::new(),
+            recursive: HashSet::new(),
+            opaque: false,
+            imported: HashMap::new(),
+            returns: Vec::new(),
+            expressions: Vec::new(),
+            errors: Vec::new(),
         };
+        // declare the names of every item before inferring any of them, so that items may refer
+        // to those after them. Declaring in reverse leaves the first definition of a name visible
+        // until it is defined, and each item rebinds its names as it is inferred
+        let declared: Vec<_> = module
+            .items
+            .iter()
+            .rev()
+            .map(|item| engine.declare(item))
+            .collect();
+        for (item, ty) in module.items.iter().zip(declared.into_iter().rev()) {
+            engine.item(item, ty);
+        }
 
-        Ok(BinaryExpr {
-            meta: Meta::new(node.meta).with_ty(ty),
-            lhs,
-            rhs,
-            operator: node.operator,
-        })
+        // holes are reported with the types inferred for them by the end of the module
+        for error in &mut engine.errors {
+            if let TypeInferenceError::Spanned { error, .. } = error
+                && let TypeInferenceError::Hole {
+                    expected, bindings, ..
+                } = error.as_mut()
+            {
+                *expected = engine.ctx.resolve(expected);
+                for (_, ty) in bindings {
+                    *ty = engine.ctx.resolve(ty);
+                }
+            }
+        }
+
+        let known = engine.ctx.scope().known.clone();
+        let bindings: BTreeMap<_, _> = known
+            .into_iter()
+            .map(|(name, ty)| {
+                let ty = engine.ctx.resolve(&ty);
+                (name, ty)
+            })
+            .collect();
+
+        // a variable left unsolved that no binding refers to could be any type. Errors and names
+        // from glob imports also leave variables unsolved, so only check modules without them
+        let expressions = std::mem::take(&mut engine.expressions);
+        if engine.errors.is_empty() && !engine.opaque {
+            let mut reported: HashSet<_> = bindings.values().flat_map(Type::variables).collect();
+            for (span, ty) in &expressions {
+                let ty = engine.ctx.resolve(ty);
+                let variables = ty.variables();
+                // report each variable once, at the innermost expression it is the type of
+                let new = variables.into_iter().filter(|idx| reported.insert(*idx));
+                if new.count() > 0 {
+                    engine.error(TypeInferenceError::AmbiguousType(ty), *span);
+                }
+            }
+        }
+
+        // check the engine against its own invariants, which hold of any module that type checks
+        if cfg!(debug_assertions) && engine.errors.is_empty() {
+            let types = expressions
+                .iter()
+                .map(|(span, ty)| (*span, engine.ctx.resolve(ty)))
+                .collect();
+            engine.errors = verify(module, &types);
+        }
+        let aliases = engine
+            .aliases
+            .iter()
+            .map(|(key, ty)| (engine.cache.resolve(key).to_string(), ty.clone()))
+            .collect();
+        (ModuleTypes { bindings, aliases }, engine.errors)
     }
 }
 
-impl Rewriter<UnaryExpr<Span>, UnaryExpr<Meta>, Context, TypeInferenceError>
-    for TypeInferenceEngine
-{
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: UnaryExpr<Span>,
-    ) -> Result<UnaryExpr<Meta>, TypeInferenceError> {
-        trace!("Rewriting UnaryExpr");
-        let inner = Self::rewrite(ctx, node.inner)?;
-        Ok(UnaryExpr {
-            meta: Meta::new(node.meta).with_ty(inner.meta().ty.clone()),
-            inner,
-            operator: node.operator,
-        })
+impl<'a> TypeInferenceEngine<'a> {
+    /// Records an error at the given span, returning the error type to substitute for the node.
+    fn error(&mut self, error: TypeInferenceError, span: Span) -> Type {
+        self.errors.push(error.at(span));
+        Type::Error
     }
-}
 
-macro_rules! rewrite_literal_variant {
-    ($node:tt, $x:tt, $variant:ident) => {
-        Literal {
-            meta: Meta::new($node.meta).with_ty(Type::Constant(Constant::$variant)),
-            kind: LiteralKind::$variant($x),
+    /// Unifies two types, recording an error at the given span if they do not unify.
+    fn unify(&mut self, expected: &Type, found: &Type, span: Span) -> Type {
+        match expected.unify(found, &mut self.ctx) {
+            Ok(ty) => ty,
+            Err(err) => {
+                let error = TypeInferenceError::UnificationFailed(
+                    Box::new(self.ctx.resolve(expected)),
+                    Box::new(self.ctx.resolve(found)),
+                    err,
+                );
+                self.error(error, span)
+            }
         }
-    };
-}
+    }
 
-impl Rewriter<Literal<Span>, Literal<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: Literal<Span>,
-    ) -> Result<Literal<Meta>, TypeInferenceError> {
-        trace!("Rewriting Literal");
-        Ok(match node.kind {
-            LiteralKind::Natural(x) => rewrite_literal_variant!(node, x, Natural),
-            LiteralKind::Integer(x) => rewrite_literal_variant!(node, x, Integer),
-            LiteralKind::Float(x) => rewrite_literal_variant!(node, x, Float),
-            LiteralKind::Bool(x) => rewrite_literal_variant!(node, x, Bool),
-            LiteralKind::String(x) => rewrite_literal_variant!(node, x, String),
-            LiteralKind::Unit => Literal {
-                meta: Meta::new(node.meta).with_ty(Type::Constant(Constant::Unit)),
-                kind: LiteralKind::Unit,
-            },
-            LiteralKind::Array(exprs) => {
-                let exprs: Vec<_> = exprs
-                    .into_iter()
-                    .map(|expr| Self::rewrite(ctx, expr))
-                    .collect::<Result<_, _>>()?;
+    /// Records an error at the given span if values of type `ty` cannot be compared, so cannot be
+    /// the elements of a set or the keys of a map.
+    fn hashable(&mut self, ty: &Type, span: Span) {
+        let ty = self.ctx.resolve(ty);
+        if contains_function(&ty) {
+            self.error(TypeInferenceError::Unhashable(ty), span);
+        }
+    }
 
-                let ty = exprs.iter().map(|expr| &expr.meta().ty).fold_unify(ctx)?;
+    /// Records an error at the given span if values of type `ty` cannot be compared by `==` and
+    /// `!=`.
+    fn comparable(&mut self, ty: &Type, span: Span) {
+        let ty = self.ctx.resolve(ty);
+        if contains_function(&ty) {
+            self.error(TypeInferenceError::Incomparable(ty), span);
+        }
+    }
 
-                Literal {
-                    meta: Meta::new(node.meta).with_ty(Type::Array(Box::new(ty))),
-                    kind: LiteralKind::Array(exprs),
-                }
+    /// Records an error at the given span if values of type `ty` have no order, so cannot be
+    /// compared by `<`, `<=`, `>` and `>=`.
+    fn ordered(&mut self, ty: &Type, span: Span) {
+        let ty = self.ctx.resolve(ty);
+        if contains_unordered(&ty) {
+            self.error(TypeInferenceError::Unordered(ty), span);
+        }
+    }
+
+    /// Records an error at the given span if values of type `ty` are not whole numbers, so cannot
+    /// be the ends of a range.
+    fn countable(&mut self, ty: &Type, span: Span) {
+        match self.ctx.resolve(ty) {
+            Type::Constant(Constant::Natural | Constant::Integer)
+            | Type::Infer(_)
+            | Type::Error => {}
+            ty => {
+                self.error(TypeInferenceError::NonNumericRange(ty), span);
             }
-            LiteralKind::Tuple(exprs) => {
-                let exprs: Vec<_> = exprs
-                    .into_iter()
-                    .map(|expr| Self::rewrite(ctx, expr))
-                    .collect::<Result<_, _>>()?;
+        }
+    }
+
+    /// Records an error at the given span if values of type `ty` have no negative values, so
+    /// cannot be negated.
+    fn signed(&mut self, ty: &Type, span: Span) {
+        let ty = self.ctx.resolve(ty);
+        if ty == Type::Constant(Constant::Natural) {
+            self.error(TypeInferenceError::UnsignedNegation(ty), span);
+        }
+    }
+
+    /// Runs `f` in a new scope, which is popped once it returns, so that scopes cannot be left
+    /// unbalanced.
+    fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.ctx.push();
+        let result = f(self);
+        if let Err(error) = self.ctx.pop() {
+            self.errors.push(error);
+        }
+        result
+    }
+
+    /// Returns the name of an identifier.
+    fn name(&self, ident: &Ident) -> String {
+        self.cache.resolve(&ident.key).to_string()
+    }
+
+    /// Declares the names an item defines, before any item is inferred.
+    ///
+    /// # Returns
+    /// The type of the value the item defines, or `None` if it defines no value.
+    fn declare(&mut self, item: &'a Item) -> Option<Type> {
+        match &item.kind {
+            ItemKind::TypeAlias(alias) => {
+                self.definitions.insert(alias.name.key, alias);
+                None
+            }
+            ItemKind::Definition(definition) => {
+                let ty = self.ctx.declare_inferred();
+                self.bind(definition, &ty);
+                Some(ty)
+            }
+            ItemKind::Function(function) => {
+                let arity = function.clauses[0].patterns.len();
+                let params = (0..arity).map(|_| self.ctx.declare_inferred()).collect();
+                let ty = Type::Lambda(params, Box::new(self.ctx.declare_inferred()));
+                let name = self.name(&function.name);
+                self.ctx.declare_known(name, ty.clone());
+                Some(ty)
+            }
+            _ => None,
+        }
+    }
 
-                let ty = Type::Tuple(exprs.iter().map(|expr| expr.meta().ty.clone()).collect());
+    /// Binds the names of a definition to its type, along with its parameters if it is a lambda.
+    fn bind(&mut self, definition: &Definition, ty: &Type) {
+        self.destructor(&definition.name, ty);
+        if let (DestructorKind::Var(name), Some(parameters)) =
+            (&definition.name.kind, parameters(&definition.expr))
+        {
+            let name = self.name(name);
+            self.ctx.declare_parameters(name, parameters);
+        }
+    }
 
-                Literal {
-                    meta: Meta::new(node.meta).with_ty(ty),
-                    kind: LiteralKind::Tuple(exprs),
+    /// Infers the types of an item, given the type [`declare`](Self::declare) declared for it.
+    fn item(&mut self, item: &'a Item, declared: Option<Type>) {
+        trace!("Inferring Item");
+        let ty = declared.unwrap_or(Type::Error);
+        match &item.kind {
+            ItemKind::Import(tree) => self.import(tree, String::new()),
+            // exported names may be defined anywhere in the module, and may name types
+            ItemKind::Export(ExportKind::List(items)) => {
+                for export in items {
+                    if !self.definitions.contains_key(&export.name.key)
+                        && !self.aliases.contains_key(&export.name.key)
+                    {
+                        self.var(&export.name);
+                    }
                 }
             }
-            LiteralKind::Struct(btree_map) => {
-                let btree_map: std::collections::BTreeMap<_, _> = btree_map
-                    .into_iter()
-                    .map(|(k, v)| Ok((k, Self::rewrite(ctx, v)?)))
-                    .collect::<Result<_, _>>()?;
+            ItemKind::Export(ExportKind::Glob { .. }) => {}
+            ItemKind::TypeAlias(alias) => {
+                // rebind the alias, as an earlier definition of it may have been visible so far
+                self.definitions.insert(alias.name.key, alias);
+                self.aliases.remove(&alias.name.key);
+                self.alias(&alias.name);
+            }
+            ItemKind::Definition(definition) => {
+                // rebind the names, as an earlier definition of them may have been visible so far
+                self.bind(definition, &ty);
+                let expr = self.expr(&definition.expr);
+                self.unify(&ty, &expr, definition.expr.span);
+            }
+            ItemKind::Function(function) => {
+                // every clause takes arguments of the same types, and returns the same type
+                let name = self.name(&function.name);
+                self.ctx.declare_known(name, ty.clone());
+                let Type::Lambda(params, ret) = ty else {
+                    unreachable!("functions are declared as lambdas");
+                };
+                self.returns.push((*ret).clone());
+                for clause in &function.clauses {
+                    let body = self.scoped(|engine| {
+                        for (pattern, param) in clause.patterns.iter().zip(&params) {
+                            engine.pattern(pattern, param);
+                        }
+                        engine.expr(&clause.body)
+                    });
+                    self.unify(&ret, &body, clause.body.span);
+                }
+                self.returns.pop();
+            }
+            ItemKind::Error => {}
+        }
+    }
 
-                let ty = Type::Record(
-                    btree_map
-                        .iter()
-                        .map(|(k, v)| (k.clone(), v.meta().ty.clone()))
-                        .collect(),
-                );
+    /// Returns the type of a variable, recording an error if it is not in scope. Names in scope
+    /// shadow the intrinsics of the prelude.
+    fn var(&mut self, ident: &Ident) -> Type {
+        let name = self.name(ident);
+        if let Some(ty) = self.ctx.get_known(&name) {
+            return ty.clone();
+        }
+        match intrinsic(&name, &mut self.ctx) {
+            Some(ty) => ty,
+            None if self.opaque => self.ctx.declare_inferred(),
+            None => {
+                let candidates = self.ctx.names().chain(INTRINSICS);
+                let suggestion = closest(&name, candidates).map(|replacement| Suggestion {
+                    span: ident.span,
+                    replacement: replacement.to_string(),
+                });
+                let error = TypeInferenceError::UnknownIdentifier { name, suggestion };
+                self.error(error, ident.span)
+            }
+        }
+    }
 
-                Literal {
-                    meta: Meta::new(node.meta).with_ty(ty),
-                    kind: LiteralKind::Struct(btree_map),
+    fn import(&mut self, tree: &ImportTree, path: String) {
+        let interface = self.interfaces.get(&path);
+        match &tree.kind {
+            ImportTreeKind::Item { name, alias } => {
+                let bound = alias.as_ref().unwrap_or(name);
+                let Some(interface) = interface else {
+                    let ty = self.ctx.declare_inferred();
+                    self.ctx.declare_known(self.name(bound), ty);
+                    return;
+                };
+                let (value, ty) = (
+                    interface.values.get(&self.name(name)),
+                    interface.types.get(&self.name(name)),
+                );
+                if let Some(value) = value {
+                    let value = self.instantiate(&path, interface, value);
+                    self.ctx.declare_known(self.name(bound), value);
+                }
+                if let Some(ty) = ty {
+                    let ty = self.instantiate(&path, interface, ty);
+                    self.aliases.insert(bound.key, ty);
+                }
+                if value.is_none() && ty.is_none() {
+                    let error = TypeInferenceError::NotExported {
+                        module: path,
+                        name: self.name(name),
+                    };
+                    self.error(error, name.span);
+                    self.ctx.declare_known(self.name(bound), Type::Error);
                 }
             }
-        })
+            ImportTreeKind::Segment { name, child } => {
+                let path = match path.is_empty() {
+                    true => self.name(name),
+                    false => format!("{}::{}", path, self.name(name)),
+                };
+                self.import(child, path);
+            }
+            ImportTreeKind::Glob => match interface {
+                Some(interface) => {
+                    for (name, ty) in &interface.values {
+                        let ty = self.instantiate(&path, interface, ty);
+                        self.ctx.declare_known(name.clone(), ty);
+                    }
+                    // a type the module never names cannot be used by it
+                    for (name, ty) in &interface.types {
+                        if let Some(key) = self.cache.get(name) {
+                            let ty = self.instantiate(&path, interface, ty);
+                            self.aliases.insert(key, ty);
+                        }
+                    }
+                }
+                None => self.opaque = true,
+            },
+            ImportTreeKind::List(trees) => trees
+                .iter()
+                .for_each(|tree| self.import(tree, path.clone())),
+        }
     }
-}
 
-impl Rewriter<Identifier<Span>, Identifier<Meta>, Context, TypeInferenceError>
-    for TypeInferenceEngine
-{
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: Identifier<Span>,
-    ) -> Result<Identifier<Meta>, TypeInferenceError> {
-        trace!("Rewriting Identifier");
-        Ok(Identifier {
-            meta: Meta::new(node.meta).with_ty(ctx.get_known(&node.value).unwrap().clone()),
-            value: node.value,
+    /// Gives the inference variables of a type from the interface of an imported module fresh
+    /// variables. Its shared variables are given the same variables by every import of the
+    /// module.
+    fn instantiate(&mut self, path: &str, interface: &ModuleInterface, ty: &Type) -> Type {
+        let mut fresh = HashMap::new();
+        let ctx = &mut self.ctx;
+        ty.map_variables(&mut |id| match interface.shared.contains(&id) {
+            true => self
+                .imported
+                .entry((path.to_string(), id))
+                .or_insert_with(|| ctx.declare_inferred())
+                .clone(),
+            false => fresh
+                .entry(id)
+                .or_insert_with(|| ctx.declare_inferred())
+                .clone(),
         })
     }
-}
 
-impl Rewriter<Conditional<Span>, Conditional<Meta>, Context, TypeInferenceError>
-    for TypeInferenceEngine
-{
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: Conditional<Span>,
-    ) -> Result<Conditional<Meta>, TypeInferenceError> {
-        trace!("Rewriting Conditional");
-
-        let condition = Self::rewrite(ctx, node.condition)?;
-        let body = Self::rewrite(ctx, node.body)?;
-        let otherwise = Self::rewrite(ctx, node.otherwise)?;
-
-        // enforce boolean condition
-        condition
-            .meta()
-            .ty
-            .unify(&Type::Constant(Constant::Bool), ctx)
-            .map_err(|err| {
-                TypeInferenceError::UnificationFailed(
-                    condition.meta().ty.clone(),
-                    Type::Constant(Constant::Bool),
-                    err,
-                )
-            })?;
-
-        let ty = body
-            .meta()
-            .ty
-            .unify(&otherwise.meta().ty, ctx)
-            .map_err(|err| {
-                TypeInferenceError::UnificationFailed(
-                    body.meta().ty.clone(),
-                    otherwise.meta().ty.clone(),
-                    err,
-                )
-            })?;
-
-        Ok(Conditional {
-            meta: Meta::new(node.meta).with_ty(ty),
-            condition,
-            body,
-            otherwise,
-        })
+    /// Lowers a type annotation into a [`Type`].
+    fn lower(&mut self, ty: &ast::Type) -> Type {
+        match &ty.kind {
+            TypeKind::Primitive(primitive) => Type::Constant(match primitive {
+                PrimitiveTypeKind::Integer => Constant::Integer,
+                PrimitiveTypeKind::Natural => Constant::Natural,
+                PrimitiveTypeKind::Float => Constant::Float,
+                PrimitiveTypeKind::Bool => Constant::Bool,
+                PrimitiveTypeKind::String => Constant::String,
+                PrimitiveTypeKind::Bytes => Constant::Bytes,
+                PrimitiveTypeKind::Unit => Constant::Unit,
+            }),
+            TypeKind::Named(name) => match self.alias(name) {
+                Some(ty) => ty,
+                None => self.constructor(name, Vec::new()),
+            },
+            TypeKind::Generic { name, arguments } => {
+                let arguments = arguments.iter().map(|ty| self.lower(ty)).collect();
+                self.constructor(name, arguments)
+            }
+            TypeKind::Tuple(types) => Type::Tuple(types.iter().map(|ty| self.lower(ty)).collect()),
+            TypeKind::List(ty) => Type::Array(Box::new(self.lower(ty))),
+            TypeKind::Record(fields) => Type::Record(
+                fields
+                    .iter()
+                    .map(|(name, ty)| (self.name(name), self.lower(ty)))
+                    .collect(),
+            ),
+            TypeKind::Fn(params, ret) => Type::Lambda(
+                params.iter().map(|ty| self.lower(ty)).collect(),
+                Box::new(self.lower(ret)),
+            ),
+            TypeKind::Union { lhs, rhs } => Type::union([self.lower(lhs), self.lower(rhs)]),
+            TypeKind::Intersection { lhs, rhs } => {
+                Type::intersection([self.lower(lhs), self.lower(rhs)])
+            }
+        }
     }
-}
 
-impl Rewriter<Lambda<Span>, Lambda<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(ctx: &mut Context, node: Lambda<Span>) -> Result<Lambda<Meta>, TypeInferenceError> {
-        trace!("Rewriting Lambda");
-        todo!("lambda")
+    /// Applies a type constructor to some types, recording an error if it takes a different number
+    /// of types.
+    fn constructor(&mut self, name: &Ident, arguments: Vec<Type>) -> Type {
+        // type aliases do not take any types
+        let expected = match self.definitions.contains_key(&name.key) {
+            true => Kind(0),
+            false => *self.kinds.entry(name.key).or_insert_with(|| {
+                intrinsic_kind(self.cache.resolve(&name.key)).unwrap_or(Kind(arguments.len()))
+            }),
+        };
+        if expected.0 != arguments.len() {
+            let error = TypeInferenceError::KindMismatch {
+                name: self.name(name),
+                expected,
+                found: Kind(arguments.len()),
+            };
+            return self.error(error, name.span);
+        }
+        Type::Parameterized(self.name(name), arguments)
     }
-}
 
-impl Rewriter<Match<Span>, Match<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(ctx: &mut Context, node: Match<Span>) -> Result<Match<Meta>, TypeInferenceError> {
-        trace!("Rewriting Match");
-        let expr = Self::rewrite(ctx, node.expr)?;
-        let branches: HashMap<_, _> = node
-            .branches
-            .into_iter()
-            .map(|(pattern, expr)| {
-                let pattern = Self::rewrite(ctx, pattern)?;
-                let expr = Self::rewrite(ctx, expr)?;
+    /// Lowers the type alias of a name, which may refer to itself through type constructors.
+    ///
+    /// # Returns
+    /// The type the alias stands for, or `None` if no alias of that name is defined.
+    fn alias(&mut self, name: &Ident) -> Option<Type> {
+        if let Some(ty) = self.aliases.get(&name.key) {
+            return Some(ty.clone());
+        }
+        let alias = *self.definitions.get(&name.key)?;
+        if self.lowering.contains(&name.key) {
+            self.recursive.insert(name.key);
+            return Some(Type::RecursiveRef(self.name(name)));
+        }
+        self.lowering.push(name.key);
+        let body = self.lower(&alias.ty);
+        self.lowering.pop();
+        let ty = match self.recursive.remove(&name.key) {
+            true if unguarded(&body, &self.name(name)) => {
+                let error = TypeInferenceError::UnproductiveType(self.name(name));
+                self.error(error, alias.name.span)
+            }
+            true => Type::Recursive(self.name(name), Box::new(body)),
+            false => body,
+        };
+        // a type referring to an alias still being lowered is only the same within that alias
+        if !self
+            .lowering
+            .iter()
+            .any(|key| refers_to(&ty, self.cache.resolve(key)))
+        {
+            self.aliases.insert(name.key, ty.clone());
+        }
+        Some(ty)
+    }
 
-                Ok((pattern, expr))
-            })
-            .collect::<Result<_, _>>()?;
-
-        // unify all branches
-        let ty = branches
-            .values()
-            .map(|expr| &expr.meta().ty)
-            .fold_unify(ctx)?;
-
-        Ok(Match {
-            meta: Meta::new(node.meta).with_ty(ty),
-            expr,
-            branches,
+    fn literal(&self, literal: &LiteralKind) -> Type {
+        Type::Constant(match literal {
+            LiteralKind::Natural(_) => Constant::Natural,
+            LiteralKind::Integer(_) => Constant::Integer,
+            LiteralKind::Float(_) => Constant::Float,
+            LiteralKind::Bool(_) => Constant::Bool,
+            LiteralKind::String(_) => Constant::String,
+            LiteralKind::Bytes(_) => Constant::Bytes,
+            LiteralKind::Unit => Constant::Unit,
         })
     }
-}
 
-impl Rewriter<Call<Span>, Call<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(ctx: &mut Context, node: Call<Span>) -> Result<Call<Meta>, TypeInferenceError> {
-        trace!("Rewriting Call");
-        let callee = Self::rewrite(ctx, node.fun)?;
-        let args = node
-            .args
-            .into_iter()
-            .map(|arg| Self::rewrite(ctx, arg))
-            .collect::<Result<_, _>>()?;
+    /// Infers the type of an expression, recording it to check for ambiguity once the module is
+    /// inferred.
+    fn expr(&mut self, expr: &Expr) -> Type {
+        let ty = self.expr_type(expr);
+        self.expressions.push((expr.span, ty.clone()));
+        ty
+    }
 
-        Ok(Call {
-            meta: Meta::new(node.meta).with_ty(ctx.declare_inferred()),
-            fun: callee,
-            args,
-        })
+    /// Infers the type of an expression, without recording it.
+    fn expr_type(&mut self, expr: &Expr) -> Type {
+        trace!("Inferring Expr");
+        let bool = Type::Constant(Constant::Bool);
+        match &expr.kind {
+            ExprKind::Var(ident) => self.var(ident),
+            ExprKind::Literal(literal) => self.literal(literal),
+            // pipelines are typed as the calls they stand for
+            ExprKind::BinaryExpr { op, lhs, rhs } if op.kind == BinaryOpKind::Pipe => {
+                self.expr(&pipe((**lhs).clone(), (**rhs).clone(), Placement::Last))
+            }
+            ExprKind::BinaryExpr { op, lhs, rhs } => {
+                let lhs = (self.expr(lhs), lhs.span);
+                let rhs = (self.expr(rhs), rhs.span);
+                self.binary(op.kind, lhs, rhs, expr.span)
+            }
+            // sections are typed as lambdas of their missing operand
+            ExprKind::Section { op, operand, side } => {
+                let param = self.ctx.declare_inferred();
+                let operand = (self.expr(operand), operand.span);
+                let ty = match side {
+                    Side::Left => {
+                        self.binary(op.kind, operand, (param.clone(), expr.span), expr.span)
+                    }
+                    Side::Right => {
+                        self.binary(op.kind, (param.clone(), expr.span), operand, expr.span)
+                    }
+                };
+                Type::Lambda(vec![param], Box::new(ty))
+            }
+            ExprKind::UnaryExpr { op, expr: inner } => {
+                let ty = self.expr(inner);
+                match op.kind {
+                    UnaryOpKind::LogicalNot => self.unify(&bool, &ty, inner.span),
+                    UnaryOpKind::Negate => {
+                        self.signed(&ty, inner.span);
+                        ty
+                    }
+                    UnaryOpKind::UnaryPlus | UnaryOpKind::BitwiseNot => ty,
+                }
+            }
+            ExprKind::Tuple(exprs) => {
+                Type::Tuple(exprs.iter().map(|expr| self.expr(expr)).collect())
+            }
+            ExprKind::List(exprs) => self.list(exprs),
+            ExprKind::Set(exprs) => {
+                let mut element_ty = self.ctx.declare_inferred();
+                for element in exprs {
+                    let ty = self.expr(element);
+                    element_ty = self.unify(&element_ty, &ty, element.span);
+                }
+                self.hashable(&element_ty, expr.span);
+                set_of(element_ty)
+            }
+            ExprKind::Range { start, end, .. } => {
+                let start_ty = self.expr(start);
+                let end_ty = self.expr(end);
+                let ty = self.unify(&start_ty, &end_ty, end.span);
+                self.countable(&ty, expr.span);
+                range_of(ty)
+            }
+            ExprKind::Map(entries) => {
+                let (mut key_ty, mut value_ty) =
+                    (self.ctx.declare_inferred(), self.ctx.declare_inferred());
+                for (key, value) in entries {
+                    let ty = self.expr(key);
+                    key_ty = self.unify(&key_ty, &ty, key.span);
+                    let ty = self.expr(value);
+                    value_ty = self.unify(&value_ty, &ty, value.span);
+                }
+                self.hashable(&key_ty, expr.span);
+                map_of(key_ty, value_ty)
+            }
+            ExprKind::Record { fields } => Type::Record(
+                fields
+                    .iter()
+                    .map(|(name, expr)| (self.name(name), self.expr(expr)))
+                    .collect(),
+            ),
+            ExprKind::Conditional {
+                condition,
+                body,
+                otherwise,
+            } => {
+                let condition_ty = self.expr(condition);
+                self.unify(&bool, &condition_ty, condition.span);
+                let body_ty = self.expr(body);
+                match otherwise {
+                    Some(otherwise) => {
+                        let otherwise_ty = self.expr(otherwise);
+                        self.unify(&body_ty, &otherwise_ty, otherwise.span)
+                    }
+                    None => Type::Constant(Constant::Unit),
+                }
+            }
+            ExprKind::Match { value, arms } => {
+                let value_ty = self.expr(value);
+                // the members of a union left to match after each arm
+                let mut remaining = match self.ctx.resolve(&value_ty).unfold() {
+                    Type::Union(types) => Some(types),
+                    _ => None,
+                };
+                let mut ty = self.ctx.declare_inferred();
+                for arm in arms {
+                    let pattern_ty = match &mut remaining {
+                        Some(members) => self.narrow(&arm.pattern, members, &value_ty),
+                        None => value_ty.clone(),
+                    };
+                    let arm_ty = self.scoped(|engine| {
+                        engine.pattern(&arm.pattern, &pattern_ty);
+                        engine.expr(&arm.expr)
+                    });
+                    ty = self.unify(&ty, &arm_ty, arm.expr.span);
+                }
+                ty
+            }
+            ExprKind::Try(operand) => {
+                let (value, error) = (self.ctx.declare_inferred(), self.ctx.declare_inferred());
+                let operand_ty = self.expr(operand);
+                self.unify(
+                    &result(value.clone(), error.clone()),
+                    &operand_ty,
+                    operand.span,
+                );
+                // the error is returned from the enclosing function, whatever its value
+                match self.returns.last().cloned() {
+                    Some(ret) => {
+                        let returned = result(self.ctx.declare_inferred(), error);
+                        self.unify(&ret, &returned, expr.span);
+                    }
+                    None => {
+                        self.error(TypeInferenceError::TryOutsideFunction, expr.span);
+                    }
+                }
+                value
+            }
+            // the tuple must be known to have the element here, as its arity cannot be inferred
+            ExprKind::Projection { tuple, index } => {
+                let tuple_ty = self.expr(tuple);
+                match self.ctx.resolve(&tuple_ty).unfold() {
+                    Type::Tuple(mut elements) if *index < elements.len() => {
+                        elements.swap_remove(*index)
+                    }
+                    ty @ Type::Tuple(_) => self.error(
+                        TypeInferenceError::TupleIndexOutOfBounds { index: *index, ty },
+                        expr.span,
+                    ),
+                    Type::Error => Type::Error,
+                    ty @ Type::Infer(_) => {
+                        self.error(TypeInferenceError::AmbiguousType(ty), tuple.span)
+                    }
+                    ty => self.error(TypeInferenceError::NotATuple(ty), tuple.span),
+                }
+            }
+            // positions cannot be negative, so lists are indexed by `nat`
+            ExprKind::Index { list, index } => {
+                let element = self.ctx.declare_inferred();
+                let list_ty = self.expr(list);
+                self.unify(&Type::Array(Box::new(element.clone())), &list_ty, list.span);
+                let index_ty = self.expr(index);
+                self.unify(&Type::Constant(Constant::Natural), &index_ty, index.span);
+                element
+            }
+            ExprKind::Sequence(exprs) => {
+                let Some((last, exprs)) = exprs.split_last() else {
+                    return Type::Constant(Constant::Unit);
+                };
+                // the values of the expressions before the last are discarded, so must be `()`
+                for expr in exprs {
+                    let ty = self.expr(expr);
+                    self.unify(&Type::Constant(Constant::Unit), &ty, expr.span);
+                }
+                self.expr(last)
+            }
+            // like an `if` without an `else`, the value of the body is discarded
+            ExprKind::While { condition, body } => {
+                let condition_ty = self.expr(condition);
+                self.unify(&bool, &condition_ty, condition.span);
+                self.expr(body);
+                Type::Constant(Constant::Unit)
+            }
+            ExprKind::For {
+                binding,
+                iterable,
+                body,
+            } => {
+                let iterable_ty = self.expr(iterable);
+                // ranges are iterated over without being collected into a list, and strings
+                // character by character
+                let element = match self.ctx.resolve(&iterable_ty) {
+                    Type::Parameterized(name, mut args) if name == RANGE => args.remove(0),
+                    ty @ Type::Constant(Constant::String) => ty,
+                    _ => {
+                        let element = self.ctx.declare_inferred();
+                        self.unify(
+                            &Type::Array(Box::new(element.clone())),
+                            &iterable_ty,
+                            iterable.span,
+                        );
+                        element
+                    }
+                };
+                let body_ty = self.scoped(|engine| {
+                    engine.destructor(binding, &element);
+                    engine.expr(body)
+                });
+                Type::Array(Box::new(body_ty))
+            }
+            ExprKind::Lambda {
+                params,
+                ret_ty,
+                body,
+            } => {
+                // default values are substituted where the argument is left out, so they cannot
+                // refer to the other parameters of the lambda
+                let defaults: Vec<_> = params
+                    .iter()
+                    .map(|param| param.default.as_ref().map(|default| self.expr(default)))
+                    .collect();
+                self.scoped(|engine| {
+                    let params = params
+                        .iter()
+                        .zip(defaults)
+                        .map(|(param, default_ty)| {
+                            let mut ty = match &param.ty {
+                                Some(ty) => engine.lower(ty),
+                                None => engine.ctx.declare_inferred(),
+                            };
+                            if let (Some(default), Some(default_ty)) = (&param.default, default_ty)
+                            {
+                                ty = engine.unify(&ty, &default_ty, default.span);
+                            }
+                            engine.destructor(&param.parameter, &ty);
+                            ty
+                        })
+                        .collect();
+                    let ret = match ret_ty {
+                        Some(ret_ty) => engine.lower(ret_ty),
+                        None => engine.ctx.declare_inferred(),
+                    };
+                    engine.returns.push(ret.clone());
+                    let body_ty = engine.expr(body);
+                    engine.returns.pop();
+                    let body_ty = engine.unify(&ret, &body_ty, body.span);
+                    Type::Lambda(params, Box::new(body_ty))
+                })
+            }
+            ExprKind::Call {
+                function,
+                arguments,
+            } => {
+                let function_ty = self.expr(function);
+                let signature = match &function.kind {
+                    ExprKind::Var(ident) => {
+                        let name = self.name(ident);
+                        self.ctx
+                            .get_parameters(&name)
+                            .map(|parameters| (name, parameters.to_vec()))
+                    }
+                    _ => None,
+                };
+                let arguments = match signature {
+                    Some((name, parameters)) if needs_matching(&parameters, arguments) => {
+                        let Some(arguments) =
+                            self.matched_arguments(&name, &parameters, arguments, expr.span)
+                        else {
+                            return Type::Error;
+                        };
+                        arguments
+                    }
+                    _ if arguments.iter().any(is_labelled) => {
+                        let labelled = arguments.iter().find(|argument| is_labelled(argument));
+                        let span = labelled.map_or(expr.span, |argument| argument.span);
+                        self.error(TypeInferenceError::UnlabelledFunction, span);
+                        self.unmatched_arguments(arguments);
+                        return Type::Error;
+                    }
+                    _ => {
+                        // arguments from the first spread onwards are passed as a single list
+                        let (fixed, rest) = match arguments
+                            .iter()
+                            .position(|argument| matches!(argument.kind, ExprKind::Spread(_)))
+                        {
+                            Some(index) => arguments.split_at(index),
+                            None => (arguments.as_slice(), [].as_slice()),
+                        };
+                        let mut types: Vec<_> = fixed.iter().map(|expr| self.expr(expr)).collect();
+                        if !rest.is_empty() {
+                            types.push(self.list(rest));
+                        } else if let Some(closure) = self.partial(&function_ty, arguments, &types)
+                        {
+                            return closure;
+                        }
+                        types
+                    }
+                };
+                let ret = self.ctx.declare_inferred();
+                let expected = Type::Lambda(arguments, Box::new(ret.clone()));
+                match self.unify(&function_ty, &expected, expr.span) {
+                    Type::Error => Type::Error,
+                    _ => ret,
+                }
+            }
+            ExprKind::Spread(list) => {
+                self.expr(list);
+                self.error(TypeInferenceError::MisplacedSpread, expr.span)
+            }
+            ExprKind::Labelled { value, .. } => {
+                self.expr(value);
+                self.error(TypeInferenceError::MisplacedLabel, expr.span)
+            }
+            ExprKind::Hole(name) => {
+                let ty = self.ctx.declare_inferred();
+                let bindings = self
+                    .ctx
+                    .names()
+                    .filter(|name| !name.starts_with('$'))
+                    .map(|name| (name.to_string(), self.ctx.get_known(name).unwrap().clone()))
+                    .collect::<BTreeMap<_, _>>()
+                    .into_iter()
+                    .collect();
+                let error = TypeInferenceError::Hole {
+                    name: self.name(name),
+                    expected: ty.clone(),
+                    bindings,
+                };
+                self.errors.push(error.at(expr.span));
+                ty
+            }
+            ExprKind::Error => Type::Error,
+        }
     }
-}
 
-impl Rewriter<Import<Span>, Import<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(ctx: &mut Context, node: Import<Span>) -> Result<Import<Meta>, TypeInferenceError> {
-        trace!("Rewriting Import");
-        Ok(Import {
-            meta: Meta::new(node.meta),
-            kind: Self::rewrite(ctx, node.kind)?,
-        })
+    /// Infers the type of a binary operation from the types and spans of its operands.
+    fn binary(
+        &mut self,
+        op: BinaryOpKind,
+        lhs: (Type, Span),
+        rhs: (Type, Span),
+        span: Span,
+    ) -> Type {
+        let bool = Type::Constant(Constant::Bool);
+        let ((lhs_ty, lhs_span), (rhs_ty, rhs_span)) = (lhs, rhs);
+        match op {
+            BinaryOpKind::Equal | BinaryOpKind::NotEqual => {
+                let ty = self.unify(&lhs_ty, &rhs_ty, span);
+                self.comparable(&ty, span);
+                bool
+            }
+            BinaryOpKind::LessThan
+            | BinaryOpKind::LessThanOrEqual
+            | BinaryOpKind::GreaterThan
+            | BinaryOpKind::GreaterThanOrEqual => {
+                let ty = self.unify(&lhs_ty, &rhs_ty, span);
+                self.ordered(&ty, span);
+                bool
+            }
+            BinaryOpKind::LogicalAnd | BinaryOpKind::LogicalOr => {
+                self.unify(&bool, &lhs_ty, lhs_span);
+                self.unify(&bool, &rhs_ty, rhs_span);
+                bool
+            }
+            BinaryOpKind::Cons => self.unify(&Type::Array(Box::new(lhs_ty)), &rhs_ty, span),
+            _ => self.unify(&lhs_ty, &rhs_ty, span),
+        }
     }
-}
 
-impl Rewriter<ImportKind<Span>, ImportKind<Meta>, Context, TypeInferenceError>
-    for TypeInferenceEngine
-{
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: ImportKind<Span>,
-    ) -> Result<ImportKind<Meta>, TypeInferenceError> {
-        trace!("Rewriting ImportKind");
-        Ok(match node {
-            ImportKind::Named { symbols, path } => {
-                let symbols = symbols
-                    .into_iter()
-                    .map(|identifier| Self::rewrite(ctx, identifier))
-                    .collect::<Result<_, _>>()?;
-                ImportKind::Named { symbols, path }
-            }
-            ImportKind::Wildcard { path } => ImportKind::Wildcard { path },
-            ImportKind::NamedWildcard { alias, path } => ImportKind::NamedWildcard {
-                alias: Self::rewrite(ctx, alias)?,
-                path,
-            },
-        })
+    /// Infers the type of a list literal, whose elements may be spreads of other lists.
+    fn list(&mut self, exprs: &[Expr]) -> Type {
+        let mut ty = self.ctx.declare_inferred();
+        for expr in exprs {
+            let (element, span) = match &expr.kind {
+                ExprKind::Spread(list) => {
+                    let list_ty = self.expr(list);
+                    let element = self.ctx.declare_inferred();
+                    self.unify(&Type::Array(Box::new(element.clone())), &list_ty, list.span);
+                    (element, list.span)
+                }
+                _ => (self.expr(expr), expr.span),
+            };
+            ty = self.unify(&ty, &element, span);
+        }
+        Type::Array(Box::new(ty))
     }
-}
 
-impl Rewriter<Export<Span>, Export<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(ctx: &mut Context, node: Export<Span>) -> Result<Export<Meta>, TypeInferenceError> {
-        Ok(Export {
-            meta: Meta::new(node.meta),
-            symbols: node
-                .symbols
-                .into_iter()
-                .map(|symbol| Self::rewrite(ctx, symbol))
-                .collect::<Result<_, _>>()?,
-        })
+    /// Infers the types of the arguments of a call to a function with known parameters, which may
+    /// pass arguments by label or leave them out, recording an error if they do not match.
+    ///
+    /// # Returns
+    /// The type of the argument passed to each parameter, or `None` if the arguments do not
+    /// match the parameters.
+    fn matched_arguments(
+        &mut self,
+        function: &str,
+        parameters: &[Parameter],
+        arguments: &[Expr],
+        span: Span,
+    ) -> Option<Vec<Type>> {
+        let matched = match match_arguments(parameters, arguments) {
+            Ok(matched) => matched,
+            Err(error) => {
+                let (error, span) = match error {
+                    ArgumentError::PositionalAfterLabelled(span) => {
+                        (TypeInferenceError::PositionalAfterLabelled, span)
+                    }
+                    ArgumentError::UnknownLabel(label) => (
+                        TypeInferenceError::UnknownLabel {
+                            function: function.to_string(),
+                            label: self.name(&label),
+                        },
+                        label.span,
+                    ),
+                    ArgumentError::Duplicate(label) => (
+                        TypeInferenceError::DuplicateArgument(self.name(&label)),
+                        label.span,
+                    ),
+                    ArgumentError::TooMany { expected, span } => (
+                        TypeInferenceError::TooManyArguments {
+                            function: function.to_string(),
+                            expected,
+                        },
+                        span,
+                    ),
+                    ArgumentError::Spread(span) => (TypeInferenceError::LabelledSpread, span),
+                };
+                self.error(error, span);
+                self.unmatched_arguments(arguments);
+                return None;
+            }
+        };
+        let mut types = Vec::with_capacity(parameters.len());
+        let mut matches = true;
+        for (index, (argument, parameter)) in matched.into_iter().zip(parameters).enumerate() {
+            let describe = |cache: &lasso::Rodeo| match parameter.label {
+                Some(label) => format!("parameter `{}`", cache.resolve(&label)),
+                None => format!("parameter {}", index + 1),
+            };
+            match (argument, &parameter.default) {
+                (Some(argument), _) => types.push(self.expr(unlabelled(&arguments[argument]))),
+                (None, Some(default)) => {
+                    // the default value is substituted here, so must still refer to the same
+                    // top-level definitions it did where it was declared
+                    if let Some(name) = variables(default).into_iter().find(|name| {
+                        let name = self.cache.resolve(&name.key);
+                        self.ctx.scope[1..]
+                            .iter()
+                            .any(|scope| scope.known.contains_key(name))
+                    }) {
+                        let error = TypeInferenceError::ShadowedDefault {
+                            parameter: describe(self.cache),
+                            name: self.name(&name),
+                        };
+                        self.error(error, span);
+                        matches = false;
+                    }
+                    types.push(self.ctx.declare_inferred());
+                }
+                (None, None) => {
+                    let error = TypeInferenceError::MissingArgument(describe(self.cache));
+                    self.error(error, span);
+                    matches = false;
+                }
+            }
+        }
+        matches.then_some(types)
+    }
+
+    /// Infers the type of a partial application, which passes fewer arguments than the function
+    /// takes and evaluates to a closure taking the rest.
+    ///
+    /// Returns `None` if the function is not known to take more arguments than are passed.
+    fn partial(&mut self, function: &Type, arguments: &[Expr], types: &[Type]) -> Option<Type> {
+        let Type::Lambda(params, ret) = self.ctx.resolve(function) else {
+            return None;
+        };
+        if arguments.is_empty() || arguments.len() >= params.len() {
+            return None;
+        }
+        for ((param, ty), argument) in params.iter().zip(types).zip(arguments) {
+            self.unify(param, ty, argument.span);
+        }
+        Some(Type::Lambda(params[arguments.len()..].to_vec(), ret))
     }
-}
 
-impl Rewriter<TypeExpr<Span>, TypeExpr<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: TypeExpr<Span>,
-    ) -> Result<TypeExpr<Meta>, TypeInferenceError> {
-        trace!("Rewriting TypeExpr");
-        todo!("type expr")
+    /// Infers the types of the values of arguments that could not be matched to the parameters
+    /// of the function, so that errors within them are still reported.
+    fn unmatched_arguments(&mut self, arguments: &[Expr]) {
+        for argument in arguments {
+            match &argument.kind {
+                ExprKind::Labelled { value, .. } | ExprKind::Spread(value) => self.expr(value),
+                _ => self.expr(argument),
+            };
+        }
     }
-}
 
-impl Rewriter<Decl<Span>, Decl<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(ctx: &mut Context, node: Decl<Span>) -> Result<Decl<Meta>, TypeInferenceError> {
-        trace!("Rewriting Decl");
-        todo!("decl")
+    /// Binds the variables of a pattern matched against a value of type `ty`.
+    fn pattern(&mut self, pattern: &Pattern, ty: &Type) {
+        match &pattern.kind {
+            PatternKind::Literal(literal) => {
+                let literal = self.literal(literal);
+                self.unify(ty, &literal, pattern.span);
+            }
+            PatternKind::Var(ident) => {
+                let name = self.name(ident);
+                self.ctx.declare_known(name, ty.clone());
+            }
+            PatternKind::Tuple(patterns) => {
+                let types: Vec<_> = patterns
+                    .iter()
+                    .map(|_| self.ctx.declare_inferred())
+                    .collect();
+                // a rest pattern matches any number of elements, so the length is unknown
+                if !patterns
+                    .iter()
+                    .any(|pattern| matches!(pattern.kind, PatternKind::Rest))
+                {
+                    self.unify(ty, &Type::Tuple(types.clone()), pattern.span);
+                }
+                for (pattern, ty) in patterns.iter().zip(&types) {
+                    self.pattern(pattern, ty);
+                }
+            }
+            PatternKind::Record(fields) => {
+                let types: BTreeMap<_, _> = fields
+                    .keys()
+                    .map(|name| (self.name(name), self.ctx.declare_inferred()))
+                    .collect();
+                self.unify(ty, &Type::Record(types.clone()), pattern.span);
+                for (name, pattern) in fields {
+                    self.pattern(pattern, &types[&self.name(name)]);
+                }
+            }
+            PatternKind::Cons { lhs, rhs } => {
+                let element = self.ctx.declare_inferred();
+                self.unify(ty, &Type::Array(Box::new(element.clone())), pattern.span);
+                self.pattern(lhs, &element);
+                self.pattern(rhs, ty);
+            }
+            PatternKind::EmptyList => {
+                let element = self.ctx.declare_inferred();
+                self.unify(ty, &Type::Array(Box::new(element)), pattern.span);
+            }
+            PatternKind::Range { start, end, .. } => {
+                let (start, end) = (self.literal(start), self.literal(end));
+                let ty = self.unify(ty, &start, pattern.span);
+                let ty = self.unify(&ty, &end, pattern.span);
+                self.countable(&ty, pattern.span);
+            }
+            PatternKind::Prefix { rest, .. } => {
+                let string = Type::Constant(Constant::String);
+                let ty = self.unify(ty, &string, pattern.span);
+                self.pattern(rest, &ty);
+            }
+            PatternKind::Or { lhs, rhs } => {
+                self.pattern(lhs, ty);
+                self.pattern(rhs, ty);
+            }
+            PatternKind::Wildcard | PatternKind::Rest => {}
+        }
     }
-}
 
-impl Rewriter<FuncDecl<Span>, FuncDecl<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: FuncDecl<Span>,
-    ) -> Result<FuncDecl<Meta>, TypeInferenceError> {
-        trace!("Rewriting FuncDecl");
-        let params: Vec<_> = node
-            .params
-            .into_iter()
-            .map(|param| Self::rewrite(ctx, param))
-            .collect::<Result<_, _>>()?;
+    /// Narrows a union to the members a pattern can match, of those left to match, and removes
+    /// the members the pattern matches every value of.
+    ///
+    /// # Returns
+    /// The union of the members the pattern can match, or `union` if it can match none of them, so
+    /// that the mismatch is reported against the whole union.
+    fn narrow(&mut self, pattern: &Pattern, members: &mut Vec<Type>, union: &Type) -> Type {
+        let matched: Vec<_> = members
+            .iter()
+            .filter(|ty| self.fits(pattern, ty))
+            .cloned()
+            .collect();
+        if matched.is_empty() {
+            return union.clone();
+        }
+        if covers(pattern) {
+            members.retain(|ty| !matched.contains(ty));
+        }
+        Type::union(matched)
+    }
 
-        ctx.push();
+    /// Returns whether a pattern can match a value of type `ty`, without binding its variables
+    /// or recording any errors.
+    fn fits(&mut self, pattern: &Pattern, ty: &Type) -> bool {
+        let snapshot = self.ctx.snapshot();
+        let errors = self.errors.len();
+        self.scoped(|engine| engine.pattern(pattern, ty));
+        let fits = self.errors.len() == errors;
+        self.errors.truncate(errors);
+        self.ctx.rollback_to(snapshot);
+        fits
+    }
 
-        for param in &params {
-            let ty = match &param.ty {
-                Some(ty) => ty.into(),
-                None => ctx.declare_inferred(),
-            };
-            ctx.declare_known(param.name.value.clone(), ty);
+    /// Binds the variables of a destructor applied to a value of type `ty`.
+    fn destructor(&mut self, destructor: &Destructor, ty: &Type) {
+        match &destructor.kind {
+            DestructorKind::Var(ident) => {
+                let name = self.name(ident);
+                self.ctx.declare_known(name, ty.clone());
+            }
+            DestructorKind::Tuple(destructors) => {
+                let types: Vec<_> = destructors
+                    .iter()
+                    .map(|_| self.ctx.declare_inferred())
+                    .collect();
+                if !destructors
+                    .iter()
+                    .any(|destructor| matches!(destructor.kind, DestructorKind::Rest))
+                {
+                    self.unify(ty, &Type::Tuple(types.clone()), destructor.span);
+                }
+                for (destructor, ty) in destructors.iter().zip(&types) {
+                    self.destructor(destructor, ty);
+                }
+            }
+            DestructorKind::Record(fields) => {
+                let types: BTreeMap<_, _> = fields
+                    .keys()
+                    .map(|name| (self.name(name), self.ctx.declare_inferred()))
+                    .collect();
+                self.unify(ty, &Type::Record(types.clone()), destructor.span);
+                for (name, destructor) in fields {
+                    self.destructor(destructor, &types[&self.name(name)]);
+                }
+            }
+            DestructorKind::Cons { lhs, rhs } => {
+                let element = self.ctx.declare_inferred();
+                self.unify(ty, &Type::Array(Box::new(element.clone())), destructor.span);
+                self.destructor(lhs, &element);
+                self.destructor(rhs, ty);
+            }
+            DestructorKind::Rest => {}
         }
+    }
+}
 
-        let body = Self::rewrite(ctx, node.body)?;
+/// Returns the value of an argument, without its label.
+fn unlabelled(argument: &Expr) -> &Expr {
+    match &argument.kind {
+        ExprKind::Labelled { value, .. } => value,
+        _ => argument,
+    }
+}
 
-        ctx.pop();
+/// Returns whether the recursive type `name` is referred to in `ty` other than through a type
+/// constructor, so that it would stand for itself.
+fn unguarded(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::RecursiveRef(other) => other == name,
+        Type::Union(types) | Type::Intersection(types) => {
+            types.iter().any(|ty| unguarded(ty, name))
+        }
+        _ => false,
+    }
+}
 
-        Ok(FuncDecl {
-            meta: Meta::new(node.meta).with_ty(Type::Lambda(
-                params.iter().map(|param| param.meta.ty.clone()).collect(),
-                Box::new(body.meta().ty.clone()),
-            )),
-            name: Self::rewrite(ctx, node.name)?,
-            params,
-            body,
-            ret_ty: node.ret_ty.map(|ty| Self::rewrite(ctx, ty)).transpose()?,
-        })
+/// Returns whether the recursive type `name` is referred to in `ty`, outside of a recursive type
+/// of the same name.
+fn refers_to(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::RecursiveRef(other) => other == name,
+        Type::Recursive(other, body) => other != name && refers_to(body, name),
+        Type::Array(ty) => refers_to(ty, name),
+        Type::Tuple(types)
+        | Type::Parameterized(_, types)
+        | Type::Union(types)
+        | Type::Intersection(types) => types.iter().any(|ty| refers_to(ty, name)),
+        Type::Record(fields) => fields.values().any(|ty| refers_to(ty, name)),
+        Type::Lambda(params, ret) => {
+            params.iter().any(|ty| refers_to(ty, name)) || refers_to(ret, name)
+        }
+        Type::Constant(_) | Type::Infer(_) | Type::Never | Type::Error => false,
     }
 }
 
-impl Rewriter<FuncDeclParam<Span>, FuncDeclParam<Meta>, Context, TypeInferenceError>
-    for TypeInferenceEngine
-{
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: FuncDeclParam<Span>,
-    ) -> Result<FuncDeclParam<Meta>, TypeInferenceError> {
-        trace!("Rewriting FuncDeclParam");
-
-        let type_expr = node.ty.map(|ty| Self::rewrite(ctx, ty)).transpose()?;
-        let ty = type_expr.clone();
-
-        Ok(FuncDeclParam {
-            meta: Meta::new(node.meta),
-            name: Self::rewrite(ctx, node.name)?,
-            ty: type_expr,
-        })
+/// Returns whether a resolved type has functions among its values, which cannot be compared.
+fn contains_function(ty: &Type) -> bool {
+    match ty {
+        Type::Lambda(..) => true,
+        Type::Array(ty) | Type::Recursive(_, ty) => contains_function(ty),
+        Type::Tuple(types)
+        | Type::Parameterized(_, types)
+        | Type::Union(types)
+        | Type::Intersection(types) => types.iter().any(contains_function),
+        Type::Record(fields) => fields.values().any(contains_function),
+        Type::Infer(_) | Type::Constant(_) | Type::RecursiveRef(_) | Type::Never | Type::Error => {
+            false
+        }
     }
 }
 
-impl Rewriter<Pattern<Span>, Pattern<Meta>, Context, TypeInferenceError> for TypeInferenceEngine {
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: Pattern<Span>,
-    ) -> Result<Pattern<Meta>, TypeInferenceError> {
-        trace!("Rewriting Pattern");
-        Ok(Pattern {
-            meta: Meta::new(node.meta),
-            kind: Self::rewrite(ctx, node.kind)?,
-        })
+/// Returns whether a type has values with no order: functions, maps and sets, or any type built
+/// from them.
+fn contains_unordered(ty: &Type) -> bool {
+    match ty {
+        Type::Lambda(..) => true,
+        Type::Parameterized(name, _) if name == MAP || name == SET => true,
+        Type::Array(ty) | Type::Recursive(_, ty) => contains_unordered(ty),
+        Type::Tuple(types)
+        | Type::Parameterized(_, types)
+        | Type::Union(types)
+        | Type::Intersection(types) => types.iter().any(contains_unordered),
+        Type::Record(fields) => fields.values().any(contains_unordered),
+        Type::Infer(_) | Type::Constant(_) | Type::RecursiveRef(_) | Type::Never | Type::Error => {
+            false
+        }
     }
 }
 
-impl Rewriter<PatternKind<Span>, PatternKind<Meta>, Context, TypeInferenceError>
-    for TypeInferenceEngine
-{
-    #[tracing::instrument(skip_all)]
-    fn rewrite(
-        ctx: &mut Context,
-        node: PatternKind<Span>,
-    ) -> Result<PatternKind<Meta>, TypeInferenceError> {
-        trace!("Rewriting PatternKind");
-        Ok(match node {
-            PatternKind::Wildcard => PatternKind::Wildcard,
-            PatternKind::Literal(literal) => PatternKind::Literal(literal),
-            PatternKind::Tuple(patterns) => PatternKind::Tuple(
-                patterns
-                    .into_iter()
-                    .map(|pattern| Self::rewrite(ctx, pattern))
-                    .collect::<Result<_, _>>()?,
-            ),
-            PatternKind::EmptyList => PatternKind::EmptyList,
-            PatternKind::Cons(head, tail) => {
-                PatternKind::Cons(Self::rewrite(ctx, head)?, Self::rewrite(ctx, tail)?)
-            }
-            PatternKind::Ident(ident) => PatternKind::Ident(Self::rewrite(ctx, ident)?),
-        })
+/// Returns whether a pattern matches every value of the types it can match.
+fn covers(pattern: &Pattern) -> bool {
+    match &pattern.kind {
+        PatternKind::Var(_)
+        | PatternKind::Wildcard
+        | PatternKind::Rest
+        | PatternKind::Literal(LiteralKind::Unit) => true,
+        PatternKind::Tuple(patterns) => patterns.iter().all(covers),
+        PatternKind::Record(fields) => fields.values().all(covers),
+        PatternKind::Or { lhs, rhs } => covers(lhs) || covers(rhs),
+        PatternKind::Literal(_)
+        | PatternKind::Cons { .. }
+        | PatternKind::EmptyList
+        | PatternKind::Range { .. }
+        | PatternKind::Prefix { .. } => false,
+    }
+}
+
+/// Collects every variable an expression refers to, including those bound within it.
+fn variables(expr: &Expr) -> Vec<Ident> {
+    let mut variables = Variables(Vec::new());
+    let Ok(()) = Walker::new(WalkOrder::PreOrder).walk_expr(&mut variables, expr);
+    variables.0
+}
+
+/// Collects the variables referred to by the expressions it visits, in the order they are visited.
+struct Variables(Vec<Ident>);
+
+impl Visitor for Variables {
+    type Error = Infallible;
+
+    fn visit_expr(&mut self, expr: &Expr) -> Result<(), Infallible> {
+        if let ExprKind::Var(ident) = expr.kind {
+            self.0.push(ident);
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,87 @@
+//! The entry point of a program, the function it starts running at.
+
+use kali_ast::{DestructorKind, ExportKind, Ident, Item, ItemKind, Module, Visibility};
+
+use crate::{Constant, Context, ModuleTypes, Type, TypeInferenceError};
+
+/// The name of the function a program starts running at.
+pub const ENTRY_POINT: &str = "main";
+
+/// How a program is started, given by the parameters of its entry point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryPoint {
+    /// `main` takes no arguments, e.g. `main : () -> int`.
+    NoArguments,
+    /// `main` takes the command-line arguments as a list of strings, e.g.
+    /// `main : ([string]) -> int`.
+    Arguments,
+}
+
+/// Finds the entry point of a module and checks its type.
+///
+/// The entry point is an exported function named [`ENTRY_POINT`], which either takes no
+/// arguments or the command-line arguments as a list of strings, and returns the exit code of
+/// the program as an `int` or a `nat`.
+///
+/// # Arguments
+///
+/// * `module` - The module the program starts in.
+/// * `types` - The types inferred for the module.
+///
+/// # Returns
+///
+/// How the entry point is called, or an error if the module has no exported entry point or its
+/// type is not one of the above.
+pub fn entry_point(module: &Module, types: &ModuleTypes) -> Result<EntryPoint, TypeInferenceError> {
+    let Some(name) = defined(module) else {
+        return Err(TypeInferenceError::MissingEntryPoint);
+    };
+    if !exported(module) {
+        return Err(TypeInferenceError::PrivateEntryPoint.at(name.span));
+    }
+    let Some(ty) = types.bindings.get(ENTRY_POINT) else {
+        return Err(TypeInferenceError::MissingEntryPoint);
+    };
+    let arguments = Type::Array(Box::new(Type::Constant(Constant::String)));
+    for (params, entry) in [
+        (vec![], EntryPoint::NoArguments),
+        (vec![arguments], EntryPoint::Arguments),
+    ] {
+        for code in [Constant::Integer, Constant::Natural] {
+            let expected = Type::Lambda(params.clone(), Box::new(Type::Constant(code)));
+            // the variables left in the type are unsolved, so a fresh context leaves them free
+            if expected.unify(ty, &mut Context::new()).is_ok() {
+                return Ok(entry);
+            }
+        }
+    }
+    Err(TypeInferenceError::InvalidEntryPoint(ty.clone()).at(name.span))
+}
+
+/// Returns the name of the entry point where it is defined, if the module defines one.
+fn defined(module: &Module) -> Option<Ident> {
+    module.items.iter().find_map(|item| name(module, item))
+}
+
+/// Returns the name an item defines, if it defines the entry point.
+fn name(module: &Module, item: &Item) -> Option<Ident> {
+    let ident = match &item.kind {
+        ItemKind::Definition(definition) => match &definition.name.kind {
+            DestructorKind::Var(ident) => *ident,
+            _ => return None,
+        },
+        ItemKind::Function(function) => function.name,
+        _ => return None,
+    };
+    (module.cache.resolve(&ident.key) == ENTRY_POINT).then_some(ident)
+}
+
+/// Returns whether the module exports the entry point, where it is defined or in an export list.
+fn exported(module: &Module) -> bool {
+    module.items.iter().any(|item| match &item.kind {
+        ItemKind::Export(ExportKind::List(items)) => items.iter().any(|export| {
+            export.alias.is_none() && module.cache.resolve(&export.name.key) == ENTRY_POINT
+        }),
+        _ => item.visibility == Visibility::Exported && name(module, item).is_some(),
+    })
+}
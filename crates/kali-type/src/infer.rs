@@ -1,22 +1,39 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
+use kali_ast::arguments::Parameter;
+use kali_span::Span;
 use thiserror::Error;
 
-use crate::{Type, TypeUnificationError};
+use crate::{Kind, Suggestion, Type, TypeUnificationError};
 
 /// The type inference context.
 ///
 /// This struct is used to store the current state of the type inference algorithm, including
 /// a map of known polymorphic types, a stack of scopes, and the state of the inference counter.
+///
+/// Inference variables form a union-find forest: a variable solved as another variable points to
+/// it, and the path to the root of its tree is compressed each time it is looked up.
 #[derive(Debug)]
 
 pub struct Context {
     /// A stack of scopes.
     pub scope: Vec<Scope>,
-    /// The next inference variable counter.
-    pub counter: Rc<RefCell<usize>>,
-    /// A map of inference types with known types.
-    pub inferred: HashMap<usize, Type>,
+    /// The ID of the next inference variable.
+    pub counter: usize,
+    /// The solutions of inference variables, indexed by their ID.
+    inferred: Vec<Option<Type>>,
+    /// The previous solutions of inference variables changed since the oldest open snapshot.
+    undo: Vec<(usize, Option<Type>)>,
+    /// The number of open snapshots.
+    snapshots: usize,
+}
+
+/// A point in inference that a [`Context`] can be rolled back to.
+#[derive(Debug)]
+#[must_use]
+pub struct Snapshot {
+    /// The length of the undo log when the snapshot was taken.
+    undo: usize,
 }
 
 impl Default for Context {
@@ -28,26 +45,33 @@ impl Default for Context {
 impl Context {
     /// Creates a new inference context, with a single top-level frame.
     pub fn new() -> Self {
-        let counter = Rc::new(RefCell::new(0));
         Self {
-            scope: vec![Scope::new(counter.clone())],
-            counter: counter.clone(),
-            inferred: HashMap::new(),
+            scope: vec![Scope::new()],
+            counter: 0,
+            inferred: Vec::new(),
+            undo: Vec::new(),
+            snapshots: 0,
         }
     }
 
     /// Pushes a new scope onto the stack.
     pub fn push(&mut self) -> &mut Self {
-        self.scope.push(Scope::new(self.counter.clone()));
+        self.scope.push(Scope::new());
         self
     }
 
-    /// Pops the current scope from the stack. Panics if there is only one scope.
-    pub fn pop(&mut self) {
+    /// Pops the current scope from the stack.
+    ///
+    /// # Returns
+    /// An internal error if only the top-level scope is left, which cannot be popped.
+    pub fn pop(&mut self) -> Result<(), TypeInferenceError> {
         if self.scope.len() == 1 {
-            panic!("cannot pop the top-level scope");
+            return Err(TypeInferenceError::Internal(
+                "cannot pop the top-level scope".to_string(),
+            ));
         }
         self.scope.pop();
+        Ok(())
     }
 
     /// Returns the current inference frame.
@@ -68,9 +92,18 @@ impl Context {
             .find_map(|scope| scope.known.get(name))
     }
 
+    /// Returns the names of all known types visible from the current scope.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scope
+            .iter()
+            .flat_map(|scope| scope.known.keys().map(String::as_str))
+    }
+
     /// Declares a known type in the context.
     pub fn declare_known(&mut self, name: String, ty: Type) {
-        self.scope_mut().known.insert(name, ty);
+        let scope = self.scope_mut();
+        scope.parameters.remove(&name);
+        scope.known.insert(name, ty);
     }
 
     /// Declares an iterator of known types in the context.
@@ -78,24 +111,183 @@ impl Context {
     where
         I: IntoIterator<Item = (String, Type)>,
     {
-        self.scope_mut().known.extend(iter);
+        for (name, ty) in iter {
+            self.declare_known(name, ty);
+        }
+    }
+
+    /// Returns the parameters of a function visible from the current scope.
+    pub fn get_parameters(&self, name: &str) -> Option<&[Parameter]> {
+        self.scope
+            .iter()
+            .rev()
+            .find(|scope| scope.known.contains_key(name))
+            .and_then(|scope| scope.parameters.get(name))
+            .map(Vec::as_slice)
+    }
+
+    /// Declares the parameters of a function known in the current scope.
+    pub fn declare_parameters(&mut self, name: String, parameters: Vec<Parameter>) {
+        self.scope_mut().parameters.insert(name, parameters);
     }
 
     /// Returns the type of an inferred type in the context.
     pub fn get_inferred(&self, idx: usize) -> Option<&Type> {
-        self.inferred.get(&idx)
+        self.inferred.get(idx).and_then(Option::as_ref)
+    }
+
+    /// Returns the solution of an inference variable, following and compressing the path to the
+    /// root of its tree.
+    ///
+    /// # Returns
+    /// The solution of the root, or the root itself if it is unsolved. `None` if `idx` is an
+    /// unsolved root.
+    pub fn find(&mut self, idx: usize) -> Option<Type> {
+        let root = self.root(idx);
+        match self.get_inferred(root) {
+            Some(ty) => Some(ty.clone()),
+            None if root == idx => None,
+            None => Some(Type::Infer(root)),
+        }
+    }
+
+    /// Returns the root of the tree an inference variable belongs to, pointing every variable on
+    /// the way directly at it.
+    fn root(&mut self, idx: usize) -> usize {
+        let mut root = idx;
+        while let Some(Type::Infer(next)) = self.get_inferred(root) {
+            root = *next;
+        }
+        let mut idx = idx;
+        while let Some(Type::Infer(next)) = self.get_inferred(idx) {
+            let next = *next;
+            if next != root {
+                self.set(idx, Type::Infer(root));
+            }
+            idx = next;
+        }
+        root
+    }
+
+    /// Sets the solution of an inference variable, recording the previous one if a snapshot is
+    /// open.
+    fn set(&mut self, idx: usize, ty: Type) {
+        if self.inferred.len() <= idx {
+            self.inferred.resize(idx + 1, None);
+        }
+        let previous = self.inferred[idx].replace(ty);
+        if self.snapshots > 0 {
+            self.undo.push((idx, previous));
+        }
+    }
+
+    /// Takes a snapshot of the inferred types, to be either rolled back to or committed.
+    pub fn snapshot(&mut self) -> Snapshot {
+        self.snapshots += 1;
+        Snapshot {
+            undo: self.undo.len(),
+        }
+    }
+
+    /// Undoes all inference since a snapshot was taken.
+    pub fn rollback_to(&mut self, snapshot: Snapshot) {
+        while self.undo.len() > snapshot.undo {
+            let (idx, ty) = self.undo.pop().unwrap();
+            self.inferred[idx] = ty;
+        }
+        self.close();
+    }
+
+    /// Keeps all inference since a snapshot was taken.
+    pub fn commit(&mut self, _snapshot: Snapshot) {
+        self.close();
+    }
+
+    /// Closes a snapshot, discarding the undo log once none are open.
+    fn close(&mut self) {
+        self.snapshots -= 1;
+        if self.snapshots == 0 {
+            self.undo.clear();
+        }
     }
 
     /// Declares a variable in the current scope.
     pub fn declare_inferred(&mut self) -> Type {
-        let counter = *self.counter.borrow();
-        *self.counter.borrow_mut() += 1;
+        let counter = self.counter;
+        self.counter += 1;
         Type::Infer(counter)
     }
 
     /// Infers a new type in the current scope.
     pub fn infer(&mut self, idx: usize, real: Type) {
-        self.inferred.insert(idx, real);
+        self.set(idx, real);
+    }
+
+    /// Returns whether the inference variable `idx` occurs within `ty`, after substituting any
+    /// inferred types.
+    pub fn occurs(&self, idx: usize, ty: &Type) -> bool {
+        match ty {
+            Type::Infer(idy) => {
+                idx == *idy
+                    || self
+                        .get_inferred(*idy)
+                        .is_some_and(|ty| self.occurs(idx, ty))
+            }
+            Type::Array(ty) => self.occurs(idx, ty),
+            Type::Tuple(types)
+            | Type::Parameterized(_, types)
+            | Type::Union(types)
+            | Type::Intersection(types) => types.iter().any(|ty| self.occurs(idx, ty)),
+            Type::Record(fields) => fields.values().any(|ty| self.occurs(idx, ty)),
+            Type::Recursive(_, body) => self.occurs(idx, body),
+            Type::Lambda(params, ret) => {
+                params.iter().any(|ty| self.occurs(idx, ty)) || self.occurs(idx, ret)
+            }
+            Type::Constant(_) | Type::RecursiveRef(_) | Type::Never | Type::Error => false,
+        }
+    }
+
+    /// Substitutes all inferred types into `ty`, leaving unsolved inference variables in place.
+    ///
+    /// The substituted solution of each variable is stored back, so it is only resolved once.
+    pub fn resolve(&mut self, ty: &Type) -> Type {
+        match ty {
+            Type::Infer(idx) => {
+                let root = self.root(*idx);
+                let Some(solution) = self.get_inferred(root).cloned() else {
+                    return Type::Infer(root);
+                };
+                let resolved = self.resolve(&solution);
+                if resolved != solution {
+                    self.set(root, resolved.clone());
+                }
+                resolved
+            }
+            Type::Array(ty) => Type::Array(Box::new(self.resolve(ty))),
+            Type::Tuple(types) => Type::Tuple(types.iter().map(|ty| self.resolve(ty)).collect()),
+            Type::Record(fields) => Type::Record(
+                fields
+                    .iter()
+                    .map(|(name, ty)| (name.clone(), self.resolve(ty)))
+                    .collect(),
+            ),
+            Type::Parameterized(name, types) => Type::Parameterized(
+                name.clone(),
+                types.iter().map(|ty| self.resolve(ty)).collect(),
+            ),
+            Type::Lambda(params, ret) => Type::Lambda(
+                params.iter().map(|ty| self.resolve(ty)).collect(),
+                Box::new(self.resolve(ret)),
+            ),
+            Type::Recursive(name, body) => {
+                Type::Recursive(name.clone(), Box::new(self.resolve(body)))
+            }
+            Type::Union(types) => Type::union(types.iter().map(|ty| self.resolve(ty))),
+            Type::Intersection(types) => {
+                Type::intersection(types.iter().map(|ty| self.resolve(ty)))
+            }
+            Type::Constant(_) | Type::RecursiveRef(_) | Type::Never | Type::Error => ty.clone(),
+        }
     }
 }
 
@@ -103,15 +295,22 @@ impl Context {
 pub struct Scope {
     /// A map of named types in the context.
     pub known: HashMap<String, Type>,
-    /// A reference to the global inference counter.
-    pub counter: Rc<RefCell<usize>>,
+    /// The parameters of known functions, for calls that pass arguments by label or leave them
+    /// out.
+    pub parameters: HashMap<String, Vec<Parameter>>,
+}
+
+impl Default for Scope {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Scope {
-    pub fn new(counter: Rc<RefCell<usize>>) -> Self {
+    pub fn new() -> Self {
         Self {
             known: HashMap::new(),
-            counter,
+            parameters: HashMap::new(),
         }
     }
 }
@@ -121,7 +320,7 @@ impl Scope {
 pub enum TypeInferenceError {
     /// Unification of types failed.
     #[error("unification failed: {0} and {1}: {2}")]
-    UnificationFailed(Type, Type, TypeUnificationError),
+    UnificationFailed(Box<Type>, Box<Type>, TypeUnificationError),
     /// Multiple errors occurred.
     #[error("multiple errors occurred")]
     Multiple(Vec<TypeInferenceError>),
@@ -136,9 +335,166 @@ pub enum TypeInferenceError {
     /// Resolution of a type failed.
     #[error("resolution failed: {0}")]
     ResolutionFailed(Type),
+    /// An identifier was used without being declared.
+    #[error(
+        "unknown identifier `{name}`{}",
+        suggestion.as_ref().map(|suggestion| format!("; did you mean `{}`?", suggestion.replacement)).unwrap_or_default()
+    )]
+    UnknownIdentifier {
+        /// The name of the identifier.
+        name: String,
+        /// A similarly named identifier that is in scope.
+        suggestion: Option<Suggestion>,
+    },
+    /// A spread was used outside of a list literal or an argument list.
+    #[error("`..` can only spread a list into a list literal or an argument list")]
+    MisplacedSpread,
+    /// A labelled argument was used outside of an argument list.
+    #[error("labelled arguments can only be passed in an argument list")]
+    MisplacedLabel,
+    /// Labelled arguments were passed to a function whose parameter names are unknown.
+    #[error("labelled arguments can only be passed to functions defined with named parameters")]
+    UnlabelledFunction,
+    /// A label does not name a parameter of the function called.
+    #[error("`{function}` has no parameter named `{label}`")]
+    UnknownLabel {
+        /// The name of the function.
+        function: String,
+        /// The label of the argument.
+        label: String,
+    },
+    /// A parameter was given more than one argument.
+    #[error("parameter `{0}` is given more than one argument")]
+    DuplicateArgument(String),
+    /// A positional argument followed a labelled argument.
+    #[error("positional arguments must come before labelled arguments")]
+    PositionalAfterLabelled,
+    /// More arguments were passed than the function has parameters.
+    #[error("`{function}` takes {expected} argument(s)")]
+    TooManyArguments {
+        /// The name of the function.
+        function: String,
+        /// The number of parameters of the function.
+        expected: usize,
+    },
+    /// A parameter was not given an argument.
+    #[error("missing argument for {0}")]
+    MissingArgument(String),
+    /// A default value refers to a variable that is shadowed where the argument is left out.
+    #[error("the default value of {parameter} refers to `{name}`, which is shadowed here")]
+    ShadowedDefault {
+        /// The parameter whose argument was left out.
+        parameter: String,
+        /// The name of the shadowed variable.
+        name: String,
+    },
+    /// A spread was passed alongside labelled arguments.
+    #[error("labelled arguments cannot be combined with `..`")]
+    LabelledSpread,
+    /// A name was imported from a module that does not export it.
+    #[error("`{module}` does not export `{name}`")]
+    NotExported {
+        /// The path of the module, e.g. `a::b`.
+        module: String,
+        /// The name imported.
+        name: String,
+    },
+    /// A type alias refers to itself other than through a type constructor, so has no values.
+    #[error("the type `{0}` is defined only in terms of itself")]
+    UnproductiveType(String),
+    /// A type constructor was applied to a different number of types than it takes.
+    #[error("`{name}` has kind {expected}, but is used as {found}")]
+    KindMismatch {
+        /// The name of the type constructor.
+        name: String,
+        /// The kind of the type constructor.
+        expected: Kind,
+        /// The kind the type constructor is used as.
+        found: Kind,
+    },
+    /// The type of an expression was left unsolved, and does not appear in the type of any
+    /// binding, so could be any type.
+    #[error("cannot infer type {0}; consider adding a type annotation")]
+    AmbiguousType(Type),
+    /// A typed hole was found where an expression is yet to be written.
+    #[error("found hole `?{name}` of type {expected}")]
+    Hole {
+        /// The name of the hole, without the `?`.
+        name: String,
+        /// The type of the expression the hole stands in for.
+        expected: Type,
+        /// The names in scope at the hole and their types, sorted by name.
+        bindings: Vec<(String, Type)>,
+    },
+    /// The elements of a set or the keys of a map are of a type whose values cannot be compared.
+    #[error("values of type {0} cannot be compared, so cannot be set elements or map keys")]
+    Unhashable(Type),
+    /// Values of a type containing functions are compared by `==` or `!=`.
+    #[error("values of type {0} contain functions, so cannot be compared")]
+    Incomparable(Type),
+    /// Values of a type with no order are compared by `<`, `<=`, `>` or `>=`.
+    #[error("values of type {0} have no order")]
+    Unordered(Type),
+    /// A `nat` is negated, though it has no negative values.
+    #[error("values of type {0} have no negative values, so cannot be negated")]
+    UnsignedNegation(Type),
+    /// The ends of a range are not whole numbers, so cannot be counted between.
+    #[error("ranges are of `nat` or `int`, but the ends of this one are of type {0}")]
+    NonNumericRange(Type),
+    /// A projection takes an element past the end of a tuple.
+    #[error("{ty} has no element {index}")]
+    TupleIndexOutOfBounds {
+        /// The index projected.
+        index: usize,
+        /// The type of the tuple.
+        ty: Type,
+    },
+    /// A projection takes an element of a value that is not a tuple.
+    #[error("only tuples have elements to project, found {0}")]
+    NotATuple(Type),
+    /// A `?` is used outside of any function, so there is nothing to return its error from.
+    #[error("`?` returns errors from the enclosing function, but is not inside one")]
+    TryOutsideFunction,
+    /// A program has no exported `main` function to start running at.
+    #[error("no exported `main` function to start the program at")]
+    MissingEntryPoint,
+    /// A program defines a `main` function, but does not export it.
+    #[error("`main` must be exported to start the program at")]
+    PrivateEntryPoint,
+    /// The `main` function of a program has a type it cannot be started with.
+    #[error("`main` must have type () -> int or ([string]) -> int, found {0}")]
+    InvalidEntryPoint(Type),
+    /// The compiler reached a state it should never be in. This is a bug in the compiler, not in
+    /// the program being compiled.
+    #[error("internal compiler error: {0}")]
+    Internal(String),
+    /// An error that occurred at a location in the source code.
+    #[error("{error}")]
+    Spanned {
+        /// The span of the offending node.
+        span: Span,
+        /// The underlying error.
+        error: Box<TypeInferenceError>,
+    },
 }
 
 impl TypeInferenceError {
+    /// Attaches a source span to this error.
+    pub fn at(self, span: Span) -> TypeInferenceError {
+        TypeInferenceError::Spanned {
+            span,
+            error: Box::new(self),
+        }
+    }
+
+    /// Returns the span this error occurred at, if known.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            TypeInferenceError::Spanned { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+
     /// Combines two type inference errors.
     pub fn combine(self, other: TypeInferenceError) -> TypeInferenceError {
         match (self, other) {
@@ -0,0 +1,120 @@
+//! Module interfaces, recording what a module exports so that the modules importing it can be
+//! type checked without type checking it again.
+//!
+//! The inference variables left unsolved in the types of a module are generalised in its
+//! interface: each import of a name is given fresh variables, so `let id = x -> x` may be
+//! imported and used as `(bool) -> bool` in one module and `(nat) -> nat` in another. A variable
+//! shared by the types of several bindings, such as the element type of a reference cell and of
+//! the function that pushes onto it, must stand for the same type wherever it is used, so is only
+//! instantiated once per importing module.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use kali_ast::{Destructor, DestructorKind, ExportKind, ItemKind, Module, Visibility};
+use serde::{Deserialize, Serialize};
+
+use crate::{ModuleTypes, Type};
+
+/// The names a module exports, and their types.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModuleInterface {
+    /// The types of the values the module exports, by name.
+    pub values: BTreeMap<String, Type>,
+    /// The types the type aliases the module exports stand for, by name.
+    pub types: BTreeMap<String, Type>,
+    /// The inference variables of the interface shared by the types of several bindings of the
+    /// module, which are not generalised. Variables are numbered from zero in the order they
+    /// first appear.
+    #[serde(default)]
+    pub shared: BTreeSet<usize>,
+}
+
+impl ModuleInterface {
+    /// Builds the interface of a module from the types inferred for it.
+    ///
+    /// # Returns
+    /// The exported items of the module, along with the names an export list exports, under their
+    /// aliases.
+    pub fn new(module: &Module, types: &ModuleTypes) -> Self {
+        let mut interface = ModuleInterface::default();
+        let mut export = |name: &str, exported: &str| {
+            if let Some(ty) = types.bindings.get(name) {
+                interface.values.insert(exported.to_string(), ty.clone());
+            }
+            if let Some(ty) = types.aliases.get(name) {
+                interface.types.insert(exported.to_string(), ty.clone());
+            }
+        };
+        for item in &module.items {
+            let mut names = Vec::new();
+            match &item.kind {
+                ItemKind::Export(ExportKind::List(items)) => {
+                    for item in items {
+                        let name = module.cache.resolve(&item.name.key);
+                        let alias = item.alias.as_ref().unwrap_or(&item.name);
+                        export(name, module.cache.resolve(&alias.key));
+                    }
+                }
+                _ if item.visibility != Visibility::Exported => {}
+                ItemKind::Definition(definition) => bound(&definition.name, &mut names),
+                ItemKind::Function(function) => names.push(function.name.key),
+                ItemKind::TypeAlias(alias) => names.push(alias.name.key),
+                _ => {}
+            }
+            for key in names {
+                let name = module.cache.resolve(&key);
+                export(name, name);
+            }
+        }
+        interface.generalise(types);
+        interface
+    }
+
+    /// Numbers the inference variables of the interface from zero, recording those shared by the
+    /// types of several bindings of the module.
+    fn generalise(&mut self, types: &ModuleTypes) {
+        let bindings = types.shared();
+        let mut numbers = HashMap::new();
+        let mut shared = BTreeSet::new();
+        let mut number = |id: usize| {
+            let next = numbers.len();
+            let number = *numbers.entry(id).or_insert(next);
+            if bindings.contains(&id) {
+                shared.insert(number);
+            }
+            Type::Infer(number)
+        };
+        for ty in self.values.values_mut().chain(self.types.values_mut()) {
+            *ty = ty.map_variables(&mut number);
+        }
+        self.shared = shared;
+    }
+
+    /// Serializes the interface as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("interfaces are always serializable")
+    }
+
+    /// Deserializes an interface from JSON, as written by [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Collects the names a destructor binds.
+fn bound(destructor: &Destructor, names: &mut Vec<lasso::Spur>) {
+    match &destructor.kind {
+        DestructorKind::Var(ident) => names.push(ident.key),
+        DestructorKind::Tuple(destructors) => destructors
+            .iter()
+            .for_each(|destructor| bound(destructor, names)),
+        DestructorKind::Record(fields) => fields
+            .values()
+            .for_each(|destructor| bound(destructor, names)),
+        DestructorKind::Cons { lhs, rhs } => {
+            bound(lhs, names);
+            bound(rhs, names);
+        }
+        DestructorKind::Rest => {}
+    }
+}
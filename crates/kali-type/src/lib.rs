@@ -1,19 +1,31 @@
 //! Provides a type system for the Kali language loosely based on the Hindley-Milner type system.
 
-use std::{collections::BTreeMap, fmt::Display};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use serde::{Deserialize, Serialize};
 
 mod engine;
+mod entry;
 mod infer;
-mod iter;
+mod interface;
+mod prelude;
+mod suggest;
 mod unify;
+mod verify;
 
 pub use engine::*;
+pub use entry::*;
 pub use infer::*;
-use kali_ast::{ConstantType, TypeExpr, TypeExprKind};
+pub use interface::*;
+pub use prelude::*;
+pub use suggest::*;
 pub use unify::*;
 
 /// A type in the Kali language.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Type {
     /// A constant type.
     Constant(Constant),
@@ -21,12 +33,22 @@ pub enum Type {
     Array(Box<Type>),
     /// A tuple type. Contains the types of the elements.
     Tuple(Vec<Type>),
-    /// A record type. Contains the types of the fields.
+    /// A record type. Contains the types of the fields, sorted by name, as the order fields are
+    /// written in does not change the type.
     Record(BTreeMap<String, Type>),
     /// A parameterized type.
     Parameterized(String, Vec<Type>),
     /// A lambda type. Contains the types of the parameters and the return type.
     Lambda(Vec<Type>, Box<Type>),
+    /// A union type, of values of any of its members. See [`Type::union`].
+    Union(Vec<Type>),
+    /// An intersection type, of values of every one of its members. See [`Type::intersection`].
+    Intersection(Vec<Type>),
+    /// A recursive type, named so that its body can refer to it by [`Type::RecursiveRef`]. It is
+    /// [unfolded](Type::unfold) where it is unified with the structure it stands for.
+    Recursive(String, Box<Type>),
+    /// A reference to the enclosing recursive type of the given name.
+    RecursiveRef(String),
     /// Represents a type that has not yet been inferred, with a unique ID.
     Infer(usize),
     /// The never type.
@@ -47,102 +69,347 @@ impl Type {
             Type::Lambda(params, ret) => {
                 params.iter().all(|ty| ty.is_monotype()) && ret.is_monotype()
             }
+            Type::Union(types) | Type::Intersection(types) => {
+                types.iter().all(|ty| ty.is_monotype())
+            }
+            Type::Recursive(_, body) => body.is_monotype(),
+            Type::RecursiveRef(_) => true,
             _ => false,
         }
     }
-}
 
-impl Display for Type {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Unfolds a recursive type once, replacing the references to it in its body with the type
+    /// itself. Any other type is returned as is.
+    pub fn unfold(&self) -> Type {
         match self {
-            Type::Constant(constant) => write!(f, "{}", constant),
-            Type::Array(ty) => write!(f, "{}[]", ty),
-            Type::Tuple(types) => {
-                write!(f, "(")?;
-                for (i, ty) in types.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ", ")?;
-                    }
-                    write!(f, "{}", ty)?;
+            Type::Recursive(name, body) => substitute(body, name, self),
+            ty => ty.clone(),
+        }
+    }
+
+    /// Builds the union of some types, flattening nested unions and removing duplicate members.
+    /// A union of a single type is that type.
+    pub fn union(types: impl IntoIterator<Item = Type>) -> Type {
+        let mut members: Vec<Type> = Vec::new();
+        for ty in types {
+            let types = match ty {
+                Type::Union(types) => types,
+                ty => vec![ty],
+            };
+            for ty in types {
+                if !members.contains(&ty) {
+                    members.push(ty);
                 }
-                write!(f, ")")
             }
-            Type::Record(_) => todo!(),
-            Type::Parameterized(_, _) => todo!(),
-            Type::Lambda(params, body) => {
-                write!(f, "(")?;
-                for (i, ty) in params.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ", ")?;
+        }
+        match members.len() {
+            1 => members.pop().unwrap(),
+            _ => Type::Union(members),
+        }
+    }
+
+    /// Builds the intersection of some types, flattening nested intersections and removing
+    /// duplicate members. Records are merged into a single record with the fields of each, as
+    /// a value of every record type must have all of their fields. An intersection of a single
+    /// type is that type.
+    pub fn intersection(types: impl IntoIterator<Item = Type>) -> Type {
+        let mut members: Vec<Type> = Vec::new();
+        let mut record: Option<BTreeMap<String, Type>> = None;
+        for ty in types {
+            let types = match ty {
+                Type::Intersection(types) => types,
+                ty => vec![ty],
+            };
+            for ty in types {
+                match (ty, &mut record) {
+                    (Type::Record(fields), Some(record)) => {
+                        for (name, ty) in fields {
+                            let ty = match record.remove(&name) {
+                                Some(other) => Type::intersection([other, ty]),
+                                None => ty,
+                            };
+                            record.insert(name, ty);
+                        }
                     }
-                    write!(f, "{}", ty)?;
+                    (Type::Record(fields), None) => record = Some(fields),
+                    (ty, _) if !members.contains(&ty) => members.push(ty),
+                    _ => {}
                 }
-                write!(f, ") -> {}", body)
             }
-            Type::Infer(id) => write!(f, "'{}", id),
-            Type::Error => write!(f, "error"),
-            Type::Never => write!(f, "never"),
+        }
+        members.extend(record.map(Type::Record));
+        match members.len() {
+            1 => members.pop().unwrap(),
+            _ => Type::Intersection(members),
+        }
+    }
+}
+
+/// Replaces the references to the recursive type `name` in `ty` with `with`.
+fn substitute(ty: &Type, name: &str, with: &Type) -> Type {
+    let substitute = |ty: &Type| substitute(ty, name, with);
+    match ty {
+        Type::RecursiveRef(other) if other == name => with.clone(),
+        // the name is shadowed by a recursive type of the same name
+        Type::Recursive(other, _) if other == name => ty.clone(),
+        Type::Recursive(other, body) => Type::Recursive(other.clone(), Box::new(substitute(body))),
+        Type::Array(ty) => Type::Array(Box::new(substitute(ty))),
+        Type::Tuple(types) => Type::Tuple(types.iter().map(substitute).collect()),
+        Type::Record(fields) => Type::Record(
+            fields
+                .iter()
+                .map(|(name, ty)| (name.clone(), substitute(ty)))
+                .collect(),
+        ),
+        Type::Parameterized(name, types) => {
+            Type::Parameterized(name.clone(), types.iter().map(substitute).collect())
+        }
+        Type::Lambda(params, ret) => Type::Lambda(
+            params.iter().map(substitute).collect(),
+            Box::new(substitute(ret)),
+        ),
+        Type::Union(types) => Type::Union(types.iter().map(substitute).collect()),
+        Type::Intersection(types) => Type::Intersection(types.iter().map(substitute).collect()),
+        Type::Constant(_) | Type::RecursiveRef(_) | Type::Infer(_) | Type::Never | Type::Error => {
+            ty.clone()
         }
     }
 }
 
-impl<Meta> From<&TypeExpr<Meta>> for Type {
-    fn from(value: &TypeExpr<Meta>) -> Self {
-        match &value.kind {
-            TypeExprKind::Constant(primitive) => match primitive {
-                ConstantType::Int => Type::Constant(Constant::Integer),
-                ConstantType::Float => Type::Constant(Constant::Float),
-                ConstantType::Bool => Type::Constant(Constant::Bool),
-                ConstantType::String => Type::Constant(Constant::String),
-                ConstantType::Unit => Type::Constant(Constant::Unit),
-            },
-            TypeExprKind::Variable(name) => todo!("TypeExpr::Variable"),
-            TypeExprKind::Function(params, ret) => {
-                let params = params.iter().map(|param| param.into()).collect();
-                Type::Lambda(params, Box::new(ret.into()))
+/// Writes a member of a union or intersection type, wrapping it in parentheses if it would
+/// otherwise be read as part of the surrounding type.
+fn write_member(
+    f: &mut std::fmt::Formatter<'_>,
+    ty: &Type,
+    union: bool,
+    names: &BTreeMap<usize, String>,
+) -> std::fmt::Result {
+    let parenthesise = match ty {
+        Type::Lambda(..) => true,
+        // unions bind tighter than intersections
+        Type::Intersection(_) => union,
+        _ => false,
+    };
+    if parenthesise {
+        write!(f, "(")?;
+        write_type(f, ty, names)?;
+        write!(f, ")")
+    } else {
+        write_type(f, ty, names)
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_type(f, self, &BTreeMap::new())
+    }
+}
+
+impl Type {
+    /// Returns a view of the type that displays it as a type scheme, naming its inference
+    /// variables `a`, `b`, `c` and so on in the order they first appear, e.g. `(a, [b]) -> a`.
+    pub fn scheme(&self) -> Scheme<'_> {
+        self.scheme_except(&BTreeSet::new())
+    }
+
+    /// Returns a view of the type that displays it as a type scheme, except that the variables in
+    /// `monomorphic` are written by their ID, as they stand for a single type yet to be solved
+    /// rather than for any type, e.g. `(nat, '3) -> nat`.
+    pub fn scheme_except(&self, monomorphic: &BTreeSet<usize>) -> Scheme<'_> {
+        let mut names = BTreeMap::new();
+        self.name_variables(&mut names, monomorphic);
+        Scheme { ty: self, names }
+    }
+
+    /// Names the inference variables of the type that have no name yet, other than those in
+    /// `monomorphic`, in the order they appear.
+    fn name_variables(&self, names: &mut BTreeMap<usize, String>, monomorphic: &BTreeSet<usize>) {
+        for id in self.variables() {
+            if monomorphic.contains(&id) {
+                continue;
             }
-            TypeExprKind::Tuple(types) => {
-                let types = types.iter().map(|ty| ty.into()).collect();
-                Type::Tuple(types)
+            let index = names.len();
+            names.entry(id).or_insert_with(|| {
+                let letter = char::from(b'a' + (index % 26) as u8);
+                match index / 26 {
+                    0 => letter.to_string(),
+                    n => format!("{}{}", letter, n),
+                }
+            });
+        }
+    }
+
+    /// Returns the IDs of the inference variables of the type, in the order they first appear.
+    pub fn variables(&self) -> Vec<usize> {
+        let mut variables = Vec::new();
+        self.collect_variables(&mut variables);
+        variables
+    }
+
+    fn collect_variables(&self, variables: &mut Vec<usize>) {
+        match self {
+            Type::Infer(id) if !variables.contains(id) => variables.push(*id),
+            Type::Array(ty) | Type::Recursive(_, ty) => ty.collect_variables(variables),
+            Type::Tuple(types)
+            | Type::Parameterized(_, types)
+            | Type::Union(types)
+            | Type::Intersection(types) => {
+                types.iter().for_each(|ty| ty.collect_variables(variables));
             }
-            TypeExprKind::Array(ty) => Type::Array(Box::new(ty.into())),
-            TypeExprKind::Record(fields) => {
-                let fields = fields
+            Type::Record(fields) => fields
+                .values()
+                .for_each(|ty| ty.collect_variables(variables)),
+            Type::Lambda(params, ret) => {
+                params.iter().for_each(|ty| ty.collect_variables(variables));
+                ret.collect_variables(variables);
+            }
+            Type::Infer(_)
+            | Type::Constant(_)
+            | Type::RecursiveRef(_)
+            | Type::Never
+            | Type::Error => {}
+        }
+    }
+
+    /// Replaces each inference variable of the type with the type `f` returns for its ID.
+    pub fn map_variables(&self, f: &mut impl FnMut(usize) -> Type) -> Type {
+        let mut map = |ty: &Type| ty.map_variables(f);
+        match self {
+            Type::Infer(id) => f(*id),
+            Type::Array(ty) => Type::Array(Box::new(map(ty))),
+            Type::Recursive(name, body) => Type::Recursive(name.clone(), Box::new(map(body))),
+            Type::Tuple(types) => Type::Tuple(types.iter().map(map).collect()),
+            Type::Record(fields) => Type::Record(
+                fields
                     .iter()
-                    .map(|(name, ty)| (name.value.clone(), ty.into()))
-                    .collect();
-                Type::Record(fields)
+                    .map(|(name, ty)| (name.clone(), map(ty)))
+                    .collect(),
+            ),
+            Type::Parameterized(name, types) => {
+                Type::Parameterized(name.clone(), types.iter().map(map).collect())
             }
+            Type::Lambda(params, ret) => {
+                Type::Lambda(params.iter().map(&mut map).collect(), Box::new(map(ret)))
+            }
+            Type::Union(types) => Type::Union(types.iter().map(map).collect()),
+            Type::Intersection(types) => Type::Intersection(types.iter().map(map).collect()),
+            Type::Constant(_) | Type::RecursiveRef(_) | Type::Never | Type::Error => self.clone(),
         }
     }
 }
 
-impl<Meta> From<TypeExpr<Meta>> for Type {
-    fn from(value: TypeExpr<Meta>) -> Self {
-        Self::from(&value)
+/// A type displayed as a type scheme, returned by [`Type::scheme`].
+#[derive(Debug, Clone)]
+pub struct Scheme<'a> {
+    /// The type being displayed.
+    ty: &'a Type,
+    /// The names of the inference variables of the type, by ID.
+    names: BTreeMap<usize, String>,
+}
+
+impl Display for Scheme<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_type(f, self.ty, &self.names)
     }
 }
 
-impl<Meta> From<Box<TypeExpr<Meta>>> for Type {
-    fn from(value: Box<TypeExpr<Meta>>) -> Self {
-        Self::from(*value)
+/// Writes a type, naming the inference variables in `names` and writing the others by their ID.
+fn write_type(
+    f: &mut std::fmt::Formatter<'_>,
+    ty: &Type,
+    names: &BTreeMap<usize, String>,
+) -> std::fmt::Result {
+    // writes a sequence of types separated by commas
+    let write_list = |f: &mut std::fmt::Formatter<'_>, types: &[Type]| {
+        for (i, ty) in types.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write_type(f, ty, names)?;
+        }
+        Ok(())
+    };
+    match ty {
+        Type::Constant(constant) => write!(f, "{}", constant),
+        Type::Array(ty) => {
+            write!(f, "[")?;
+            write_type(f, ty, names)?;
+            write!(f, "]")
+        }
+        Type::Tuple(types) => {
+            write!(f, "(")?;
+            write_list(f, types)?;
+            write!(f, ")")
+        }
+        Type::Record(fields) => {
+            write!(f, "{{ ")?;
+            for (i, (name, ty)) in fields.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}: ", name)?;
+                write_type(f, ty, names)?;
+            }
+            write!(f, " }}")
+        }
+        Type::Parameterized(name, types) => {
+            write!(f, "{}", name)?;
+            if !types.is_empty() {
+                write!(f, "<")?;
+                write_list(f, types)?;
+                write!(f, ">")?;
+            }
+            Ok(())
+        }
+        Type::Lambda(params, body) => {
+            write!(f, "(")?;
+            write_list(f, params)?;
+            write!(f, ") -> ")?;
+            write_type(f, body, names)
+        }
+        Type::Union(types) | Type::Intersection(types) => {
+            let union = matches!(ty, Type::Union(_));
+            for (i, ty) in types.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", if union { " | " } else { " & " })?;
+                }
+                write_member(f, ty, union, names)?;
+            }
+            Ok(())
+        }
+        // recursive types are abbreviated to their name, as their structure is infinite
+        Type::Recursive(name, _) | Type::RecursiveRef(name) => write!(f, "{}", name),
+        Type::Infer(id) => match names.get(id) {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "'{}", id),
+        },
+        Type::Error => write!(f, "error"),
+        Type::Never => write!(f, "never"),
     }
 }
 
-impl<Meta> From<&Box<TypeExpr<Meta>>> for Type {
-    fn from(value: &Box<TypeExpr<Meta>>) -> Self {
-        Self::from(&**value)
+/// The kind of a type constructor, which is the number of types it is applied to before it is a
+/// type itself. A type has kind `*`, and a constructor of one type has kind `* -> *`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Kind(pub usize);
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for _ in 0..self.0 {
+            write!(f, "* -> ")?;
+        }
+        write!(f, "*")
     }
 }
 
 /// Constant types in the Kali language.
-#[derive(Clone, Debug, PartialEq, strum::Display)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, strum::Display)]
 pub enum Constant {
     /// A signed integer type.
-    #[strum(serialize = "integer")]
+    #[strum(serialize = "int")]
     Integer,
     /// An unsigned integer type.
-    #[strum(serialize = "natural")]
+    #[strum(serialize = "nat")]
     Natural,
     /// A floating-point type.
     #[strum(serialize = "float")]
@@ -153,6 +420,9 @@ pub enum Constant {
     /// A string type.
     #[strum(serialize = "string")]
     String,
+    /// A byte string type.
+    #[strum(serialize = "bytes")]
+    Bytes,
     /// A unit type.
     #[strum(serialize = "()")]
     Unit,
@@ -172,7 +442,52 @@ mod tests {
                 "{}",
                 crate::Type::Array(Box::new(crate::Type::Constant(crate::Constant::Integer)))
             ),
-            "int[]"
+            "[int]"
         );
+        // unions and intersections
+        let int = crate::Type::Constant(crate::Constant::Integer);
+        let lambda = crate::Type::Lambda(vec![int.clone()], Box::new(int.clone()));
+        let union = crate::Type::union([int.clone(), lambda, int.clone()]);
+        assert_eq!(format!("{}", union), "int | ((int) -> int)");
+        assert_eq!(
+            format!(
+                "{}",
+                crate::Type::union([crate::Type::Intersection(vec![int.clone(), int]), union])
+            ),
+            "(int & int) | int | ((int) -> int)"
+        );
+        // type schemes name inference variables in order
+        let scheme = crate::Type::Lambda(
+            vec![
+                crate::Type::Infer(7),
+                crate::Type::Constant(crate::Constant::Integer),
+            ],
+            Box::new(crate::Type::Array(Box::new(crate::Type::Infer(7)))),
+        );
+        assert_eq!(format!("{}", scheme), "('7, int) -> ['7]");
+        assert_eq!(format!("{}", scheme.scheme()), "(a, int) -> [a]");
+        let scheme = crate::Type::Tuple(vec![crate::Type::Infer(3), crate::Type::Infer(7)]);
+        let monomorphic = [7].into();
+        assert_eq!(format!("{}", scheme.scheme_except(&monomorphic)), "(a, '7)");
+        // kinds
+        assert_eq!(format!("{}", crate::Kind(0)), "*");
+        assert_eq!(format!("{}", crate::Kind(2)), "* -> * -> *");
+    }
+
+    #[test]
+    fn scopes() {
+        let mut context = crate::Context::new();
+        context.push();
+        context.declare_known("x".to_string(), crate::Type::Never);
+        assert_eq!(context.get_known("x"), Some(&crate::Type::Never));
+        context.pop().unwrap();
+        assert_eq!(context.get_known("x"), None);
+
+        // the top-level scope is never popped
+        assert!(matches!(
+            context.pop(),
+            Err(crate::TypeInferenceError::Internal(_))
+        ));
+        assert_eq!(context.scope.len(), 1);
     }
 }
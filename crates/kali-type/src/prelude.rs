@@ -0,0 +1,301 @@
+//! The intrinsics every module can use without importing them.
+//!
+//! Mutation is provided by reference cells: `ref x` makes a cell holding `x`, `get r` reads the
+//! value a cell holds, and `set r x` replaces it. Errors are returned as results, made by `ok`
+//! and `err`, and missing values as options, made by `some` and `none`.
+//!
+//! The combinators of options and results take the option or result last, so that they can be
+//! used in pipelines, e.g. `x |> map double |> unwrap_or 0`. There is no overloading, so the
+//! combinators of results that options also have are suffixed with `_ok`.
+//!
+//! Maps from keys to values are made by literals such as `#{ "a": 1 }`, and are changed by
+//! `insert` and `remove`, which return a new map. `lookup` finds the value of a key, as `get`
+//! reads cells. Sets are made by literals such as `#[1, 2]`, and combined by `union` and
+//! `intersection`. The elements of sets and the keys of maps cannot be functions, as functions
+//! cannot be compared.
+//!
+//! Ranges such as `1..10` are counted as they are iterated over, so a `for` loop over a long range
+//! does not make a list of it first. `collect` makes that list when it is wanted.
+//!
+//! `fst` and `snd` take the elements of pairs, like the projections `t.0` and `t.1`.
+//!
+//! Strings are counted in characters, which are Unicode scalar values, rather than in the bytes
+//! of their UTF-8 encoding: `length` and the positions given to `slice` count characters, and
+//! `chars` splits a string into strings of one character each. `byte_length` is the number of
+//! bytes the string is encoded in.
+
+use crate::{Constant, Context, Kind, Type};
+
+/// The name of the type of reference cells.
+pub const REF: &str = "Ref";
+
+/// The name of the type of results, which are either a value or an error.
+pub const RESULT: &str = "Result";
+
+/// The name of the type of options, which either hold a value or are none.
+pub const OPTION: &str = "Option";
+
+/// The name of the type of maps from keys to values.
+pub const MAP: &str = "Map";
+
+/// The name of the type of sets of values.
+pub const SET: &str = "Set";
+
+/// The name of the type of ranges of numbers.
+pub const RANGE: &str = "Range";
+
+/// The names of the intrinsics.
+pub const INTRINSICS: [&str; 33] = [
+    "ref",
+    "get",
+    "set",
+    "ok",
+    "err",
+    "some",
+    "none",
+    "is_some",
+    "is_none",
+    "map",
+    "and_then",
+    "unwrap_or",
+    "ok_or",
+    "is_ok",
+    "is_err",
+    "map_ok",
+    "map_err",
+    "and_then_ok",
+    "unwrap_ok_or",
+    "insert",
+    "lookup",
+    "remove",
+    "size",
+    "union",
+    "intersection",
+    "member",
+    "collect",
+    "fst",
+    "snd",
+    "length",
+    "byte_length",
+    "slice",
+    "chars",
+];
+
+/// Returns the type of results of a value of type `value`, or an error of type `error`.
+pub fn result(value: Type, error: Type) -> Type {
+    Type::Parameterized(RESULT.to_string(), vec![value, error])
+}
+
+/// Returns the type of options of a value of type `value`.
+pub fn option(value: Type) -> Type {
+    Type::Parameterized(OPTION.to_string(), vec![value])
+}
+
+/// Returns the type of maps from keys of type `key` to values of type `value`.
+pub fn map_of(key: Type, value: Type) -> Type {
+    Type::Parameterized(MAP.to_string(), vec![key, value])
+}
+
+/// Returns the type of sets of values of type `element`.
+pub fn set_of(element: Type) -> Type {
+    Type::Parameterized(SET.to_string(), vec![element])
+}
+
+/// Returns the type of ranges of numbers of type `element`.
+pub fn range_of(element: Type) -> Type {
+    Type::Parameterized(RANGE.to_string(), vec![element])
+}
+
+/// Returns the type of an intrinsic, with fresh inference variables for the types it is generic
+/// over, so that each use of it may be instantiated differently.
+///
+/// # Returns
+/// The type of the intrinsic, or `None` if there is no intrinsic of that name.
+pub fn intrinsic(name: &str, ctx: &mut Context) -> Option<Type> {
+    let cell = |ty: &Type| Type::Parameterized(REF.to_string(), vec![ty.clone()]);
+    let function = |params: Vec<Type>, ret: Type| Type::Lambda(params, Box::new(ret));
+    let bool = Type::Constant(Constant::Bool);
+    let (nat, string) = (
+        Type::Constant(Constant::Natural),
+        Type::Constant(Constant::String),
+    );
+    let ty = match name {
+        // ref : (a) -> Ref<a>
+        "ref" => {
+            let a = ctx.declare_inferred();
+            Type::Lambda(vec![a.clone()], Box::new(cell(&a)))
+        }
+        // get : (Ref<a>) -> a
+        "get" => {
+            let a = ctx.declare_inferred();
+            Type::Lambda(vec![cell(&a)], Box::new(a))
+        }
+        // set : (Ref<a>, a) -> ()
+        "set" => {
+            let a = ctx.declare_inferred();
+            Type::Lambda(vec![cell(&a), a], Box::new(Type::Constant(Constant::Unit)))
+        }
+        // ok : (t) -> Result<t, e>
+        "ok" => {
+            let (t, e) = (ctx.declare_inferred(), ctx.declare_inferred());
+            Type::Lambda(vec![t.clone()], Box::new(result(t, e)))
+        }
+        // err : (e) -> Result<t, e>
+        "err" => {
+            let (t, e) = (ctx.declare_inferred(), ctx.declare_inferred());
+            Type::Lambda(vec![e.clone()], Box::new(result(t, e)))
+        }
+        // some : (t) -> Option<t>
+        "some" => {
+            let t = ctx.declare_inferred();
+            function(vec![t.clone()], option(t))
+        }
+        // none : Option<t>
+        "none" => option(ctx.declare_inferred()),
+        // is_some, is_none : (Option<t>) -> bool
+        "is_some" | "is_none" => function(vec![option(ctx.declare_inferred())], bool),
+        // map : ((t) -> u, Option<t>) -> Option<u>
+        "map" => {
+            let (t, u) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(
+                vec![function(vec![t.clone()], u.clone()), option(t)],
+                option(u),
+            )
+        }
+        // and_then : ((t) -> Option<u>, Option<t>) -> Option<u>
+        "and_then" => {
+            let (t, u) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(
+                vec![function(vec![t.clone()], option(u.clone())), option(t)],
+                option(u),
+            )
+        }
+        // unwrap_or : (t, Option<t>) -> t
+        "unwrap_or" => {
+            let t = ctx.declare_inferred();
+            function(vec![t.clone(), option(t.clone())], t)
+        }
+        // ok_or : (e, Option<t>) -> Result<t, e>
+        "ok_or" => {
+            let (t, e) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(vec![e.clone(), option(t.clone())], result(t, e))
+        }
+        // is_ok, is_err : (Result<t, e>) -> bool
+        "is_ok" | "is_err" => {
+            let (t, e) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(vec![result(t, e)], bool)
+        }
+        // map_ok : ((t) -> u, Result<t, e>) -> Result<u, e>
+        "map_ok" => {
+            let (t, u, e) = (
+                ctx.declare_inferred(),
+                ctx.declare_inferred(),
+                ctx.declare_inferred(),
+            );
+            function(
+                vec![function(vec![t.clone()], u.clone()), result(t, e.clone())],
+                result(u, e),
+            )
+        }
+        // map_err : ((e) -> f, Result<t, e>) -> Result<t, f>
+        "map_err" => {
+            let (t, e, f) = (
+                ctx.declare_inferred(),
+                ctx.declare_inferred(),
+                ctx.declare_inferred(),
+            );
+            function(
+                vec![function(vec![e.clone()], f.clone()), result(t.clone(), e)],
+                result(t, f),
+            )
+        }
+        // and_then_ok : ((t) -> Result<u, e>, Result<t, e>) -> Result<u, e>
+        "and_then_ok" => {
+            let (t, u, e) = (
+                ctx.declare_inferred(),
+                ctx.declare_inferred(),
+                ctx.declare_inferred(),
+            );
+            function(
+                vec![
+                    function(vec![t.clone()], result(u.clone(), e.clone())),
+                    result(t, e.clone()),
+                ],
+                result(u, e),
+            )
+        }
+        // unwrap_ok_or : (t, Result<t, e>) -> t
+        "unwrap_ok_or" => {
+            let (t, e) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(vec![t.clone(), result(t.clone(), e)], t)
+        }
+        // insert : (k, v, Map<k, v>) -> Map<k, v>
+        "insert" => {
+            let (k, v) = (ctx.declare_inferred(), ctx.declare_inferred());
+            let map = map_of(k.clone(), v.clone());
+            function(vec![k, v, map.clone()], map)
+        }
+        // lookup : (k, Map<k, v>) -> Option<v>
+        "lookup" => {
+            let (k, v) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(vec![k.clone(), map_of(k, v.clone())], option(v))
+        }
+        // remove : (k, Map<k, v>) -> Map<k, v>
+        "remove" => {
+            let (k, v) = (ctx.declare_inferred(), ctx.declare_inferred());
+            let map = map_of(k.clone(), v);
+            function(vec![k, map.clone()], map)
+        }
+        // size : (Map<k, v>) -> nat
+        "size" => {
+            let (k, v) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(vec![map_of(k, v)], Type::Constant(Constant::Natural))
+        }
+        // union, intersection : (Set<a>, Set<a>) -> Set<a>
+        "union" | "intersection" => {
+            let set = set_of(ctx.declare_inferred());
+            function(vec![set.clone(), set.clone()], set)
+        }
+        // member : (a, Set<a>) -> bool
+        "member" => {
+            let a = ctx.declare_inferred();
+            function(vec![a.clone(), set_of(a)], bool)
+        }
+        // collect : (Range<a>) -> [a]
+        "collect" => {
+            let a = ctx.declare_inferred();
+            function(vec![range_of(a.clone())], Type::Array(Box::new(a)))
+        }
+        // fst : ((a, b)) -> a
+        "fst" => {
+            let (a, b) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(vec![Type::Tuple(vec![a.clone(), b])], a)
+        }
+        // snd : ((a, b)) -> b
+        "snd" => {
+            let (a, b) = (ctx.declare_inferred(), ctx.declare_inferred());
+            function(vec![Type::Tuple(vec![a, b.clone()])], b)
+        }
+        // length, byte_length : (string) -> nat
+        "length" | "byte_length" => function(vec![string.clone()], nat),
+        // slice : (string, nat, nat) -> string
+        "slice" => function(vec![string.clone(), nat.clone(), nat], string),
+        // chars : (string) -> [string]
+        "chars" => function(vec![string.clone()], Type::Array(Box::new(string))),
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// Returns the kind of a type constructor the prelude defines, if it defines one of that name.
+pub fn intrinsic_kind(name: &str) -> Option<Kind> {
+    match name {
+        REF => Some(Kind(1)),
+        RESULT => Some(Kind(2)),
+        OPTION => Some(Kind(1)),
+        MAP => Some(Kind(2)),
+        SET => Some(Kind(1)),
+        RANGE => Some(Kind(1)),
+        _ => None,
+    }
+}
@@ -0,0 +1,84 @@
+//! Suggestions for misspelled identifiers.
+
+use kali_span::Span;
+
+/// A suggested replacement for a span of source code.
+///
+/// This can be applied directly as a text edit, e.g. by a language server code action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The span of source code to replace.
+    pub span: Span,
+    /// The text to replace the span with.
+    pub replacement: String,
+}
+
+/// Computes the edit distance between two strings, in characters.
+///
+/// This is the Levenshtein distance extended with transpositions of adjacent characters (the
+/// optimal string alignment distance), as swapped letters are a common typo.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // the two previous rows of the distance matrix
+    let mut previous = vec![0; b.len() + 1];
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut next = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            next[j] = (row[j - 1] + cost).min(row[j] + 1).min(next[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                next[j] = next[j].min(previous[j - 2] + 1);
+            }
+        }
+        previous = std::mem::replace(&mut row, next);
+    }
+    row[b.len()]
+}
+
+/// Finds the candidate closest to `name`, if any is close enough to plausibly be a typo.
+///
+/// # Arguments
+/// * `name` - The unknown name.
+/// * `candidates` - The names that are known.
+///
+/// # Returns
+/// The closest candidate within a third of the length of `name`, preferring the
+/// lexicographically smallest candidate on ties.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closest, edit_distance};
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("length", "lenght"), 1);
+        assert_eq!(edit_distance("ab", "ba"), 1);
+        assert_eq!(edit_distance("λx", "λy"), 1);
+    }
+
+    #[test]
+    fn closest_candidate() {
+        let candidates = ["print", "println", "length", "map"];
+        assert_eq!(closest("pritn", candidates), Some("print"));
+        assert_eq!(closest("lenght", candidates), Some("length"));
+        assert_eq!(closest("mop", candidates), Some("map"));
+        assert_eq!(closest("filter", candidates), None);
+        assert_eq!(closest("x", ["y", "z"]), Some("y"));
+    }
+}
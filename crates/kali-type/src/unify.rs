@@ -5,7 +5,7 @@ use std::cmp::Ordering;
 use thiserror::Error;
 use tracing::trace;
 
-use crate::{Context, Type};
+use crate::{Context, OPTION, RESULT, Type};
 
 /// An error that occurs during unification of types.
 #[derive(Error, Debug)]
@@ -16,13 +16,50 @@ pub enum TypeUnificationError {
     /// A struct type has mismatched fields.
     #[error("mismatched fields: {0}")]
     MismatchedFields(String),
+    /// The types are of different shapes, so cannot be the same type.
+    #[error("expected {expected}, found {found}")]
+    Mismatch {
+        /// The type expected.
+        expected: Type,
+        /// The type found.
+        found: Type,
+    },
+    /// An inference variable would have to contain itself.
+    #[error("infinite type: '{0} occurs in {1}")]
+    InfiniteType(usize, Type),
+    /// A type is not a member of a union, or no member of an intersection is the type expected.
+    #[error("{0} is not a subtype of {1}")]
+    NotSubtype(Type, Type),
+    /// An option or result was found where the value it may hold is expected.
+    #[error("{found} may hold no value, so must be unwrapped with `{combinator}`")]
+    Unwrap {
+        /// The type of the option or result.
+        found: Type,
+        /// The prelude function that unwraps it, given a default value.
+        combinator: &'static str,
+    },
 }
 
 impl Type {
     /// Unified two types.
+    ///
+    /// Unions and intersections are unified by subsumption, where `self` is the type expected
+    /// and `other` the type found: a union is expected wherever one of its members is found, and
+    /// an intersection is found wherever one of its members is expected.
     #[tracing::instrument(skip(context))]
     pub fn unify(&self, other: &Self, context: &mut Context) -> Result<Self, TypeUnificationError> {
         trace!("unify");
+        // substitute inference variables that have already been solved
+        if let Type::Infer(idx) = self
+            && let Some(ty) = context.find(*idx)
+        {
+            return ty.unify(other, context);
+        }
+        if let Type::Infer(idx) = other
+            && let Some(ty) = context.find(*idx)
+        {
+            return self.unify(&ty, context);
+        }
         match (self, other) {
             // enforce ordering of inference types
             (Type::Infer(idx), Type::Infer(idy)) => match idx.cmp(idy) {
@@ -38,9 +75,70 @@ impl Type {
             },
             // if either type is an inference type, return the other type.
             (Type::Infer(idx), x) | (x, Type::Infer(idx)) => {
+                if context.occurs(*idx, x) {
+                    return Err(TypeUnificationError::InfiniteType(*idx, x.clone()));
+                }
                 context.infer(*idx, x.clone());
                 Ok(x.clone())
             }
+            // errors have already been reported, so unify with anything to avoid cascading
+            (Type::Error, x) | (x, Type::Error) => Ok(x.clone()),
+            // recursive types are only the same as themselves
+            (Type::Recursive(..), Type::Recursive(..)) => {
+                if self == other {
+                    Ok(self.clone())
+                } else {
+                    Err(TypeUnificationError::Mismatch {
+                        expected: self.clone(),
+                        found: other.clone(),
+                    })
+                }
+            }
+            // otherwise they are unfolded to be unified with the structure they stand for
+            (Type::Recursive(..), _) => {
+                self.unfold().unify(other, context)?;
+                Ok(self.clone())
+            }
+            (_, Type::Recursive(..)) => {
+                self.unify(&other.unfold(), context)?;
+                Ok(other.clone())
+            }
+            // a union is found where each of its members would be
+            (_, Type::Union(types)) => {
+                for ty in types {
+                    self.unify(ty, context)?;
+                }
+                Ok(self.clone())
+            }
+            // an intersection is expected where each of its members would be
+            (Type::Intersection(types), _) => {
+                for ty in types {
+                    ty.unify(other, context)?;
+                }
+                Ok(other.clone())
+            }
+            // a union is expected where any one of its members would be
+            (Type::Union(types), _) => {
+                if types.iter().any(|ty| ty.unify_or_rollback(other, context)) {
+                    Ok(self.clone())
+                } else {
+                    Err(TypeUnificationError::NotSubtype(
+                        other.clone(),
+                        self.clone(),
+                    ))
+                }
+            }
+            // an intersection is found where any one of its members would be
+            (_, Type::Intersection(types)) => {
+                if types.iter().any(|ty| self.unify_or_rollback(ty, context)) {
+                    Ok(self.clone())
+                } else {
+                    Err(TypeUnificationError::NotSubtype(
+                        other.clone(),
+                        self.clone(),
+                    ))
+                }
+            }
             // arrays unify if their element types unify.
             (Type::Array(a), Type::Array(b)) => a.unify(b, context).map(|t| Type::Array(t.into())),
             // tuples unify if their element types unify.
@@ -90,19 +188,65 @@ impl Type {
                 let ret = lhs_ret.unify(rhs_ret, context)?;
                 Ok(Type::Lambda(params, Box::new(ret)))
             }
+            // applications of the same type constructor unify if their types unify
+            (Type::Parameterized(a_name, a), Type::Parameterized(b_name, b))
+                if a_name == b_name && a.len() == b.len() =>
+            {
+                let types = a
+                    .iter()
+                    .zip(b)
+                    .map(|(a, b)| a.unify(b, context))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Type::Parameterized(a_name.clone(), types))
+            }
             // otherwise, the types must be identical
             (x, y) => {
                 if x == y {
                     Ok(x.clone())
+                } else if let Some(combinator) = unwrapping(x, y, context) {
+                    Err(TypeUnificationError::Unwrap {
+                        found: context.resolve(y),
+                        combinator,
+                    })
                 } else {
-                    Err(TypeUnificationError::MismatchedFields(format!(
-                        "{:?} != {:?}",
-                        x, y
-                    )))
+                    Err(TypeUnificationError::Mismatch {
+                        expected: context.resolve(x),
+                        found: context.resolve(y),
+                    })
                 }
             }
         }
     }
+
+    /// Unifies two types, undoing any inference the attempt made if they do not unify.
+    ///
+    /// # Returns
+    /// Whether the types unify.
+    fn unify_or_rollback(&self, other: &Self, context: &mut Context) -> bool {
+        let snapshot = context.snapshot();
+        let unifies = self.unify(other, context).is_ok();
+        if unifies {
+            context.commit(snapshot);
+        } else {
+            context.rollback_to(snapshot);
+        }
+        unifies
+    }
+}
+
+/// Returns the prelude function that unwraps `found` to the `expected` value it may hold, if it
+/// is an option or a result of that value.
+fn unwrapping(expected: &Type, found: &Type, context: &mut Context) -> Option<&'static str> {
+    let (combinator, value) = match found {
+        Type::Parameterized(name, types) if name == OPTION && types.len() == 1 => {
+            ("unwrap_or", &types[0])
+        }
+        Type::Parameterized(name, types) if name == RESULT && types.len() == 2 => {
+            ("unwrap_ok_or", &types[0])
+        }
+        _ => return None,
+    };
+    (context.resolve(value) == context.resolve(expected)).then_some(combinator)
 }
 
 #[cfg(test)]
@@ -159,6 +303,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unify_inferred_parameterized() {
+        let int = Type::Constant(Constant::Integer);
+        let list = |ty: Type| Type::Parameterized("List".to_string(), vec![ty]);
+
+        assert_eq!(
+            list(Type::Infer(0))
+                .unify(&list(int.clone()), &mut Context::default())
+                .unwrap(),
+            list(int.clone())
+        );
+        let other = Type::Parameterized("Set".to_string(), vec![int.clone()]);
+        assert!(list(int).unify(&other, &mut Context::default()).is_err());
+    }
+
     #[test]
     fn unify_inferred_structs() {
         let int = Type::Constant(Constant::Integer);
@@ -195,4 +354,81 @@ mod tests {
             ]))
         );
     }
+
+    #[test]
+    fn unify_unions() {
+        let int = Type::Constant(Constant::Integer);
+        let string = Type::Constant(Constant::String);
+        let bool = Type::Constant(Constant::Bool);
+        let union = Type::union([int.clone(), string.clone()]);
+        let mut context = Context::default();
+
+        assert_eq!(union.unify(&int, &mut context).unwrap(), union);
+        assert_eq!(union.unify(&union, &mut context).unwrap(), union);
+        assert!(union.unify(&bool, &mut context).is_err());
+        assert!(int.unify(&union, &mut context).is_err());
+
+        // a failed attempt to unify with one member does not infer anything
+        let tuple = Type::Tuple(vec![Type::Infer(0), bool.clone()]);
+        let union = Type::union([Type::Tuple(vec![int.clone(), int.clone()]), bool.clone()]);
+        assert!(union.unify(&tuple, &mut context).is_err());
+        assert_eq!(context.resolve(&Type::Infer(0)), Type::Infer(0));
+    }
+
+    #[test]
+    fn unify_intersections() {
+        let int = Type::Constant(Constant::Integer);
+        let string = Type::Constant(Constant::String);
+        let intersection = Type::Intersection(vec![
+            Type::Parameterized("A".to_string(), Vec::new()),
+            int.clone(),
+        ]);
+        let mut context = Context::default();
+
+        assert_eq!(int.unify(&intersection, &mut context).unwrap(), int);
+        assert!(string.unify(&intersection, &mut context).is_err());
+        assert!(intersection.unify(&int, &mut context).is_err());
+    }
+
+    #[test]
+    fn unify_errors() {
+        let int = Type::Constant(Constant::Integer);
+        let mut context = Context::default();
+
+        assert_eq!(Type::Error.unify(&int, &mut context).unwrap(), int);
+        assert_eq!(int.unify(&Type::Error, &mut context).unwrap(), int);
+
+        // an inference variable unified with an error is resolved to the error
+        Type::Infer(0).unify(&Type::Error, &mut context).unwrap();
+        assert_eq!(context.resolve(&Type::Infer(0)), Type::Error);
+    }
+
+    #[test]
+    fn unify_chains() {
+        let int = Type::Constant(Constant::Integer);
+        let mut context = Context::default();
+
+        // a chain of variables resolves to the solution of its root
+        for idx in 0..3 {
+            Type::Infer(idx)
+                .unify(&Type::Infer(idx + 1), &mut context)
+                .unwrap();
+        }
+        assert_eq!(context.find(0), Some(Type::Infer(3)));
+        assert_eq!(context.get_inferred(1), Some(&Type::Infer(3)));
+
+        let snapshot = context.snapshot();
+        Type::Infer(0).unify(&int, &mut context).unwrap();
+        assert_eq!(context.resolve(&Type::Infer(1)), int);
+        context.rollback_to(snapshot);
+        assert_eq!(context.resolve(&Type::Infer(1)), Type::Infer(3));
+
+        // nested snapshots are kept once committed
+        let outer = context.snapshot();
+        let inner = context.snapshot();
+        Type::Infer(3).unify(&int, &mut context).unwrap();
+        context.commit(inner);
+        context.commit(outer);
+        assert_eq!(context.resolve(&Type::Infer(0)), int);
+    }
 }
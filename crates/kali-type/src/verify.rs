@@ -0,0 +1,119 @@
+//! A consistency check of the types inferred for a module, run in debug builds to catch bugs in
+//! the inference engine rather than in the module.
+
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+};
+
+use kali_ast::{
+    BinaryOpKind, Expr, ExprKind, Module,
+    visit::{Visitor, WalkOrder, Walker},
+};
+use kali_span::Span;
+
+use crate::{Constant, Type, TypeInferenceError};
+
+/// Checks the invariants of the types inferred for a module that type checks.
+///
+/// # Returns
+/// An internal error for each invariant that does not hold.
+pub(crate) fn verify(module: &Module, types: &HashMap<Span, Type>) -> Vec<TypeInferenceError> {
+    let mut verifier = Verifier {
+        types,
+        piped: HashSet::new(),
+        errors: Vec::new(),
+    };
+    let Ok(()) = Walker::new(WalkOrder::PreOrder).walk_module(&mut verifier, module);
+    verifier.errors
+}
+
+/// Checks expressions against the resolved types of the expressions at each span.
+struct Verifier<'a> {
+    /// The resolved type of the outermost expression at each span.
+    types: &'a HashMap<Span, Type>,
+    /// The spans of calls that a pipeline passes its value to, which are typed along with the
+    /// value rather than on their own.
+    piped: HashSet<Span>,
+    /// The invariants found not to hold.
+    errors: Vec<TypeInferenceError>,
+}
+
+impl Verifier<'_> {
+    /// Records an internal error at the given span.
+    fn error(&mut self, message: String, span: Span) {
+        self.errors
+            .push(TypeInferenceError::Internal(message).at(span));
+    }
+}
+
+impl Visitor for Verifier<'_> {
+    type Error = Infallible;
+
+    fn visit_expr(&mut self, expr: &Expr) -> Result<(), Infallible> {
+        // labels and spreads are typed as part of the argument list or list they are in
+        if matches!(expr.kind, ExprKind::Labelled { .. } | ExprKind::Spread(_))
+            || self.piped.contains(&expr.span)
+        {
+            return Ok(());
+        }
+        if !self.types.contains_key(&expr.span) {
+            self.error("expression was never inferred".to_string(), expr.span);
+            return Ok(());
+        }
+        match &expr.kind {
+            ExprKind::BinaryExpr { op, rhs, .. }
+                if op.kind == BinaryOpKind::Pipe && matches!(rhs.kind, ExprKind::Call { .. }) =>
+            {
+                self.piped.insert(rhs.span);
+            }
+            ExprKind::Conditional { condition, .. } | ExprKind::While { condition, .. } => {
+                match self.types.get(&condition.span) {
+                    Some(
+                        Type::Constant(Constant::Bool)
+                        | Type::Intersection(_)
+                        | Type::Never
+                        | Type::Error,
+                    )
+                    | None => {}
+                    Some(ty) => {
+                        let message = format!("condition is of type {ty}, not bool");
+                        self.error(message, condition.span);
+                    }
+                }
+            }
+            ExprKind::Call {
+                function,
+                arguments,
+            } if !arguments
+                .iter()
+                .any(|argument| matches!(argument.kind, ExprKind::Spread(_))) =>
+            {
+                match self.types.get(&function.span).map(Type::unfold) {
+                    Some(Type::Lambda(params, _)) if arguments.len() > params.len() => {
+                        let message = format!(
+                            "{} arguments are passed to a function of {} parameters",
+                            arguments.len(),
+                            params.len()
+                        );
+                        self.error(message, expr.span);
+                    }
+                    Some(
+                        Type::Lambda(..)
+                        | Type::Union(_)
+                        | Type::Intersection(_)
+                        | Type::Never
+                        | Type::Error,
+                    )
+                    | None => {}
+                    Some(ty) => {
+                        let message = format!("callee is of type {ty}, not a function");
+                        self.error(message, function.span);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use kali_parse::parse_str;
+use kali_type::{Type, TypeInferenceEngine, TypeInferenceError};
+
+#[test]
+fn test_ambiguous_type() {
+    // the elements of the list are never used, so could be of any type
+    let module = parse_str("let a = match [] { [] -> 1, _ -> 2 }").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert_eq!(error.span().unwrap().into_range(), 14..16);
+    let TypeInferenceError::Spanned { error, .. } = error else {
+        panic!("expected a spanned error");
+    };
+    assert!(
+        matches!(*error, TypeInferenceError::AmbiguousType(Type::Array(ref element)) if matches!(**element, Type::Infer(_))),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+fn test_unsolved_bindings_are_not_ambiguous() {
+    // a variable in the type of a binding is a parameter of it
+    let module = parse_str("let id = x -> x;\nlet first = xs -> match xs { x :: _ -> x }").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_ok());
+
+    // once used, the list has a type
+    let module = parse_str("let a = match [] { [] -> 1, x :: _ -> x + 1 }").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_ok());
+}
+
+#[test]
+fn test_ambiguity_after_errors() {
+    // an error can leave a variable unsolved, so is not also reported as ambiguous
+    let module = parse_str("let a = match [] { [] -> c, _ -> 2 }").unwrap();
+    let (_, errors) = TypeInferenceEngine::infer_recovering(&module);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
@@ -0,0 +1,28 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+#[test]
+fn test_sequence() {
+    let src = "let r = ref 0;\nlet a = do { set(r, 1); set(r, 2); get(r) == 2 }";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Bool));
+
+    // the branches of an `if` may be sequences too
+    let src = "let r = ref 0;\nlet a = if true { set(r, 1); 1 } else { 2 }";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Natural));
+}
+
+#[test]
+fn test_discarded_value() {
+    // only `()` may be discarded
+    let module = parse_str("let a = do { 1; 2 }").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    let TypeInferenceError::Spanned { span, error } = error else {
+        panic!("expected a spanned error, found {:?}", error);
+    };
+    assert!(matches!(*error, TypeInferenceError::UnificationFailed(..)));
+    assert_eq!(span.into_range(), 13..14);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine};
+
+#[test]
+fn test_function_clauses() {
+    let src = "fn fib 0 = 0;\nfn fib 1 = 1;\nfn fib n = fib(n - 1) + fib(n - 2);\nlet a = fib(10)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let natural = Type::Constant(Constant::Natural);
+    assert_eq!(
+        types.bindings["fib"],
+        Type::Lambda(vec![natural.clone()], Box::new(natural.clone()))
+    );
+    assert_eq!(types.bindings["a"], natural);
+}
+
+#[test]
+fn test_clause_scope() {
+    // the variables bound by a clause are not visible in the others
+    let errors = infer_errors("fn f x 0 = x;\nfn f y z = x");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
+
+#[test]
+fn test_clause_mismatch() {
+    // every clause takes arguments of the same types
+    let errors = infer_errors("fn f 0 = 1;\nfn f true = 2");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+
+    // and returns the same type
+    let errors = infer_errors("fn f 0 = 1;\nfn f n = true");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
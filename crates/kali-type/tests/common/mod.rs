@@ -0,0 +1,24 @@
+//! Helpers shared by the type inference tests.
+
+// each test file uses only some of these helpers
+#![allow(dead_code)]
+
+use kali_parse::parse_str;
+use kali_type::{TypeInferenceEngine, TypeInferenceError};
+
+/// Infers the types of `src`, returning the errors.
+pub fn infer_spanned_errors(src: &str) -> Vec<TypeInferenceError> {
+    let module = parse_str(src).unwrap();
+    TypeInferenceEngine::infer_recovering(&module).1
+}
+
+/// Infers the types of `src`, returning the errors without their spans.
+pub fn infer_errors(src: &str) -> Vec<TypeInferenceError> {
+    infer_spanned_errors(src)
+        .into_iter()
+        .map(|error| match error {
+            TypeInferenceError::Spanned { error, .. } => *error,
+            error => error,
+        })
+        .collect()
+}
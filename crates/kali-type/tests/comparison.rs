@@ -0,0 +1,40 @@
+mod common;
+
+use common::infer_errors;
+use kali_type::{Type, TypeInferenceError};
+
+#[test]
+fn test_structural_comparison() {
+    let src = "let a = (1, \"a\") == (1, \"b\");\nlet b = [1, 2] < [1, 2, 3];\n\
+               let c = p { x: int, y: bool } -> (p >= p);\nlet d = #[1] != #[2];\n\
+               let e = some(1) <= none";
+    let errors = infer_errors(src);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn test_functions_are_incomparable() {
+    let errors = infer_errors("let f = (x) -> x;\nlet a = (1, f) == (1, f)");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::Incomparable(Type::Tuple(..))]
+        ),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn test_unordered() {
+    let errors = infer_errors("let a = #[1] < #[2];\nlet b = [(x) -> x] > []");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [
+                TypeInferenceError::Unordered(Type::Parameterized(..)),
+                TypeInferenceError::Unordered(Type::Array(..)),
+            ]
+        ),
+        "{errors:?}"
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine};
+
+const ADD: &str = "let add = x, y -> (x + y);\n";
+
+#[test]
+fn test_partial_application() {
+    let src = format!(
+        "{}let add1 = add 1;\nlet a = add1 2;\nlet b = add(1)(2)",
+        ADD
+    );
+    let module = parse_str(&src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let nat = Type::Constant(Constant::Natural);
+    assert_eq!(
+        types.bindings["add1"],
+        Type::Lambda(vec![nat.clone()], Box::new(nat.clone()))
+    );
+    assert_eq!(types.bindings["a"], nat);
+    assert_eq!(types.bindings["b"], nat);
+}
+
+#[test]
+fn test_returned_closure() {
+    let src = format!("{}let f = n -> (add n);\nlet b = f(1)(2)", ADD);
+    let module = parse_str(&src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let nat = Type::Constant(Constant::Natural);
+    assert_eq!(
+        types.bindings["f"],
+        Type::Lambda(
+            vec![nat.clone()],
+            Box::new(Type::Lambda(vec![nat.clone()], Box::new(nat.clone())))
+        )
+    );
+    assert_eq!(types.bindings["b"], nat);
+}
+
+#[test]
+fn test_partial_pipeline() {
+    let src = format!(
+        "{}let apply = f, x -> f(x);\nlet a = 2 |> apply (add 1)",
+        ADD
+    );
+    let module = parse_str(&src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Natural));
+}
+
+#[test]
+fn test_partial_application_errors() {
+    // the arguments passed are still checked
+    let errors = infer_errors(&format!("{}let a = add 1;\nlet b = add true", ADD));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+
+    // and so are those passed to the closure
+    let errors = infer_errors(&format!("{}let a = add(1)(true)", ADD));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+
+    // labelled arguments must still be passed to every parameter without a default value
+    let errors = infer_errors(&format!("{}let a = add(y: 1)", ADD));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].to_string(), "missing argument for parameter `x`");
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::infer_spanned_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+const GREET: &str = "let greet = name, greeting = \"hello\" -> (greeting, name);\n";
+
+#[test]
+fn test_default_argument() {
+    let src = format!(
+        "{}let a = greet(1);\nlet b = greet(2, greeting: \"hi\")",
+        GREET
+    );
+    let module = parse_str(&src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let ty = Type::Tuple(vec![
+        Type::Constant(Constant::String),
+        Type::Constant(Constant::Natural),
+    ]);
+    assert_eq!(types.bindings["a"], ty);
+    assert_eq!(types.bindings["b"], ty);
+}
+
+#[test]
+fn test_default_type() {
+    // default values are checked against the type of their parameter
+    let errors = infer_spanned_errors(&format!("{}let f = x nat = true -> x", GREET));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    let TypeInferenceError::Spanned { span, error } = &errors[0] else {
+        panic!("expected a spanned error");
+    };
+    assert!(matches!(**error, TypeInferenceError::UnificationFailed(..)));
+    assert_eq!(span.into_range().start - GREET.len(), 16);
+
+    // and so are the arguments that replace them
+    let errors = infer_spanned_errors(&format!("{}let a = greet(1, greeting: 2)", GREET));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
+
+#[test]
+fn test_default_scope() {
+    // default values are evaluated outside of the lambda, so cannot see its parameters
+    let errors = infer_spanned_errors(&format!("{}let f = x, y = x -> y", GREET));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].to_string().starts_with("unknown identifier `x`"));
+
+    // and refer to the definitions visible where they are declared, which must not be
+    // shadowed where they are substituted
+    let src = "let n = 1;\nlet f = x = n -> x;\nlet a = f();\nlet b = n -> f()";
+    let errors = infer_spanned_errors(&format!("{}{}", GREET, src));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(
+        errors[0].to_string(),
+        "the default value of parameter `x` refers to `n`, which is shadowed here"
+    );
+}
+
+#[test]
+fn test_missing_argument() {
+    let errors = infer_spanned_errors(&format!("{}let a = greet()", GREET));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(
+        errors[0].to_string(),
+        "missing argument for parameter `name`"
+    );
+
+    // parameters without a default value must still be passed
+    let errors = infer_spanned_errors(&format!("{}let a = greet(greeting: \"hi\")", GREET));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
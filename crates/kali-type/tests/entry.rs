@@ -0,0 +1,47 @@
+use kali_parse::parse_str;
+use kali_type::{EntryPoint, TypeInferenceEngine, TypeInferenceError, entry_point};
+
+/// Infers the types of `src` and finds its entry point.
+fn entry(src: &str) -> Result<EntryPoint, TypeInferenceError> {
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    entry_point(&module, &types)
+}
+
+#[test]
+fn test_entry_point() {
+    assert_eq!(
+        entry("export let main = -> -1").unwrap(),
+        EntryPoint::NoArguments
+    );
+    assert_eq!(
+        entry("let main = args [string] -> 0;\nexport { main }").unwrap(),
+        EntryPoint::Arguments
+    );
+    // the type of the arguments may be left to be inferred
+    assert_eq!(
+        entry("export let main = args -> 0").unwrap(),
+        EntryPoint::Arguments
+    );
+}
+
+#[test]
+fn test_invalid_entry_point() {
+    assert!(matches!(
+        entry("let f = -> 0"),
+        Err(TypeInferenceError::MissingEntryPoint)
+    ));
+
+    let error = entry("let main = -> 0").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`main` must be exported to start the program at"
+    );
+    assert_eq!(error.span().unwrap().into_range(), 4..8);
+
+    let error = entry("export let main = x nat -> true").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`main` must have type () -> int or ([string]) -> int, found (nat) -> bool"
+    );
+}
@@ -0,0 +1,106 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Suggestion, Type, TypeInferenceEngine, TypeInferenceError};
+
+#[test]
+fn test_well_typed() {
+    let module = parse_str("let f = x -> (x + 1);\nlet a = f 2").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let nat = Type::Constant(Constant::Natural);
+    assert_eq!(
+        types.bindings["f"],
+        Type::Lambda(vec![nat.clone()], Box::new(nat.clone()))
+    );
+    assert_eq!(types.bindings["a"], nat);
+}
+
+#[test]
+fn test_multiple_errors() {
+    let src = "let a = 1 + true;\nlet b = c;\nlet d = if 1 { 2 } else { \"e\" }";
+    let module = parse_str(src).unwrap();
+    let (types, errors) = TypeInferenceEngine::infer_recovering(&module);
+    let spans: Vec<_> = errors
+        .iter()
+        .map(|error| error.span().unwrap().into_range())
+        .collect();
+    assert_eq!(spans, [8..16, 26..27, 40..41, 55..58], "{:?}", errors);
+    assert!(matches!(
+        &errors[1],
+        TypeInferenceError::Spanned { error, .. }
+            if matches!(**error, TypeInferenceError::UnknownIdentifier { ref name, .. } if name == "c")
+    ));
+    assert_eq!(types.bindings["a"], Type::Error);
+
+    match TypeInferenceEngine::infer(&module) {
+        Err(TypeInferenceError::Multiple(errors)) => assert_eq!(errors.len(), 4),
+        result => panic!("expected multiple errors, found {:?}", result),
+    }
+}
+
+#[test]
+fn test_errors_do_not_cascade() {
+    let module = parse_str("let a = c + 1;\nlet b = a * 2").unwrap();
+    let (_, errors) = TypeInferenceEngine::infer_recovering(&module);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
+
+#[test]
+fn test_did_you_mean() {
+    let src = "let length = 1;\nlet f = x -> lenght";
+    let module = parse_str(src).unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unknown identifier `lenght`; did you mean `length`?"
+    );
+
+    let TypeInferenceError::Spanned { error, .. } = error else {
+        panic!("expected a spanned error");
+    };
+    let TypeInferenceError::UnknownIdentifier {
+        suggestion: Some(Suggestion { span, replacement }),
+        ..
+    } = *error
+    else {
+        panic!("expected a suggestion");
+    };
+    let mut fixed = src.to_string();
+    fixed.replace_range(span.into_range(), &replacement);
+    assert!(TypeInferenceEngine::infer(&parse_str(&fixed).unwrap()).is_ok());
+}
+
+#[test]
+fn test_bytes() {
+    let module = parse_str("let f = x bytes -> x;\nlet a = f b\"\\x89PNG\"").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Bytes));
+
+    // byte strings are not strings
+    let module = parse_str("let f = x bytes -> x;\nlet a = f \"PNG\"").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
+
+#[test]
+fn test_negation() {
+    let module = parse_str("let f = x int -> -x;\nlet a = f(-2);\nlet g = x -> -x").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_ok());
+
+    // nats have no negative values
+    let module = parse_str("let x = 1;\nlet y = -x").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert_eq!(error.span().unwrap().into_range(), 20..21);
+    assert_eq!(
+        error.to_string(),
+        "values of type nat have no negative values, so cannot be negated"
+    );
+}
+
+#[test]
+fn test_unknown_export() {
+    let module = parse_str("let length = 1;\nexport {length as len, lenght}").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unknown identifier `lenght`; did you mean `length`?"
+    );
+    assert_eq!(error.span().unwrap().into_range(), 39..45);
+}
@@ -0,0 +1,54 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+/// Returns the hole errors reported for a module, without their spans.
+fn holes(src: &str) -> Vec<TypeInferenceError> {
+    let module = parse_str(src).unwrap();
+    TypeInferenceEngine::infer_recovering(&module)
+        .1
+        .into_iter()
+        .map(|error| match error {
+            TypeInferenceError::Spanned { error, .. } => *error,
+            error => error,
+        })
+        .collect()
+}
+
+#[test]
+fn test_hole_expected_type() {
+    let natural = Type::Constant(Constant::Natural);
+    // the type of a hole is inferred from its surroundings, including later uses
+    let errors = holes("let a = 1 + ?rest");
+    let [TypeInferenceError::Hole { name, expected, .. }] = errors.as_slice() else {
+        panic!("expected a single hole, found {:?}", errors);
+    };
+    assert_eq!(name, "rest");
+    assert_eq!(*expected, natural);
+
+    let errors = holes("let f = x -> ?body;\nlet a = f(1) && true");
+    let [TypeInferenceError::Hole { expected, .. }] = errors.as_slice() else {
+        panic!("expected a single hole, found {:?}", errors);
+    };
+    assert_eq!(*expected, Type::Constant(Constant::Bool));
+}
+
+#[test]
+fn test_hole_bindings() {
+    let errors = holes("let n = 1;\nlet f = x string, y -> ?todo");
+    let [TypeInferenceError::Hole { bindings, .. }] = errors.as_slice() else {
+        panic!("expected a single hole, found {:?}", errors);
+    };
+    let names: Vec<_> = bindings.iter().map(|(name, _)| name.as_str()).collect();
+    // a definition is in scope of its own body, so that it can recurse
+    assert_eq!(names, ["f", "n", "x", "y"]);
+    assert_eq!(bindings[1].1, Type::Constant(Constant::Natural));
+    assert_eq!(bindings[2].1, Type::Constant(Constant::String));
+}
+
+#[test]
+fn test_hole_is_an_error() {
+    let module = parse_str("let a = ?a").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert_eq!(error.span().unwrap().into_range(), 8..10);
+    assert!(error.to_string().starts_with("found hole `?a` of type"));
+}
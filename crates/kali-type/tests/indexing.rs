@@ -0,0 +1,45 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+#[test]
+fn test_index() {
+    let src = "let xs = [\"a\", \"b\"];\nlet a = xs[1];\nlet b = [[true]][0][0];\n\
+               let f = ys, i -> ys[i + 1]";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::String));
+    assert_eq!(types.bindings["b"], Type::Constant(Constant::Bool));
+    let Type::Lambda(params, ret) = &types.bindings["f"] else {
+        panic!("expected a function, found {}", types.bindings["f"]);
+    };
+    assert_eq!(params[0], Type::Array(ret.clone()));
+    assert_eq!(params[1], Type::Constant(Constant::Natural));
+}
+
+#[test]
+fn test_index_errors() {
+    // lists are indexed by `nat`
+    let errors = infer_errors("let a = [1, 2][-1]");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::UnificationFailed(..)]
+        ),
+        "{:?}",
+        errors
+    );
+
+    // and only lists are indexed
+    let errors = infer_errors("let a = (1, 2)[0]");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::UnificationFailed(..)]
+        ),
+        "{:?}",
+        errors
+    );
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use kali_parse::parse_str;
+use kali_type::{Constant, ModuleInterface, Type, TypeInferenceEngine, TypeInferenceError};
+
+fn nat() -> Type {
+    Type::Constant(Constant::Natural)
+}
+
+fn interface(src: &str) -> ModuleInterface {
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    ModuleInterface::new(&module, &types)
+}
+
+#[test]
+fn test_interface_exports() {
+    let src = "type n = nat;\nlet f = x n -> (x + 1);\nlet g = f 1;\nexport { f, n as m }";
+    let interface = interface(src);
+    let values: Vec<_> = interface.values.keys().collect();
+    assert_eq!(values, ["f"]);
+    assert_eq!(
+        interface.values["f"],
+        Type::Lambda(vec![nat()], Box::new(nat()))
+    );
+    let types: Vec<_> = interface.types.keys().collect();
+    assert_eq!(types, ["m"]);
+
+    // an interface reads back as it was written
+    let json = interface.to_json();
+    assert_eq!(ModuleInterface::from_json(&json).unwrap(), interface);
+}
+
+#[test]
+fn test_import_from_interface() {
+    let interfaces = HashMap::from([(
+        "a::b".to_string(),
+        interface("type n = nat;\nlet f = x n -> (x + 1);\nexport { f, n }"),
+    )]);
+
+    let module = parse_str("import a::b::{f, n};\nlet g = x n -> f(x)").unwrap();
+    let (types, errors) = TypeInferenceEngine::infer_recovering_with(&module, &interfaces);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(
+        types.bindings["g"],
+        Type::Lambda(vec![nat()], Box::new(nat()))
+    );
+
+    // the types of imported names are checked
+    let module = parse_str("import a::b::{f as h};\nlet g = h true").unwrap();
+    let (_, errors) = TypeInferenceEngine::infer_recovering_with(&module, &interfaces);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+
+    let module = parse_str("import a::b::*;\nlet g = f 1").unwrap();
+    let (types, errors) = TypeInferenceEngine::infer_recovering_with(&module, &interfaces);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(types.bindings["g"], nat());
+}
+
+#[test]
+fn test_import_not_exported() {
+    let interfaces = HashMap::from([(
+        "a".to_string(),
+        interface("let f = 1;\nlet g = 2;\nexport { f }"),
+    )]);
+    let module = parse_str("import a::{f, h}").unwrap();
+    let (_, errors) = TypeInferenceEngine::infer_recovering_with(&module, &interfaces);
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::Spanned { error, .. }]
+                if matches!(**error, TypeInferenceError::NotExported { ref module, ref name } if module == "a" && name == "h")
+        ),
+        "{:?}",
+        errors
+    );
+}
+
+#[test]
+fn test_generic_imports() {
+    let lib = interface("export let id = x -> x");
+    assert_eq!(lib.values["id"].to_string(), "('0) -> '0");
+    let interfaces = HashMap::from([("lib".to_string(), lib)]);
+
+    // imported variables do not clash with the variables of the importing module
+    let module = parse_str("import lib::id;\nlet a = [];\nlet b = id(true)").unwrap();
+    let (types, errors) = TypeInferenceEngine::infer_recovering_with(&module, &interfaces);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(types.bindings["b"], Type::Constant(Constant::Bool));
+
+    // each import is given its own variables
+    let src = "import lib::{id, id as same};\nlet a = id(1);\nlet b = same(true)";
+    let module = parse_str(src).unwrap();
+    let (_, errors) = TypeInferenceEngine::infer_recovering_with(&module, &interfaces);
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_shared_variables() {
+    // the cell and the function pushing onto it must agree on the type of its elements
+    let lib = interface("export let xs = ref([]);\nexport let push = x -> set(xs, x :: get(xs))");
+    assert_eq!(lib.shared.len(), 1);
+    let interfaces = HashMap::from([("lib".to_string(), lib)]);
+    let module = parse_str("import lib::{xs, push};\nlet a = push(1);\nlet b = [true] == get(xs)");
+    let (_, errors) = TypeInferenceEngine::infer_recovering_with(&module.unwrap(), &interfaces);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
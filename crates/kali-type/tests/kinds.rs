@@ -0,0 +1,52 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Kind, TypeInferenceEngine, TypeInferenceError};
+
+#[test]
+fn test_consistent_kinds() {
+    let errors = infer_errors("type a = Map<string, List<int>>;\nlet f = xs List<string> -> xs");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_kind_mismatch() {
+    let module = parse_str("type a = Option<int>;\ntype b = Option<int, int>").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`Option` has kind * -> *, but is used as * -> * -> *"
+    );
+    assert_eq!(error.span().unwrap().into_range(), 31..37);
+
+    // a constructor must be applied to its types
+    let errors = infer_errors("type a = Map<string, int>;\nlet f = m Map -> m");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::KindMismatch {
+                expected: Kind(2),
+                found: Kind(0),
+                ..
+            }]
+        ),
+        "{:?}",
+        errors
+    );
+}
+
+#[test]
+fn test_applied_alias() {
+    // type aliases take no types
+    let errors = infer_errors("type a = int;\ntype b = a<int>");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::KindMismatch { name, expected: Kind(0), found: Kind(1) }]
+                if name == "a"
+        ),
+        "{:?}",
+        errors
+    );
+}
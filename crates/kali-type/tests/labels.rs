@@ -0,0 +1,98 @@
+mod common;
+
+use common::infer_spanned_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+const DRAW: &str = "let draw = x, y bool -> x;\n";
+
+/// Returns the underlying error and the range of the source it refers to.
+fn unspan(error: &TypeInferenceError) -> (&TypeInferenceError, std::ops::Range<usize>) {
+    let TypeInferenceError::Spanned { span, error } = error else {
+        panic!("expected a spanned error, found {:?}", error);
+    };
+    (
+        error,
+        span.into_range().start - DRAW.len()..span.end - DRAW.len(),
+    )
+}
+
+#[test]
+fn test_labelled_arguments() {
+    let src = format!(
+        "{}let a = draw(y: true, x: 1);\nlet b = draw(1, y: false)",
+        DRAW
+    );
+    let module = parse_str(&src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Natural));
+    assert_eq!(types.bindings["b"], Type::Constant(Constant::Natural));
+
+    // arguments are checked against the parameters they are passed to, not their position
+    let errors = infer_spanned_errors(&format!("{}let a = draw(x: 1, y: 1)", DRAW));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(matches!(
+        unspan(&errors[0]).0,
+        TypeInferenceError::UnificationFailed(..)
+    ));
+}
+
+#[test]
+fn test_unknown_label() {
+    let errors = infer_spanned_errors(&format!("{}let a = draw(x: 1, z: true)", DRAW));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    let (error, span) = unspan(&errors[0]);
+    assert_eq!(error.to_string(), "`draw` has no parameter named `z`");
+    assert_eq!(span, 19..20);
+}
+
+#[test]
+fn test_misordered_arguments() {
+    let errors = infer_spanned_errors(&format!("{}let a = draw(y: true, 1)", DRAW));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    let (error, span) = unspan(&errors[0]);
+    assert!(matches!(error, TypeInferenceError::PositionalAfterLabelled));
+    assert_eq!(span, 22..23);
+
+    let errors = infer_spanned_errors(&format!("{}let a = draw(1, x: 2, y: true)", DRAW));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(
+        unspan(&errors[0]).0.to_string(),
+        "parameter `x` is given more than one argument"
+    );
+
+    let errors = infer_spanned_errors(&format!("{}let a = draw(1, true, 3, y: true)", DRAW));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(
+        unspan(&errors[0]).0.to_string(),
+        "`draw` takes 2 argument(s)"
+    );
+}
+
+#[test]
+fn test_missing_argument() {
+    let errors = infer_spanned_errors(&format!("{}let a = draw(y: true)", DRAW));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    let (error, span) = unspan(&errors[0]);
+    assert_eq!(error.to_string(), "missing argument for parameter `x`");
+    assert_eq!(span, 8..21);
+}
+
+#[test]
+fn test_unlabelled_function() {
+    // only functions defined with named parameters accept labels, so neither parameters of
+    // lambdas nor functions that destructure their argument do
+    let errors = infer_spanned_errors(&format!("{}let a = f -> f(x: 1)", DRAW));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(matches!(
+        unspan(&errors[0]).0,
+        TypeInferenceError::UnlabelledFunction
+    ));
+
+    let errors = infer_spanned_errors(&format!("{}let f = (a, b) -> a;\nlet c = f(a: 1)", DRAW));
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(
+        unspan(&errors[0]).0.to_string(),
+        "`f` has no parameter named `a`"
+    );
+}
@@ -0,0 +1,54 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+#[test]
+fn test_while_loop() {
+    let src = "let r = ref 0;\nlet a = while get(r) < 10 do set(r, get(r) + 1)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Unit));
+
+    // the condition must be a bool
+    let module = parse_str("let a = while 1 do 2").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    let TypeInferenceError::Spanned { span, error } = error else {
+        panic!("expected a spanned error, found {:?}", error);
+    };
+    assert!(matches!(*error, TypeInferenceError::UnificationFailed(..)));
+    assert_eq!(span.into_range(), 14..15);
+}
+
+#[test]
+fn test_for_loop() {
+    let module = parse_str("let a = for x in [1, 2] do x == 1").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["a"],
+        Type::Array(Box::new(Type::Constant(Constant::Bool)))
+    );
+
+    // the binding may destructure each element, and is only in scope in the body
+    let module = parse_str("let f = xs -> (for (a, b) in xs do a + b :: [b])").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let Type::Lambda(params, ret) = &types.bindings["f"] else {
+        panic!("expected a function, found {}", types.bindings["f"]);
+    };
+    assert_eq!(params.len(), 1);
+    assert!(matches!(&**ret, Type::Array(element) if matches!(**element, Type::Array(_))));
+
+    let module = parse_str("let a = for x in [1] do x;\nlet b = x").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert!(
+        matches!(
+            &error,
+            TypeInferenceError::Spanned { error, .. }
+                if matches!(**error, TypeInferenceError::UnknownIdentifier { .. })
+        ),
+        "{:?}",
+        error
+    );
+
+    // only lists can be iterated over
+    let module = parse_str("let a = for x in 1 do x").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
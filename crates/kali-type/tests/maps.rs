@@ -0,0 +1,59 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Kind, Type, TypeInferenceEngine, TypeInferenceError, map_of, option};
+
+#[test]
+fn test_map_literal() {
+    let module = parse_str("let m = #{ \"a\": 1, \"b\": 2 };\nlet e = #{}").unwrap();
+    let types = TypeInferenceEngine::infer_recovering(&module).0;
+    let (nat, string) = (
+        Type::Constant(Constant::Natural),
+        Type::Constant(Constant::String),
+    );
+    assert_eq!(types.bindings["m"], map_of(string.clone(), nat.clone()));
+    assert!(matches!(
+        &types.bindings["e"],
+        Type::Parameterized(name, types) if name == "Map" && types.len() == 2
+    ));
+
+    // every key has the same type, as does every value
+    let module = parse_str("let m = #{ \"a\": 1, 2: 3 }").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+    let module = parse_str("let m = #{ \"a\": 1, \"b\": \"c\" }").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
+
+#[test]
+fn test_map_operations() {
+    let src = "let m = #{ \"a\": 1 };\nlet n = m |> insert(\"b\", 2) |> remove \"a\";\n\
+               let v = lookup(\"b\", n);\nlet s = size n";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let (nat, string) = (
+        Type::Constant(Constant::Natural),
+        Type::Constant(Constant::String),
+    );
+    assert_eq!(types.bindings["n"], map_of(string, nat.clone()));
+    assert_eq!(types.bindings["v"], option(nat.clone()));
+    assert_eq!(types.bindings["s"], nat);
+
+    // a map can be annotated, and takes two types
+    let module = parse_str("let f = m Map<string, int> -> size(m)").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_ok());
+    let module = parse_str("let f = m Map<string> -> size(m)").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    let TypeInferenceError::Spanned { error, .. } = error else {
+        panic!("expected a spanned error, found {:?}", error);
+    };
+    assert!(
+        matches!(
+            *error,
+            TypeInferenceError::KindMismatch {
+                expected: Kind(2),
+                found: Kind(1),
+                ..
+            }
+        ),
+        "{:?}",
+        error
+    );
+}
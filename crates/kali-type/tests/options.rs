@@ -0,0 +1,123 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{
+    Constant, Type, TypeInferenceEngine, TypeInferenceError, TypeUnificationError, option, result,
+};
+
+#[test]
+fn test_option_combinators() {
+    let src = "let a = some 1;\nlet b = map(x -> (x == 1), a);\n\
+               let c = and_then(x -> if x { some \"yes\" } else { none }, b);\n\
+               let d = c |> unwrap_or \"no\";\nlet e = (is_some(a), is_none(c));\n\
+               let f = a |> ok_or \"missing\"";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let (nat, bool, string) = (
+        Type::Constant(Constant::Natural),
+        Type::Constant(Constant::Bool),
+        Type::Constant(Constant::String),
+    );
+    assert_eq!(types.bindings["a"], option(nat.clone()));
+    assert_eq!(types.bindings["b"], option(bool.clone()));
+    assert_eq!(types.bindings["c"], option(string.clone()));
+    assert_eq!(types.bindings["d"], string.clone());
+    assert_eq!(types.bindings["e"], Type::Tuple(vec![bool.clone(), bool]));
+    assert_eq!(types.bindings["f"], result(nat, string.clone()));
+
+    // an option can be annotated
+    let module = parse_str("let f = x Option<string> -> unwrap_or(\"none\", x)").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["f"],
+        Type::Lambda(vec![option(string.clone())], Box::new(string))
+    );
+}
+
+#[test]
+fn test_result_combinators() {
+    let src = "let a = ok 1;\nlet b = map_err(e -> (e, e), map_ok(x -> (x == 1), a));\n\
+               let c = and_then_ok(x -> if x { ok \"yes\" } else { err((\"no\", \"no\")) }, b);\n\
+               let d = c |> unwrap_ok_or \"no\";\nlet e = (is_ok(a), is_err(c))";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let (bool, string) = (
+        Type::Constant(Constant::Bool),
+        Type::Constant(Constant::String),
+    );
+    let pair = Type::Tuple(vec![string.clone(), string.clone()]);
+    assert_eq!(types.bindings["b"], result(bool.clone(), pair.clone()));
+    assert_eq!(types.bindings["c"], result(string.clone(), pair));
+    assert_eq!(types.bindings["d"], string);
+    assert_eq!(types.bindings["e"], Type::Tuple(vec![bool.clone(), bool]));
+}
+
+#[test]
+fn test_unwrap_suggestion() {
+    // an option used as the value it may hold suggests unwrapping it
+    let errors = infer_errors(
+        "let f = x Option<string> -> (\"a\" == x);\nlet g = y string -> y;\nlet h = g(some \"a\")",
+    );
+    let suggested = |error: &TypeInferenceError| {
+        matches!(
+            error,
+            TypeInferenceError::UnificationFailed(
+                _,
+                _,
+                TypeUnificationError::Unwrap {
+                    combinator: "unwrap_or",
+                    ..
+                }
+            )
+        )
+    };
+    assert!(
+        errors.len() == 2 && errors.iter().all(suggested),
+        "{:?}",
+        errors
+    );
+
+    let module = parse_str("let g = y string -> y;\nlet a = g(some \"a\")").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .ends_with("Option<string> may hold no value, so must be unwrapped with `unwrap_or`"),
+        "{}",
+        error
+    );
+
+    // and so does a result
+    let errors = infer_errors("let g = y string -> y;\nlet a = g(ok \"a\")");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::UnificationFailed(
+                _,
+                _,
+                TypeUnificationError::Unwrap {
+                    combinator: "unwrap_ok_or",
+                    ..
+                }
+            )]
+        ),
+        "{:?}",
+        errors
+    );
+
+    // but not an option of a different type
+    let errors = infer_errors("let g = y string -> y;\nlet a = g(some 1)");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::UnificationFailed(
+                _,
+                _,
+                TypeUnificationError::Mismatch { .. }
+            )]
+        ),
+        "{:?}",
+        errors
+    );
+}
@@ -0,0 +1,26 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine};
+
+#[test]
+fn test_pipe() {
+    let src = "let add = x, y -> (x + y);\nlet neg = x -> -x;\nlet a = 1 |> add 2 |> neg";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Natural));
+
+    // the piped value is passed as the last argument
+    let src = "let pair = x, y -> (x, y);\nlet a = true |> pair 1";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["a"],
+        Type::Tuple(vec![
+            Type::Constant(Constant::Natural),
+            Type::Constant(Constant::Bool)
+        ])
+    );
+
+    let src = "let neg = x -> -x;\nlet a = true |> neg |> neg 1";
+    let module = parse_str(src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError, range_of};
+
+#[test]
+fn test_range() {
+    let src = "let a = 1..10;\nlet b = -5..=-1;\nlet c = collect(a);\n\
+               let d = for i in b do i * i;\nlet e = for i in collect(0..3) do i == 1";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let (nat, int) = (
+        Type::Constant(Constant::Natural),
+        Type::Constant(Constant::Integer),
+    );
+    assert_eq!(types.bindings["a"], range_of(nat.clone()));
+    assert_eq!(types.bindings["b"], range_of(int.clone()));
+    assert_eq!(types.bindings["c"], Type::Array(Box::new(nat)));
+    assert_eq!(types.bindings["d"], Type::Array(Box::new(int)));
+    assert_eq!(
+        types.bindings["e"],
+        Type::Array(Box::new(Type::Constant(Constant::Bool)))
+    );
+
+    // both ends have the same type, which is a whole number
+    assert!(!infer_errors("let a = 1..\"b\"").is_empty());
+    let errors = infer_errors("let a = true..=false");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::NonNumericRange(Type::Constant(
+                Constant::Bool
+            ))]
+        ),
+        "{:?}",
+        errors
+    );
+}
+
+#[test]
+fn test_range_pattern() {
+    let src = "let f = n -> match n { 0 -> \"none\", 1..=9 -> \"some\", _ -> \"many\" }";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["f"],
+        Type::Lambda(
+            vec![Type::Constant(Constant::Natural)],
+            Box::new(Type::Constant(Constant::String))
+        )
+    );
+
+    let errors = infer_errors("let a = match \"b\" { \"a\"..\"z\" -> 1, _ -> 2 }");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::NonNumericRange(Type::Constant(
+                Constant::String
+            ))]
+        ),
+        "{:?}",
+        errors
+    );
+}
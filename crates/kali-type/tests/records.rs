@@ -0,0 +1,28 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine};
+
+#[test]
+fn test_field_order() {
+    // the order fields are written in does not change the type
+    let src = "let f = p { x: int, y: bool } -> (p);\nlet g = q { y: bool, x: int } -> (f q)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let record = Type::Record(
+        [
+            ("x".to_string(), Type::Constant(Constant::Integer)),
+            ("y".to_string(), Type::Constant(Constant::Bool)),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    assert_eq!(
+        types.bindings["g"],
+        Type::Lambda(vec![record.clone()], Box::new(record.clone()))
+    );
+    // and fields are displayed sorted by name
+    assert_eq!(record.to_string(), "{ x: int, y: bool }");
+
+    let src = "let f = p { x: int } -> (p);\nlet g = q { y: int } -> (f q)";
+    let module = parse_str(src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
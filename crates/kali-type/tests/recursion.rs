@@ -0,0 +1,53 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine};
+
+#[test]
+fn test_mutual_recursion() {
+    let src = "let even = n -> if n == 0 { true } else { odd(n - 1) };\nlet odd = n -> if n == 0 { false } else { even(n - 1) }";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let predicate = Type::Lambda(
+        vec![Type::Constant(Constant::Natural)],
+        Box::new(Type::Constant(Constant::Bool)),
+    );
+    assert_eq!(types.bindings["even"], predicate);
+    assert_eq!(types.bindings["odd"], predicate);
+}
+
+#[test]
+fn test_mutually_recursive_clauses() {
+    let src =
+        "fn even 0 = true;\nfn even n = odd(n - 1);\nfn odd 0 = false;\nfn odd n = even(n - 1)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["even"], types.bindings["odd"]);
+}
+
+#[test]
+fn test_forward_reference() {
+    // a definition may use one after it, and is checked against its type
+    let module = parse_str("let a = b + 1;\nlet b = 2").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Natural));
+
+    let module = parse_str("let a = b + 1;\nlet b = \"b\"").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+
+    // names may also be exported before they are defined
+    let module = parse_str("export {f};\nlet f = 1").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_ok());
+}
+
+#[test]
+fn test_redefinition() {
+    // a name refers to its first definition until it is defined, and to the latest after
+    let src = "let b = a;\nlet a = 1;\nlet c = a;\nlet a = \"a\";\nlet d = a";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let natural = Type::Constant(Constant::Natural);
+    let string = Type::Constant(Constant::String);
+    assert_eq!(types.bindings["b"], natural);
+    assert_eq!(types.bindings["c"], natural);
+    assert_eq!(types.bindings["d"], string);
+    assert_eq!(types.bindings["a"], string);
+}
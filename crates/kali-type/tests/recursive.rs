@@ -0,0 +1,60 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+const LIST: &str = "type List = () | (nat, List)";
+
+#[test]
+fn test_recursive_alias() {
+    let src = format!(
+        "{};\nlet sum = xs List -> match xs {{ () -> 0, (x, rest) -> x + sum(rest) }}",
+        LIST
+    );
+    let module = parse_str(&src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let Type::Lambda(params, ret) = &types.bindings["sum"] else {
+        panic!("expected a lambda");
+    };
+    assert!(matches!(&params[0], Type::Recursive(name, _) if name == "List"));
+    assert_eq!(**ret, Type::Constant(Constant::Natural));
+    // recursive types are displayed by name
+    assert_eq!(types.bindings["sum"].to_string(), "(List) -> nat");
+}
+
+#[test]
+fn test_recursive_values() {
+    // a value of the structure a recursive type stands for is a value of the type
+    let src = format!("{};\nlet f = xs List -> xs;\nlet a = f((1, (2, ())))", LIST);
+    let module = parse_str(&src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"].to_string(), "List");
+
+    let src = format!("{};\nlet f = xs List -> xs;\nlet a = f((1, 2))", LIST);
+    let module = parse_str(&src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
+
+#[test]
+fn test_mutually_recursive_aliases() {
+    let src = "type Tree = (nat, Forest);\ntype Forest = [Tree];\nlet f = t Tree -> t;\nlet a = f((1, [(2, [])]))";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["f"].to_string(), "(Tree) -> Tree");
+}
+
+#[test]
+fn test_unproductive_alias() {
+    for src in ["type a = a | int", "type a = b;\ntype b = a & int"] {
+        let module = parse_str(src).unwrap();
+        let errors = TypeInferenceEngine::infer_recovering(&module).1;
+        let [TypeInferenceError::Spanned { error, span }] = errors.as_slice() else {
+            panic!("{}: expected a single error, found {:?}", src, errors);
+        };
+        assert!(
+            matches!(**error, TypeInferenceError::UnproductiveType(ref name) if name == "a"),
+            "{}: {:?}",
+            src,
+            error
+        );
+        assert_eq!(span.into_range(), 5..6);
+    }
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Kind, Type, TypeInferenceEngine, TypeInferenceError};
+
+#[test]
+fn test_ref_cells() {
+    let module = parse_str("let r = ref 0;\nlet a = set r 1;\nlet b = get r").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let nat = Type::Constant(Constant::Natural);
+    assert_eq!(
+        types.bindings["r"],
+        Type::Parameterized("Ref".to_string(), vec![nat.clone()])
+    );
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Unit));
+    assert_eq!(types.bindings["b"], nat);
+
+    // each use of an intrinsic is instantiated separately
+    let module = parse_str("let r = ref 0;\nlet s = ref \"a\";\nlet t = (get(r), get(s))").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["t"],
+        Type::Tuple(vec![nat, Type::Constant(Constant::String)])
+    );
+
+    // a cell can be annotated
+    let module = parse_str("let f = r Ref<int> -> (get r)").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let int = Type::Constant(Constant::Integer);
+    assert_eq!(
+        types.bindings["f"],
+        Type::Lambda(
+            vec![Type::Parameterized("Ref".to_string(), vec![int.clone()])],
+            Box::new(int)
+        )
+    );
+}
+
+#[test]
+fn test_ref_cell_errors() {
+    // a cell only holds values of one type
+    let errors = infer_errors("let r = ref 0;\nlet a = set r true");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::UnificationFailed(..)]
+        ),
+        "{:?}",
+        errors
+    );
+
+    let errors = infer_errors("let f = r Ref<int, int> -> r");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::KindMismatch {
+                expected: Kind(1),
+                found: Kind(2),
+                ..
+            }]
+        ),
+        "{:?}",
+        errors
+    );
+
+    // intrinsics are suggested for typos
+    let errors = infer_errors("let a = sett 0");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::UnknownIdentifier { suggestion: Some(suggestion), .. }]
+                if suggestion.replacement == "set"
+        ),
+        "{:?}",
+        errors
+    );
+}
+
+#[test]
+fn test_shadowed_intrinsics() {
+    let module = parse_str("let get = x -> (x + 1);\nlet a = get 1").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Natural));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError, result};
+
+#[test]
+fn test_try() {
+    let src = "let parse = s string -> if s == \"\" { err(\"empty\") } else { ok(1) };\n\
+               let twice = s -> ok(parse(s)? * 2)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let (nat, string) = (
+        Type::Constant(Constant::Natural),
+        Type::Constant(Constant::String),
+    );
+    assert_eq!(
+        types.bindings["twice"],
+        Type::Lambda(vec![string.clone()], Box::new(result(nat, string)))
+    );
+
+    // functions defined by clauses return errors too
+    let src = "fn first [] = err(\"empty\");\nfn first (x :: _) = ok(x);\n\
+               let f = xs -> ok(first(xs)? + 1)";
+    assert!(infer_errors(src).is_empty(), "{:?}", infer_errors(src));
+}
+
+#[test]
+fn test_try_errors() {
+    // the errors of every `?` in a function are returned as the same type
+    let errors = infer_errors("let f = a, b -> ok(a? + b?);\nlet g = f(err(1), err(\"b\"))");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::UnificationFailed(..)]
+        ),
+        "{:?}",
+        errors
+    );
+
+    // a function returning a `?` must return a result
+    let errors = infer_errors("let f = r -> (r? + 1)");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::UnificationFailed(..)]
+        ),
+        "{:?}",
+        errors
+    );
+
+    let module = parse_str("let a = ok(1)?").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`?` returns errors from the enclosing function, but is not inside one"
+    );
+    assert_eq!(error.span().unwrap().into_range(), 8..14);
+}
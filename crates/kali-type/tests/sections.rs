@@ -0,0 +1,41 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine};
+
+#[test]
+fn test_section() {
+    let natural = Type::Constant(Constant::Natural);
+    let src = "let inc = (+ 1);\nlet a = inc 2";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["inc"],
+        Type::Lambda(vec![natural.clone()], Box::new(natural.clone()))
+    );
+    assert_eq!(types.bindings["a"], natural);
+
+    // comparisons are functions to a boolean
+    let src = "let is_zero = (0 ==)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["is_zero"],
+        Type::Lambda(
+            vec![natural.clone()],
+            Box::new(Type::Constant(Constant::Bool))
+        )
+    );
+
+    // the missing operand of `::` is a list of the given one
+    let src = "let push = (1 ::)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let list = Type::Array(Box::new(natural));
+    assert_eq!(
+        types.bindings["push"],
+        Type::Lambda(vec![list.clone()], Box::new(list))
+    );
+
+    let src = "let inc = (+ 1);\nlet a = inc true";
+    let module = parse_str(src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError, set_of};
+
+#[test]
+fn test_set_operations() {
+    let src = "let a = #[1, 2];\nlet b = #[2, 3];\nlet c = union(a, b) |> intersection #[3];\n\
+               let d = member(2, c);\nlet e = #[(\"a\", [true]), (\"b\", [])]";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let nat = Type::Constant(Constant::Natural);
+    assert_eq!(types.bindings["c"], set_of(nat));
+    assert_eq!(types.bindings["d"], Type::Constant(Constant::Bool));
+    assert_eq!(
+        types.bindings["e"],
+        set_of(Type::Tuple(vec![
+            Type::Constant(Constant::String),
+            Type::Array(Box::new(Type::Constant(Constant::Bool))),
+        ]))
+    );
+
+    // every element has the same type
+    assert!(!infer_errors("let a = #[1, \"b\"]").is_empty());
+    assert!(!infer_errors("let a = member(\"b\", #[1])").is_empty());
+}
+
+#[test]
+fn test_unhashable_elements() {
+    // functions cannot be compared, so cannot be set elements or map keys
+    let errors = infer_errors("let a = #[x -> x, y -> y]");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::Unhashable(Type::Lambda(..))]
+        ),
+        "{:?}",
+        errors
+    );
+    let errors = infer_errors("let f = n nat -> n;\nlet m = #{ (1, f): 2 }");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::Unhashable(Type::Tuple(..))]
+        ),
+        "{:?}",
+        errors
+    );
+
+    // but may be the values of maps
+    let module = parse_str("let f = n nat -> n;\nlet m = #{ 1: f }").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_ok());
+
+    let module = parse_str("let a = #[n nat -> n]").unwrap();
+    let error = TypeInferenceEngine::infer(&module).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .ends_with("cannot be compared, so cannot be set elements or map keys"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,69 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+fn nats() -> Type {
+    Type::Array(Box::new(Type::Constant(Constant::Natural)))
+}
+
+#[test]
+fn test_list_spread() {
+    let src = "let xs = [1, 2];\nlet ys = [0, ..xs, 3, ..xs]";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["ys"], nats());
+
+    let module = parse_str("let f = xs -> [..xs];\nlet a = f [true]").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["a"],
+        Type::Array(Box::new(Type::Constant(Constant::Bool)))
+    );
+}
+
+#[test]
+fn test_spread_argument() {
+    // arguments from the first spread onwards are passed as a single list
+    let src = "let f = a, xs -> (a :: xs);\nlet ys = [2];\nlet b = f(1, ..ys, 3)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(types.bindings["b"], nats());
+
+    let src = "let sum = xs -> 0;\nlet xs = [1];\nlet c = sum(..xs)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    assert_eq!(
+        types.bindings["sum"],
+        Type::Lambda(vec![nats()], Box::new(Type::Constant(Constant::Natural)))
+    );
+}
+
+#[test]
+fn test_spread_of_non_list() {
+    let module = parse_str("let a = [1, ..2]").unwrap();
+    let (_, errors) = TypeInferenceEngine::infer_recovering(&module);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].span().unwrap().into_range(), 14..15);
+
+    let module = parse_str("let a = [1, ..[true]]").unwrap();
+    let (_, errors) = TypeInferenceEngine::infer_recovering(&module);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
+
+#[test]
+fn test_misplaced_spread() {
+    // the parser only produces spreads in lists and argument lists, but other producers of
+    // syntax trees may not
+    let mut module = parse_str("let a = [..xs]").unwrap();
+    let kali_ast::ItemKind::Definition(definition) = &mut module.items[0].kind else {
+        panic!("expected a definition");
+    };
+    let kali_ast::ExprKind::List(elements) = &mut definition.expr.kind else {
+        panic!("expected a list");
+    };
+    definition.expr = elements.remove(0);
+    let (_, errors) = TypeInferenceEngine::infer_recovering(&module);
+    assert!(errors.iter().any(|error| matches!(
+        error,
+        TypeInferenceError::Spanned { error, .. } if matches!(**error, TypeInferenceError::MisplacedSpread)
+    )));
+}
@@ -0,0 +1,40 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, option};
+
+#[test]
+fn test_string_intrinsics() {
+    let src = "let s = \"Москва\";\nlet a = length(s) + byte_length s;\n\
+               let b = slice(s, 0, length(s) - 1);\nlet c = chars(b);\n\
+               let d = for c in s do c == \"a\"";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let string = Type::Constant(Constant::String);
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Natural));
+    assert_eq!(types.bindings["b"], string.clone());
+    assert_eq!(types.bindings["c"], Type::Array(Box::new(string)));
+    assert_eq!(
+        types.bindings["d"],
+        Type::Array(Box::new(Type::Constant(Constant::Bool)))
+    );
+
+    // positions are counted in `nat`s
+    let module = parse_str("let a = slice(\"abc\", -1, 2)").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
+
+#[test]
+fn test_prefix_patterns() {
+    let src = "let route = s -> match s {\n  \"GET \" ++ path -> some(path),\n  \
+               \"\" | \"HEAD\" -> none,\n  _ -> none,\n};\nlet a = route(\"GET /\")";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let string = Type::Constant(Constant::String);
+    assert_eq!(
+        types.bindings["route"],
+        Type::Lambda(vec![string.clone()], Box::new(option(string)))
+    );
+
+    // only strings have prefixes
+    let module = parse_str("let a = match 1 { \"a\" ++ _ -> 1, _ -> 2 }").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
@@ -0,0 +1,30 @@
+use std::thread;
+
+use kali_parse::parse_str;
+use kali_type::{Context, ModuleTypes, TypeInferenceEngine};
+
+fn assert_send<T: Send>() {}
+
+#[test]
+fn test_context_is_send() {
+    assert_send::<Context>();
+    assert_send::<TypeInferenceEngine>();
+    assert_send::<ModuleTypes>();
+}
+
+#[test]
+fn test_parallel_inference() {
+    // independent modules are inferred on their own threads
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            thread::spawn(move || {
+                let module = parse_str(&format!("let f = x nat -> (x + {})", i)).unwrap();
+                TypeInferenceEngine::infer(&module).unwrap()
+            })
+        })
+        .collect();
+    for handle in handles {
+        let types = handle.join().unwrap();
+        assert_eq!(types.bindings["f"].to_string(), "(nat) -> nat");
+    }
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::infer_errors;
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine, TypeInferenceError};
+
+#[test]
+fn test_projection() {
+    let src = "let t = (1, (\"a\", true));\nlet a = t.0;\nlet b = t.1.1;\n\
+               let c = fst(t.1);\nlet d = snd t;\nlet f = p (int, string) -> p.1";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let (string, bool) = (
+        Type::Constant(Constant::String),
+        Type::Constant(Constant::Bool),
+    );
+    assert_eq!(types.bindings["a"], Type::Constant(Constant::Natural));
+    assert_eq!(types.bindings["b"], bool.clone());
+    assert_eq!(types.bindings["c"], string.clone());
+    assert_eq!(types.bindings["d"], Type::Tuple(vec![string.clone(), bool]));
+    assert_eq!(
+        types.bindings["f"],
+        Type::Lambda(
+            vec![Type::Tuple(vec![
+                Type::Constant(Constant::Integer),
+                string.clone()
+            ])],
+            Box::new(string)
+        )
+    );
+}
+
+#[test]
+fn test_projection_errors() {
+    let errors = infer_errors("let a = (1, 2).2");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::TupleIndexOutOfBounds { index: 2, .. }]
+        ),
+        "{:?}",
+        errors
+    );
+
+    let errors = infer_errors("let a = [1, 2].0");
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [TypeInferenceError::NotATuple(Type::Array(_))]
+        ),
+        "{:?}",
+        errors
+    );
+
+    // the arity of the tuple must be known where it is projected
+    let errors = infer_errors("let f = t -> t.0");
+    assert!(
+        matches!(errors.as_slice(), [TypeInferenceError::AmbiguousType(_)]),
+        "{:?}",
+        errors
+    );
+}
@@ -0,0 +1,86 @@
+use kali_parse::parse_str;
+use kali_type::{Constant, Type, TypeInferenceEngine};
+
+fn int() -> Type {
+    Type::Constant(Constant::Integer)
+}
+
+fn string() -> Type {
+    Type::Constant(Constant::String)
+}
+
+#[test]
+fn test_union_annotation() {
+    let module = parse_str("let f = x int | string | int -> x").unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    // duplicate members are removed
+    let union = Type::Union(vec![int(), string()]);
+    assert_eq!(
+        types.bindings["f"],
+        Type::Lambda(vec![union.clone()], Box::new(union))
+    );
+    assert_eq!(
+        types.bindings["f"].to_string(),
+        "(int | string) -> int | string"
+    );
+}
+
+#[test]
+fn test_union_subsumption() {
+    // a member is accepted where the union is expected
+    let src = "let f = x bool | string -> x;\nlet a = f true;\nlet b = f \"b\"";
+    let module = parse_str(src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_ok());
+
+    let module = parse_str("let f = x bool | string -> x;\nlet a = f 1").unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+
+    // but a union is not accepted where only one of its members is
+    let src = "let g = n int -> n;\nlet f = x int | string -> g x";
+    let module = parse_str(src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
+
+#[test]
+fn test_intersection_of_records() {
+    // a value of both record types has the fields of each
+    let src = "type p = { x: int } & { y: string };\nlet f = v p -> v;\nlet g = q { y: string, x: int } -> (f q)";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let record = Type::Record(
+        [("x".to_string(), int()), ("y".to_string(), string())]
+            .into_iter()
+            .collect(),
+    );
+    assert_eq!(
+        types.bindings["g"],
+        Type::Lambda(vec![record.clone()], Box::new(record))
+    );
+
+    let src =
+        "type p = { x: int } & { y: string };\nlet f = v p -> v;\nlet g = q { x: int } -> (f q)";
+    let module = parse_str(src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
+
+#[test]
+fn test_match_narrowing() {
+    // once the tuple is matched, only the list is left
+    let src = "let f = v (int, int) | [int] -> match v { (a, b) -> [a, b], xs -> xs }";
+    let module = parse_str(src).unwrap();
+    let types = TypeInferenceEngine::infer(&module).unwrap();
+    let Type::Lambda(_, ret) = &types.bindings["f"] else {
+        panic!("expected a lambda");
+    };
+    assert_eq!(**ret, Type::Array(Box::new(int())));
+
+    // a literal does not match every value of its type, so leaves it to be matched
+    let src = "let f = v int | string -> match v { 0 -> \"zero\", s -> s }";
+    let module = parse_str(src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+
+    // a pattern that matches no member is an error
+    let src = "let f = v int | string -> match v { true -> 1, _ -> 2 }";
+    let module = parse_str(src).unwrap();
+    assert!(TypeInferenceEngine::infer(&module).is_err());
+}
@@ -0,0 +1,207 @@
+//! The Kali compiler, as a library.
+//!
+//! A [`Compiler`] runs source code through every pass of the compiler in turn: parsing, linting,
+//! desugaring, type checking and constant evaluation. It does the same work as `kali check`, so
+//! that Rust applications can compile Kali without going through the command line interface:
+//!
+//! ```
+//! let mut compiler = kali::Compiler::new().with_entry_point();
+//! let compilation = compiler.compile_str("export let main = -> 0");
+//! assert!(compilation.is_ok());
+//! ```
+//!
+//! Every pass runs even if an earlier one fails, so that a single compilation reports as many
+//! problems as possible. The exception is constant evaluation, which only runs once the module
+//! has type checked, as it relies on every value being of the type it was inferred to be.
+//!
+//! Syntax trees are `Send` and `Sync`, and the module of a [`Compilation`] is behind an [`Arc`], so
+//! that later passes can share it between threads without copying it. Strings are only interned
+//! into a module while it is parsed and desugared, so a shared module only resolves them.
+
+use std::{collections::HashMap, sync::Arc};
+
+pub use kali_ast::Module;
+pub use kali_consteval::{Constants, Value};
+pub use kali_error::{Diagnostic, LintConfig};
+pub use kali_span::{FileId, SourceMap};
+use kali_type::TypeInferenceEngine;
+pub use kali_type::{ModuleInterface, ModuleTypes};
+
+/// The path reported for source code compiled with [`Compiler::compile_str`].
+const INPUT: &str = "<input>";
+
+/// The size of the stack the passes of a compilation run on.
+///
+/// Every pass recurses once per level of nesting, which the parser bounds, so this leaves room
+/// for the deepest code it accepts even in debug builds, on threads with small stacks.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Finds the interfaces of the modules a module may import, by path, e.g. `a::b`.
+type Imports<'a> = Box<dyn FnMut(&Module) -> HashMap<String, ModuleInterface> + 'a>;
+
+/// Receives each diagnostic of a compilation, alongside the sources its spans refer to.
+type Sink<'a> = Box<dyn FnMut(&Diagnostic, &SourceMap) + 'a>;
+
+/// Compiles Kali source code, configured with builder methods.
+///
+/// The compiler keeps the source of everything it compiles in a [`SourceMap`], so that the spans
+/// of every [`Compilation`] can be looked up with [`Compiler::sources`].
+pub struct Compiler<'a> {
+    /// The levels at which lints are reported.
+    lints: LintConfig,
+    /// Whether modules must export a `main` function to start at.
+    entry_point: bool,
+    /// Finds the interfaces of the modules each module imports.
+    imports: Imports<'a>,
+    /// Receives the diagnostics of each compilation, if set.
+    sink: Option<Sink<'a>>,
+    /// The sources compiled so far.
+    sources: SourceMap,
+}
+
+impl Default for Compiler<'_> {
+    fn default() -> Self {
+        Compiler {
+            lints: LintConfig::default(),
+            entry_point: false,
+            imports: Box::new(|_| HashMap::new()),
+            sink: None,
+            sources: SourceMap::new(),
+        }
+    }
+}
+
+impl<'a> Compiler<'a> {
+    /// Creates a compiler that reports lints at their default levels, and resolves no imports.
+    pub fn new() -> Compiler<'a> {
+        Compiler::default()
+    }
+
+    /// Sets the levels at which lints are reported.
+    pub fn with_lints(mut self, lints: LintConfig) -> Compiler<'a> {
+        self.lints = lints;
+        self
+    }
+
+    /// Requires compiled modules to be programs, exporting an
+    /// [entry point](kali_type::entry_point) to start at.
+    pub fn with_entry_point(mut self) -> Compiler<'a> {
+        self.entry_point = true;
+        self
+    }
+
+    /// Sets the interfaces of the modules every compiled module may import, by path, e.g. `a::b`.
+    pub fn with_interfaces(self, interfaces: HashMap<String, ModuleInterface>) -> Compiler<'a> {
+        self.with_imports(move |_| interfaces.clone())
+    }
+
+    /// Sets how the interfaces of the modules a module imports are found, once it has been
+    /// parsed. Names imported from modules without an interface are of unknown type.
+    pub fn with_imports(
+        mut self,
+        imports: impl FnMut(&Module) -> HashMap<String, ModuleInterface> + 'a,
+    ) -> Compiler<'a> {
+        self.imports = Box::new(imports);
+        self
+    }
+
+    /// Passes each diagnostic to `sink` as a compilation finishes, in the order of their spans.
+    pub fn with_diagnostics(
+        mut self,
+        sink: impl FnMut(&Diagnostic, &SourceMap) + 'a,
+    ) -> Compiler<'a> {
+        self.sink = Some(Box::new(sink));
+        self
+    }
+
+    /// Returns the sources compiled so far, which the spans of each compilation refer to.
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    /// Compiles source code that was not read from a file.
+    pub fn compile_str(&mut self, source: &str) -> Compilation {
+        self.compile(INPUT, source)
+    }
+
+    /// Compiles the source code of a module.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path the source was read from, used when reporting spans in it.
+    /// * `source` - The source code of the module.
+    pub fn compile(&mut self, path: impl Into<String>, source: impl Into<String>) -> Compilation {
+        let file_id = self.sources.add(path, source);
+        stacker::maybe_grow(STACK_SIZE, STACK_SIZE, || self.compile_file(file_id))
+    }
+
+    /// Runs every pass of the compiler over a file added to the sources.
+    fn compile_file(&mut self, file_id: FileId) -> Compilation {
+        let source = self.sources.source(file_id);
+
+        let (mut module, errors, mistakes) = kali_parse::parse_file_with_mistakes(file_id, source);
+        let mut diagnostics: Vec<_> = errors.into_iter().map(Diagnostic::from).collect();
+        // syntax errors caused by common mistakes come with a fix
+        diagnostics.extend(mistakes.into_iter().map(Diagnostic::from));
+        diagnostics.extend(kali_lint::lint(&module, &self.lints));
+        // lints see the code as written, while the type checker only handles the core language
+        kali_desugar::desugar(&mut module);
+        let interfaces = (self.imports)(&module);
+        let (types, errors) = TypeInferenceEngine::infer_recovering_with(&module, &interfaces);
+        let typed = errors.is_empty();
+        diagnostics.extend(errors.into_iter().flat_map(Diagnostic::from_type_error));
+        if self.entry_point
+            && let Err(error) = kali_type::entry_point(&module, &types)
+        {
+            diagnostics.extend(Diagnostic::from_type_error(error));
+        }
+        // constant definitions that would stop the program are found before it runs
+        let constants = if typed {
+            kali_consteval::constants(&module)
+        } else {
+            Constants::default()
+        };
+        diagnostics.extend(constants.errors.iter().cloned().map(Diagnostic::from));
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+
+        if let Some(sink) = &mut self.sink {
+            for diagnostic in &diagnostics {
+                sink(diagnostic, &self.sources);
+            }
+        }
+        Compilation {
+            file_id,
+            module: Arc::new(module),
+            types,
+            constants,
+            diagnostics,
+        }
+    }
+}
+
+/// The result of compiling a module.
+#[derive(Debug)]
+pub struct Compilation {
+    /// The ID of the compiled source in the [`Compiler::sources`] of the compiler.
+    pub file_id: FileId,
+    /// The desugared syntax tree of the module, which can be shared between threads.
+    pub module: Arc<Module>,
+    /// The types inferred for the module.
+    pub types: ModuleTypes,
+    /// The values of the top-level definitions of the module that are constant.
+    pub constants: Constants,
+    /// Every problem found in the module, in the order of their spans.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Compilation {
+    /// Returns whether the module compiled without errors. It may still have warnings.
+    pub fn is_ok(&self) -> bool {
+        !self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+
+    /// Returns the interface other modules import the module through.
+    pub fn interface(&self) -> ModuleInterface {
+        ModuleInterface::new(&self.module, &self.types)
+    }
+}
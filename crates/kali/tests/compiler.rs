@@ -0,0 +1,128 @@
+use std::{collections::HashMap, sync::Arc, thread};
+
+use kali::{Compiler, LintConfig, Value};
+use kali_error::{Lint, LintLevel, Severity};
+use kali_type::{Constant, Type};
+
+#[test]
+fn test_compile() {
+    let mut compiler = Compiler::new();
+    let compilation = compiler.compile("main.kali", "let f = x nat -> (x + 1)");
+    assert!(compilation.is_ok(), "{:?}", compilation.diagnostics);
+    let nat = Type::Constant(Constant::Natural);
+    assert_eq!(
+        compilation.types.bindings["f"],
+        Type::Lambda(vec![nat.clone()], Box::new(nat))
+    );
+    assert_eq!(compiler.sources().path(compilation.file_id), "main.kali");
+}
+
+#[test]
+fn test_every_pass_reports() {
+    // the syntax error does not stop the lint or the type error from being reported
+    let mut compiler = Compiler::new();
+    let compilation = compiler.compile_str("let x = 1;\nlet x = true + 1;\nlet y = (");
+    assert!(!compilation.is_ok());
+    let lints: Vec<_> = compilation.diagnostics.iter().map(|d| d.lint).collect();
+    assert_eq!(lints, [Some(Lint::Shadowing), None, None], "{:?}", lints);
+    // diagnostics are in the order of their spans
+    assert!(
+        compilation
+            .diagnostics
+            .is_sorted_by_key(|diagnostic| diagnostic.span.start)
+    );
+}
+
+#[test]
+fn test_lints() {
+    let mut config = LintConfig::default();
+    config.set(Lint::Shadowing, LintLevel::Deny);
+    let mut compiler = Compiler::new().with_lints(config);
+    let compilation = compiler.compile_str("let x = 1;\nlet x = 2");
+    assert!(!compilation.is_ok());
+    assert_eq!(compilation.diagnostics[0].severity, Severity::Error);
+}
+
+#[test]
+fn test_entry_point() {
+    let src = "let main = -> 0";
+    assert!(Compiler::new().compile_str(src).is_ok());
+    assert!(!Compiler::new().with_entry_point().compile_str(src).is_ok());
+}
+
+#[test]
+fn test_interfaces() {
+    let mut compiler = Compiler::new();
+    let lib = compiler.compile("a.kali", "let f = x nat -> (x + 1);\nexport { f }");
+    let interfaces = HashMap::from([("a".to_string(), lib.interface())]);
+    let mut compiler = Compiler::new().with_interfaces(interfaces);
+    let compilation = compiler.compile_str("import a::f;\nlet g = f true");
+    assert_eq!(
+        compilation.diagnostics.len(),
+        1,
+        "{:?}",
+        compilation.diagnostics
+    );
+}
+
+#[test]
+fn test_diagnostics_sink() {
+    let mut messages = Vec::new();
+    let mut compiler = Compiler::new().with_diagnostics(|diagnostic, sources| {
+        messages.push(format!(
+            "{}: {}",
+            sources.lookup(diagnostic.span),
+            diagnostic
+        ))
+    });
+    let compilation = compiler.compile("main.kali", "let x = true + 1");
+    let diagnostics = compilation.diagnostics.len();
+    drop(compiler);
+    assert_eq!(messages.len(), diagnostics);
+    assert!(messages[0].starts_with("main.kali:1:"), "{:?}", messages);
+}
+
+#[test]
+fn test_share_module() {
+    let mut compiler = Compiler::new();
+    let compilation = compiler.compile_str("let x = 1;\nlet f = y -> (y + x)");
+    // passes on other threads borrow the module rather than copying it
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let module = Arc::clone(&compilation.module);
+            thread::spawn(move || kali_lint::lint(&module, &LintConfig::default()).len())
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 0);
+    }
+    assert_eq!(Arc::strong_count(&compilation.module), 1);
+}
+
+#[test]
+fn test_constants() {
+    let mut compiler = Compiler::new();
+    let compilation = compiler.compile_str("let size = 4 * 1024;\nlet f = x -> x");
+    assert!(compilation.is_ok(), "{:?}", compilation.diagnostics);
+    assert_eq!(compilation.constants.values["size"], Value::Natural(4096));
+    assert!(!compilation.constants.values.contains_key("f"));
+    // a definition that would stop the program is an error
+    let compilation = compiler.compile_str("let x = [1, 2][2]");
+    assert!(!compilation.is_ok());
+    assert_eq!(compilation.diagnostics[0].span.into_range(), 15..16);
+}
+
+#[test]
+fn test_constants_need_types() {
+    // a module that does not type check reports its type errors alone
+    let mut compiler = Compiler::new();
+    let compilation = compiler.compile_str("let x = 1;\nlet y = -x");
+    assert_eq!(
+        compilation.diagnostics.len(),
+        1,
+        "{:?}",
+        compilation.diagnostics
+    );
+    assert_eq!(compilation.diagnostics[0].span.into_range(), 20..21);
+    assert!(compilation.constants.values.is_empty());
+}
@@ -0,0 +1,86 @@
+//! Snapshot tests of the diagnostics reported for each `.kali` file in `tests/ui`.
+//!
+//! The reports of a file are compared with the `.stderr` file next to it. Run with `KALI_BLESS=1`
+//! to write the `.stderr` files from the reports instead, and review the changes before
+//! committing them.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use kali::Compiler;
+
+/// The environment variable that writes the snapshots rather than checking them.
+const BLESS: &str = "KALI_BLESS";
+
+/// Removes the ANSI escape sequences that colour a report.
+fn strip_colours(report: &str) -> String {
+    let mut stripped = String::with_capacity(report.len());
+    let mut chars = report.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // an escape sequence runs up to and including its final letter
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Compiles a file and renders the report of every diagnostic, as `kali check` prints them.
+fn render(file: &Path) -> String {
+    let source = fs::read_to_string(file).unwrap();
+    let name = format!("ui/{}", file.file_name().unwrap().to_string_lossy());
+    let mut compiler = Compiler::new();
+    let compilation = compiler.compile(name, source);
+    let mut out = Vec::new();
+    for diagnostic in &compilation.diagnostics {
+        let cache = kali_error::cache(compiler.sources());
+        diagnostic
+            .report(compiler.sources())
+            .write(cache, &mut out)
+            .unwrap();
+    }
+    strip_colours(&String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn test_ui() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/ui");
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "kali"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "no tests in {}", dir.display());
+
+    let bless = std::env::var_os(BLESS).is_some();
+    let mut failures = Vec::new();
+    for file in &files {
+        let found = render(file);
+        let snapshot = file.with_extension("stderr");
+        if bless {
+            fs::write(&snapshot, &found).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&snapshot).unwrap_or_default();
+        if found != expected {
+            failures.push(format!(
+                "{}\n--- expected\n{}\n--- found\n{}",
+                file.display(),
+                expected,
+                found
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} snapshot(s) differ, run with {}=1 to update them:\n\n{}",
+        failures.len(),
+        BLESS,
+        failures.join("\n\n")
+    );
+}
@@ -69,6 +69,7 @@ Like most programming languages, Kali has support for a variety of primitive dat
 - Floats
 - Booleans
 - Strings
+- Bytes
 
 ==== Integers
 
@@ -79,6 +80,10 @@ let x = 10
 let y = -5
 ```
 
+Integers are 64 bits wide on every target. The `int` type holds signed values from $-2^63$ to $2^63 - 1$, and the `nat` type holds unsigned values from $0$ to $2^64 - 1$. A literal that does not fit in its type is an error, and so is negating a `nat`, which has no negative values.
+
+A negative literal is a `-` followed by a natural literal. Between two operands `-` always subtracts, so `x -1` is `x - 1` rather than `x` applied to `-1`.
+
 ==== Floats
 
 Floats are numbers with a decimal point, and can be positive or negative.
@@ -117,6 +122,33 @@ let city = "Москва"
 let mood = "Feeling great! 😊"
 ```
 
+Strings are measured and indexed in characters, which are Unicode scalar values, rather than in the bytes of their encoding. `length` counts the characters of a string and `byte_length` the bytes of its UTF-8 encoding. `slice(s, start, end)` is the characters of `s` from position `start` up to, but not including, position `end`, and `chars` splits a string into its characters, each as a string of one character. A `for` loop over a string evaluates its body once for each character in the same way.
+
+#table(
+  columns: 2,
+  [*Function*], [*Type*],
+  [`length`], [`(string) -> nat`],
+  [`byte_length`], [`(string) -> nat`],
+  [`slice`], [`(string, nat, nat) -> string`],
+  [`chars`], [`(string) -> [string]`],
+)
+
+```kali
+let city = "Москва";
+let n = length(city); # 6, although it is encoded in 12 bytes
+let start = slice(city, 0, 3) # "Мос"
+```
+
+Slicing past the last character stops the program with an error, as indexing past the end of an array does.
+
+==== Bytes
+
+Byte strings hold arbitrary binary data, such as the contents of a file, and have the type `bytes`. They are written like strings with a `b` prefix. Characters stand for their UTF-8 encoding, and any byte can be written with an escape sequence: `\xNN` for a byte in hexadecimal, or `\n`, `\r`, `\t`, `\0`, `\\` and `\"`.
+
+```kali
+let magic = b"\x89PNG\r\n"
+```
+
 === Composite Types
 
 Kali has support for four composite data types:
@@ -151,6 +183,20 @@ The syntax for a tuple is as follows:
 tuple = "(" type { "," type } ")"
 ```
 
+The elements of a tuple are taken by their index, counted from zero. `fst` and `snd` take the elements of a pair:
+
+```kali
+let person = ("John", 30);
+let name = person.0;
+let age = snd(person)
+```
+
+```ebnf
+projection = expr "." natural
+```
+
+Like an argument list, a projection binds to the expression it directly follows, so `f x.0` is `f (x.0)`. The type of the tuple must be known where it is projected, so that its index can be checked against the number of its elements: `t -> t.0` is ambiguous, but `t (string, int) -> t.0` has type `((string, int)) -> string`.
+
 The unit type is a special case of the tuple type, with no elements.
 
 ```kali
@@ -187,6 +233,79 @@ array_literal = "[" expr ("," expr)* "]"
 array_access = expr "[" expr "]"
 ```
 
+The elements of an array are taken by their position, counted from zero, which is a `nat`. Like an argument list, the `[` of an index must directly follow the array, since `f [0]` applies `f` to an array:
+
+```kali
+let numbers = [1, 2, 3, 4, 5];
+let first = numbers[0];
+let last = numbers[4]
+```
+
+Taking an element past the end of an array stops the program with an error at the span of the index.
+
+==== Maps
+
+Maps associate keys with values, all keys being of one type and all values of another. A map from keys of type `k` to values of type `v` has type `Map<k, v>`, and is written as its entries between `#{` and `}`:
+
+```kali
+let ages = #{ "alice": 31, "bob": 27 }
+```
+
+```ebnf
+map_literal = "#{" [ entry ("," entry)* [","] ] "}"
+entry = expr ":" expr
+```
+
+Entries are evaluated in the order they are written. Keys cannot be functions, as functions cannot be compared. A `#` directly followed by `{` opens a map rather than a comment. Maps are changed by functions every module can use without importing them, which return a new map rather than changing the one they are given:
+
+#table(
+  columns: 2,
+  [*Function*], [*Type*],
+  [`insert`], [`(k, v, Map<k, v>) -> Map<k, v>`],
+  [`lookup`], [`(k, Map<k, v>) -> Option<v>`],
+  [`remove`], [`(k, Map<k, v>) -> Map<k, v>`],
+  [`size`], [`(Map<k, v>) -> nat`],
+)
+
+==== Sets
+
+Sets hold values of one type, each at most once. A set of values of type `a` has type `Set<a>`, and is written as its elements between `#[` and `]`:
+
+```kali
+let primes = #[2, 3, 5, 7]
+```
+
+```ebnf
+set_literal = "#[" [ expr ("," expr)* [","] ] "]"
+```
+
+Like the keys of maps, the elements of sets cannot be functions. Sets are persistent, so combining them makes a new set rather than changing either. Sets are combined by functions every module can use without importing them:
+
+#table(
+  columns: 2,
+  [*Function*], [*Type*],
+  [`union`], [`(Set<a>, Set<a>) -> Set<a>`],
+  [`intersection`], [`(Set<a>, Set<a>) -> Set<a>`],
+  [`member`], [`(a, Set<a>) -> bool`],
+)
+
+==== Ranges
+
+A range is the whole numbers from its start up to its end. `a..b` stops before `b`, and `a..=b` includes it:
+
+```kali
+let digits = 0..10;
+let dice = 1..=6
+```
+
+```ebnf
+range = expr (".." | "..=") expr
+```
+
+Both ends must be of the same type, `nat` or `int`, and a range of `nat`s has type `Range<nat>`. Ranges bind looser than every operator but `::` and `|>`, so `1..n + 1` ends at `n + 1`, and they do not chain, so `a..b..c` is an error.
+
+A range is counted as it is iterated over rather than stored, so a `for` loop over `0..1000000` does not make a list of a million numbers. `collect`, of type `(Range<a>) -> [a]`, makes that list when it is wanted.
+
 ==== Records
 
 Records are a convenient way to define named composite data types.
@@ -202,6 +321,8 @@ record = "{" field { "," field } "}"
 field = identifier ":" type
 ```
 
+A record type is identified by the names and types of its fields, so the order fields are written in does not matter: `{ x: Int, y: Int }` and `{ y: Int, x: Int }` are the same type, and a record pattern matches fields by name. Types are displayed with their fields sorted by name, and the fields of a record are laid out in memory in the same order. Record expressions evaluate their fields in the order they are written. A field may only be given once.
+
 Fields on a record can be accessed using the `.` operator.
 
 ```kali
@@ -230,6 +351,19 @@ The `Never` type is a special data type that has no values. It is used to repres
 
 > *Note:* The `Never` type is a theoretical type used internally by the compiler and type checker. It cannot be written or referenced directly in Kali source code. Programmers will never need to annotate a variable or function as `Never`, nor can a value of type `Never` be constructed or matched in user code.
 
+=== Equality and Ordering
+
+Values are compared by their structure rather than by where they live. Two tuples, arrays or records are equal when their elements or fields are pairwise equal, and two values of a sum type are equal when they are built by the same variant from equal contents.
+
+```kali
+let a = (1, [true]) == (1, [true]) // true
+let b = [1, 2] < [1, 2, 3]        // true
+```
+
+`<`, `<=`, `>` and `>=` order values lexicographically. Tuples and arrays are compared element by element from the first, and a shorter array that is a prefix of a longer one is less than it. Records are compared field by field, in the order of their names. Values of a sum type are ordered first by variant, in the order the variants are declared, and then by contents, so `None` is less than any `Some`. `false` is less than `true`, and strings are ordered by their characters.
+
+Both sides of a comparison must have the same type. Functions have no meaningful equality, so values containing them cannot be compared at all. Maps and sets may be compared for equality but have no order.
+
 == Control Flow
 
 === If Expressions
@@ -299,6 +433,27 @@ let result = match x {
 }
 ```
 
+A range of literals matches the numbers in that range:
+
+```kali
+let size = n nat -> match n {
+  0 -> "none",
+  1..=9 -> "some",
+  _ -> "many",
+}
+```
+
+A string literal pattern matches that string exactly, and `"prefix" ++ rest` matches any string starting with `prefix`, matching the characters after it against `rest`. The prefix must be a string literal, and prefixes chain to the right, so `"a" ++ "b" ++ rest` is the same as `"ab" ++ rest`. `++` binds tighter than `|`, and its right operand is a single pattern, so an alternative of the rest is parenthesised:
+
+```kali
+let describe = request -> match request {
+  "GET " ++ ("/" | "/index") -> "home",
+  "GET " ++ path -> path,
+  "" -> "empty",
+  _ -> "unknown",
+}
+```
+
 Patterns can destructure tuples, arrays, and records:
 
 ```kali
@@ -311,8 +466,63 @@ let description = match point {
 
 Pattern matching in Kali is exhaustive: the compiler will warn if not all possible cases are covered, unless a catch-all `_` pattern is provided.
 
+=== Loops
+
+A `while` loop evaluates its body for as long as its condition holds, and a `for` loop evaluates its body once for each element of a list, number of a range or character of a string, binding it to a pattern:
+
+```kali
+let r = ref(0);
+let pairs = [(1, 2), (3, 4)];
+let counted = while get(r) < 10 do set(r, get(r) + 1);
+let sums = for (a, b) in pairs do a + b
+```
+
+The condition of a `while` loop must be a `bool`, and the loop itself is `()`, as the values of its body are discarded. A `for` loop collects the values of its body into a list, so iterating over a `[t]` with a body of type `u` gives a `[u]`. The body of a loop extends as far to the right as it can, so a loop used as an operand is parenthesised.
+
+=== Blocks
+
+A block evaluates a sequence of expressions separated by `;` in order, and its value is that of the last. The expressions before the last are evaluated for their effects, so their values must be `()`. A block is written in braces after `do`, and the branches of an `if` and the body of a loop are blocks without it:
+
+```kali
+let r = ref(0);
+let xs = [1, 2, 3];
+let next = do { set(r, get(r) + 1); get(r) };
+let evens = for x in xs do { set(r, x); x * 2 }
+```
+
+A `;` may not follow the last expression of a block, as the block would have no value.
+
 == Functions
 
+The definitions of a module may refer to one another regardless of the order they are written in, so functions may be mutually recursive:
+
+```kali
+let even = n -> if n == 0 { true } else { odd(n - 1) };
+let odd = n -> if n == 0 { false } else { even(n - 1) }
+```
+
+=== Entry Point
+
+A program starts running at the `main` function its first module exports. `main` either takes no arguments, or the command-line arguments as a list of strings, and returns the exit code of the process:
+
+```kali
+export let main = args [string] -> 0
+```
+
+The type of `main` must be `() -> int` or `([string]) -> int`, where `nat` may stand in for `int`. The exit code is the returned value modulo 256. `kali check --bin` checks that a file is a program in this sense.
+
+=== Function Types
+
+The type of a function is written as its parameter types and its return type, separated by an arrow. A function of a single parameter may leave out the parentheses:
+
+```kali
+type compare = (int, int) -> bool;
+type next = int -> int;
+type thunk = () -> int
+```
+
+Arrows bind more loosely than any other type operator, and associate to the right, so `int -> int -> int` returns a function and `int | bool -> int` takes a union. A single tuple parameter needs its own parentheses, as in `((int, int)) -> bool`. The type annotation of a lambda parameter must be parenthesised if it is a function type, since its arrow would otherwise end the parameters.
+
 === Higher-order Functions
 
 === Currying
@@ -324,14 +534,139 @@ let add = x: Int, y: Int => x + y
 let add1 = add 1
 ```
 
+Applying a function to fewer arguments than it takes evaluates to a closure taking the remaining arguments, so `add1 2` is `3`. Only calls that pass their arguments by position are partial: a call that leaves out parameters with default values passes those values instead, unless it also leaves out a parameter without one.
+
+=== Pipelines
+
+The pipeline operator `|>` passes the value on its left to the function on its right. When the function is a call, the value is passed after its other arguments, so `a` and `b` below are equal:
+
+```kali
+let ages = #{ "alice": 31, "bob": 27 };
+let double = x nat -> (x * 2);
+let a = ages |> lookup "alice" |> map double |> unwrap_or 0;
+let b = unwrap_or(0, map(double, lookup("alice", ages)))
+```
+
+Pipelines bind more loosely than any other operator, and associate to the left.
+
+=== Operator Sections
+
+A binary operator written in parentheses with only one of its operands is a section, a function of the missing operand:
+
+```kali
+let inc = (+ 1);    # x -> x + 1
+let double = (2 *)  # x -> 2 * x
+```
+
+`(- x)` is a negation rather than a section, so the only sections of subtraction give its left operand, such as `(x -)`. Pipelines have no sections.
+
 = Type System
 
 Kali's type system is heavily inspired by that of TypeScript and OCaml, and is designed to be both expressive and flexible.
 
+=== Error Propagation
+
+A function that may fail returns a `Result<t, e>`, which is either a value made by `ok : (t) -> Result<t, e>` or an error made by `err : (e) -> Result<t, e>`. A `?` after a result is its value, or returns its error from the enclosing function:
+
+```kali
+let parse = s string -> if s == "" { err("empty") } else { ok(s) };
+let both = a, b -> ok((parse(a)?, parse(b)?))
+```
+
+The enclosing function must return a result with the same type of error, so every `?` in a function propagates errors of one type. A `?` binds to the expression it directly follows, like an argument list, and may only be used inside a function.
+
+=== Options and Results
+
+A value that may be missing is an `Option<t>`, which is either `some(x)` or `none`. Options and results have combinators every module can use without importing them, which take the option or result last so that they can be used in pipelines:
+
+```kali
+let names = #{ 0: "root" };
+let parse = text string -> if text == "" { err(()) } else { ok(8080) };
+let name = names |> lookup 0 |> unwrap_or "anonymous";
+let size = names |> lookup 0 |> map length |> unwrap_or 0;
+let port = parse("") |> map_err (_e -> "bad port") |> unwrap_ok_or 80
+```
+
+#table(
+  columns: 2,
+  [*Function*], [*Type*],
+  [`is_some`, `is_none`], [`(Option<t>) -> bool`],
+  [`map`], [`((t) -> u, Option<t>) -> Option<u>`],
+  [`and_then`], [`((t) -> Option<u>, Option<t>) -> Option<u>`],
+  [`unwrap_or`], [`(t, Option<t>) -> t`],
+  [`ok_or`], [`(e, Option<t>) -> Result<t, e>`],
+  [`is_ok`, `is_err`], [`(Result<t, e>) -> bool`],
+  [`map_ok`], [`((t) -> u, Result<t, e>) -> Result<u, e>`],
+  [`map_err`], [`((e) -> f, Result<t, e>) -> Result<t, f>`],
+  [`and_then_ok`], [`((t) -> Result<u, e>, Result<t, e>) -> Result<u, e>`],
+  [`unwrap_ok_or`], [`(t, Result<t, e>) -> t`],
+)
+
+Functions are not overloaded, so the combinators of results that options also have are suffixed with `_ok`. An option or result used where the value it may hold is expected is an error that suggests unwrapping it with `unwrap_or` or `unwrap_ok_or`.
+
+== Mutation
+
+Bindings cannot be reassigned. Mutable state is held in a reference cell of type `Ref<t>`, which is created, read and written by the `ref`, `get` and `set` functions every module can use without importing them:
+
+```kali
+let counter = ref 0;
+let increment = -> set(counter, get(counter) + 1)
+```
+
+`ref : (t) -> Ref<t>` creates a cell holding a value, `get : (Ref<t>) -> t` reads the value a cell holds, and `set : (Ref<t>, t) -> ()` replaces it. A cell holds values of a single type. A definition of the same name shadows each of them.
+
 == Traits
 
 == Type Inferrence
 
+=== Unions and Intersections
+
+A union type `a | b` is the type of values of either type, and an intersection type `a & b` the type of values of both. A value of a member of a union may be used where the union is expected, but a value of the union may only be used where every one of its members could be. An intersection of record types is a record with the fields of each:
+
+```kali
+type Point = { x: int } & { y: int }; # { x: int, y: int }
+let show = v int | string -> v
+```
+
+The arms of a match on a value of a union type are narrowed to the members their patterns can match. Once a pattern has matched every value of a member, the arms after it no longer see that member, so below `xs` is a list:
+
+```kali
+let f = v (int, int) | [int] -> match v { (a, b) -> [a, b], xs -> xs }
+```
+
+=== Recursive Types
+
+A type alias may refer to itself, or to aliases that refer back to it, through a type constructor such as a tuple, list, record or function. A value of the structure the alias stands for is a value of the alias, and the alias is unfolded where a value of it is matched against that structure:
+
+```kali
+type List = () | (nat, List);
+let sum = xs List -> match xs { () -> 0, (x, rest) -> x + sum(rest) }
+```
+
+An alias that refers to itself only through unions and intersections, such as `type a = a | int`, has no values and is an error. Recursive types are written by their name in messages from the compiler.
+
+=== Kinds
+
+A type constructor such as `List` or `Map` must be applied to the same number of types wherever it is used, which is its kind: `List<int>` has kind `* -> *` and `Map<string, int>` kind `* -> * -> *`. The first use of a constructor fixes its kind, so a later `List<int, int>` or bare `List` is an error. Type aliases take no types.
+
+=== Ambiguous Types
+
+A type that the program leaves open, and that does not appear in the type of any binding, could be any type, so is an error. The list below is never used, so the type of its elements cannot be inferred:
+
+```kali
+let a = match [] { [] -> 1, _ -> 2 } # cannot infer the type of the elements
+```
+
+=== Holes
+
+A name prefixed with `?` is a hole, standing in for an expression that is yet to be written. A hole takes whatever type its surroundings require, and the compiler reports that type alongside the names in scope and their types:
+
+```kali
+let volume = w nat, h nat -> (w * h * ?depth) # found hole `?depth` of type nat
+```
+
+Holes are errors, so a program containing one does not compile.
+
 = Memory Management
 
 = Modules
@@ -356,6 +691,13 @@ export type Point = { x: Int, y: Int };
 
 You can export multiple items from a module. Only exported items are accessible from other modules; non-exported items are private to the module.
 
+Names defined elsewhere in the module can also be exported by a separate statement, optionally under another name, and every item exported by another module can be exported again:
+
+```kali
+export { area_circle, PI as pi };
+export * from "geometry/shapes";
+```
+
 === Importing
 
 To use exported items from another module, use the `import` keyword with path-based syntax. You can import all exported symbols from a module into scope:
@@ -379,6 +721,12 @@ import std::vec::vec;
 
 This system allows Kali code to be modular, maintainable, and easy to share, using a concise and familiar path-based import syntax.
 
+=== Module Interfaces
+
+A module path names a file relative to the importing module, so `import shapes::circle::area` in `main.kali` imports from `shapes/circle.kali`. Once a module type checks, the compiler writes its interface next to it as `shapes/circle.kali-iface`: the names it exports and their types. Modules that import it are type checked against the interface. The module itself is only checked again when it, or an interface it depends on, has changed since.
+
+Types left unknown by a module are generic in its interface, so each import of `let id = x -> x` may use it at a different type. An unknown type shared by several bindings, such as the elements of a reference cell and the argument of a function pushing onto it, is the same type for every import of them by a module.
+
 = Standard Library
 
 Kali comes with a standard library that provides a wide range of functionality, including data structures, algorithms, and utilities.
@@ -393,8 +741,88 @@ Kali is designed to run on a virtual machine, namely the Kali Virtual Machine (K
   "NOP", "0x00", "No operation"
 )
 
+== Resource Limits
+
+Embedders running untrusted programs can limit the resources the KVM spends on them, so that a program that loops forever or allocates without bound stops with an error instead of hanging or exhausting the memory of the host. Each limit is optional, and none is set by default:
+
+- *Fuel* bounds the number of instructions executed. Every instruction consumes one unit of fuel, and calls and allocations consume one more per argument or element.
+- *Heap size* bounds the number of bytes of live values on the heap, checked whenever a value is allocated.
+- *Timeout* bounds the wall-clock time spent running, checked at least once every thousand instructions.
+
+A program that exceeds a limit stops with `RuntimeError::LimitExceeded`, naming the limit. Unlike other runtime errors it cannot be recovered from within the program, and the runtime can be reused once the limits are raised or reset.
+
+The parser is limited in the same way. Expressions, patterns and types may be nested at most 256 levels deep, where each delimiter, prefix operator, right-associative operator and lambda body is a level deeper than those before it in the same expression, and the elements of a list or block start from the depth of its delimiter. Left-associative operators, calls and `else` chains do not nest their operands, but the syntax tree of a file may be at most 512 levels deep, counting each of them too. Both are checked before parsing, so that input such as thousands of nested parentheses or chained operators fails with an "expression nested too deeply" error instead of exhausting the stack of the compiler. The parser also has a budget of work per token of input, so that other pathological input fails with a "parse too complex" error rather than taking quadratic time.
+
+> *Note:* The KVM is not yet implemented, so only the limits of the parser are enforced.
+
+== Deterministic Execution
+
+The core language is deterministic: evaluation order is fixed, and no intrinsic reads the clock or a source of randomness. A runtime in deterministic mode keeps it that way for everything the host provides, so that replaying a program's bytecode, or running it on both the KVM and the JIT, produces identical results:
+
+- Maps and sets are traversed in the order their keys were first inserted, rather than in the order of their hash table.
+- Host functions that read the clock or a source of randomness fail with a runtime error, unless the embedder registers a seeded replacement.
+- Float operations are evaluated exactly as written, without fusing a multiplication and an addition.
+
+> *Note:* The KVM is not yet implemented. Maps and sets cannot be traversed yet, and there are no clock or random intrinsics, so programs checked today already satisfy these rules.
+
+== Backends
+
+Kali programs are interpreted by the KVM, or compiled to native code with Cranelift. The two backends must agree: for every program, both produce the same result, or stop with the same runtime error at the same span. Differential tests check this by compiling each program in the test corpus, and programs generated at random, to both backends, running them in deterministic mode, and comparing their results and errors. A program on which the backends disagree is a bug in one of them, and is kept in the corpus once fixed.
+
+> *Note:* Neither backend is implemented yet, so there are no differential tests. The parser and printer are already tested against each other in the same way, by checking that printing any generated syntax tree and parsing the result gives back the same tree.
+
 = Interoperability
 
+== Embedding
+
+Rust applications embed Kali through the `kali` crate rather than the command line interface. A `Compiler` runs source code through every pass of the compiler, and is configured with builder methods:
+
+```rust
+let mut compiler = kali::Compiler::new()
+    .with_lints(config)
+    .with_diagnostics(|diagnostic, sources| eprintln!("{}", diagnostic));
+let compilation = compiler.compile("main.kali", source);
+```
+
+Each call to `compile` returns a `Compilation`, holding the desugared module, the types inferred for its top-level bindings, the values of those that are constant, and every diagnostic reported for it:
+
+```rust
+let compilation = compiler.compile_str("let size = 2 * 21;\nlet grow = x -> (x + size)");
+assert!(compilation.is_ok());
+assert_eq!(compilation.types.bindings["grow"].to_string(), "(nat) -> nat");
+assert_eq!(compilation.constants.values["size"], kali::Value::Natural(42));
+```
+
+The interface other modules import a compiled module through is given by `Compilation::interface`, and is passed to the compiler of an importing module with `Compiler::with_interfaces`.
+
+=== Evaluation
+
+> *Note:* This is a planned design. It needs the KVM, which is not yet implemented, so none of the API below exists yet.
+
+An `Engine` evaluates Kali code on the KVM, converting its result to a Rust value:
+
+```rust
+let mut engine = kali::Engine::new();
+engine.register("greet", |name: String| format!("Hello, {name}!"));
+let n: u64 = engine.eval_str("length(greet(\"world\"))")?;
+```
+
+Values cross between Rust and Kali through two conversion traits. `IntoKali` converts a Rust value into a Kali value, and `FromKali` converts a Kali value back, failing if it has a different type. Both are implemented for the following types, and for tuples, `Vec`s, `Option`s and `Result`s of them:
+
+#table(
+  columns: 2,
+  [*Rust*], [*Kali*],
+  [`u64`], [`nat`],
+  [`i64`], [`int`],
+  [`f64`], [`float`],
+  [`bool`], [`bool`],
+  [`String`], [`string`],
+  [`Vec<u8>`], [`bytes`],
+  [`()`], [`()`],
+)
+
+Both traits can be derived for structs with named fields, which convert to records with the same field names. A host function registered with the engine can be called from Kali as any other function, with the Kali type given by the types of its parameters and result, and its name shadows an intrinsic of the same name.
+
 = Tooling
 
 == Compiler
@@ -407,8 +835,26 @@ The parser is a fully-fledged LALR(1) parser generated using the `lalrpop` parse
 
 == Passes
 
+After type checking, the top-level definitions whose values can be computed without running the program are evaluated at compile time. Such a constant definition is built from literals, operators, tuples, lists, conditionals and matches, and only refers to the constants defined before it. A constant definition that would stop the program, such as one dividing by zero or indexing past the end of a list, is a compile-time error, as top-level definitions are evaluated when their module is loaded.
+
 == Formatter
 
 == Package Manager
 
+A package is a directory with a `kali.toml` manifest, naming the package, the directories its modules are in, and the packages it depends on by path:
+
+```toml
+[package]
+name = "shapes"
+version = "0.1.0"
+sources = ["src"]  # the default
+
+[dependencies]
+geometry = { path = "../geometry" }
+```
+
+Module paths are resolved against the source directories of the package, so `import circle::area` imports from `src/circle.kali`. A path starting with the name of a dependency is resolved against the source directories of that dependency instead: `import geometry::point::distance` imports from its `point.kali`, and `import geometry::distance` from its `lib.kali`.
+
+`kali check` run without a file checks every module of the package containing the current directory.
+
 = Appendix